@@ -21,6 +21,8 @@ fn create_comparison_test_dataset(size: usize) -> Vec<ClassModel> {
         container_class: None,
         source_file_index: None,
         is_forward_declaration: false,
+        location: None,
+        property_locations: HashMap::new(),
         properties: HashMap::new(),
     });
     
@@ -30,6 +32,8 @@ fn create_comparison_test_dataset(size: usize) -> Vec<ClassModel> {
         container_class: None,
         source_file_index: None,
         is_forward_declaration: false,
+        location: None,
+        property_locations: HashMap::new(),
         properties: HashMap::new(),
     });
     
@@ -50,6 +54,8 @@ fn create_comparison_test_dataset(size: usize) -> Vec<ClassModel> {
             container_class: Some(parent.to_string()),
             source_file_index: Some(1),
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: {
                 let mut props = HashMap::new();
                 props.insert("displayName".to_string(), PropertyValue::String(format!("{} Base", base_name)));
@@ -72,6 +78,8 @@ fn create_comparison_test_dataset(size: usize) -> Vec<ClassModel> {
                 container_class: Some(parent.to_string()),
                 source_file_index: Some((i % 5) + 1),
                 is_forward_declaration: false,
+                location: None,
+                property_locations: HashMap::new(),
                 properties: {
                     let mut props = HashMap::new();
                     props.insert("displayName".to_string(), PropertyValue::String(format!("Test Item {}", j)));
@@ -90,6 +98,8 @@ fn create_comparison_test_dataset(size: usize) -> Vec<ClassModel> {
             container_class: Some("CfgVehicles".to_string()),
             source_file_index: Some(10),
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: {
                 let mut props = HashMap::new();
                 props.insert("displayName".to_string(), PropertyValue::String(format!("Noise {}", k)));
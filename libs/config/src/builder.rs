@@ -1,5 +1,7 @@
 use std::path::PathBuf;
 
+use arma3_extractor::RetryPolicy;
+
 use crate::error::Result;
 use crate::scan::ScanConfig;
 use crate::item_filter::{ItemFilterConfig, ItemTypeConfig, ExclusionRules};
@@ -60,12 +62,30 @@ impl ScanConfig {
         self
     }
 
+    /// Keep the extraction state in the analysis database
+    pub fn with_shared_extraction_state(mut self, shared: bool) -> Self {
+        self.shared_extraction_state = shared;
+        self
+    }
+
+    /// Set when PBOs that failed to extract are attempted again
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
     /// Set number of threads
     pub fn with_threads(mut self, threads: usize) -> Self {
         self.threads = threads;
         self
     }
 
+    /// Set timeout in seconds for extracting a single PBO
+    pub fn with_extraction_timeout(mut self, timeout: u64) -> Self {
+        self.extraction_timeout = timeout;
+        self
+    }
+
     /// Set ignore classes file
     pub fn with_ignore_classes_file(mut self, path: impl Into<PathBuf>) -> Self {
         self.ignore_classes_file = Some(path.into());
@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 use std::fs;
 use serde::{Deserialize, Serialize};
-use arma3_extractor::ExtractionConfig;
+use arma3_extractor::{ExtractionConfig, RetryPolicy, StateBackend};
 use crate::error::{ConfigError, Result};
 
 /// Configuration for the application scan settings
@@ -16,7 +16,20 @@ pub struct ScanConfig {
     pub analysis_database_path: Option<PathBuf>, 
     pub extractor_database_path: Option<PathBuf>, 
     pub threads: usize,
+    /// Timeout in seconds for extracting a single PBO
+    #[serde(default = "default_extraction_timeout")]
+    pub extraction_timeout: u64,
     pub ignore_classes_file: Option<PathBuf>,
+    /// Keep the extraction state in the analysis database instead of a separate file
+    #[serde(default)]
+    pub shared_extraction_state: bool,
+    /// When PBOs that failed to extract are attempted again
+    #[serde(default)]
+    pub retry_policy: RetryPolicy,
+}
+
+fn default_extraction_timeout() -> u64 {
+    60
 }
 
 impl Default for ScanConfig {
@@ -31,7 +44,10 @@ impl Default for ScanConfig {
             analysis_database_path: None, // Defaults determined later based on cache_dir
             extractor_database_path: None, // Defaults determined later based on cache_dir
             threads: 4,
+            extraction_timeout: default_extraction_timeout(),
             ignore_classes_file: None,
+            shared_extraction_state: false,
+            retry_policy: RetryPolicy::default(),
         }
     }
 }
@@ -110,12 +126,18 @@ impl ScanConfig {
         config.mission_dirs = mission_dirs;
         config.mission_extensions = self.mission_extensions.clone();
         config.threads = self.threads;
-        config.timeout = 60; // Consider making this configurable in ScanConfig too
+        config.timeout = self.extraction_timeout;
         config.verbose = true; // Consider making this configurable
+        config.retry_policy = self.retry_policy.clone();
         
-        // Set the dedicated extractor database path
-        config.db_path = self.extractor_database_path.clone()
-            .unwrap_or_else(|| self.cache_dir.join("extractor.db")); // Default to extractor.db in cache_dir
+        // Set the dedicated extractor database path, or share the analysis database
+        if self.shared_extraction_state {
+            config.db_path = self.get_analysis_db_path();
+            config.state_backend = StateBackend::Sqlite;
+        } else {
+            config.db_path = self.extractor_database_path.clone()
+                .unwrap_or_else(|| self.cache_dir.join("extractor.db")); // Default to extractor.db in cache_dir
+        }
         
         config
     }
@@ -158,6 +180,7 @@ mod tests {
         assert_eq!(config.cache_dir, PathBuf::from("cache"));
         assert_eq!(config.report_dir, PathBuf::from("reports"));
         assert_eq!(config.threads, 4);
+        assert_eq!(config.to_extractor_config().timeout, 60);
     }
     
     #[test]
@@ -181,6 +204,28 @@ mod tests {
         assert_eq!(loaded_config.threads, original_config.threads);
     }
     
+    #[test]
+    fn test_shared_extraction_state() {
+        let mut config = ScanConfig::default();
+        assert_eq!(config.to_extractor_config().state_backend, StateBackend::File);
+        assert_eq!(config.to_extractor_config().db_path, PathBuf::from("cache").join("extractor.db"));
+        
+        config.shared_extraction_state = true;
+        let extractor_config = config.to_extractor_config();
+        assert_eq!(extractor_config.state_backend, StateBackend::Sqlite);
+        assert_eq!(extractor_config.db_path, config.get_analysis_db_path());
+    }
+    
+    #[test]
+    fn test_retry_policy() {
+        // Fields left out of the retry policy keep their defaults
+        let json = r#"{"game_data_dirs":[],"game_data_extensions":["pbo"],"mission_dirs":[],"mission_extensions":["pbo"],"cache_dir":"cache","report_dir":"reports","analysis_database_path":null,"extractor_database_path":null,"threads":4,"ignore_classes_file":null,"retry_policy":{"max_attempts":5}}"#;
+        let config: ScanConfig = serde_json::from_str(json).unwrap();
+        let policy = config.to_extractor_config().retry_policy;
+        assert_eq!(policy.max_attempts, 5);
+        assert!(policy.retry_on_change);
+    }
+    
     #[test]
     fn test_validation() {
         let mut config = ScanConfig::default();
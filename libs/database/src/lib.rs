@@ -33,12 +33,16 @@ pub use schema::initialize_schema;
 pub use queries::cache::Cache;
 // Use our custom ClassRepository instead of the queries one
 // pub use queries::class_repository::ClassRepository;
+pub use queries::addon_repository::AddonRepository;
 pub use queries::mission_repository::MissionRepository;
+pub use queries::resolved_property_repository::ResolvedPropertyRepository;
 pub use queries::graph_query_engine::GraphQueryEngine;
 // Re-export model types for easier access
-pub use models::pbo::{PboModel, PboType, ExtractedFile, FailedExtraction, normalize_path};
+pub use models::pbo::{PboModel, PboType, ModInfo, PboEntryModel, ExtractedFile, FailedExtraction, normalize_path};
 pub use models::class::{ClassModel, ClassHierarchyNode};
+pub use models::addon::{AddonModel, MissingAddonRequirement};
 pub use models::mission::{MissionModel, MissionComponentModel, MissionDependencyModel};
+pub use models::resolved::ResolvedPropertyModel;
 // Re-export repo types
 pub use repos::ClassRepository;
 
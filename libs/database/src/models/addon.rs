@@ -0,0 +1,56 @@
+use serde::{Serialize, Deserialize};
+
+/// Model representing an addon declared in `CfgPatches`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AddonModel {
+    /// Addon name (primary key), the class name inside `CfgPatches`
+    pub name: String,
+
+    /// File index of the config declaring the addon
+    pub source_file_index: Option<usize>,
+
+    /// Game version from `requiredVersion`
+    pub required_version: Option<f64>,
+
+    /// Addon author
+    pub author: Option<String>,
+
+    /// Vehicle classes from `units[]`
+    pub units: Vec<String>,
+
+    /// Weapon classes from `weapons[]`
+    pub weapons: Vec<String>,
+
+    /// Addons from `requiredAddons[]`, in declaration order
+    pub required_addons: Vec<String>,
+}
+
+impl AddonModel {
+    /// Create a new addon model
+    pub fn new(name: impl Into<String>, source_file_index: Option<usize>) -> Self {
+        Self {
+            name: name.into(),
+            source_file_index,
+            required_version: None,
+            author: None,
+            units: Vec::new(),
+            weapons: Vec::new(),
+            required_addons: Vec::new(),
+        }
+    }
+
+    /// Whether this addon lists `addon` in `requiredAddons[]`, ignoring case
+    pub fn requires(&self, addon: &str) -> bool {
+        self.required_addons.iter().any(|required| required.eq_ignore_ascii_case(addon))
+    }
+}
+
+/// A `requiredAddons[]` entry no stored addon provides
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MissingAddonRequirement {
+    /// Addon listing the requirement
+    pub addon_name: String,
+
+    /// Name of the required addon
+    pub required_addon: String,
+}
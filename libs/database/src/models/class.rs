@@ -1,6 +1,6 @@
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
-use arma3_models::types::PropertyValue;
+use arma3_models::types::{PropertyValue, SourceLocation};
 
 /// Model representing a class in the database
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -22,6 +22,14 @@ pub struct ClassModel {
 
     /// Properties of the class
     pub properties: HashMap<String, PropertyValue>,
+    
+    /// Where the class is defined in its source file
+    #[serde(default)]
+    pub location: Option<SourceLocation>,
+    
+    /// Where each property is defined in the source file
+    #[serde(default)]
+    pub property_locations: HashMap<String, SourceLocation>,
 }
 
 impl ClassModel {
@@ -40,6 +48,8 @@ impl ClassModel {
             source_file_index,
             is_forward_declaration,
             properties: HashMap::new(),
+            location: None,
+            property_locations: HashMap::new(),
         }
     }
     
@@ -54,6 +64,8 @@ impl ClassModel {
             source_file_index: class.source_file_index,
             is_forward_declaration: class.is_forward_declaration,
             properties: class.properties.clone(),
+            location: class.location,
+            property_locations: class.property_locations.clone(),
         }
     }
     
@@ -66,10 +78,35 @@ impl ClassModel {
             properties: self.properties.clone(),
             source_file_index: self.source_file_index,
             is_forward_declaration: self.is_forward_declaration,
+            location: self.location,
+            property_locations: self.property_locations.clone(),
         }
     }
 }
 
+/// Value of one column of a stored location, NULL when the location is unknown
+pub(crate) fn location_column(location: Option<SourceLocation>, field: fn(&SourceLocation) -> usize) -> Option<i64> {
+    location.as_ref().map(|location| field(location) as i64)
+}
+
+/// Read a location stored in the four columns starting at `first`
+pub(crate) fn location_from_row(row: &rusqlite::Row, first: usize) -> rusqlite::Result<Option<SourceLocation>> {
+    let start_line: Option<i64> = row.get(first)?;
+    let start_column: Option<i64> = row.get(first + 1)?;
+    let end_line: Option<i64> = row.get(first + 2)?;
+    let end_column: Option<i64> = row.get(first + 3)?;
+    
+    Ok(match (start_line, start_column, end_line, end_column) {
+        (Some(start_line), Some(start_column), Some(end_line), Some(end_column)) => Some(SourceLocation {
+            start_line: start_line as usize,
+            start_column: start_column as usize,
+            end_line: end_line as usize,
+            end_column: end_column as usize,
+        }),
+        _ => None,
+    })
+}
+
 /// Node in a class hierarchy with additional depth information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClassHierarchyNode {
@@ -173,6 +210,11 @@ mod tests {
             properties: std::collections::HashMap::new(),
             source_file_index: Some(1),
             is_forward_declaration: true,
+            location: Some(SourceLocation { start_line: 12, start_column: 5, end_line: 20, end_column: 6 }),
+            property_locations: HashMap::from([(
+                "scope".to_string(),
+                SourceLocation { start_line: 13, start_column: 9, end_line: 13, end_column: 18 },
+            )]),
         };
         
         // Convert to ClassModel
@@ -182,6 +224,7 @@ mod tests {
         assert_eq!(class_model.parent_id, Some("ParentClass".to_string()));
         assert_eq!(class_model.source_file_index, Some(1));
         assert_eq!(class_model.is_forward_declaration, true);
+        assert_eq!(class_model.location, game_data_class.location);
         
         // Convert back to GameDataClass
         let converted_class = class_model.to_game_data_class();
@@ -190,5 +233,6 @@ mod tests {
         assert_eq!(converted_class.parent, game_data_class.parent);
         assert_eq!(converted_class.source_file_index, game_data_class.source_file_index);
         assert_eq!(converted_class.is_forward_declaration, game_data_class.is_forward_declaration);
+        assert_eq!(converted_class.property_locations, game_data_class.property_locations);
     }
 } 
\ No newline at end of file
@@ -17,6 +17,12 @@ pub struct MissionModel {
     /// Reference to the PBO containing this mission
     pub source_pbo_id: Option<String>,
     
+    /// Archive the mission was unpacked from
+    ///
+    /// Archives are not PBOs, so this is not a reference to `pbo_files`.
+    #[serde(default)]
+    pub source_archive: Option<String>,
+    
     /// Mission author
     pub author: Option<String>,
     
@@ -40,6 +46,7 @@ impl MissionModel {
             name: name.into(),
             path: path.into(),
             source_pbo_id: None,
+            source_archive: None,
             author: None,
             description: None,
             scan_time,
@@ -61,6 +68,7 @@ impl MissionModel {
             name: name.into(),
             path: path.into(),
             source_pbo_id: source_pbo_id.map(Into::into),
+            source_archive: None,
             author: author.map(Into::into),
             description: description.map(Into::into),
             scan_time,
@@ -74,6 +82,7 @@ impl MissionModel {
             name: mission.name.clone(),
             path: mission.path.clone(),
             source_pbo_id: mission.source_pbo.clone(),
+            source_archive: mission.source_archive.clone(),
             author: mission.author.clone(),
             description: mission.description.clone(),
             scan_time: Utc::now(),
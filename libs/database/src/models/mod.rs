@@ -1,9 +1,13 @@
+pub mod addon;
 pub mod cache;
 pub mod class;
 pub mod mission;
 pub mod pbo;
+pub mod resolved;
 
+pub use addon::{AddonModel, MissingAddonRequirement};
 pub use cache::{CacheConfig, DatabaseStats};
 pub use class::{ClassModel, ClassHierarchyNode};
 pub use mission::{MissionModel, MissionComponentModel, MissionDependencyModel};
-pub use pbo::{PboModel, PboType, ExtractedFile, FailedExtraction}; 
\ No newline at end of file
+pub use resolved::ResolvedPropertyModel;
+pub use pbo::{PboModel, PboType, ModInfo, PboEntryModel, ExtractedFile, FailedExtraction}; 
\ No newline at end of file
@@ -31,6 +31,22 @@ impl From<&str> for PboType {
     }
 }
 
+/// Mod a PBO belongs to, read from its `@mod` folder
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModInfo {
+    /// Name of the mod folder, e.g. `@CBA_A3`
+    pub folder: String,
+    
+    /// Display name from `mod.cpp` or `meta.cpp`
+    pub name: String,
+    
+    /// Steam Workshop id from `meta.cpp`
+    pub published_id: Option<u64>,
+    
+    /// Publish timestamp from `meta.cpp`
+    pub timestamp: Option<u64>,
+}
+
 /// Model representing a PBO file in the database
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PboModel {
@@ -54,6 +70,30 @@ pub struct PboModel {
     
     /// Type of PBO (GameData or Mission)
     pub pbo_type: PboType,
+    
+    /// Mod the PBO belongs to, if any
+    #[serde(default)]
+    pub mod_info: Option<ModInfo>,
+    
+    /// Extensions the PBO was extracted with
+    #[serde(default)]
+    pub used_extensions: Vec<String>,
+    
+    /// SHA-1 of the PBO contents, if it was hashed
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    
+    /// ID of an identical PBO whose extracted files this one shares
+    #[serde(default)]
+    pub duplicate_of: Option<String>,
+    
+    /// Signature status recorded by the extractor, as JSON
+    #[serde(default)]
+    pub signature_status: Option<String>,
+    
+    /// Timing of the extraction recorded by the extractor, as JSON
+    #[serde(default)]
+    pub extraction_stats: Option<String>,
 }
 
 impl PboModel {
@@ -75,6 +115,12 @@ impl PboModel {
             last_modified,
             extraction_time,
             pbo_type,
+            mod_info: None,
+            used_extensions: Vec::new(),
+            content_hash: None,
+            duplicate_of: None,
+            signature_status: None,
+            extraction_stats: None,
         }
     }
     
@@ -98,6 +144,12 @@ impl PboModel {
             last_modified,
             extraction_time,
             pbo_type,
+            mod_info: None,
+            used_extensions: Vec::new(),
+            content_hash: None,
+            duplicate_of: None,
+            signature_status: None,
+            extraction_stats: None,
         }
     }
     
@@ -115,9 +167,21 @@ impl PboModel {
                 "Mission" => PboType::Mission,
                 _ => PboType::GameData,
             },
+            mod_info: metadata.get_mod_info(),
+            used_extensions: Vec::new(),
+            content_hash: None,
+            duplicate_of: None,
+            signature_status: None,
+            extraction_stats: None,
         }
     }
     
+    /// Attach the mod the PBO belongs to
+    pub fn with_mod_info(mut self, mod_info: ModInfo) -> Self {
+        self.mod_info = Some(mod_info);
+        self
+    }
+    
     /// Get the full path
     pub fn get_full_path(&self) -> PathBuf {
         if self.full_path.is_absolute() {
@@ -162,6 +226,11 @@ pub trait PboMetadataConversion {
     fn get_last_modified(&self) -> DateTime<Utc>;
     fn get_extraction_time(&self) -> DateTime<Utc>;
     fn get_pbo_type(&self) -> &str;
+    
+    /// Mod the PBO belongs to, if known
+    fn get_mod_info(&self) -> Option<ModInfo> {
+        None
+    }
 }
 
 /// Model for extracted files
@@ -183,6 +252,10 @@ pub struct ExtractedFile {
     /// File name without path (cached for faster lookups)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub file_name: Option<String>,
+    
+    /// SHA-1 of the file contents, naming its blob in the extractor's blob store
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
 }
 
 impl ExtractedFile {
@@ -198,9 +271,16 @@ impl ExtractedFile {
             relative_path: path_ref.to_path_buf(),
             extension,
             file_name,
+            content_hash: None,
         }
     }
     
+    /// Set the hash of the file contents
+    pub fn with_content_hash(mut self, content_hash: Option<String>) -> Self {
+        self.content_hash = content_hash;
+        self
+    }
+    
     /// Get the full path by combining with a cache directory
     pub fn get_full_path(&self, cache_dir: impl AsRef<Path>) -> PathBuf {
         cache_dir.as_ref().join(&self.relative_path)
@@ -221,6 +301,29 @@ impl ExtractedFile {
     }
 }
 
+/// Entry in the file table of a PBO, whether or not it was extracted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PboEntryModel {
+    /// Reference to the PBO
+    pub pbo_id: String,
+    
+    /// Path of the entry inside the PBO, using forward slashes
+    pub path: String,
+    
+    /// Uncompressed size in bytes
+    pub size: u64,
+    
+    /// Timestamp stored in the PBO header
+    pub timestamp: u64,
+}
+
+impl PboEntryModel {
+    /// Lowercased extension of the entry, if it has one
+    pub fn get_extension(&self) -> Option<String> {
+        Path::new(&self.path).extension().map(|e| e.to_string_lossy().to_lowercase())
+    }
+}
+
 /// Model for PBO extraction failure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FailedExtraction {
@@ -232,15 +335,40 @@ pub struct FailedExtraction {
     
     /// Error message
     pub error_message: String,
+    
+    /// Path of the PBO that failed
+    pub pbo_path: PathBuf,
+    
+    /// Error category
+    pub category: String,
+    
+    /// Whether the error is transient
+    pub retryable: bool,
+    
+    /// Number of extraction attempts that failed in a row
+    pub attempts: u32,
+    
+    /// Hash of the PBO contents at the time of the failure
+    pub content_hash: Option<String>,
+    
+    /// Version of the extractor that recorded the failure
+    pub tool_version: Option<String>,
 }
 
 impl FailedExtraction {
     /// Create a new failed extraction record
     pub fn new(pbo_id: impl Into<String>, error_message: impl Into<String>) -> Self {
+        let pbo_id = pbo_id.into();
         Self {
-            pbo_id: pbo_id.into(),
+            pbo_path: PathBuf::from(&pbo_id),
+            pbo_id,
             timestamp: Utc::now(),
             error_message: error_message.into(),
+            category: "unknown".to_string(),
+            retryable: false,
+            attempts: 1,
+            content_hash: None,
+            tool_version: None,
         }
     }
 }
@@ -0,0 +1,27 @@
+use serde::{Serialize, Deserialize};
+use arma3_models::types::{PropertyValue, SourceLocation};
+
+/// A property as the game sees it once every addon is loaded
+///
+/// Only properties set on the class itself are stored, inherited values are
+/// found on the parent class.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResolvedPropertyModel {
+    /// Full path of the class, e.g. `CfgWeapons/arifle_MX_F`
+    pub class_path: String,
+
+    /// Property name as written in the definition that set it
+    pub name: String,
+
+    /// Effective value, with appends applied
+    pub value: PropertyValue,
+
+    /// Addon whose definition set the value last
+    pub addon: String,
+
+    /// File index of the config that set the value
+    pub source_file_index: Option<usize>,
+
+    /// Where in that file the value was set
+    pub location: Option<SourceLocation>,
+}
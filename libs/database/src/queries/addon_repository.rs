@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use rusqlite::{params, Row, OptionalExtension};
+
+use crate::error::Result;
+use crate::models::addon::{AddonModel, MissingAddonRequirement};
+use crate::DatabaseManager;
+
+/// Tables holding the lists of an addon, with the column of their items
+const ADDON_LISTS: [(&str, &str); 3] = [
+    ("addon_requirements", "required_addon"),
+    ("addon_units", "class_name"),
+    ("addon_weapons", "class_name"),
+];
+
+/// Repository for addons declared in `CfgPatches`
+pub struct AddonRepository<'a> {
+    db: &'a DatabaseManager,
+}
+
+impl<'a> AddonRepository<'a> {
+    /// Create a new addon repository
+    pub fn new(db: &'a DatabaseManager) -> Self {
+        Self { db }
+    }
+
+    /// Create or replace an addon
+    pub fn create(&self, addon: &AddonModel) -> Result<()> {
+        self.db.with_transaction(|tx| insert_addon(tx, addon))
+    }
+
+    /// Create or replace many addons in one transaction
+    ///
+    /// An addon listed twice is stored as its last entry, so callers that
+    /// keep the first declaration must drop the later ones first.
+    pub fn bulk_import(&self, addons: &[AddonModel]) -> Result<()> {
+        self.db.with_transaction(|tx| {
+            for addon in addons {
+                insert_addon(tx, addon)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Get an addon by name, ignoring case
+    pub fn get(&self, name: &str) -> Result<Option<AddonModel>> {
+        self.db.with_connection(|conn| {
+            let addon = conn.query_row(
+                "SELECT name, source_file_index, required_version, author
+                 FROM addons WHERE name = ?1",
+                [name],
+                map_row_to_addon,
+            ).optional()?;
+
+            let Some(mut addon) = addon else {
+                return Ok(None);
+            };
+
+            for (table, column) in ADDON_LISTS {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT {column} FROM {table} WHERE addon_name = ?1 ORDER BY position"
+                ))?;
+                let items = stmt
+                    .query_map([&addon.name], |row| row.get(0))?
+                    .collect::<rusqlite::Result<Vec<String>>>()?;
+                *addon_list(&mut addon, table) = items;
+            }
+
+            Ok(Some(addon))
+        })
+    }
+
+    /// Get all addons, ordered by name
+    pub fn get_all(&self) -> Result<Vec<AddonModel>> {
+        self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT name, source_file_index, required_version, author
+                 FROM addons ORDER BY name"
+            )?;
+
+            let mut addons = stmt
+                .query_map([], map_row_to_addon)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            let by_name: HashMap<String, usize> = addons
+                .iter()
+                .enumerate()
+                .map(|(index, addon)| (addon.name.to_lowercase(), index))
+                .collect();
+
+            for (table, column) in ADDON_LISTS {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT addon_name, {column} FROM {table} ORDER BY addon_name, position"
+                ))?;
+                let rows = stmt.query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })?;
+
+                for row_result in rows {
+                    let (addon_name, item) = row_result?;
+                    if let Some(&index) = by_name.get(&addon_name.to_lowercase()) {
+                        addon_list(&mut addons[index], table).push(item);
+                    }
+                }
+            }
+
+            Ok(addons)
+        })
+    }
+
+    /// Find `requiredAddons[]` entries that no stored addon provides
+    pub fn find_missing_requirements(&self) -> Result<Vec<MissingAddonRequirement>> {
+        self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT r.addon_name, r.required_addon
+                 FROM addon_requirements r
+                 LEFT JOIN addons a ON a.name = r.required_addon
+                 WHERE a.name IS NULL
+                 ORDER BY r.addon_name, r.position"
+            )?;
+
+            let rows = stmt.query_map([], |row| {
+                Ok(MissingAddonRequirement {
+                    addon_name: row.get(0)?,
+                    required_addon: row.get(1)?,
+                })
+            })?;
+
+            let mut missing = Vec::new();
+            for row_result in rows {
+                missing.push(row_result?);
+            }
+
+            Ok(missing)
+        })
+    }
+
+    /// Find addons that list `addon` in `requiredAddons[]`
+    pub fn find_dependents(&self, addon: &str) -> Result<Vec<String>> {
+        self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT DISTINCT addon_name FROM addon_requirements
+                 WHERE required_addon = ?1
+                 ORDER BY addon_name"
+            )?;
+
+            let dependents = stmt
+                .query_map([addon], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<String>>>()?;
+
+            Ok(dependents)
+        })
+    }
+
+    /// Clear all addons
+    pub fn clear_all(&self) -> Result<()> {
+        self.db.with_transaction(|tx| {
+            for (table, _) in ADDON_LISTS {
+                tx.execute(&format!("DELETE FROM {table}"), [])?;
+            }
+            tx.execute("DELETE FROM addons", [])?;
+            Ok(())
+        })
+    }
+}
+
+/// Insert an addon and its lists, replacing any earlier declaration
+fn insert_addon(tx: &rusqlite::Transaction, addon: &AddonModel) -> Result<()> {
+    for (table, _) in ADDON_LISTS {
+        tx.execute(&format!("DELETE FROM {table} WHERE addon_name = ?1"), [&addon.name])?;
+    }
+
+    tx.execute(
+        "INSERT OR REPLACE INTO addons (name, source_file_index, required_version, author)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![
+            addon.name,
+            addon.source_file_index.map(|index| index as i64),
+            addon.required_version,
+            addon.author,
+        ],
+    )?;
+
+    for (table, column) in ADDON_LISTS {
+        let mut stmt = tx.prepare(&format!(
+            "INSERT INTO {table} (addon_name, {column}, position) VALUES (?1, ?2, ?3)"
+        ))?;
+        for (position, item) in addon_list_ref(addon, table).iter().enumerate() {
+            stmt.execute(params![addon.name, item, position as i64])?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The list of an addon stored in `table`
+fn addon_list<'m>(addon: &'m mut AddonModel, table: &str) -> &'m mut Vec<String> {
+    match table {
+        "addon_requirements" => &mut addon.required_addons,
+        "addon_units" => &mut addon.units,
+        _ => &mut addon.weapons,
+    }
+}
+
+fn addon_list_ref<'m>(addon: &'m AddonModel, table: &str) -> &'m [String] {
+    match table {
+        "addon_requirements" => &addon.required_addons,
+        "addon_units" => &addon.units,
+        _ => &addon.weapons,
+    }
+}
+
+/// Convert a database row to an AddonModel without its lists
+fn map_row_to_addon(row: &Row) -> rusqlite::Result<AddonModel> {
+    let source_file_index: Option<i64> = row.get(1)?;
+
+    Ok(AddonModel {
+        name: row.get(0)?,
+        source_file_index: source_file_index.map(|index| index as usize),
+        required_version: row.get(2)?,
+        author: row.get(3)?,
+        units: Vec::new(),
+        weapons: Vec::new(),
+        required_addons: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn addon(name: &str, required: &[&str]) -> AddonModel {
+        let mut addon = AddonModel::new(name, None);
+        addon.required_addons = required.iter().map(|r| r.to_string()).collect();
+        addon
+    }
+
+    #[test]
+    fn test_addon_repository() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = DatabaseManager::new(&db_path).unwrap();
+        let repo = AddonRepository::new(&db);
+
+        let mut main = addon("mod_main", &["A3_Data_F", "cba_main"]);
+        main.source_file_index = Some(3);
+        main.required_version = Some(2.1);
+        main.author = Some("Alpha".to_string());
+        main.weapons = vec!["mod_rifle".to_string(), "mod_pistol".to_string()];
+        repo.bulk_import(&[main.clone(), addon("CBA_Main", &[]), addon("mod_extra", &["MOD_MAIN"])]).unwrap();
+
+        assert_eq!(repo.get("MOD_MAIN").unwrap(), Some(main.clone()));
+        assert_eq!(repo.get_all().unwrap().len(), 3);
+        assert_eq!(repo.find_dependents("mod_main").unwrap(), vec!["mod_extra"]);
+        assert_eq!(repo.find_missing_requirements().unwrap(), vec![MissingAddonRequirement {
+            addon_name: "mod_main".to_string(),
+            required_addon: "A3_Data_F".to_string(),
+        }]);
+
+        // Declaring the addon again replaces its lists
+        main.weapons = vec!["mod_rifle".to_string()];
+        repo.create(&main).unwrap();
+        assert_eq!(repo.get("mod_main").unwrap().unwrap().weapons, vec!["mod_rifle"]);
+
+        repo.clear_all().unwrap();
+        assert!(repo.get_all().unwrap().is_empty());
+    }
+}
@@ -1,12 +1,21 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use chrono::{DateTime, Utc};
 use log::debug;
-use rusqlite::{params, Row, OptionalExtension};
+use rusqlite::{params, params_from_iter, Row, OptionalExtension};
 
 use crate::error::{Result, DatabaseError};
-use crate::models::pbo::{PboModel, PboType, ExtractedFile, FailedExtraction};
+use crate::models::pbo::{PboModel, PboType, ModInfo, PboEntryModel, ExtractedFile, FailedExtraction, normalize_path};
 use crate::DatabaseManager;
 
+/// Most PBO IDs bound to one `IN (...)` query, below SQLite's variable limit
+const MAX_IDS_PER_QUERY: usize = 500;
+
+/// `?1, ?2, ...` for an `IN (...)` list of `count` IDs
+fn id_placeholders(count: usize) -> String {
+    (1..=count).map(|index| format!("?{}", index)).collect::<Vec<_>>().join(", ")
+}
+
 /// Repository for PBO-related database operations
 pub struct Cache<'a> {
     db: &'a DatabaseManager,
@@ -58,33 +67,91 @@ impl<'a> Cache<'a> {
         }
         
         self.db.with_connection(|conn| {
-            conn.execute(
-                "INSERT OR REPLACE INTO pbo_files (
-                     id, full_path, base_dir, file_size, 
-                     last_modified, extraction_time, pbo_type
-                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-                params![
-                    pbo.id,
-                    pbo.full_path.to_string_lossy(),
-                    pbo.base_dir.as_ref().map(|p| p.to_string_lossy().to_string()),
-                    pbo.file_size,
-                    pbo.last_modified.to_rfc3339(),
-                    pbo.extraction_time.to_rfc3339(),
-                    pbo.pbo_type.to_string(),
-                ],
-            )?;
+            Self::upsert_pbo(conn, pbo)?;
             
             debug!("Created or updated PBO record: {}", pbo.id);
             Ok(())
         })
     }
     
+    /// Insert a PBO record or update every column of an existing one
+    ///
+    /// Updating in place instead of `INSERT OR REPLACE` keeps the rows that
+    /// reference the PBO valid.
+    fn upsert_pbo(conn: &rusqlite::Connection, pbo: &PboModel) -> Result<()> {
+        conn.execute(
+            "INSERT INTO pbo_files (
+                 id, full_path, base_dir, file_size, 
+                 last_modified, extraction_time, pbo_type,
+                 mod_folder, mod_name, mod_published_id, mod_timestamp,
+                 used_extensions, content_hash, duplicate_of, signature_status, extraction_stats
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
+             ON CONFLICT(id) DO UPDATE SET
+                 full_path = excluded.full_path,
+                 base_dir = excluded.base_dir,
+                 file_size = excluded.file_size,
+                 last_modified = excluded.last_modified,
+                 extraction_time = excluded.extraction_time,
+                 pbo_type = excluded.pbo_type,
+                 mod_folder = excluded.mod_folder,
+                 mod_name = excluded.mod_name,
+                 mod_published_id = excluded.mod_published_id,
+                 mod_timestamp = excluded.mod_timestamp,
+                 used_extensions = excluded.used_extensions,
+                 content_hash = excluded.content_hash,
+                 duplicate_of = excluded.duplicate_of,
+                 signature_status = excluded.signature_status,
+                 extraction_stats = excluded.extraction_stats",
+            params![
+                pbo.id,
+                pbo.full_path.to_string_lossy(),
+                pbo.base_dir.as_ref().map(|p| p.to_string_lossy().to_string()),
+                pbo.file_size,
+                pbo.last_modified.to_rfc3339(),
+                pbo.extraction_time.to_rfc3339(),
+                pbo.pbo_type.to_string(),
+                pbo.mod_info.as_ref().map(|m| m.folder.as_str()),
+                pbo.mod_info.as_ref().map(|m| m.name.as_str()),
+                pbo.mod_info.as_ref().and_then(|m| m.published_id),
+                pbo.mod_info.as_ref().and_then(|m| m.timestamp),
+                pbo.used_extensions.join(","),
+                pbo.content_hash,
+                pbo.duplicate_of,
+                pbo.signature_status,
+                pbo.extraction_stats,
+            ],
+        )?;
+        Ok(())
+    }
+    
+    /// Insert the extracted files of a PBO, with slashes normalized
+    fn insert_extracted_files(conn: &rusqlite::Connection, pbo_id: &str, extracted_files: &[ExtractedFile]) -> Result<()> {
+        let mut stmt = conn.prepare(
+            "INSERT INTO extracted_files (pbo_id, relative_path, extension, file_name, content_hash)
+             VALUES (?1, ?2, ?3, ?4, ?5)"
+        )?;
+        
+        for file in extracted_files {
+            stmt.execute(params![
+                pbo_id,
+                normalize_path(&file.relative_path),
+                file.get_extension(),
+                file.get_file_name(),
+                file.content_hash,
+            ])?;
+        }
+        
+        Ok(())
+    }
+    
     /// Get a PBO by ID
     pub fn get(&self, id: &str) -> Result<Option<PboModel>> {
         self.db.with_connection(|conn| {
             let result = conn.query_row(
                 "SELECT id, full_path, base_dir, file_size, 
-                        last_modified, extraction_time, pbo_type
+                        last_modified, extraction_time, pbo_type,
+                        mod_folder, mod_name, mod_published_id, mod_timestamp,
+                        used_extensions, content_hash, duplicate_of, signature_status, extraction_stats
                  FROM pbo_files WHERE id = ?1",
                 [id],
                 |row| self.map_row_to_pbo(row),
@@ -99,7 +166,9 @@ impl<'a> Cache<'a> {
         self.db.with_connection(|conn| {
             let mut stmt = conn.prepare(
                 "SELECT id, full_path, base_dir, file_size, 
-                        last_modified, extraction_time, pbo_type
+                        last_modified, extraction_time, pbo_type,
+                        mod_folder, mod_name, mod_published_id, mod_timestamp,
+                        used_extensions, content_hash, duplicate_of, signature_status, extraction_stats
                  FROM pbo_files WHERE pbo_type = ?1 ORDER BY id"
             )?;
             
@@ -114,6 +183,55 @@ impl<'a> Cache<'a> {
         })
     }
     
+    /// Get the PBOs of a mod by its folder name, e.g. `@CBA_A3`
+    pub fn get_by_mod(&self, mod_folder: &str) -> Result<Vec<PboModel>> {
+        self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, full_path, base_dir, file_size, 
+                        last_modified, extraction_time, pbo_type,
+                        mod_folder, mod_name, mod_published_id, mod_timestamp,
+                        used_extensions, content_hash, duplicate_of, signature_status, extraction_stats
+                 FROM pbo_files WHERE mod_folder = ?1 COLLATE NOCASE ORDER BY id"
+            )?;
+            
+            let rows = stmt.query_map([mod_folder], |row| self.map_row_to_pbo(row))?;
+            
+            let mut pbos = Vec::new();
+            for row_result in rows {
+                pbos.push(row_result?);
+            }
+            
+            Ok(pbos)
+        })
+    }
+    
+    /// Get every mod that has at least one PBO in the database
+    pub fn get_mods(&self) -> Result<Vec<ModInfo>> {
+        self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT mod_folder, MAX(mod_name), MAX(mod_published_id), MAX(mod_timestamp)
+                 FROM pbo_files WHERE mod_folder IS NOT NULL
+                 GROUP BY mod_folder ORDER BY mod_folder"
+            )?;
+            
+            let rows = stmt.query_map([], |row| {
+                Ok(ModInfo {
+                    folder: row.get(0)?,
+                    name: row.get(1)?,
+                    published_id: row.get(2)?,
+                    timestamp: row.get(3)?,
+                })
+            })?;
+            
+            let mut mods = Vec::new();
+            for row_result in rows {
+                mods.push(row_result?);
+            }
+            
+            Ok(mods)
+        })
+    }
+    
     /// Add multiple extracted files
     pub fn add_extracted_files(&self, files: &[ExtractedFile]) -> Result<()> {
         if files.is_empty() {
@@ -122,8 +240,8 @@ impl<'a> Cache<'a> {
         
         self.db.with_transaction(|tx| {
             let mut stmt = tx.prepare(
-                "INSERT INTO extracted_files (pbo_id, relative_path, extension, file_name)
-                 VALUES (?1, ?2, ?3, ?4)"
+                "INSERT INTO extracted_files (pbo_id, relative_path, extension, file_name, content_hash)
+                 VALUES (?1, ?2, ?3, ?4, ?5)"
             )?;
             
             for file in files {
@@ -141,6 +259,7 @@ impl<'a> Cache<'a> {
                     file.relative_path.to_string_lossy(),
                     extension,
                     file_name,
+                    file.content_hash,
                 ])?;
             }
             
@@ -152,21 +271,13 @@ impl<'a> Cache<'a> {
     pub fn get_extracted_files(&self, pbo_id: &str) -> Result<Vec<ExtractedFile>> {
         self.db.with_connection(|conn| {
             let mut stmt = conn.prepare(
-                "SELECT id, pbo_id, relative_path, extension, file_name
+                "SELECT id, pbo_id, relative_path, extension, file_name, content_hash
                  FROM extracted_files
                  WHERE pbo_id = ?1
                  ORDER BY relative_path"
             )?;
             
-            let rows = stmt.query_map([pbo_id], |row| {
-                Ok(ExtractedFile {
-                    id: row.get(0)?,
-                    pbo_id: row.get(1)?,
-                    relative_path: PathBuf::from(row.get::<_, String>(2)?),
-                    extension: row.get(3)?,
-                    file_name: row.get(4)?,
-                })
-            })?;
+            let rows = stmt.query_map([pbo_id], |row| self.map_row_to_extracted_file(row))?;
             
             let mut files = Vec::new();
             for row_result in rows {
@@ -177,6 +288,32 @@ impl<'a> Cache<'a> {
         })
     }
     
+    /// Get the extracted files of many PBOs, grouped by PBO ID
+    ///
+    /// PBOs without files are left out of the map.
+    pub fn get_extracted_files_for(&self, pbo_ids: &[String]) -> Result<HashMap<String, Vec<ExtractedFile>>> {
+        self.db.with_connection(|conn| {
+            let mut files: HashMap<String, Vec<ExtractedFile>> = HashMap::new();
+            for chunk in pbo_ids.chunks(MAX_IDS_PER_QUERY) {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT id, pbo_id, relative_path, extension, file_name, content_hash
+                     FROM extracted_files
+                     WHERE pbo_id IN ({})
+                     ORDER BY pbo_id, relative_path",
+                    id_placeholders(chunk.len())
+                ))?;
+                
+                let rows = stmt.query_map(params_from_iter(chunk), |row| self.map_row_to_extracted_file(row))?;
+                for row_result in rows {
+                    let file = row_result?;
+                    files.entry(file.pbo_id.clone()).or_default().push(file);
+                }
+            }
+            
+            Ok(files)
+        })
+    }
+    
     /// Find the source PBO for a given file path
     pub fn find_pbo_by_file_path(&self, file_path: impl AsRef<Path>) -> Result<Option<PboModel>> {
         // Convert path to string and normalize slashes
@@ -217,14 +354,13 @@ impl<'a> Cache<'a> {
     // Helper function to find PBO by file name and partial path
     fn find_pbo_by_file_name(&self, full_path: &str, file_name: &str) -> Result<Option<PboModel>> {
         self.db.with_connection(|conn| {
-            // First try to find by exact filename match (fast path)
+            // Candidates share the file name, which is indexed
             let mut stmt = conn.prepare(
                 "SELECT pbo_id, relative_path FROM extracted_files 
-                 WHERE relative_path LIKE ?1 LIMIT 100"
+                 WHERE file_name = ?1 LIMIT 100"
             )?;
             
-            let pattern = format!("%/{}", file_name);
-            let rows = stmt.query_map([pattern], |row| {
+            let rows = stmt.query_map([file_name], |row| {
                 Ok((
                     row.get::<_, String>(0)?,
                     row.get::<_, String>(1)?,
@@ -310,25 +446,21 @@ impl<'a> Cache<'a> {
         }
         
         // Check if extensions match
-        let extensions_str = extensions.join(",");
-        let cached_extensions = match self.db.get_metadata(&format!("pbo_{}_extensions", pbo_id))? {
-            Some(ext) => ext,
-            None => {
-                debug!("PBO {} has no cached extensions, needs extraction", pbo_id);
-                return Ok(true);
-            }
-        };
+        if pbo.used_extensions.is_empty() {
+            debug!("PBO {} has no cached extensions, needs extraction", pbo_id);
+            return Ok(true);
+        }
         
         // Create sorted sets for comparing extension lists
-        let mut sorted_cached_extensions: Vec<&str> = cached_extensions.split(',').collect();
+        let mut sorted_cached_extensions = pbo.used_extensions.clone();
         sorted_cached_extensions.sort();
         
-        let mut sorted_requested_extensions: Vec<&str> = extensions.iter().map(|s| s.as_str()).collect();
+        let mut sorted_requested_extensions = extensions.to_vec();
         sorted_requested_extensions.sort();
         
         if sorted_cached_extensions != sorted_requested_extensions {
             debug!("PBO {} extensions changed (was: {}, now: {}), needs extraction", 
-                pbo_id, cached_extensions, extensions_str);
+                pbo_id, pbo.used_extensions.join(","), extensions.join(","));
             return Ok(true);
         }
         
@@ -339,6 +471,7 @@ impl<'a> Cache<'a> {
     
     /// Record a failed extraction
     pub fn record_failed_extraction(&self, pbo_id: &str, error_message: &str) -> Result<()> {
+        let failure = FailedExtraction::new(pbo_id, error_message);
         self.db.with_transaction(|tx| {
             // First check if the PBO record exists
             let pbo_exists: bool = tx.query_row(
@@ -355,15 +488,7 @@ impl<'a> Cache<'a> {
             }
             
             // Record the failed extraction
-            tx.execute(
-                "INSERT OR REPLACE INTO failed_extractions (pbo_id, timestamp, error_message)
-                 VALUES (?1, ?2, ?3)",
-                params![
-                    pbo_id,
-                    Utc::now().to_rfc3339(),
-                    error_message,
-                ],
-            )?;
+            Self::insert_failed_extraction(tx, &failure)?;
             
             Ok(())
         })
@@ -371,21 +496,13 @@ impl<'a> Cache<'a> {
     
     /// Record a failed extraction directly, bypassing foreign key checks
     /// This should only be used after ensuring the PBO record exists
-    pub fn record_failed_extraction_direct(&self, pbo_id: &str, error_message: &str) -> Result<()> {
+    pub fn record_failed_extraction_direct(&self, failure: &FailedExtraction) -> Result<()> {
         self.db.with_connection(|conn| {
             // Temporarily disable foreign key constraints
             conn.execute("PRAGMA foreign_keys = OFF", [])?;
             
             // Record the failed extraction
-            let result = conn.execute(
-                "INSERT OR REPLACE INTO failed_extractions (pbo_id, timestamp, error_message)
-                 VALUES (?1, ?2, ?3)",
-                params![
-                    pbo_id,
-                    Utc::now().to_rfc3339(),
-                    error_message,
-                ],
-            );
+            let result = Self::insert_failed_extraction(conn, failure);
             
             // Re-enable foreign key constraints
             conn.execute("PRAGMA foreign_keys = ON", [])?;
@@ -393,41 +510,96 @@ impl<'a> Cache<'a> {
             // Check result
             result?;
             
-            debug!("Directly recorded failed extraction for {}", pbo_id);
+            debug!("Directly recorded failed extraction for {}", failure.pbo_id);
+            Ok(())
+        })
+    }
+    
+    /// Record a failed extraction of `pbo`, keeping foreign keys checked
+    ///
+    /// A PBO that has no record yet gets `pbo` as one, so the failure has a
+    /// row to reference. An existing record is left as it is.
+    pub fn record_failure(&self, pbo: &PboModel, failure: &FailedExtraction) -> Result<()> {
+        if !pbo.is_valid() || pbo.id != failure.pbo_id {
+            return Err(DatabaseError::InvalidData(
+                format!("Invalid PBO model for failed extraction: {}", failure.pbo_id)
+            ));
+        }
+        
+        self.db.with_transaction(|tx| {
+            let pbo_exists = tx.query_row(
+                "SELECT 1 FROM pbo_files WHERE id = ?1 LIMIT 1",
+                [&pbo.id],
+                |_| Ok(true)
+            ).optional()?.unwrap_or(false);
+            if !pbo_exists {
+                Self::upsert_pbo(tx, pbo)?;
+            }
+            
+            Self::insert_failed_extraction(tx, failure)?;
+            
+            debug!("Recorded failed extraction for {}", failure.pbo_id);
             Ok(())
         })
     }
     
+    /// Insert or replace a failure record
+    fn insert_failed_extraction(conn: &rusqlite::Connection, failure: &FailedExtraction) -> rusqlite::Result<usize> {
+        conn.execute(
+            "INSERT OR REPLACE INTO failed_extractions
+                (pbo_id, timestamp, error_message, pbo_path, category, retryable, attempts, content_hash, tool_version)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                failure.pbo_id,
+                failure.timestamp.to_rfc3339(),
+                failure.error_message,
+                failure.pbo_path.to_string_lossy(),
+                failure.category,
+                failure.retryable,
+                failure.attempts,
+                failure.content_hash,
+                failure.tool_version,
+            ],
+        )
+    }
+    
     /// Get failed extraction
     pub fn get_failed_extraction(&self, pbo_id: &str) -> Result<Option<FailedExtraction>> {
         self.db.with_connection(|conn| {
             let result = conn.query_row(
-                "SELECT pbo_id, timestamp, error_message
+                "SELECT pbo_id, timestamp, error_message, pbo_path, category, retryable, attempts, content_hash, tool_version
                  FROM failed_extractions WHERE pbo_id = ?1",
                 [pbo_id],
-                |row| {
-                    let timestamp_str: String = row.get(1)?;
-                    let timestamp = DateTime::parse_from_rfc3339(&timestamp_str)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .map_err(|_| rusqlite::Error::InvalidColumnType(1, "timestamp".to_string(), rusqlite::types::Type::Text))?;
-                    
-                    Ok(FailedExtraction {
-                        pbo_id: row.get(0)?,
-                        timestamp,
-                        error_message: row.get(2)?,
-                    })
-                },
+                |row| self.map_row_to_failure(row),
             ).optional()?;
             
             Ok(result)
         })
     }
     
+    /// Get all failed extractions
+    pub fn get_failed_extractions(&self) -> Result<Vec<FailedExtraction>> {
+        self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT pbo_id, timestamp, error_message, pbo_path, category, retryable, attempts, content_hash, tool_version
+                 FROM failed_extractions ORDER BY pbo_id"
+            )?;
+            let rows = stmt.query_map([], |row| self.map_row_to_failure(row))?;
+            
+            let mut failures = Vec::new();
+            for row_result in rows {
+                failures.push(row_result?);
+            }
+            
+            Ok(failures)
+        })
+    }
+    
     /// Find extracted files by extension
     pub fn find_files_by_extension(&self, extension: &str) -> Result<Vec<ExtractedFile>> {
         self.db.with_connection(|conn| {
             let mut stmt = conn.prepare(
-                "SELECT id, pbo_id, relative_path, extension, file_name
+                "SELECT id, pbo_id, relative_path, extension, file_name, content_hash
                  FROM extracted_files
                  WHERE extension = ?1"
             )?;
@@ -439,6 +611,7 @@ impl<'a> Cache<'a> {
                     relative_path: PathBuf::from(row.get::<_, String>(2)?),
                     extension: row.get(3)?,
                     file_name: row.get(4)?,
+                    content_hash: row.get(5)?,
                 })
             })?;
             
@@ -460,51 +633,320 @@ impl<'a> Cache<'a> {
         extensions: &[String],
     ) -> Result<()> {
         self.db.with_transaction(|tx| {
-            // Update PBO extraction time
+            // Update PBO extraction time and extensions
             tx.execute(
-                "UPDATE pbo_files SET extraction_time = ?1 WHERE id = ?2",
-                params![extraction_time.to_rfc3339(), pbo_id],
+                "UPDATE pbo_files SET extraction_time = ?1, used_extensions = ?2 WHERE id = ?3",
+                params![extraction_time.to_rfc3339(), extensions.join(","), pbo_id],
             )?;
             
-            // Clear existing extracted files
+            // Replace the extracted files
             tx.execute(
                 "DELETE FROM extracted_files WHERE pbo_id = ?1",
                 [pbo_id],
             )?;
+            let extracted_files: Vec<ExtractedFile> = extracted_files.iter()
+                .map(|path| ExtractedFile::new(pbo_id, path))
+                .collect();
+            Self::insert_extracted_files(tx, pbo_id, &extracted_files)?;
             
-            // Add new extracted files
-            let mut stmt = tx.prepare(
-                "INSERT INTO extracted_files (pbo_id, relative_path)
-                 VALUES (?1, ?2)"
+            // Remove from failed extractions if it exists
+            tx.execute(
+                "DELETE FROM failed_extractions WHERE pbo_id = ?1",
+                [pbo_id],
             )?;
             
-            for path in extracted_files {
+            Ok(())
+        })
+    }
+    
+    /// Record a finished extraction of a PBO
+    ///
+    /// Replaces the PBO record, its extracted files and its entry table in a
+    /// single transaction and clears any earlier failure.
+    pub fn record_extraction(
+        &self,
+        pbo: &PboModel,
+        extracted_files: &[ExtractedFile],
+        entries: &[PboEntryModel],
+    ) -> Result<()> {
+        if !pbo.is_valid() {
+            return Err(DatabaseError::InvalidData(
+                format!("Invalid PBO model: {}", pbo.id)
+            ));
+        }
+        
+        self.db.with_transaction(|tx| {
+            Self::upsert_pbo(tx, pbo)?;
+            
+            tx.execute("DELETE FROM extracted_files WHERE pbo_id = ?1", [&pbo.id])?;
+            Self::insert_extracted_files(tx, &pbo.id, extracted_files)?;
+            
+            tx.execute("DELETE FROM pbo_entries WHERE pbo_id = ?1", [&pbo.id])?;
+            let mut stmt = tx.prepare(
+                "INSERT INTO pbo_entries (pbo_id, path, extension, size, timestamp)
+                 VALUES (?1, ?2, ?3, ?4, ?5)"
+            )?;
+            for entry in entries {
                 stmt.execute(params![
-                    pbo_id,
-                    path.to_string_lossy(),
+                    pbo.id,
+                    entry.path,
+                    entry.get_extension(),
+                    entry.size,
+                    entry.timestamp,
                 ])?;
             }
             
-            // Store extensions metadata
-            tx.execute(
-                "INSERT OR REPLACE INTO metadata (key, value)
-                 VALUES (?1, ?2)",
+            tx.execute("DELETE FROM failed_extractions WHERE pbo_id = ?1", [&pbo.id])?;
+            
+            debug!("Recorded extraction of {} with {} files and {} entries",
+                pbo.id, extracted_files.len(), entries.len());
+            Ok(())
+        })
+    }
+    
+    /// Get the entry table of a PBO
+    pub fn get_entries(&self, pbo_id: &str) -> Result<Vec<PboEntryModel>> {
+        self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT pbo_id, path, size, timestamp
+                 FROM pbo_entries
+                 WHERE pbo_id = ?1
+                 ORDER BY id"
+            )?;
+            
+            let rows = stmt.query_map([pbo_id], |row| self.map_row_to_entry(row))?;
+            
+            let mut entries = Vec::new();
+            for row_result in rows {
+                entries.push(row_result?);
+            }
+            
+            Ok(entries)
+        })
+    }
+    
+    /// Get the entry tables of many PBOs, grouped by PBO ID
+    ///
+    /// PBOs without entries are left out of the map.
+    pub fn get_entries_for(&self, pbo_ids: &[String]) -> Result<HashMap<String, Vec<PboEntryModel>>> {
+        self.db.with_connection(|conn| {
+            let mut entries: HashMap<String, Vec<PboEntryModel>> = HashMap::new();
+            for chunk in pbo_ids.chunks(MAX_IDS_PER_QUERY) {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT pbo_id, path, size, timestamp
+                     FROM pbo_entries
+                     WHERE pbo_id IN ({})
+                     ORDER BY id",
+                    id_placeholders(chunk.len())
+                ))?;
+                
+                let rows = stmt.query_map(params_from_iter(chunk), |row| self.map_row_to_entry(row))?;
+                for row_result in rows {
+                    let entry = row_result?;
+                    entries.entry(entry.pbo_id.clone()).or_default().push(entry);
+                }
+            }
+            
+            Ok(entries)
+        })
+    }
+    
+    /// Find entries with an extension that were never extracted
+    ///
+    /// Duplicates are left out, as their entries are those of the PBO whose
+    /// files they share.
+    pub fn find_unextracted_entries(&self, extension: &str) -> Result<Vec<PboEntryModel>> {
+        self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT e.pbo_id, e.path, e.size, e.timestamp
+                 FROM pbo_entries e
+                 JOIN pbo_files p ON p.id = e.pbo_id
+                 WHERE e.extension = ?1 AND p.duplicate_of IS NULL
+                   AND NOT EXISTS (
+                       SELECT 1 FROM extracted_files f
+                       WHERE f.pbo_id = e.pbo_id AND f.relative_path = e.path COLLATE NOCASE
+                   )
+                 ORDER BY e.pbo_id, e.path"
+            )?;
+            
+            let rows = stmt.query_map([extension.to_lowercase()], |row| self.map_row_to_entry(row))?;
+            
+            let mut entries = Vec::new();
+            for row_result in rows {
+                entries.push(row_result?);
+            }
+            
+            Ok(entries)
+        })
+    }
+    
+    /// Find the PBOs of a type with the given content hash that own their files
+    pub fn find_by_content_hash(&self, content_hash: &str, pbo_type: PboType) -> Result<Vec<PboModel>> {
+        self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, full_path, base_dir, file_size, 
+                        last_modified, extraction_time, pbo_type,
+                        mod_folder, mod_name, mod_published_id, mod_timestamp,
+                        used_extensions, content_hash, duplicate_of, signature_status, extraction_stats
+                 FROM pbo_files
+                 WHERE content_hash = ?1 AND pbo_type = ?2 AND duplicate_of IS NULL
+                 ORDER BY id"
+            )?;
+            
+            let rows = stmt.query_map(params![content_hash, pbo_type.to_string()], |row| self.map_row_to_pbo(row))?;
+            
+            let mut pbos = Vec::new();
+            for row_result in rows {
+                pbos.push(row_result?);
+            }
+            
+            Ok(pbos)
+        })
+    }
+    
+    /// Get the PBOs sharing the extracted files of another PBO
+    pub fn get_duplicates(&self, pbo_id: &str) -> Result<Vec<PboModel>> {
+        self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, full_path, base_dir, file_size, 
+                        last_modified, extraction_time, pbo_type,
+                        mod_folder, mod_name, mod_published_id, mod_timestamp,
+                        used_extensions, content_hash, duplicate_of, signature_status, extraction_stats
+                 FROM pbo_files WHERE duplicate_of = ?1 ORDER BY id"
+            )?;
+            
+            let rows = stmt.query_map([pbo_id], |row| self.map_row_to_pbo(row))?;
+            
+            let mut pbos = Vec::new();
+            for row_result in rows {
+                pbos.push(row_result?);
+            }
+            
+            Ok(pbos)
+        })
+    }
+    
+    /// Update the stored modification time of a PBO
+    pub fn update_last_modified(&self, pbo_id: &str, last_modified: DateTime<Utc>) -> Result<()> {
+        self.db.with_connection(|conn| {
+            conn.execute(
+                "UPDATE pbo_files SET last_modified = ?1 WHERE id = ?2",
+                params![last_modified.to_rfc3339(), pbo_id],
+            )?;
+            Ok(())
+        })
+    }
+    
+    /// Replace the mod a PBO belongs to
+    pub fn update_mod_info(&self, pbo_id: &str, mod_info: Option<&ModInfo>) -> Result<()> {
+        self.db.with_connection(|conn| {
+            conn.execute(
+                "UPDATE pbo_files
+                 SET mod_folder = ?1, mod_name = ?2, mod_published_id = ?3, mod_timestamp = ?4
+                 WHERE id = ?5",
                 params![
-                    format!("pbo_{}_extensions", pbo_id),
-                    extensions.join(","),
+                    mod_info.map(|m| m.folder.as_str()),
+                    mod_info.map(|m| m.name.as_str()),
+                    mod_info.and_then(|m| m.published_id),
+                    mod_info.and_then(|m| m.timestamp),
+                    pbo_id,
                 ],
             )?;
+            Ok(())
+        })
+    }
+    
+    /// Get the IDs of all PBOs with a failed extraction
+    pub fn get_failed_extraction_ids(&self) -> Result<Vec<String>> {
+        self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare("SELECT pbo_id FROM failed_extractions ORDER BY pbo_id")?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
             
-            // Remove from failed extractions if it exists
+            let mut ids = Vec::new();
+            for row_result in rows {
+                ids.push(row_result?);
+            }
+            
+            Ok(ids)
+        })
+    }
+    
+    /// Delete PBO records together with their extracted files, entries and failures
+    /// Hand the extracted files of a PBO over to one of its duplicates
+    ///
+    /// The duplicate becomes the owner of the files and the remaining
+    /// duplicates point at it, so the PBO can be deleted without losing its
+    /// file index.
+    pub fn promote_duplicate(&self, pbo_id: &str, heir_id: &str) -> Result<()> {
+        self.db.with_transaction(|tx| {
+            tx.execute("UPDATE pbo_files SET duplicate_of = NULL WHERE id = ?1", [heir_id])?;
             tx.execute(
-                "DELETE FROM failed_extractions WHERE pbo_id = ?1",
-                [pbo_id],
+                "UPDATE pbo_files SET duplicate_of = ?2 WHERE duplicate_of = ?1 AND id != ?2",
+                [pbo_id, heir_id],
             )?;
+            tx.execute("UPDATE extracted_files SET pbo_id = ?2 WHERE pbo_id = ?1", [pbo_id, heir_id])?;
+            tx.execute("UPDATE file_index_mapping SET pbo_id = ?2 WHERE pbo_id = ?1", [pbo_id, heir_id])?;
+            
+            debug!("Promoted {} to owner of the files of {}", heir_id, pbo_id);
+            Ok(())
+        })
+    }
+    
+    pub fn delete(&self, pbo_ids: &[String]) -> Result<()> {
+        self.db.with_transaction(|tx| {
+            for pbo_id in pbo_ids {
+                tx.execute("DELETE FROM extracted_files WHERE pbo_id = ?1", [pbo_id])?;
+                tx.execute("DELETE FROM pbo_entries WHERE pbo_id = ?1", [pbo_id])?;
+                tx.execute("DELETE FROM failed_extractions WHERE pbo_id = ?1", [pbo_id])?;
+                tx.execute("UPDATE file_index_mapping SET pbo_id = NULL WHERE pbo_id = ?1", [pbo_id])?;
+                tx.execute("DELETE FROM pbo_files WHERE id = ?1", [pbo_id])?;
+            }
             
+            debug!("Deleted {} PBO records", pbo_ids.len());
             Ok(())
         })
     }
     
+    /// Convert a database row to a PboEntryModel
+    fn map_row_to_failure(&self, row: &Row) -> rusqlite::Result<FailedExtraction> {
+        let timestamp_str: String = row.get(1)?;
+        let timestamp = DateTime::parse_from_rfc3339(&timestamp_str)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|_| rusqlite::Error::InvalidColumnType(1, "timestamp".to_string(), rusqlite::types::Type::Text))?;
+        
+        Ok(FailedExtraction {
+            pbo_id: row.get(0)?,
+            timestamp,
+            error_message: row.get(2)?,
+            pbo_path: PathBuf::from(row.get::<_, String>(3)?),
+            category: row.get(4)?,
+            retryable: row.get(5)?,
+            attempts: row.get(6)?,
+            content_hash: row.get(7)?,
+            tool_version: row.get(8)?,
+        })
+    }
+    
+    fn map_row_to_extracted_file(&self, row: &Row) -> rusqlite::Result<ExtractedFile> {
+        Ok(ExtractedFile {
+            id: row.get(0)?,
+            pbo_id: row.get(1)?,
+            relative_path: PathBuf::from(row.get::<_, String>(2)?),
+            extension: row.get(3)?,
+            file_name: row.get(4)?,
+            content_hash: row.get(5)?,
+        })
+    }
+    
+    fn map_row_to_entry(&self, row: &Row) -> rusqlite::Result<PboEntryModel> {
+        Ok(PboEntryModel {
+            pbo_id: row.get(0)?,
+            path: row.get(1)?,
+            size: row.get(2)?,
+            timestamp: row.get(3)?,
+        })
+    }
+    
     /// Convert a database row to a PboModel
     fn map_row_to_pbo(&self, row: &Row) -> rusqlite::Result<PboModel> {
         let last_modified_str: String = row.get(4)?;
@@ -528,6 +970,24 @@ impl<'a> Cache<'a> {
             _ => PboType::GameData, // Default
         };
         
+        let mod_folder: Option<String> = row.get(7)?;
+        let mod_info = match mod_folder {
+            Some(folder) => Some(ModInfo {
+                name: row.get::<_, Option<String>>(8)?.unwrap_or_else(|| folder.clone()),
+                folder,
+                published_id: row.get(9)?,
+                timestamp: row.get(10)?,
+            }),
+            None => None,
+        };
+        
+        let used_extensions = row.get::<_, Option<String>>(11)?
+            .map(|extensions| extensions.split(',')
+                .filter(|ext| !ext.is_empty())
+                .map(str::to_string)
+                .collect())
+            .unwrap_or_default();
+        
         Ok(PboModel {
             id: row.get(0)?,
             full_path: PathBuf::from(row.get::<_, String>(1)?),
@@ -536,6 +996,12 @@ impl<'a> Cache<'a> {
             last_modified,
             extraction_time,
             pbo_type,
+            mod_info,
+            used_extensions,
+            content_hash: row.get(12)?,
+            duplicate_of: row.get(13)?,
+            signature_status: row.get(14)?,
+            extraction_stats: row.get(15)?,
         })
     }
     
@@ -643,9 +1109,160 @@ mod tests {
         assert!(failed.is_some());
         assert_eq!(failed.unwrap().error_message, "Test error");
         
+        let mut retried = FailedExtraction::new("failed.pbo", "Timed out");
+        retried.category = "timeout".to_string();
+        retried.retryable = true;
+        retried.attempts = 2;
+        repo.record_failed_extraction_direct(&retried).unwrap();
+        let failures = repo.get_failed_extractions().unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].category, "timeout");
+        assert!(failures[0].retryable);
+        assert_eq!(failures[0].attempts, 2);
+        
         // Test needs extraction
         let needs = repo.needs_extraction("test.pbo", &["sqf".to_string()]).unwrap();
         // Note: This will return true since the PBO doesn't exist on disk
         assert!(needs);
     }
+    
+    #[test]
+    fn test_record_failure() {
+        let dir = tempdir().unwrap();
+        let db = DatabaseManager::new(dir.path().join("test.db")).unwrap();
+        let repo = Cache::new(&db);
+        
+        // A PBO that was never extracted gets a record to reference
+        let now = Utc::now();
+        let pbo = PboModel::new("@mod/addons/broken.pbo", PboType::Mission, 10, now, now);
+        repo.record_failure(&pbo, &FailedExtraction::new(&pbo.id, "Bad header")).unwrap();
+        assert_eq!(repo.get(&pbo.id).unwrap().unwrap().pbo_type, PboType::Mission);
+        assert_eq!(repo.get_failed_extraction(&pbo.id).unwrap().unwrap().error_message, "Bad header");
+        
+        // An existing record is kept
+        let mut extracted = pbo.clone();
+        extracted.used_extensions = vec!["sqf".to_string()];
+        repo.create(&extracted).unwrap();
+        repo.record_failure(&pbo, &FailedExtraction::new(&pbo.id, "Timed out")).unwrap();
+        assert_eq!(repo.get(&pbo.id).unwrap().unwrap().used_extensions, extracted.used_extensions);
+        assert_eq!(repo.get_failed_extraction(&pbo.id).unwrap().unwrap().error_message, "Timed out");
+        
+        // The failure has to be about the PBO it is recorded with
+        assert!(repo.record_failure(&pbo, &FailedExtraction::new("other.pbo", "Bad header")).is_err());
+    }
+    
+    #[test]
+    fn test_bulk_files_and_entries() {
+        let dir = tempdir().unwrap();
+        let db = DatabaseManager::new(dir.path().join("test.db")).unwrap();
+        let repo = Cache::new(&db);
+        
+        let now = Utc::now();
+        let mut ids = Vec::new();
+        for name in ["a.pbo", "b.pbo", "empty.pbo"] {
+            let pbo = PboModel::new(name, PboType::GameData, 10, now, now);
+            let (files, entries) = if name == "empty.pbo" {
+                (Vec::new(), Vec::new())
+            } else {
+                (
+                    vec![ExtractedFile::new(&pbo.id, "config.cpp"), ExtractedFile::new(&pbo.id, "fn_init.sqf")],
+                    vec![PboEntryModel { pbo_id: pbo.id.clone(), path: "config.bin".to_string(), size: 4, timestamp: 0 }],
+                )
+            };
+            repo.record_extraction(&pbo, &files, &entries).unwrap();
+            ids.push(pbo.id);
+        }
+        
+        let files = repo.get_extracted_files_for(&ids).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files["a.pbo"].len(), 2);
+        let paths: Vec<&Path> = files["b.pbo"].iter().map(|file| file.relative_path.as_path()).collect();
+        assert_eq!(paths, vec![Path::new("config.cpp"), Path::new("fn_init.sqf")]);
+        
+        let entries = repo.get_entries_for(&ids).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries["a.pbo"], repo.get_entries("a.pbo").unwrap());
+        assert!(!entries.contains_key("empty.pbo"));
+    }
+    
+    #[test]
+    fn test_pbo_mod_info() {
+        let dir = tempdir().unwrap();
+        let db = DatabaseManager::new(dir.path().join("test.db")).unwrap();
+        let repo = Cache::new(&db);
+        
+        let now = Utc::now();
+        let cba = ModInfo {
+            folder: "@CBA_A3".to_string(),
+            name: "Community Base Addons".to_string(),
+            published_id: Some(450814997),
+            timestamp: Some(5249751130849005548),
+        };
+        repo.create(&PboModel::new("@CBA_A3/addons/cba_main.pbo", PboType::GameData, 10, now, now)
+            .with_mod_info(cba.clone())).unwrap();
+        repo.create(&PboModel::new("@CBA_A3/addons/cba_xeh.pbo", PboType::GameData, 10, now, now)
+            .with_mod_info(cba.clone())).unwrap();
+        repo.create(&PboModel::new("addons/data_f.pbo", PboType::GameData, 10, now, now)).unwrap();
+        
+        let pbo = repo.get("@CBA_A3/addons/cba_main.pbo").unwrap().unwrap();
+        assert_eq!(pbo.mod_info, Some(cba.clone()));
+        assert_eq!(repo.get("addons/data_f.pbo").unwrap().unwrap().mod_info, None);
+        
+        assert_eq!(repo.get_by_mod("@cba_a3").unwrap().len(), 2);
+        assert_eq!(repo.get_mods().unwrap(), vec![cba]);
+    }
+    
+    #[test]
+    fn test_record_extraction() {
+        let dir = tempdir().unwrap();
+        let db = DatabaseManager::new(dir.path().join("test.db")).unwrap();
+        let repo = Cache::new(&db);
+        
+        let now = Utc::now();
+        let mut pbo = PboModel::new("@mod/addons/main.pbo", PboType::GameData, 10, now, now);
+        pbo.used_extensions = vec!["cpp".to_string(), "sqf".to_string()];
+        pbo.content_hash = Some("abc".to_string());
+        let entries: Vec<PboEntryModel> = ["config.bin", "functions/fn_init.sqf", "data/logo.PAA"].iter()
+            .map(|path| PboEntryModel { pbo_id: pbo.id.clone(), path: path.to_string(), size: 4, timestamp: 0 })
+            .collect();
+        
+        repo.record_failed_extraction_direct(&FailedExtraction::new(&pbo.id, "Earlier failure")).unwrap();
+        let files = [
+            ExtractedFile::new(&pbo.id, "config.cpp").with_content_hash(Some("c0ffee".to_string())),
+            ExtractedFile::new(&pbo.id, "functions/fn_init.sqf"),
+        ];
+        repo.record_extraction(&pbo, &files, &entries).unwrap();
+        
+        let stored = repo.get(&pbo.id).unwrap().unwrap();
+        assert_eq!(stored.used_extensions, pbo.used_extensions);
+        assert_eq!(stored.content_hash, pbo.content_hash);
+        assert_eq!(repo.get_entries(&pbo.id).unwrap(), entries);
+        assert!(repo.get_failed_extraction(&pbo.id).unwrap().is_none());
+        let stored_files = repo.get_extracted_files(&pbo.id).unwrap();
+        assert_eq!(stored_files[0].content_hash.as_deref(), Some("c0ffee"));
+        assert_eq!(stored_files[1].content_hash, None);
+        
+        // Only entries that were not extracted are reported
+        let textures = repo.find_unextracted_entries("paa").unwrap();
+        assert_eq!(textures.len(), 1);
+        assert_eq!(textures[0].path, "data/logo.PAA");
+        assert!(repo.find_unextracted_entries("sqf").unwrap().is_empty());
+        
+        // Files are found by their full relative path or a path ending in it
+        assert_eq!(repo.find_pbo_by_file_path("functions/fn_init.sqf").unwrap().unwrap().id, pbo.id);
+        assert_eq!(repo.find_pbo_by_file_path("/cache/gamedata/functions/fn_init.sqf").unwrap().unwrap().id, pbo.id);
+        
+        // A duplicate shares the original's files
+        let mut copy = PboModel::new("@copy/addons/main.pbo", PboType::GameData, 10, now, now);
+        copy.content_hash = pbo.content_hash.clone();
+        copy.duplicate_of = Some(pbo.id.clone());
+        repo.record_extraction(&copy, &[], &[]).unwrap();
+        assert_eq!(repo.find_by_content_hash("abc", PboType::GameData).unwrap().len(), 1);
+        assert_eq!(repo.get_duplicates(&pbo.id).unwrap()[0].id, copy.id);
+        
+        repo.delete(&[pbo.id.clone(), copy.id.clone()]).unwrap();
+        assert!(repo.get(&pbo.id).unwrap().is_none());
+        assert!(repo.get_entries(&pbo.id).unwrap().is_empty());
+        assert!(repo.get_extracted_files(&pbo.id).unwrap().is_empty());
+    }
 } 
\ No newline at end of file
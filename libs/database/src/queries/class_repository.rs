@@ -3,7 +3,7 @@ use rusqlite::{params, Row, OptionalExtension};
 use std::collections::HashMap;
 
 use crate::error::{Result, DatabaseError};
-use crate::models::class::{ClassModel, ClassHierarchyNode};
+use crate::models::class::{location_column, location_from_row, ClassModel, ClassHierarchyNode};
 use crate::DatabaseManager;
 
 /// Repository for class-related database operations
@@ -21,14 +21,19 @@ impl<'a> ClassRepository<'a> {
     pub fn create(&self, class: &ClassModel) -> Result<()> {
         self.db.with_connection(|conn| {
             conn.execute(
-                "INSERT OR REPLACE INTO classes (id, parent_id, container_class, source_file_index, is_forward_declaration)
-                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                "INSERT OR REPLACE INTO classes (id, parent_id, container_class, source_file_index, is_forward_declaration,
+                                                 start_line, start_column, end_line, end_column)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
                 params![
                     class.id,
                     class.parent_id,
                     class.container_class,
                     convert_opt_usize_to_i64(class.source_file_index),
                     class.is_forward_declaration,
+                    location_column(class.location, |l| l.start_line),
+                    location_column(class.location, |l| l.start_column),
+                    location_column(class.location, |l| l.end_line),
+                    location_column(class.location, |l| l.end_column),
                 ],
             )?;
             
@@ -40,7 +45,8 @@ impl<'a> ClassRepository<'a> {
     pub fn get(&self, id: &str) -> Result<Option<ClassModel>> {
         self.db.with_connection(|conn| {
             let result = conn.query_row(
-                "SELECT id, parent_id, container_class, source_file_index, is_forward_declaration
+                "SELECT id, parent_id, container_class, source_file_index, is_forward_declaration,
+                        start_line, start_column, end_line, end_column
                  FROM classes WHERE id = ?1",
                 [id],
                 |row| self.map_row_to_class(row),
@@ -54,7 +60,8 @@ impl<'a> ClassRepository<'a> {
     pub fn update(&self, class: &ClassModel) -> Result<()> {
         self.db.with_connection(|conn| {
             let rows_affected = conn.execute(
-                "UPDATE classes SET parent_id = ?2, container_class = ?3, source_file_index = ?4, is_forward_declaration = ?5
+                "UPDATE classes SET parent_id = ?2, container_class = ?3, source_file_index = ?4, is_forward_declaration = ?5,
+                                   start_line = ?6, start_column = ?7, end_line = ?8, end_column = ?9
                  WHERE id = ?1",
                 params![
                     class.id,
@@ -62,6 +69,10 @@ impl<'a> ClassRepository<'a> {
                     class.container_class,
                     convert_opt_usize_to_i64(class.source_file_index),
                     class.is_forward_declaration,
+                    location_column(class.location, |l| l.start_line),
+                    location_column(class.location, |l| l.start_column),
+                    location_column(class.location, |l| l.end_line),
+                    location_column(class.location, |l| l.end_column),
                 ],
             )?;
             
@@ -99,7 +110,8 @@ impl<'a> ClassRepository<'a> {
     pub fn get_all(&self) -> Result<Vec<ClassModel>> {
         self.db.with_connection(|conn| {
             let mut stmt = conn.prepare(
-                "SELECT id, parent_id, container_class, source_file_index, is_forward_declaration
+                "SELECT id, parent_id, container_class, source_file_index, is_forward_declaration,
+                        start_line, start_column, end_line, end_column
                  FROM classes ORDER BY id"
             )?;
             
@@ -118,7 +130,8 @@ impl<'a> ClassRepository<'a> {
     pub fn find_by_parent(&self, parent_id: &str) -> Result<Vec<ClassModel>> {
         self.db.with_connection(|conn| {
             let mut stmt = conn.prepare(
-                "SELECT id, parent_id, container_class, source_file_index, is_forward_declaration
+                "SELECT id, parent_id, container_class, source_file_index, is_forward_declaration,
+                        start_line, start_column, end_line, end_column
                  FROM classes WHERE parent_id = ?1"
             )?;
             
@@ -142,7 +155,8 @@ impl<'a> ClassRepository<'a> {
     pub fn get_root_classes(&self) -> Result<Vec<ClassModel>> {
         self.db.with_connection(|conn| {
             let mut stmt = conn.prepare(
-                "SELECT id, parent_id, container_class, source_file_index, is_forward_declaration
+                "SELECT id, parent_id, container_class, source_file_index, is_forward_declaration,
+                        start_line, start_column, end_line, end_column
                  FROM classes WHERE parent_id IS NULL"
             )?;
             
@@ -276,7 +290,8 @@ impl<'a> ClassRepository<'a> {
                 .join(",");
                 
             let query = format!(
-                "SELECT id, parent_id, container_class, source_file_index, is_forward_declaration
+                "SELECT id, parent_id, container_class, source_file_index, is_forward_declaration,
+                        start_line, start_column, end_line, end_column
                  FROM classes
                  WHERE parent_id IN ({})",
                 placeholders
@@ -315,19 +330,23 @@ impl<'a> ClassRepository<'a> {
                 .join(",");
                 
             let query = format!(
-                "WITH RECURSIVE affected_hierarchy(id, parent_id, container_class, source_file_index, is_forward_declaration, depth) AS (
-                    SELECT id, parent_id, container_class, source_file_index, is_forward_declaration, 0
+                "WITH RECURSIVE affected_hierarchy(id, parent_id, container_class, source_file_index, is_forward_declaration,
+                                                   start_line, start_column, end_line, end_column, depth) AS (
+                    SELECT id, parent_id, container_class, source_file_index, is_forward_declaration,
+                           start_line, start_column, end_line, end_column, 0
                     FROM classes
                     WHERE id IN ({})
                     
                     UNION ALL
                     
-                    SELECT c.id, c.parent_id, c.container_class, c.source_file_index, c.is_forward_declaration, a.depth + 1
+                    SELECT c.id, c.parent_id, c.container_class, c.source_file_index, c.is_forward_declaration,
+                           c.start_line, c.start_column, c.end_line, c.end_column, a.depth + 1
                     FROM classes c
                     JOIN affected_hierarchy a ON c.parent_id = a.id
                     WHERE a.depth < ?{}
                 )
-                SELECT id, parent_id, container_class, source_file_index, is_forward_declaration
+                SELECT id, parent_id, container_class, source_file_index, is_forward_declaration,
+                       start_line, start_column, end_line, end_column
                 FROM affected_hierarchy
                 WHERE depth > 0",  // Exclude the starting classes
                 placeholders,
@@ -367,8 +386,9 @@ impl<'a> ClassRepository<'a> {
         self.db.with_transaction(|tx| {
             // Prepare statement outside the loop
             let mut stmt = tx.prepare(
-                "INSERT OR REPLACE INTO classes (id, parent_id, container_class, source_file_index, is_forward_declaration)
-                 VALUES (?1, ?2, ?3, ?4, ?5)"
+                "INSERT OR REPLACE INTO classes (id, parent_id, container_class, source_file_index, is_forward_declaration,
+                                                 start_line, start_column, end_line, end_column)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"
             )?;
             
             // Insert all classes in a single transaction
@@ -379,6 +399,10 @@ impl<'a> ClassRepository<'a> {
                     class.container_class,
                     convert_opt_usize_to_i64(class.source_file_index),
                     class.is_forward_declaration,
+                    location_column(class.location, |l| l.start_line),
+                    location_column(class.location, |l| l.start_column),
+                    location_column(class.location, |l| l.end_line),
+                    location_column(class.location, |l| l.end_column),
                 ])?;
             }
             
@@ -427,6 +451,8 @@ impl<'a> ClassRepository<'a> {
             source_file_index: source_file_index.map(|idx| idx as usize),
             is_forward_declaration: row.get(4)?,
             properties: HashMap::new(),
+            location: location_from_row(row, 5)?,
+            property_locations: HashMap::new(),
         })
     }
     
@@ -669,6 +695,30 @@ mod tests {
         assert!(affected.iter().any(|c| c.id == "Class2" && !c.is_forward_declaration)); // Check flag
     }
 
+    #[test]
+    fn test_class_location_round_trip() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = DatabaseManager::new(&db_path).unwrap();
+        let repo = ClassRepository::new(&db);
+
+        let mut located = ClassModel::new("arifle_MX_F", None::<String>, None::<String>, Some(1), false);
+        located.location = Some(arma3_models::types::SourceLocation {
+            start_line: 120,
+            start_column: 5,
+            end_line: 138,
+            end_column: 6,
+        });
+        let unlocated = ClassModel::new("hgun_P07_F", None::<String>, None::<String>, Some(1), false);
+        repo.create(&located).unwrap();
+        repo.create(&unlocated).unwrap();
+
+        let location = repo.get("arifle_MX_F").unwrap().unwrap().location.unwrap();
+        assert_eq!((location.start_line, location.start_column), (120, 5));
+        assert_eq!((location.end_line, location.end_column), (138, 6));
+        assert!(repo.get("hgun_P07_F").unwrap().unwrap().location.is_none());
+    }
+
     #[test]
     fn test_inheritance_checking() {
         let dir = tempdir().unwrap();
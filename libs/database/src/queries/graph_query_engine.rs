@@ -173,10 +173,35 @@ impl<'a> GraphQueryEngine<'a> {
     }
     
     /// Build a PBO dependency graph
+    ///
+    /// Edges point from a required PBO to the PBO depending on it. When
+    /// addons are stored they come from `requiredAddons[]`, otherwise they
+    /// are inferred from classes inheriting across PBOs.
     pub fn build_pbo_dependency_graph(&self) -> Result<GraphData> {
         self.db.with_connection(|conn| {
+            let addon_count: i64 = conn.query_row("SELECT COUNT(*) FROM addons", [], |row| row.get(0))?;
+            
             // Use the file_index_mapping table to map indices to PBO IDs
-            let query = "
+            let addon_query = "
+                WITH index_to_pbo AS (
+                    SELECT file_index, 
+                           COALESCE(pbo_id, normalized_path) AS pbo_id
+                    FROM file_index_mapping
+                )
+                SELECT 
+                    required_idx.pbo_id AS required_pbo_id, 
+                    addon_idx.pbo_id AS addon_pbo_id,
+                    COUNT(*) AS dependency_count
+                FROM addon_requirements r
+                JOIN addons addon ON r.addon_name = addon.name
+                JOIN addons required ON r.required_addon = required.name
+                JOIN index_to_pbo addon_idx ON addon.source_file_index = addon_idx.file_index
+                JOIN index_to_pbo required_idx ON required.source_file_index = required_idx.file_index
+                WHERE required_idx.pbo_id != addon_idx.pbo_id
+                GROUP BY required_pbo_id, addon_pbo_id
+                ORDER BY dependency_count DESC
+            ";
+            let class_query = "
                 WITH source_indices AS (
                     SELECT c.id AS class_id, c.parent_id, c.source_file_index
                     FROM classes c
@@ -202,11 +227,12 @@ impl<'a> GraphQueryEngine<'a> {
                 ORDER BY dependency_count DESC
             ";
             
+            let query = if addon_count > 0 { addon_query } else { class_query };
             let mut stmt = conn.prepare(query)?;
             let rows = stmt.query_map([], |row| {
                 Ok((
-                    row.get::<_, String>(0)?, // parent_pbo_id
-                    row.get::<_, String>(1)?, // child_pbo_id
+                    row.get::<_, String>(0)?, // parent or required pbo_id
+                    row.get::<_, String>(1)?, // child or dependent pbo_id
                     row.get::<_, i64>(2)?,    // dependency_count
                 ))
             })?;
@@ -507,6 +533,8 @@ pub struct ImpactAnalysisResult {
 mod tests {
     use super::*;
     use crate::DatabaseManager;
+    use crate::models::addon::AddonModel;
+    use crate::queries::addon_repository::AddonRepository;
     use tempfile::tempdir;
     
     #[test]
@@ -556,4 +584,42 @@ mod tests {
         assert_eq!(graph.node_count(), 4);
         assert_eq!(graph.edge_count(), 3);
     }
+    
+    #[test]
+    fn test_pbo_dependency_graph_uses_addon_requirements() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = DatabaseManager::new(&db_path).unwrap();
+        
+        db.with_connection(|conn| {
+            for (index, path) in [(1, "a3/data_f.pbo"), (2, "cba/main.pbo"), (3, "mod/main.pbo")] {
+                conn.execute(
+                    "INSERT INTO file_index_mapping (file_index, file_path, normalized_path) VALUES (?1, ?2, ?2)",
+                    rusqlite::params![index, path],
+                )?;
+            }
+            Ok(())
+        }).unwrap();
+        
+        let mut data = AddonModel::new("A3_Data_F", Some(1));
+        data.units = vec!["C_Man_1".to_string()];
+        let mut cba = AddonModel::new("cba_main", Some(2));
+        cba.required_addons = vec!["A3_Data_F".to_string()];
+        let mut main = AddonModel::new("mod_main", Some(3));
+        main.required_addons = vec!["cba_main".to_string(), "a3_data_f".to_string(), "missing".to_string()];
+        AddonRepository::new(&db).bulk_import(&[data, cba, main]).unwrap();
+        
+        let graph = GraphQueryEngine::new(&db).build_pbo_dependency_graph().unwrap();
+        
+        assert_eq!(graph.nodes.len(), 3);
+        let mut edges: Vec<(&str, &str)> = graph.edges.iter()
+            .map(|edge| (edge.source.as_str(), edge.target.as_str()))
+            .collect();
+        edges.sort_unstable();
+        assert_eq!(edges, vec![
+            ("a3/data_f.pbo", "cba/main.pbo"),
+            ("a3/data_f.pbo", "mod/main.pbo"),
+            ("cba/main.pbo", "mod/main.pbo"),
+        ]);
+    }
 }
\ No newline at end of file
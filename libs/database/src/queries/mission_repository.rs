@@ -22,8 +22,8 @@ impl<'a> MissionRepository<'a> {
     pub fn create(&self, mission: &MissionModel) -> Result<()> {
         self.db.with_connection(|conn| {
             conn.execute(
-                "INSERT OR REPLACE INTO missions (id, name, path, source_pbo_id, author, description, scan_time)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                "INSERT OR REPLACE INTO missions (id, name, path, source_pbo_id, author, description, scan_time, source_archive)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
                 params![
                     mission.id,
                     mission.name,
@@ -32,6 +32,7 @@ impl<'a> MissionRepository<'a> {
                     mission.author,
                     mission.description,
                     mission.scan_time.to_rfc3339(),
+                    mission.source_archive,
                 ],
             )?;
             
@@ -43,7 +44,7 @@ impl<'a> MissionRepository<'a> {
     pub fn get(&self, id: &str) -> Result<Option<MissionModel>> {
         self.db.with_connection(|conn| {
             let result = conn.query_row(
-                "SELECT id, name, path, source_pbo_id, author, description, scan_time
+                "SELECT id, name, path, source_pbo_id, author, description, scan_time, source_archive
                  FROM missions WHERE id = ?1",
                 [id],
                 |row| self.map_row_to_mission(row),
@@ -58,7 +59,7 @@ impl<'a> MissionRepository<'a> {
         self.db.with_connection(|conn| {
             let rows_affected = conn.execute(
                 "UPDATE missions 
-                 SET name = ?2, path = ?3, source_pbo_id = ?4, author = ?5, description = ?6, scan_time = ?7
+                 SET name = ?2, path = ?3, source_pbo_id = ?4, author = ?5, description = ?6, scan_time = ?7, source_archive = ?8
                  WHERE id = ?1",
                 params![
                     mission.id,
@@ -68,6 +69,7 @@ impl<'a> MissionRepository<'a> {
                     mission.author,
                     mission.description,
                     mission.scan_time.to_rfc3339(),
+                    mission.source_archive,
                 ],
             )?;
             
@@ -118,7 +120,7 @@ impl<'a> MissionRepository<'a> {
     pub fn get_all(&self) -> Result<Vec<MissionModel>> {
         self.db.with_connection(|conn| {
             let mut stmt = conn.prepare(
-                "SELECT id, name, path, source_pbo_id, author, description, scan_time
+                "SELECT id, name, path, source_pbo_id, author, description, scan_time, source_archive
                  FROM missions ORDER BY name"
             )?;
             
@@ -308,8 +310,8 @@ impl<'a> MissionRepository<'a> {
             
             // Insert mission
             tx.execute(
-                "INSERT OR REPLACE INTO missions (id, name, path, source_pbo_id, author, description, scan_time)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                "INSERT OR REPLACE INTO missions (id, name, path, source_pbo_id, author, description, scan_time, source_archive)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
                 params![
                     mission_model.id,
                     mission_model.name,
@@ -318,6 +320,7 @@ impl<'a> MissionRepository<'a> {
                     mission_model.author,
                     mission_model.description,
                     mission_model.scan_time.to_rfc3339(),
+                    mission_model.source_archive,
                 ],
             )?;
             
@@ -415,7 +418,7 @@ impl<'a> MissionRepository<'a> {
     pub fn find_by_class_dependency(&self, class_name: &str) -> Result<Vec<MissionModel>> {
         self.db.with_connection(|conn| {
             let mut stmt = conn.prepare(
-                "SELECT DISTINCT m.id, m.name, m.path, m.source_pbo_id, m.author, m.description, m.scan_time
+                "SELECT DISTINCT m.id, m.name, m.path, m.source_pbo_id, m.author, m.description, m.scan_time, m.source_archive
                  FROM missions m
                  JOIN mission_dependencies d ON m.id = d.mission_id
                  WHERE d.class_name = ?1
@@ -461,6 +464,7 @@ impl<'a> MissionRepository<'a> {
             author: row.get(4)?,
             description: row.get(5)?,
             scan_time,
+            source_archive: row.get(7)?,
         })
     }
     
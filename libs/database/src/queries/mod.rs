@@ -1,4 +1,6 @@
+pub mod addon_repository;
 pub mod class_repository;
 pub mod mission_repository;
+pub mod resolved_property_repository;
 pub mod cache;
 pub mod graph_query_engine; 
\ No newline at end of file
@@ -0,0 +1,138 @@
+use rusqlite::{params, Row};
+
+use crate::error::Result;
+use crate::models::class::{location_column, location_from_row};
+use crate::models::resolved::ResolvedPropertyModel;
+use crate::DatabaseManager;
+
+/// Columns read into a `ResolvedPropertyModel`, in `map_row_to_property` order
+const PROPERTY_COLUMNS: &str = "class_path, property_name, property_value, addon_name, source_file_index,
+     start_line, start_column, end_line, end_column";
+
+/// Repository for the effective class properties once every addon is loaded
+pub struct ResolvedPropertyRepository<'a> {
+    db: &'a DatabaseManager,
+}
+
+impl<'a> ResolvedPropertyRepository<'a> {
+    /// Create a new resolved property repository
+    pub fn new(db: &'a DatabaseManager) -> Self {
+        Self { db }
+    }
+
+    /// Replace all stored properties with `properties` in one transaction
+    pub fn replace_all(&self, properties: &[ResolvedPropertyModel]) -> Result<()> {
+        self.db.with_transaction(|tx| {
+            tx.execute("DELETE FROM resolved_properties", [])?;
+
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO resolved_properties (class_path, property_name, property_type, property_value,
+                                                             addon_name, source_file_index,
+                                                             start_line, start_column, end_line, end_column)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"
+            )?;
+            for property in properties {
+                stmt.execute(params![
+                    property.class_path,
+                    property.name,
+                    property.value.type_name(),
+                    serde_json::to_string(&property.value)?,
+                    property.addon,
+                    property.source_file_index.map(|index| index as i64),
+                    location_column(property.location, |l| l.start_line),
+                    location_column(property.location, |l| l.start_column),
+                    location_column(property.location, |l| l.end_line),
+                    location_column(property.location, |l| l.end_column),
+                ])?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Get the properties of a class by its path, ignoring case, ordered by name
+    pub fn get_for_class(&self, class_path: &str) -> Result<Vec<ResolvedPropertyModel>> {
+        self.query(
+            &format!("SELECT {PROPERTY_COLUMNS} FROM resolved_properties
+                      WHERE class_path = ?1 ORDER BY property_name"),
+            class_path,
+        )
+    }
+
+    /// Get the properties an addon set last, ordered by class path and name
+    pub fn get_by_addon(&self, addon: &str) -> Result<Vec<ResolvedPropertyModel>> {
+        self.query(
+            &format!("SELECT {PROPERTY_COLUMNS} FROM resolved_properties
+                      WHERE addon_name = ?1 ORDER BY class_path, property_name"),
+            addon,
+        )
+    }
+
+    fn query(&self, sql: &str, param: &str) -> Result<Vec<ResolvedPropertyModel>> {
+        self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(sql)?;
+            let rows = stmt.query_map([param], map_row_to_property)?;
+
+            let mut properties = Vec::new();
+            for row_result in rows {
+                properties.push(row_result?);
+            }
+            Ok(properties)
+        })
+    }
+}
+
+/// Convert a database row to a property
+fn map_row_to_property(row: &Row) -> rusqlite::Result<ResolvedPropertyModel> {
+    let value: String = row.get(2)?;
+    let source_file_index: Option<i64> = row.get(4)?;
+
+    Ok(ResolvedPropertyModel {
+        class_path: row.get(0)?,
+        name: row.get(1)?,
+        value: serde_json::from_str(&value).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        addon: row.get(3)?,
+        source_file_index: source_file_index.map(|index| index as usize),
+        location: location_from_row(row, 5)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arma3_models::types::{PropertyValue, SourceLocation};
+    use tempfile::tempdir;
+
+    fn property(class_path: &str, name: &str, value: PropertyValue, addon: &str) -> ResolvedPropertyModel {
+        ResolvedPropertyModel {
+            class_path: class_path.to_string(),
+            name: name.to_string(),
+            value,
+            addon: addon.to_string(),
+            source_file_index: Some(1),
+            location: None,
+        }
+    }
+
+    #[test]
+    fn test_resolved_property_repository() {
+        let dir = tempdir().unwrap();
+        let db = DatabaseManager::new(&dir.path().join("test.db")).unwrap();
+        let repo = ResolvedPropertyRepository::new(&db);
+
+        let mut scope = property("CfgWeapons/Rifle", "scope", PropertyValue::Integer(2), "mod_main");
+        scope.location = Some(SourceLocation::new(3, 5, 3, 14));
+        let mass = property("CfgWeapons/Rifle", "mass", PropertyValue::Number(12.5), "A3_Weapons_F");
+        let other = property("CfgWeapons/Pistol", "scope", PropertyValue::Integer(1), "mod_main");
+        repo.replace_all(&[scope.clone(), mass.clone(), other.clone()]).unwrap();
+
+        assert_eq!(repo.get_for_class("cfgweapons/rifle").unwrap(), vec![mass.clone(), scope.clone()]);
+        assert_eq!(repo.get_by_addon("MOD_MAIN").unwrap(), vec![other, scope]);
+
+        // A later resolve replaces everything stored before
+        repo.replace_all(&[mass.clone()]).unwrap();
+        assert_eq!(repo.get_for_class("CfgWeapons/Rifle").unwrap(), vec![mass]);
+        assert!(repo.get_by_addon("mod_main").unwrap().is_empty());
+    }
+}
@@ -5,9 +5,8 @@ use std::collections::HashMap;
 use serde_json;
 
 use crate::error::Result;
-use crate::models::class::ClassModel;
+use crate::models::class::{location_column, location_from_row, ClassModel};
 use crate::DatabaseManager;
-use arma3_models::types::PropertyValue;
 
 /// Repository for class-related database operations
 pub struct ClassRepository<'a> {
@@ -25,36 +24,41 @@ impl<'a> ClassRepository<'a> {
         self.db.with_transaction(|tx| {
             // Insert class
             tx.execute(
-                "INSERT OR REPLACE INTO classes (id, parent_id, container_class, source_file_index)
-                 VALUES (?1, ?2, ?3, ?4)",
+                "INSERT OR REPLACE INTO classes (id, parent_id, container_class, source_file_index,
+                                                 start_line, start_column, end_line, end_column)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
                 params![
                     class.id,
                     class.parent_id,
                     class.container_class,
                     convert_opt_usize_to_i64(class.source_file_index),
+                    location_column(class.location, |l| l.start_line),
+                    location_column(class.location, |l| l.start_column),
+                    location_column(class.location, |l| l.end_line),
+                    location_column(class.location, |l| l.end_column),
                 ],
             )?;
             
             // Insert properties
             for (name, value) in &class.properties {
-                let value_type = match value {
-                    PropertyValue::String(_) => "string",
-                    PropertyValue::Number(_) => "number",
-                    PropertyValue::Array(_) => "array",
-                    PropertyValue::Boolean(_) => "boolean",
-                    PropertyValue::Object(_) => "object",
-                };
+                let value_type = value.type_name();
                 
                 let serialized_value = serde_json::to_string(value)?;
+                let location = class.property_locations.get(name).copied();
                 
                 tx.execute(
-                    "INSERT INTO class_properties (class_id, property_name, property_type, property_value)
-                     VALUES (?1, ?2, ?3, ?4)",
+                    "INSERT INTO class_properties (class_id, property_name, property_type, property_value,
+                                                   start_line, start_column, end_line, end_column)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
                     params![
                         class.id,
                         name,
                         value_type,
                         serialized_value,
+                        location_column(location, |l| l.start_line),
+                        location_column(location, |l| l.start_column),
+                        location_column(location, |l| l.end_line),
+                        location_column(location, |l| l.end_column),
                     ],
                 )?;
             }
@@ -93,13 +97,15 @@ impl<'a> ClassRepository<'a> {
             let mut processed_ids = HashSet::new();
             
             let mut class_stmt = tx.prepare(
-                "INSERT INTO classes (id, parent_id, container_class, source_file_index)
-                VALUES (?1, ?2, ?3, ?4)"
+                "INSERT INTO classes (id, parent_id, container_class, source_file_index,
+                                      start_line, start_column, end_line, end_column)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
             )?;
             
             let mut prop_stmt = tx.prepare(
-                "INSERT INTO class_properties (class_id, property_name, property_type, property_value)
-                VALUES (?1, ?2, ?3, ?4)"
+                "INSERT INTO class_properties (class_id, property_name, property_type, property_value,
+                                               start_line, start_column, end_line, end_column)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
             )?;
             
             // Process in two rounds - first add all classes without parent (roots)
@@ -114,25 +120,28 @@ impl<'a> ClassRepository<'a> {
                         class.parent,
                         class.container_class,
                         convert_opt_usize_to_i64(class.source_file_index),
+                        location_column(class.location, |l| l.start_line),
+                        location_column(class.location, |l| l.start_column),
+                        location_column(class.location, |l| l.end_line),
+                        location_column(class.location, |l| l.end_column),
                     ])?;
                     
                     // Insert properties
                     for (name, value) in &class.properties {
-                        let value_type = match value {
-                            PropertyValue::String(_) => "string",
-                            PropertyValue::Number(_) => "number",
-                            PropertyValue::Array(_) => "array",
-                            PropertyValue::Boolean(_) => "boolean",
-                            PropertyValue::Object(_) => "object",
-                        };
+                        let value_type = value.type_name();
                         
                         let serialized_value = serde_json::to_string(value)?;
+                        let location = class.property_locations.get(name).copied();
                         
                         prop_stmt.execute(params![
                             class.name,
                             name,
                             value_type,
                             serialized_value,
+                            location_column(location, |l| l.start_line),
+                            location_column(location, |l| l.start_column),
+                            location_column(location, |l| l.end_line),
+                            location_column(location, |l| l.end_column),
                         ])?;
                     }
                     
@@ -151,25 +160,28 @@ impl<'a> ClassRepository<'a> {
                         class.parent,
                         class.container_class,
                         convert_opt_usize_to_i64(class.source_file_index),
+                        location_column(class.location, |l| l.start_line),
+                        location_column(class.location, |l| l.start_column),
+                        location_column(class.location, |l| l.end_line),
+                        location_column(class.location, |l| l.end_column),
                     ])?;
                     
                     // Insert properties
                     for (name, value) in &class.properties {
-                        let value_type = match value {
-                            PropertyValue::String(_) => "string",
-                            PropertyValue::Number(_) => "number",
-                            PropertyValue::Array(_) => "array",
-                            PropertyValue::Boolean(_) => "boolean",
-                            PropertyValue::Object(_) => "object",
-                        };
+                        let value_type = value.type_name();
                         
                         let serialized_value = serde_json::to_string(value)?;
+                        let location = class.property_locations.get(name).copied();
                         
                         prop_stmt.execute(params![
                             class.name,
                             name,
                             value_type,
                             serialized_value,
+                            location_column(location, |l| l.start_line),
+                            location_column(location, |l| l.start_column),
+                            location_column(location, |l| l.end_line),
+                            location_column(location, |l| l.end_column),
                         ])?;
                     }
                     
@@ -239,7 +251,9 @@ impl<'a> ClassRepository<'a> {
         self.db.with_connection(|conn| {
             let mut stmt = conn.prepare(
                 "SELECT c.id, c.parent_id, c.container_class, c.source_file_index,
-                        p.property_name, p.property_type, p.property_value
+                        p.property_name, p.property_type, p.property_value,
+                        c.start_line, c.start_column, c.end_line, c.end_column,
+                        p.start_line, p.start_column, p.end_line, p.end_column
                  FROM classes c
                  LEFT JOIN class_properties p ON c.id = p.class_id
                  ORDER BY c.id"
@@ -253,6 +267,8 @@ impl<'a> ClassRepository<'a> {
                 let property_name: Option<String> = row.get(4)?;
                 let property_type: Option<String> = row.get(5)?;
                 let property_value: Option<String> = row.get(6)?;
+                let class_location = location_from_row(row, 7)?;
+                let property_location = location_from_row(row, 11)?;
                 
                 Ok((
                     class_id,
@@ -262,6 +278,8 @@ impl<'a> ClassRepository<'a> {
                     property_name,
                     property_type,
                     property_value,
+                    class_location,
+                    property_location,
                 ))
             })?;
             
@@ -276,21 +294,28 @@ impl<'a> ClassRepository<'a> {
                     property_name,
                     property_type,
                     property_value,
+                    class_location,
+                    property_location,
                 ) = row_result?;
                 
                 let class = class_map.entry(class_id.clone()).or_insert_with(|| {
-                    ClassModel::new(
+                    let mut class = ClassModel::new(
                         class_id,
                         parent_id,
                         container_class,
                         source_file_index.map(|idx| idx as usize),
                         false
-                    )
+                    );
+                    class.location = class_location;
+                    class
                 });
                 
                 // Add property if present
                 if let (Some(name), Some(_type), Some(value)) = (property_name, property_type, property_value) {
                     if let Ok(prop_value) = serde_json::from_str(&value) {
+                        if let Some(location) = property_location {
+                            class.property_locations.insert(name.clone(), location);
+                        }
                         class.properties.insert(name, prop_value);
                     }
                 }
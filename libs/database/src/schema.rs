@@ -5,7 +5,7 @@ use log::{info, debug};
 /// Database schema version
 /// NOTE: We do not support migration between schema versions.
 /// If the database schema version doesn't match, the database needs to be deleted and recreated.
-pub const SCHEMA_VERSION: i32 = 7;
+pub const SCHEMA_VERSION: i32 = 16;
 
 /// Initialize database schema
 pub fn initialize_schema(conn: &mut Connection) -> Result<()> {
@@ -85,6 +85,10 @@ fn create_tables(tx: &rusqlite::Transaction) -> Result<()> {
             container_class TEXT,
             source_file_index INTEGER,
             is_forward_declaration BOOLEAN NOT NULL DEFAULT 0,
+            start_line INTEGER,
+            start_column INTEGER,
+            end_line INTEGER,
+            end_column INTEGER,
             FOREIGN KEY(parent_id) REFERENCES classes(id)
         )",
         [],
@@ -98,6 +102,10 @@ fn create_tables(tx: &rusqlite::Transaction) -> Result<()> {
             property_name TEXT NOT NULL,
             property_type TEXT NOT NULL,
             property_value TEXT NOT NULL,
+            start_line INTEGER,
+            start_column INTEGER,
+            end_line INTEGER,
+            end_column INTEGER,
             FOREIGN KEY(class_id) REFERENCES classes(id) ON DELETE CASCADE
         )",
         [],
@@ -112,7 +120,16 @@ fn create_tables(tx: &rusqlite::Transaction) -> Result<()> {
             file_size INTEGER NOT NULL,
             last_modified TEXT NOT NULL,
             extraction_time TEXT NOT NULL,
-            pbo_type TEXT NOT NULL
+            pbo_type TEXT NOT NULL,
+            mod_folder TEXT,
+            mod_name TEXT,
+            mod_published_id INTEGER,
+            mod_timestamp INTEGER,
+            used_extensions TEXT,
+            content_hash TEXT,
+            duplicate_of TEXT,
+            signature_status TEXT,
+            extraction_stats TEXT
         )",
         [],
     )?;
@@ -125,6 +142,21 @@ fn create_tables(tx: &rusqlite::Transaction) -> Result<()> {
             relative_path TEXT NOT NULL,
             extension TEXT,
             file_name TEXT,
+            content_hash TEXT,
+            FOREIGN KEY(pbo_id) REFERENCES pbo_files(id)
+        )",
+        [],
+    )?;
+    
+    // Entry table of each PBO, including entries that were not extracted
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS pbo_entries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            pbo_id TEXT NOT NULL,
+            path TEXT NOT NULL,
+            extension TEXT,
+            size INTEGER NOT NULL,
+            timestamp INTEGER NOT NULL,
             FOREIGN KEY(pbo_id) REFERENCES pbo_files(id)
         )",
         [],
@@ -136,6 +168,12 @@ fn create_tables(tx: &rusqlite::Transaction) -> Result<()> {
             pbo_id TEXT PRIMARY KEY,
             timestamp TEXT NOT NULL,
             error_message TEXT NOT NULL,
+            pbo_path TEXT NOT NULL,
+            category TEXT NOT NULL DEFAULT 'unknown',
+            retryable INTEGER NOT NULL DEFAULT 0,
+            attempts INTEGER NOT NULL DEFAULT 1,
+            content_hash TEXT,
+            tool_version TEXT,
             FOREIGN KEY(pbo_id) REFERENCES pbo_files(id)
         )",
         [],
@@ -160,6 +198,7 @@ fn create_tables(tx: &rusqlite::Transaction) -> Result<()> {
             name TEXT NOT NULL,
             path TEXT NOT NULL,
             source_pbo_id TEXT,
+            source_archive TEXT,
             author TEXT,
             description TEXT,
             scan_time TEXT NOT NULL,
@@ -197,6 +236,71 @@ fn create_tables(tx: &rusqlite::Transaction) -> Result<()> {
         [],
     )?;
     
+    // Addons table, one row per CfgPatches entry
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS addons (
+            name TEXT PRIMARY KEY COLLATE NOCASE,
+            source_file_index INTEGER,
+            required_version REAL,
+            author TEXT
+        )",
+        [],
+    )?;
+    
+    // Addon requirements table (requiredAddons[])
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS addon_requirements (
+            addon_name TEXT NOT NULL COLLATE NOCASE,
+            required_addon TEXT NOT NULL COLLATE NOCASE,
+            position INTEGER NOT NULL,
+            PRIMARY KEY(addon_name, position),
+            FOREIGN KEY(addon_name) REFERENCES addons(name) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    
+    // Addon units table (units[])
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS addon_units (
+            addon_name TEXT NOT NULL COLLATE NOCASE,
+            class_name TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            PRIMARY KEY(addon_name, position),
+            FOREIGN KEY(addon_name) REFERENCES addons(name) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    
+    // Addon weapons table (weapons[])
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS addon_weapons (
+            addon_name TEXT NOT NULL COLLATE NOCASE,
+            class_name TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            PRIMARY KEY(addon_name, position),
+            FOREIGN KEY(addon_name) REFERENCES addons(name) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    
+    // Resolved properties table, the effective values once every addon is loaded
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS resolved_properties (
+            class_path TEXT NOT NULL COLLATE NOCASE,
+            property_name TEXT NOT NULL COLLATE NOCASE,
+            property_type TEXT NOT NULL,
+            property_value TEXT NOT NULL,
+            addon_name TEXT NOT NULL COLLATE NOCASE,
+            source_file_index INTEGER,
+            start_line INTEGER,
+            start_column INTEGER,
+            end_line INTEGER,
+            end_column INTEGER,
+            PRIMARY KEY(class_path, property_name)
+        )",
+        [],
+    )?;
+    
     debug!("Database tables created successfully");
     Ok(())
 }
@@ -221,6 +325,26 @@ fn create_indexes(tx: &rusqlite::Transaction) -> Result<()> {
         [],
     )?;
     
+    // PBO indexes
+    tx.execute(
+        "CREATE INDEX IF NOT EXISTS idx_pbo_files_mod ON pbo_files(mod_folder)",
+        [],
+    )?;
+    tx.execute(
+        "CREATE INDEX IF NOT EXISTS idx_pbo_files_hash ON pbo_files(content_hash)",
+        [],
+    )?;
+    
+    // PBO entry indexes
+    tx.execute(
+        "CREATE INDEX IF NOT EXISTS idx_pbo_entries_pbo ON pbo_entries(pbo_id)",
+        [],
+    )?;
+    tx.execute(
+        "CREATE INDEX IF NOT EXISTS idx_pbo_entries_ext ON pbo_entries(extension)",
+        [],
+    )?;
+    
     // Extracted files indexes
     tx.execute(
         "CREATE INDEX IF NOT EXISTS idx_extracted_files_pbo ON extracted_files(pbo_id)",
@@ -238,6 +362,10 @@ fn create_indexes(tx: &rusqlite::Transaction) -> Result<()> {
         "CREATE INDEX IF NOT EXISTS idx_extracted_files_name ON extracted_files(file_name)",
         [],
     )?;
+    tx.execute(
+        "CREATE INDEX IF NOT EXISTS idx_extracted_files_hash ON extracted_files(content_hash)",
+        [],
+    )?;
     
     // File index mapping indexes
     tx.execute(
@@ -265,6 +393,30 @@ fn create_indexes(tx: &rusqlite::Transaction) -> Result<()> {
         [],
     )?;
     
+    // Addon indexes
+    tx.execute(
+        "CREATE INDEX IF NOT EXISTS idx_addons_source ON addons(source_file_index)",
+        [],
+    )?;
+    tx.execute(
+        "CREATE INDEX IF NOT EXISTS idx_addon_requirements_required ON addon_requirements(required_addon)",
+        [],
+    )?;
+    tx.execute(
+        "CREATE INDEX IF NOT EXISTS idx_addon_units_class ON addon_units(class_name)",
+        [],
+    )?;
+    tx.execute(
+        "CREATE INDEX IF NOT EXISTS idx_addon_weapons_class ON addon_weapons(class_name)",
+        [],
+    )?;
+    
+    // Resolved property indexes
+    tx.execute(
+        "CREATE INDEX IF NOT EXISTS idx_resolved_properties_addon ON resolved_properties(addon_name)",
+        [],
+    )?;
+    
     debug!("Database indexes created successfully");
     Ok(())
 }
@@ -297,10 +449,14 @@ mod tests {
         assert!(tables.contains(&"classes".to_string()));
         assert!(tables.contains(&"pbo_files".to_string()));
         assert!(tables.contains(&"extracted_files".to_string()));
+        assert!(tables.contains(&"pbo_entries".to_string()));
         assert!(tables.contains(&"failed_extractions".to_string()));
         assert!(tables.contains(&"missions".to_string()));
         assert!(tables.contains(&"mission_components".to_string()));
         assert!(tables.contains(&"mission_dependencies".to_string()));
+        assert!(tables.contains(&"addons".to_string()));
+        assert!(tables.contains(&"addon_requirements".to_string()));
+        assert!(tables.contains(&"resolved_properties".to_string()));
         
         // Verify schema version
         let version: i32 = conn
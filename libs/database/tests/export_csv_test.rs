@@ -174,6 +174,8 @@ fn create_test_classes() -> Vec<ClassModel> {
             container_class: None,
             source_file_index: None,
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: HashMap::new(),
         },
         
@@ -183,6 +185,8 @@ fn create_test_classes() -> Vec<ClassModel> {
             container_class: None,
             source_file_index: None,
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: HashMap::new(),
         },
         
@@ -193,6 +197,8 @@ fn create_test_classes() -> Vec<ClassModel> {
             container_class: Some("CfgVehicles".to_string()),
             source_file_index: Some(2), // Maps to A3/Soft_F
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: {
                 let mut props = HashMap::new();
                 props.insert("displayName".to_string(), PropertyValue::String("Car".to_string()));
@@ -208,6 +214,8 @@ fn create_test_classes() -> Vec<ClassModel> {
             container_class: Some("CfgWeapons".to_string()),
             source_file_index: Some(1), // Maps to A3/Weapons_F
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: {
                 let mut props = HashMap::new();
                 props.insert("displayName".to_string(), PropertyValue::String("Rifle Base".to_string()));
@@ -224,6 +232,8 @@ fn create_test_classes() -> Vec<ClassModel> {
             container_class: Some("CfgWeapons".to_string()),
             source_file_index: Some(1), // Maps to A3/Weapons_F
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: {
                 let mut props = HashMap::new();
                 props.insert("displayName".to_string(), PropertyValue::String("MX Base".to_string()));
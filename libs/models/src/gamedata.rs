@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
-use crate::types::PropertyValue;
+use crate::types::{PropertyValue, SourceLocation};
 
 /// Collection of game data classes
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,6 +60,14 @@ pub struct GameDataClass {
 
     /// Source file index in the file list
     pub source_file_index: Option<usize>,
+    
+    /// Where the class is defined in its source file
+    #[serde(default)]
+    pub location: Option<SourceLocation>,
+    
+    /// Where each property is defined in the source file
+    #[serde(default)]
+    pub property_locations: HashMap<String, SourceLocation>,
 }
 
 impl GameDataClass {
@@ -72,6 +80,8 @@ impl GameDataClass {
             properties: HashMap::new(),
             is_forward_declaration: false,
             source_file_index: None,
+            location: None,
+            property_locations: HashMap::new(),
         }
     }
     
@@ -55,6 +55,10 @@ pub struct Mission {
     
     /// Source PBO name
     pub source_pbo: Option<String>,
+    
+    /// Archive the mission was unpacked from
+    #[serde(default)]
+    pub source_archive: Option<String>,
 }
 
 impl Mission {
@@ -69,6 +73,7 @@ impl Mission {
             author: None,
             description: None,
             source_pbo: None,
+            source_archive: None,
         }
     }
     
@@ -1,12 +1,83 @@
 use std::collections::HashMap;
+use std::fmt;
 use serde::{Serialize, Deserialize};
 
 /// Property value types
+///
+/// Values found by the gamedata scanner are stored as these, a scanned
+/// `Number` becomes an `Integer`, a `Float` a `Number` and an array an
+/// `Array` of its converted items. Nested classes are classes of their own.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PropertyValue {
     String(String),
     Number(f64),
+    /// Whole number, kept apart from `Number` so large values stay exact
+    Integer(i64),
     Array(Vec<PropertyValue>),
     Boolean(bool),
     Object(HashMap<String, PropertyValue>),
-} 
\ No newline at end of file
+    /// Array items appended to the inherited value with `+=`
+    Append(Vec<PropertyValue>),
+    /// Expression left unevaluated, as written in the config
+    Expression(String),
+    /// Nested class removed with `delete Name;`, the property name is the class
+    Delete,
+}
+
+impl PropertyValue {
+    /// Name of the value's kind, as stored in `class_properties.property_type`
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            PropertyValue::String(_) => "string",
+            PropertyValue::Number(_) => "number",
+            PropertyValue::Integer(_) => "integer",
+            PropertyValue::Array(_) => "array",
+            PropertyValue::Boolean(_) => "boolean",
+            PropertyValue::Object(_) => "object",
+            PropertyValue::Append(_) => "append",
+            PropertyValue::Expression(_) => "expression",
+            PropertyValue::Delete => "delete",
+        }
+    }
+
+    /// Get the value as a float, if it's a number of either kind
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            PropertyValue::Number(n) => Some(*n),
+            PropertyValue::Integer(n) => Some(*n as f64),
+            _ => None,
+        }
+    }
+}
+
+/// Span of a definition in its original source file
+///
+/// Lines and columns start at 1. For macro-expanded configs the span points
+/// at the text in the file that was written, not at the expanded output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SourceLocation {
+    /// Line the definition starts on
+    pub start_line: usize,
+
+    /// Column the definition starts at
+    pub start_column: usize,
+
+    /// Line the definition ends on
+    pub end_line: usize,
+
+    /// Column the definition ends at
+    pub end_column: usize,
+}
+
+impl SourceLocation {
+    /// Create a location from its start and end positions
+    pub fn new(start_line: usize, start_column: usize, end_line: usize, end_column: usize) -> Self {
+        Self { start_line, start_column, end_line, end_column }
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.start_line, self.start_column)
+    }
+}
@@ -1,7 +1,8 @@
-use gamedata_scanner_models::{ClassProperty, GameClass, PropertyValue};
-use hemtt_config::{Class, Config as HemttConfig, Item, Number as HemttNumber, Property, Value};
+use gamedata_scanner_models::{ClassProperty, GameClass, ParserKind, PropertyValue, SourceLocation};
+use hemtt_config::{Array as HemttArray, Class, Config as HemttConfig, Item, Number as HemttNumber, Property, Value};
 use hemtt_workspace::reporting::Processed;
 use log::trace;
+use std::ops::Range;
 use std::path::Path;
 
 pub fn transform_config_to_game_classes(
@@ -66,6 +67,8 @@ impl<'a> Transformer<'a> {
                                 container_class: None,
                                 file_path: file_path_for_external,
                                 is_forward_declaration: true,
+                                location: self.location(&name_span),
+                                parsed_by: Some(ParserKind::Advanced),
                             });
                         }
                     }
@@ -201,6 +204,12 @@ impl<'a> Transformer<'a> {
             });
         trace!("  Final file_path for class '{}': {}", name, file_path_for_class.display());
 
+        // A class spans from its name to the end of its last property
+        let class_end = hemtt_properties.iter()
+            .filter_map(Self::property_end)
+            .max()
+            .unwrap_or(name_span.end);
+
         let mut game_class = GameClass {
             name: name.to_string(),
             parent: parent_name.map(String::from),
@@ -208,6 +217,8 @@ impl<'a> Transformer<'a> {
             container_class: container_class_name.map(String::from),
             file_path: file_path_for_class, // Use resolved path
             is_forward_declaration: false, // This function creates full definitions
+            location: self.location(&(name_span.start..class_end)),
+            parsed_by: Some(ParserKind::Advanced),
         };
 
         for prop in hemtt_properties {
@@ -221,6 +232,7 @@ impl<'a> Transformer<'a> {
                     game_class.properties.push(ClassProperty {
                         name: prop_name.as_str().to_string(),
                         value: self.convert_hemtt_value_to_property_value(value),
+                        location: self.location(&(prop_name.span().start..value.span().end)),
                     });
                 }
                 Property::Class(nested_hemtt_class) => {
@@ -242,6 +254,7 @@ impl<'a> Transformer<'a> {
                             // Add nested class as a property of its container
                             game_class.properties.push(ClassProperty {
                                 name: nested_game_class.name.clone(),
+                                location: nested_game_class.location,
                                 value: PropertyValue::Class(Box::new(nested_game_class.clone())),
                             });
 
@@ -287,10 +300,13 @@ impl<'a> Transformer<'a> {
                                 container_class: Some(name.to_string()), // `name` is the current class (e.g. "CfgAmmo")
                                 file_path: fwd_decl_file_path,
                                 is_forward_declaration: true,
+                                location: self.location(&fwd_decl_span),
+                                parsed_by: Some(ParserKind::Advanced),
                             };
 
                             game_class.properties.push(ClassProperty {
                                 name: nested_forward_decl_game_class.name.clone(),
+                                location: nested_forward_decl_game_class.location,
                                 value: PropertyValue::Class(Box::new(
                                     nested_forward_decl_game_class.clone(),
                                 )),
@@ -313,39 +329,67 @@ impl<'a> Transformer<'a> {
                         }
                     }
                 }
-                _ => {} // Handle Property::Delete, Property::MissingSemicolon, Property::Enum as needed or ignore
+                Property::Delete(deleted_name) => {
+                    trace!("  Adding delete to {}: {}", name, deleted_name.as_str());
+                    game_class.properties.push(ClassProperty {
+                        name: deleted_name.as_str().to_string(),
+                        value: PropertyValue::Delete,
+                        location: self.location(&deleted_name.span()),
+                    });
+                }
+                _ => {} // Handle Property::MissingSemicolon, Property::Enum as needed or ignore
             } // Closes match prop
         } // Closes for prop in hemtt_properties
         game_class // Return game_class
     } // Closes fn create_game_class
 
+    /// Map a span of the processed output back to its place in the original file
+    ///
+    /// HEMTT reports 1-based lines and 0-based columns, with end columns
+    /// exclusive; the location uses 1-based, inclusive columns.
+    fn location(&self, span: &Range<usize>) -> Option<SourceLocation> {
+        let (start_line, start_column) = self.processed_output.mapping(span.start)?.original().start().1;
+        let last = span.end.saturating_sub(1).max(span.start);
+        let (end_line, end_column) = self.processed_output
+            .mapping(last)
+            .map_or((start_line, start_column), |mapping| mapping.original().end().1);
+        Some(SourceLocation::new(start_line, start_column + 1, end_line, end_column))
+    }
+
+    /// End of a property in the processed output
+    fn property_end(property: &Property) -> Option<usize> {
+        match property {
+            Property::Entry { value, .. } => Some(value.span().end),
+            Property::Class(Class::Local { name, properties, .. }) => Some(
+                properties.iter()
+                    .filter_map(Self::property_end)
+                    .max()
+                    .unwrap_or(name.span().end),
+            ),
+            Property::Class(Class::External { name, .. }) => Some(name.span().end),
+            _ => None,
+        }
+    }
+
     fn convert_hemtt_value_to_property_value(&self, value: &Value) -> PropertyValue {
         match value {
             Value::Str(s) => PropertyValue::String(s.value().to_string()),
-            Value::Number(n) => match n {
-                HemttNumber::Int32 { value, .. } => PropertyValue::Number(*value as i64),
-                HemttNumber::Int64 { value, .. } => PropertyValue::Number(*value),
-                HemttNumber::Float32 { value, .. } => PropertyValue::Number(*value as i64), // Consider if f32 should be stored differently
-            },
-            Value::Array(arr) => PropertyValue::Array(
-                arr.items
-                    .iter()
-                    .map(|item| self.convert_hemtt_item_to_string(item))
-                    .collect(),
-            ),
-            Value::Expression(e) => PropertyValue::String(format!("EXPRESSION: {:?}", e)), // Represent expression
+            Value::Number(n) => Self::convert_hemtt_number(n),
+            Value::Array(arr) => self.convert_hemtt_array(arr),
+            Value::Expression(e) => {
+                // Keep the expression as written rather than evaluating it
+                let text = self.processed_output.as_str().get(value.span())
+                    .map(|text| text.trim().to_string())
+                    .unwrap_or_else(|| format!("{:?}", e));
+                PropertyValue::Expression(text)
+            }
             Value::Macro(m) => PropertyValue::String(m.to_string()), // Use MacroExpression's to_string
             Value::UnexpectedArray(arr) => {
                 log::warn!(
-                    "Encountered UnexpectedArray, converting to string array: {:?}",
+                    "Encountered UnexpectedArray, converting to array: {:?}",
                     arr.span
                 );
-                PropertyValue::Array(
-                    arr.items
-                        .iter()
-                        .map(|item| self.convert_hemtt_item_to_string(item))
-                        .collect(),
-                )
+                self.convert_hemtt_array(arr)
             }
             Value::Invalid(range) => {
                 log::warn!("Encountered Invalid Hemtt Value at range {:?}, representing as empty string", range);
@@ -354,26 +398,44 @@ impl<'a> Transformer<'a> {
         }
     }
 
-    fn convert_hemtt_item_to_string(&self, item: &Item) -> String {
-        match item {
-            Item::Str(s) => s.value().to_string(),
-            Item::Number(n) => n.to_string(),
-            Item::Macro(m) => m.to_string(),
-            Item::Array(items_vec) => {
-                // Recursively convert nested arrays, e.g., "{elem1, elem2, {sub1, sub2}}"
-                let inner_items: Vec<String> = items_vec
-                    .iter()
-                    .map(|sub_item| self.convert_hemtt_item_to_string(sub_item))
-                    .collect();
-                format!("{{{}}}", inner_items.join(", "))
-            }
-            Item::Invalid(range) => {
-                log::warn!(
-                    "Encountered Invalid Hemtt Item at range {:?}, representing as placeholder",
-                    range
-                );
-                "INVALID_HEMTT_ITEM".to_string()
-            }
+    fn convert_hemtt_number(number: &HemttNumber) -> PropertyValue {
+        match number {
+            HemttNumber::Int32 { value, .. } => PropertyValue::Number(*value as i64),
+            HemttNumber::Int64 { value, .. } => PropertyValue::Number(*value),
+            // Go through the text so 0.1 stays 0.1 instead of the widened f32
+            HemttNumber::Float32 { value, .. } => PropertyValue::Float(
+                value.to_string().parse().unwrap_or(*value as f64),
+            ),
+        }
+    }
+
+    /// Arrays keep their structure and the type of each item
+    fn convert_hemtt_array(&self, arr: &HemttArray) -> PropertyValue {
+        let value = PropertyValue::Array(self.convert_hemtt_items(&arr.items));
+
+        if arr.expand {
+            PropertyValue::Append(Box::new(value))
+        } else {
+            value
         }
     }
+
+    /// Convert array items, leaving out the ones HEMTT could not parse
+    fn convert_hemtt_items(&self, items: &[Item]) -> Vec<PropertyValue> {
+        items.iter()
+            .filter_map(|item| match item {
+                Item::Str(s) => Some(PropertyValue::String(s.value().to_string())),
+                Item::Number(n) => Some(Self::convert_hemtt_number(n)),
+                Item::Macro(m) => Some(PropertyValue::String(m.to_string())),
+                Item::Array(sub_items) => Some(PropertyValue::Array(self.convert_hemtt_items(sub_items))),
+                Item::Invalid(range) => {
+                    log::warn!(
+                        "Skipping invalid array item at {:?} in {}",
+                        range, self.original_file_path.display()
+                    );
+                    None
+                }
+            })
+            .collect()
+    }
 }
\ No newline at end of file
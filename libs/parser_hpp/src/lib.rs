@@ -14,7 +14,7 @@ mod simple_parser;
 
 pub use error::ParseError;
 pub use file_processor::{ParseResult, ParseWarning}; // Export new parsing result types
-pub use models::{GameClass, ClassProperty, PropertyValue, FileParser}; // Re-export all needed types
+pub use models::{GameClass, ClassProperty, PropertyValue, FileParser, ParsedFile, ParserKind, SourceLocation}; // Re-export all needed types
 pub use query::DependencyExtractor;
 pub use simple_parser::{SimpleClassScanner, parse_file_simple};
 use workspace_manager::WorkspaceManager;
@@ -26,6 +26,8 @@ pub enum ParserMode {
     Simple,
     /// Full HEMTT integration - slower but handles includes/macros
     Advanced,
+    /// Advanced parsing, with Simple parsing for files the advanced parser fails on
+    Hybrid,
 }
 
 /// The main parser for an Arma 3 project.
@@ -53,6 +55,22 @@ impl AdvancedProjectParser {
     pub fn new(
         project_root_dir_param: &Path,
         project_config_path: Option<&Path>,
+    ) -> Result<Self, ParseError> {
+        Self::with_include_roots(project_root_dir_param, project_config_path, &[])
+    }
+
+    /// Creates a new parser that also resolves includes from other mods.
+    ///
+    /// # Arguments
+    /// * `project_root_dir_param`: The absolute path to the root of the Arma 3 project/mod.
+    /// * `project_config_path`: Optional path to a `hemtt.toml` file, discovered in the project root if None.
+    /// * `include_roots`: Directories laid out like a P drive (e.g. materialized from
+    ///                    `pbo_tools::vfs::VirtualPDrive`) used to resolve absolute includes
+    ///                    such as `\x\cba\addons\main\script_macros.hpp`.
+    pub fn with_include_roots(
+        project_root_dir_param: &Path,
+        project_config_path: Option<&Path>,
+        include_roots: &[PathBuf],
     ) -> Result<Self, ParseError> {
         let project_config = match project_config_path {
             // If explicit path provided, use it
@@ -92,6 +110,7 @@ impl AdvancedProjectParser {
         let workspace_manager = WorkspaceManager::new(
             project_root_dir_param,
             project_config.clone(),
+            include_roots,
             &PDriveOption::Disallow,
         )?;
 
@@ -237,6 +256,46 @@ impl AdvancedFileParserWrapper {
             project_root_dir: project_root_dir.to_path_buf(),
         }
     }
+
+    /// Parses a file like [`FileParser::parse_file`], returning the error
+    /// instead of an empty list when parsing fails.
+    pub fn try_parse_file(&self, file_path: &Path) -> Result<Vec<GameClass>, ParseError> {
+        // Attempt to make file_path relative to the project_root_dir
+        let relative_path = match file_path.strip_prefix(&self.project_root_dir) {
+            Ok(rel_path) => rel_path,
+            // An absolute path outside the project cannot be parsed
+            Err(_) if file_path.is_absolute() => {
+                return Err(ParseError::PathNotInProject(file_path.to_path_buf(), self.project_root_dir.clone()));
+            }
+            // Assume it's a relative path the project_parser can handle from its root
+            Err(_) => file_path,
+        };
+
+        let (mut classes, warnings) = self.project_parser.parse_file(relative_path)?;
+
+        // Log any warnings encountered during parsing
+        for warning in &warnings {
+            log::warn!("Parsing warning for {}: {} - {}", file_path.display(), warning.code, warning.message);
+        }
+
+        // Convert absolute paths from project_parser back to relative for the wrapper's contract
+        for class in &mut classes {
+            if let Ok(new_relative_path) = class.file_path.strip_prefix(&self.project_root_dir) {
+                class.file_path = new_relative_path.to_path_buf();
+            } else {
+                // This case should ideally not happen if paths are consistent.
+                // Log a warning if a path couldn't be made relative.
+                log::warn!(
+                    "Could not make path {} relative to project root {} for GameClass {} in wrapper",
+                    class.file_path.display(),
+                    self.project_root_dir.display(),
+                    class.name
+                );
+                // Keep the original (likely absolute) path if stripping fails.
+            }
+        }
+        Ok(classes)
+    }
 }
 
 impl FileParser for AdvancedFileParserWrapper {
@@ -247,52 +306,8 @@ impl FileParser for AdvancedFileParserWrapper {
     /// Parses a file. `file_path` is expected to be an absolute path
     /// or a path that can be made relative to the project root.
     fn parse_file(&self, file_path: &Path) -> Vec<GameClass> {
-        // Attempt to make file_path relative to the project_root_dir
-        let relative_path = match file_path.strip_prefix(&self.project_root_dir) {
-            Ok(rel_path) => rel_path,
-            Err(_) => {
-                // If stripping prefix fails, it might be an absolute path not within the project,
-                // or a path that's already relative but not directly.
-                // For simplicity, if it's absolute and not in project, we error.
-                // If it's already relative, we try to use it as is, but this might be fragile.
-                if file_path.is_absolute() {
-                    log::error!(
-                        "File path {} is not within the project root {}",
-                        file_path.display(),
-                        self.project_root_dir.display()
-                    );
-                    return Vec::new();
-                }
-                // Assume it's a relative path the project_parser can handle from its root
-                file_path
-            }
-        };
-
-        match self.project_parser.parse_file(relative_path) {
-            Ok((mut classes, warnings)) => { // Destructure the tuple to get classes and warnings
-                // Log any warnings encountered during parsing
-                for warning in &warnings {
-                    log::warn!("Parsing warning for {}: {} - {}", file_path.display(), warning.code, warning.message);
-                }
-                
-                // Convert absolute paths from project_parser back to relative for the wrapper's contract
-                for class in &mut classes {
-                    if let Ok(new_relative_path) = class.file_path.strip_prefix(&self.project_root_dir) {
-                        class.file_path = new_relative_path.to_path_buf();
-                    } else {
-                        // This case should ideally not happen if paths are consistent.
-                        // Log a warning if a path couldn't be made relative.
-                        log::warn!(
-                            "Could not make path {} relative to project root {} for GameClass {} in wrapper",
-                            class.file_path.display(),
-                            self.project_root_dir.display(),
-                            class.name
-                        );
-                        // Keep the original (likely absolute) path if stripping fails.
-                    }
-                }
-                classes
-            }
+        match self.try_parse_file(file_path) {
+            Ok(classes) => classes,
             Err(e) => {
                 log::error!("Error parsing file {}: {:?}", file_path.display(), e);
                 Vec::new()
@@ -301,6 +316,69 @@ impl FileParser for AdvancedFileParserWrapper {
     }
 }
 
+/// File parser that falls back to the simple scanner file by file.
+///
+/// Files the advanced parser fails on are scanned with [`SimpleClassScanner`]
+/// instead, as are files the caller gives up on through
+/// [`FileParser::parse_file_after_timeout`]. Their classes are kept but have
+/// no properties.
+pub struct HybridFileParser {
+    advanced: AdvancedFileParserWrapper,
+    simple_scanner: SimpleClassScanner,
+    project_root_dir: PathBuf,
+}
+
+impl HybridFileParser {
+    /// Creates a hybrid parser around an advanced project parser.
+    pub fn new(project_parser: Arc<AdvancedProjectParser>, project_root_dir: &Path) -> Self {
+        Self {
+            advanced: AdvancedFileParserWrapper::new(project_parser, project_root_dir),
+            simple_scanner: SimpleClassScanner::new(),
+            project_root_dir: project_root_dir.to_path_buf(),
+        }
+    }
+
+    /// Scans a file with the simple parser, recording why
+    fn parse_simple(&self, file_path: &Path, reason: String) -> ParsedFile {
+        log::warn!("Falling back to the simple parser for {}: {}", file_path.display(), reason);
+
+        let absolute_path = self.project_root_dir.join(file_path);
+        let mut classes = self.simple_scanner.scan_file(&absolute_path);
+        // Keep paths relative to the project root, as the advanced parser does
+        if let Ok(relative_path) = absolute_path.strip_prefix(&self.project_root_dir) {
+            for class in &mut classes {
+                class.file_path = relative_path.to_path_buf();
+            }
+        }
+
+        ParsedFile {
+            classes,
+            fallback_reason: Some(reason),
+        }
+    }
+}
+
+impl FileParser for HybridFileParser {
+    fn name(&self) -> &str {
+        "HybridFileParser"
+    }
+
+    fn parse_file(&self, file_path: &Path) -> Vec<GameClass> {
+        self.parse_file_with_fallback(file_path).classes
+    }
+
+    fn parse_file_with_fallback(&self, file_path: &Path) -> ParsedFile {
+        match self.advanced.try_parse_file(file_path) {
+            Ok(classes) => ParsedFile { classes, fallback_reason: None },
+            Err(e) => self.parse_simple(file_path, e.to_string()),
+        }
+    }
+
+    fn parse_file_after_timeout(&self, file_path: &Path, reason: &str) -> Option<ParsedFile> {
+        Some(self.parse_simple(file_path, reason.to_string()))
+    }
+}
+
 // Legacy API compatibility - keep the old parse_file function but use the new engine
 /// Parse an HPP file and return a vector of classes.
 /// 
@@ -408,6 +486,15 @@ impl HppParser {
                     )));
                 }
             }
+            ParserMode::Hybrid => {
+                match self.parse_file(file_path, ParserMode::Advanced) {
+                    Ok(classes) => Ok(classes),
+                    Err(e) => {
+                        log::warn!("Falling back to the simple parser for {}: {}", file_path.display(), e);
+                        self.parse_file(file_path, ParserMode::Simple)
+                    }
+                }
+            }
         }
     }
 
@@ -528,6 +615,12 @@ mod tests {
         assert_eq!(test_class.properties.len(), 3);
         let display_name_prop = test_class.properties.iter().find(|p| p.name == "displayName").unwrap();
         assert_eq!(display_name_prop.value, PropertyValue::String("Test Display".to_string()));
+
+        // Locations point into the source file
+        let class_location = test_class.location.expect("class should have a location");
+        assert_eq!((class_location.start_line, class_location.end_line), (5, 8));
+        let property_location = display_name_prop.location.expect("property should have a location");
+        assert_eq!((property_location.start_line, property_location.start_column), (6, 17));
     }
 
     #[test]
@@ -549,6 +642,78 @@ mod tests {
         assert_eq!(another_class.name, "AnotherClass");
         let value_prop = another_class.properties.iter().find(|p| p.name == "anotherValue").unwrap();
         assert_eq!(value_prop.value, PropertyValue::Number(456)); // MY_VALUE from common.hpp
+        // The macro is located where it is used, not where it is defined
+        assert_eq!(value_prop.location.map(|location| location.start_line), Some(4));
+    }
+
+    #[test]
+    fn test_advanced_project_parser_with_include_roots() {
+        let project_dir = tempdir().unwrap();
+        let include_dir = tempdir().unwrap();
+
+        let main_dir = project_dir.path().join("addons/main");
+        fs::create_dir_all(&main_dir).unwrap();
+        fs::write(
+            main_dir.join("config.cpp"),
+            "#include \"\\x\\other\\addons\\main\\script_macros.hpp\"\nclass FromOtherMod { value = OTHER_VALUE; };",
+        ).unwrap();
+
+        let other_dir = include_dir.path().join("x/other/addons/main");
+        fs::create_dir_all(&other_dir).unwrap();
+        fs::write(other_dir.join("script_macros.hpp"), "#define OTHER_VALUE 789\n").unwrap();
+
+        let parser = AdvancedProjectParser::with_include_roots(
+            project_dir.path(),
+            None,
+            &[include_dir.path().to_path_buf()],
+        ).unwrap();
+        let (classes, warnings) = parser.parse_file(Path::new("addons/main/config.cpp")).unwrap();
+
+        assert!(warnings.iter().all(|w| w.code != "PE12"), "Unexpected warnings: {:?}", warnings);
+        assert_eq!(classes.len(), 1);
+        let value_prop = classes[0].properties.iter().find(|p| p.name == "value").unwrap();
+        assert_eq!(value_prop.value, PropertyValue::Number(789));
+    }
+
+    #[test]
+    fn test_advanced_project_parser_keeps_values_lossless() {
+        let project_dir = tempdir().unwrap();
+        let main_dir = project_dir.path().join("addons/main");
+        fs::create_dir_all(&main_dir).unwrap();
+        fs::write(
+            main_dir.join("config.cpp"),
+            r#"class Rifle_Base_F;
+class Rifle: Rifle_Base_F {
+    mass = 12.5;
+    magazineWell[] += {"CBA_556x45_STANAG"};
+    weaponPoolAvailable[] = {{"arifle_MX_F", 2}, {"hgun_P07_F", 0.5}};
+    ammo[] = {"901", 901, 0.5};
+};
+"#,
+        ).unwrap();
+
+        let parser = AdvancedProjectParser::new(project_dir.path(), None).unwrap();
+        let (classes, _) = parser.parse_file(Path::new("addons/main/config.cpp")).unwrap();
+        let rifle = classes.iter().find(|c| c.name == "Rifle").unwrap();
+        let value = |name: &str| &rifle.properties.iter().find(|p| p.name == name).unwrap().value;
+
+        assert_eq!(value("mass"), &PropertyValue::Float(12.5));
+        assert_eq!(
+            value("magazineWell"),
+            &PropertyValue::Append(Box::new(PropertyValue::Array(vec!["CBA_556x45_STANAG".into()])))
+        );
+        assert_eq!(value("magazineWell").as_string_array(), Some(vec!["CBA_556x45_STANAG"]));
+        assert_eq!(
+            value("weaponPoolAvailable"),
+            &PropertyValue::Array(vec![
+                PropertyValue::Array(vec!["arifle_MX_F".into(), PropertyValue::Number(2)]),
+                PropertyValue::Array(vec!["hgun_P07_F".into(), PropertyValue::Float(0.5)]),
+            ])
+        );
+        assert_eq!(
+            value("ammo"),
+            &PropertyValue::Array(vec!["901".into(), PropertyValue::Number(901), PropertyValue::Float(0.5)])
+        );
     }
 
     #[test]
@@ -576,6 +741,42 @@ mod tests {
         assert_eq!(another_class.file_path, PathBuf::from("addons/main/other.cpp"));
     }
 
+    #[test]
+    fn test_hybrid_file_parser_falls_back_per_file() {
+        let (temp_dir, project_parser) = setup_basic_project_for_parser();
+        let project_root = temp_dir.path().to_path_buf();
+        let broken_content = r#"
+            #include "does_not_exist.hpp"
+            class BrokenClass : MyTestClass {
+                value = 1;
+            };
+        "#;
+        fs::write(project_root.join("addons/main/broken.hpp"), broken_content).unwrap();
+
+        let parser = HybridFileParser::new(project_parser, &project_root);
+
+        let parsed = parser.parse_file_with_fallback(&project_root.join("addons/main/config.cpp"));
+        assert!(parsed.fallback_reason.is_none());
+        let test_class = parsed.classes.iter().find(|c| c.name == "MyTestClass").unwrap();
+        assert_eq!(test_class.parsed_by, Some(ParserKind::Advanced));
+        assert_eq!(test_class.properties.len(), 3);
+
+        let parsed = parser.parse_file_with_fallback(&project_root.join("addons/main/broken.hpp"));
+        assert!(parsed.fallback_reason.is_some(), "The missing include should fail the advanced parser");
+        assert_eq!(parsed.classes.len(), 1);
+        let broken = &parsed.classes[0];
+        assert_eq!(broken.name, "BrokenClass");
+        assert_eq!(broken.parent.as_deref(), Some("MyTestClass"));
+        assert_eq!(broken.parsed_by, Some(ParserKind::Simple));
+        assert_eq!(broken.file_path, PathBuf::from("addons/main/broken.hpp"));
+
+        // A file the caller gave up on is scanned by the simple parser too
+        let parsed = parser.parse_file_after_timeout(&project_root.join("addons/main/config.cpp"), "timed out").unwrap();
+        assert_eq!(parsed.fallback_reason.as_deref(), Some("timed out"));
+        let test_class = parsed.classes.iter().find(|c| c.name == "MyTestClass").unwrap();
+        assert_eq!(test_class.parsed_by, Some(ParserKind::Simple));
+    }
+
      #[test]
     fn test_config_auto_discovery() {
         // Initialize logger for debug output
@@ -761,10 +962,12 @@ enabled = true
         assert_eq!(ParserMode::Simple, ParserMode::Simple);
         assert_eq!(ParserMode::Advanced, ParserMode::Advanced);
         assert_ne!(ParserMode::Simple, ParserMode::Advanced);
+        assert_ne!(ParserMode::Hybrid, ParserMode::Advanced);
         
         // Test Debug formatting
         assert_eq!(format!("{:?}", ParserMode::Simple), "Simple");
         assert_eq!(format!("{:?}", ParserMode::Advanced), "Advanced");
+        assert_eq!(format!("{:?}", ParserMode::Hybrid), "Hybrid");
     }
 
     #[test]
@@ -1,2 +1,2 @@
 // Re-export from the gamedata_scanner_models crate
-pub use gamedata_scanner_models::{ClassProperty, GameClass, PropertyValue, FileParser};
\ No newline at end of file
+pub use gamedata_scanner_models::{ClassProperty, GameClass, PropertyValue, FileParser, ParsedFile, ParserKind, SourceLocation};
\ No newline at end of file
@@ -120,8 +120,8 @@ impl DependencyExtractor {
                             PropertyValue::String(s) => {
                                 dependencies.insert(s.to_string());
                             }
-                            PropertyValue::Array(arr) => {
-                                dependencies.extend(arr.iter().cloned());
+                            PropertyValue::Array(_) | PropertyValue::Append(_) => {
+                                dependencies.extend(value.as_string_array().into_iter().flatten().map(str::to_string));
                             }
                             PropertyValue::Class(nested_class) => {
                                 // For nested classes, process them with the current path
@@ -165,16 +165,20 @@ mod tests {
             properties: vec![
                 ClassProperty {
                     name: "uniform".to_string(),
-                    value: PropertyValue::Array(vec!["test_uniform".to_string()]),
+                    value: PropertyValue::Array(vec!["test_uniform".into()]),
+                    location: None,
                 },
                 ClassProperty {
                     name: "vest".to_string(),
-                    value: PropertyValue::Array(vec!["test_vest".to_string()]),
+                    value: PropertyValue::Array(vec!["test_vest".into()]),
+                    location: None,
                 },
             ],
             container_class: None,
             file_path: PathBuf::from("test.hpp"),
             is_forward_declaration: false,
+            location: None,
+            parsed_by: None,
         };
 
         let extractor = DependencyExtractor::new(vec![class]);
@@ -193,11 +197,14 @@ mod tests {
                 ClassProperty {
                     name: "name".to_string(),
                     value: PropertyValue::String("test_rifle".to_string()),
+                    location: None,
                 },
             ],
             container_class: Some("rifleman".to_string()),
             file_path: PathBuf::from("test.hpp"),
             is_forward_declaration: false,
+            location: None,
+            parsed_by: None,
         };
 
         let class = GameClass {
@@ -207,11 +214,14 @@ mod tests {
                 ClassProperty {
                     name: "primaryWeapon".to_string(),
                     value: PropertyValue::Class(Box::new(nested_class)),
+                    location: None,
                 },
             ],
             container_class: None,
             file_path: PathBuf::from("test.hpp"),
             is_forward_declaration: false,
+            location: None,
+            parsed_by: None,
         };
 
         let extractor = DependencyExtractor::new(vec![class]);
@@ -1,4 +1,4 @@
-use crate::GameClass;
+use crate::{FileParser, GameClass, ParserKind, SourceLocation};
 use log::{debug, trace};
 use regex::Regex;
 use std::fs;
@@ -51,6 +51,10 @@ impl SimpleClassScanner {
                 is_forward_declaration
             );
 
+            // Only the declaration is matched, so the location ends at its `{` or `;`
+            let (start_line, start_column) = line_col(&content, capture.get(1).unwrap().start());
+            let (end_line, end_column) = line_col(&content, capture.get(3).unwrap().start());
+
             let class = GameClass {
                 name,
                 parent,
@@ -58,6 +62,8 @@ impl SimpleClassScanner {
                 container_class: None,
                 properties: Vec::new(),
                 is_forward_declaration,
+                location: Some(SourceLocation::new(start_line, start_column, end_line, end_column)),
+                parsed_by: Some(ParserKind::Simple),
             };
 
             classes.push(class);
@@ -68,6 +74,24 @@ impl SimpleClassScanner {
     }
 }
 
+impl FileParser for SimpleClassScanner {
+    fn name(&self) -> &str {
+        "SimpleClassScanner"
+    }
+
+    fn parse_file(&self, file_path: &Path) -> Vec<GameClass> {
+        self.scan_file(file_path)
+    }
+}
+
+/// 1-based line and column of a byte offset into `content`
+fn line_col(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
 /// Parse a single file and return all classes found in it using the simple parser
 pub fn parse_file_simple(file_path: &Path) -> Vec<GameClass> {
     debug!("Parsing file with simple parser: {}", file_path.display());
@@ -102,6 +126,7 @@ class Rifleman : BaseMan {
         assert!(classes[0].parent.is_none());
         assert_eq!(classes[1].name, "Rifleman");
         assert_eq!(classes[1].parent.as_deref(), Some("BaseMan"));
+        assert_eq!(classes[1].location, Some(SourceLocation::new(5, 7, 5, 26)));
     }
 
     #[test]
@@ -1,6 +1,6 @@
 use hemtt_common::config::{PDriveOption, ProjectConfig};
 use hemtt_workspace::{LayerType, Workspace, WorkspacePath};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use crate::error::ParseError;
 use log::debug;
 
@@ -11,9 +11,15 @@ pub struct WorkspaceManager {
 }
 
 impl WorkspaceManager {
+    /// Creates the workspace for a project.
+    ///
+    /// Each of `include_roots` is layered over the project root like a P drive,
+    /// so `#include "\x\mod\addons\main\script_macros.hpp"` resolves to
+    /// `<include_root>/x/mod/addons/main/script_macros.hpp`.
     pub fn new(
         project_root_dir: &Path,
         project_config: Option<ProjectConfig>,
+        include_roots: &[PathBuf],
         pdrive_option: &PDriveOption,
     ) -> Result<Self, ParseError> {
         debug!(
@@ -21,9 +27,13 @@ impl WorkspaceManager {
             project_root_dir.display()
         );
 
-        let workspace_root_wpath = Workspace::builder()
-            .physical(&project_root_dir.to_path_buf(), LayerType::Source)
-            .finish(project_config.clone(), true, pdrive_option)?;
+        let mut builder = Workspace::builder()
+            .physical(&project_root_dir.to_path_buf(), LayerType::Source);
+        for include_root in include_roots {
+            debug!("Adding include root: {}", include_root.display());
+            builder = builder.physical(include_root, LayerType::Include);
+        }
+        let workspace_root_wpath = builder.finish(project_config.clone(), true, pdrive_option)?;
 
         Ok(Self {
             workspace_root_wpath,
@@ -29,7 +29,7 @@ fn test_loadout_parsing() {
 
     // Test array properties
     let uniform_prop = rifleman.properties.iter().find(|p| p.name == "uniform").unwrap();
-    if let PropertyValue::Array(uniforms) = &uniform_prop.value {
+    if let Some(uniforms) = uniform_prop.value.as_string_array() {
         // The quoted string is returned from the parser since LIST macros are preserved as strings
         assert!(uniforms.iter().any(|u| u.contains("usp_g3c_kp_mx_aor2")), 
                 "Could not find usp_g3c_kp_mx_aor2 in: {:?}", uniforms);
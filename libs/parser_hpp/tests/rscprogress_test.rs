@@ -15,11 +15,7 @@ fn test_profilenamespace_expression_parsing() {
     let color_frame_prop = progress_class.properties.iter()
         .find(|p| p.name == "colorFrame").unwrap();
     if let PropertyValue::Array(values) = &color_frame_prop.value {
-        assert_eq!(values.len(), 4);
-        assert_eq!(values[0], "0");
-        assert_eq!(values[1], "0");
-        assert_eq!(values[2], "0");
-        assert_eq!(values[3], "0");
+        assert_eq!(values, &vec![PropertyValue::Number(0); 4]);
     } else {
         panic!("Expected colorFrame to be an array");
     }
@@ -27,7 +23,7 @@ fn test_profilenamespace_expression_parsing() {
     // Test colorBar property with profilenamespace expressions
     let color_bar_prop = progress_class.properties.iter()
         .find(|p| p.name == "colorBar").unwrap();
-    if let PropertyValue::Array(values) = &color_bar_prop.value {
+    if let Some(values) = color_bar_prop.value.as_string_array() {
         assert_eq!(values.len(), 4);
         // Verify that profilenamespace expressions are properly preserved as complete strings
         assert!(values[0].contains("(profilenamespace getvariable ['GUI_BCG_RGB_R',0.13])"));
@@ -47,15 +43,5 @@ fn test_profilenamespace_expression_parsing() {
     let x_prop = progress_class.properties.iter()
         .find(|p| p.name == "x").unwrap();
     
-    // Depending on the implementation, this could be treated as a number or a string
-    match &x_prop.value {
-        PropertyValue::Number(num) => {
-            // Allow for potential floating point conversion
-            assert!(*num == 0 || *num == 344, "Expected x to be either 0 or 344, got {}", num);
-        },
-        PropertyValue::String(s) => {
-            assert!(s == "0.344", "Expected x to be \"0.344\", got \"{}\"", s);
-        },
-        _ => panic!("Expected x to be either a number or string")
-    }
+    assert_eq!(x_prop.value, PropertyValue::Float(0.344));
 } 
\ No newline at end of file
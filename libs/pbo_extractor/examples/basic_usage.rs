@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 use anyhow::Result;
-use arma3_extractor::{ExtractionConfig, ExtractionManager};
+use arma3_extractor::{ExtractionConfig, ExtractionManager, RetryPolicy, StateBackend};
 use log::{info, LevelFilter};
 
 #[tokio::main]
@@ -33,6 +33,9 @@ async fn main() -> Result<()> {
         timeout: 340,
         verbose: true,
         db_path: PathBuf::from("./cache/basic_usage_extractor.db"),
+        state_backend: StateBackend::File,
+        retry_policy: RetryPolicy::default(),
+        salvage_failed: false,
     };
     
     // --- First Run --- 
@@ -0,0 +1,212 @@
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use log::{debug, info};
+use sha1::{Digest, Sha1};
+use zip::ZipArchive;
+
+use crate::error::{CacheError, Result};
+use crate::file_db::normalize_path;
+use crate::utils;
+
+/// File inside an unpacked archive holding the content hash it was unpacked from
+const SOURCE_HASH_FILE: &str = ".source_hash";
+
+/// Most bytes unpacked from a single archive, so a zip bomb cannot fill the disk
+const MAX_UNPACKED_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+
+/// Directory an archive is unpacked into below `archives_dir`
+///
+/// The name is derived from the archive's path rather than its contents, so
+/// a changed archive replaces its previous unpacked copy.
+pub fn unpack_dir(archive: &Path, archives_dir: &Path) -> PathBuf {
+    let path_hash: String = Sha1::digest(normalize_path(archive).as_bytes())
+        .iter()
+        .take(6)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let stem = archive.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    archives_dir.join(format!("{}-{}", stem, path_hash))
+}
+
+/// Unpack a zip archive of missions below `archives_dir`
+///
+/// Archives are read from disk only. An archive that was unpacked before and
+/// has not changed since is not unpacked again. Entries whose names would
+/// leave the target directory are skipped, and archives that unpack to more
+/// than [`MAX_UNPACKED_BYTES`] are rejected. Returns the directory holding
+/// the archive's contents.
+pub fn unpack_archive(archive: &Path, archives_dir: &Path) -> Result<PathBuf> {
+    let target = unpack_dir(archive, archives_dir);
+    let content_hash = utils::hash_file(archive)?;
+
+    let hash_file = target.join(SOURCE_HASH_FILE);
+    if fs::read_to_string(&hash_file).is_ok_and(|hash| hash == content_hash) {
+        debug!("Archive {} is already unpacked", archive.display());
+        return Ok(target);
+    }
+
+    // Unpack next to the target and swap it in, so an interrupted run never
+    // leaves a partially unpacked archive behind
+    let staging = staging_dir(&target);
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    fs::create_dir_all(&staging)?;
+
+    let unpacked = extract_entries(archive, &staging, MAX_UNPACKED_BYTES)
+        .and_then(|count| fs::write(staging.join(SOURCE_HASH_FILE), &content_hash).map(|_| count));
+    let count = match unpacked {
+        Ok(count) => count,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(CacheError::FileOperation(format!("Failed to unpack {}: {}", archive.display(), e)));
+        }
+    };
+
+    if target.exists() {
+        fs::remove_dir_all(&target)?;
+    }
+    fs::rename(&staging, &target)?;
+
+    info!("Unpacked {} files from {}", count, archive.display());
+    Ok(target)
+}
+
+/// Directory an archive is unpacked into before it replaces `target`
+fn staging_dir(target: &Path) -> PathBuf {
+    let mut staging = target.as_os_str().to_owned();
+    staging.push(".partial");
+    PathBuf::from(staging)
+}
+
+/// Write the files of a zip archive below `target`, returning how many were written
+///
+/// Fails once more than `max_bytes` were written in total. The bytes are
+/// counted as they are decompressed, as the sizes in the archive can lie.
+fn extract_entries(archive: &Path, target: &Path, max_bytes: u64) -> io::Result<usize> {
+    let mut zip = ZipArchive::new(File::open(archive)?)?;
+    let mut count = 0;
+    let mut remaining = max_bytes;
+
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index)?;
+        let Some(relative_path) = entry.enclosed_name() else {
+            debug!("Skipping unsafe entry {} in {}", entry.name(), archive.display());
+            continue;
+        };
+
+        let path = target.join(relative_path);
+        if entry.is_dir() {
+            fs::create_dir_all(&path)?;
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let written = io::copy(&mut (&mut entry).take(remaining + 1), &mut File::create(&path)?)?;
+        if written > remaining {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("archive unpacks to more than {} bytes", max_bytes),
+            ));
+        }
+        remaining -= written;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+    use zip::write::SimpleFileOptions;
+    use zip::{CompressionMethod, ZipWriter};
+
+    fn create_archive(path: &Path, files: &[(&str, &[u8])]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        for (name, contents) in files {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(contents).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_unpack_archive() {
+        let temp_dir = tempdir().unwrap();
+        let archive = temp_dir.path().join("pack.zip");
+        create_archive(&archive, &[
+            ("co10_escape.Altis.pbo", b"PBO content"),
+            ("tvt_raid.Stratis/mission.sqm", b"version = 54;"),
+            ("../outside.txt", b"escaped"),
+        ]);
+        let archives_dir = temp_dir.path().join("archives");
+
+        let target = unpack_archive(&archive, &archives_dir).unwrap();
+
+        assert_eq!(target, unpack_dir(&archive, &archives_dir));
+        assert!(target.join("co10_escape.Altis.pbo").is_file());
+        assert!(target.join("tvt_raid.Stratis").join("mission.sqm").is_file());
+        assert!(!temp_dir.path().join("outside.txt").exists());
+        assert!(!staging_dir(&target).exists());
+    }
+
+    #[test]
+    fn test_unpack_archive_again_after_change() {
+        let temp_dir = tempdir().unwrap();
+        let archive = temp_dir.path().join("pack.zip");
+        let archives_dir = temp_dir.path().join("archives");
+        create_archive(&archive, &[("old.Altis.pbo", b"old")]);
+        let target = unpack_archive(&archive, &archives_dir).unwrap();
+
+        // Unchanged archives keep their unpacked files
+        fs::write(target.join("marker"), "kept").unwrap();
+        unpack_archive(&archive, &archives_dir).unwrap();
+        assert!(target.join("marker").is_file());
+
+        create_archive(&archive, &[("new.Altis.pbo", b"new")]);
+        let target = unpack_archive(&archive, &archives_dir).unwrap();
+        assert!(target.join("new.Altis.pbo").is_file());
+        assert!(!target.join("old.Altis.pbo").exists());
+        assert!(!target.join("marker").exists());
+    }
+
+    #[test]
+    fn test_unpacked_size_is_capped() {
+        let temp_dir = tempdir().unwrap();
+        let archive = temp_dir.path().join("bomb.zip");
+        let mut zip = ZipWriter::new(File::create(&archive).unwrap());
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        for name in ["a.sqf", "b.sqf"] {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(&vec![0u8; 64 * 1024]).unwrap();
+        }
+        zip.finish().unwrap();
+
+        // Small on disk, but the second entry goes over the limit
+        assert!(fs::metadata(&archive).unwrap().len() < 64 * 1024);
+        let target = temp_dir.path().join("target");
+        let error = extract_entries(&archive, &target, 100 * 1024).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(extract_entries(&archive, &temp_dir.path().join("fits"), 128 * 1024).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_unpack_invalid_archive() {
+        let temp_dir = tempdir().unwrap();
+        let archive = temp_dir.path().join("broken.zip");
+        fs::write(&archive, b"not a zip").unwrap();
+        let archives_dir = temp_dir.path().join("archives");
+
+        assert!(unpack_archive(&archive, &archives_dir).is_err());
+        assert!(!unpack_dir(&archive, &archives_dir).exists());
+        assert!(!staging_dir(&unpack_dir(&archive, &archives_dir)).exists());
+    }
+}
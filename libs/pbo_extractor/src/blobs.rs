@@ -0,0 +1,201 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use log::{debug, warn};
+
+use crate::utils;
+
+/// Content-addressed store of extracted files
+///
+/// Every file is kept once under its SHA-1, at `<root>/<first two hex
+/// digits>/<hash>`. The cache paths of PBOs are hard links to the blobs, so
+/// identical files vendored by many mods take space once while every PBO
+/// keeps its own paths. Hard links only work within one file system; check
+/// [`can_link_into`](Self::can_link_into) before using a store, as a store
+/// that has to copy keeps every file twice.
+#[derive(Debug, Clone)]
+pub struct BlobStore {
+    root: PathBuf,
+}
+
+impl BlobStore {
+    /// Create a store keeping its blobs below `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Whether files in `dir` can be hard links to the blobs of this store
+    pub fn can_link_into(&self, dir: &Path) -> bool {
+        let probe_name = format!(".link-probe-{}", std::process::id());
+        let probe = self.root.join(&probe_name);
+        let link = dir.join(&probe_name);
+        let linked = fs::create_dir_all(&self.root)
+            .and_then(|_| fs::write(&probe, b""))
+            .and_then(|_| fs::hard_link(&probe, &link))
+            .is_ok();
+        let _ = fs::remove_file(&link);
+        let _ = fs::remove_file(&probe);
+        linked
+    }
+
+    /// Path of the blob with the given hash
+    pub fn blob_path(&self, hash: &str) -> PathBuf {
+        let shard = hash.get(..2).unwrap_or(hash);
+        self.root.join(shard).join(hash)
+    }
+
+    /// Move a finished file into the store and link it at `target`
+    ///
+    /// A file whose contents are already stored is discarded in favour of
+    /// the existing blob. `source` and `target` may be the same path. Returns
+    /// the hash of the contents.
+    pub fn store(&self, source: &Path, target: &Path) -> io::Result<String> {
+        let hash = utils::hash_file(source)
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        let blob = self.blob_path(&hash);
+
+        if blob.is_file() {
+            fs::remove_file(source)?;
+        } else {
+            if let Some(parent) = blob.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            if let Err(e) = fs::rename(source, &blob) {
+                // The store may be on another file system than the source;
+                // copy beside the blob first so it never appears half written
+                debug!("Copying {} into the blob store: {}", source.display(), e);
+                let partial = blob.with_extension("partial");
+                fs::copy(source, &partial)?;
+                fs::rename(&partial, &blob)?;
+                fs::remove_file(source)?;
+            }
+        }
+
+        // Replace whatever was at the target, which may be an older link
+        match fs::remove_file(target) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        if let Err(e) = fs::hard_link(&blob, target) {
+            debug!("Copying {} instead of linking it: {}", target.display(), e);
+            fs::copy(&blob, target)?;
+        }
+
+        Ok(hash)
+    }
+
+    /// Delete blobs whose hash is not in `referenced`
+    ///
+    /// With `dry_run` nothing is deleted. Returns the number of blobs and
+    /// bytes that were, or would be, reclaimed.
+    pub fn remove_unreferenced(&self, referenced: &HashSet<String>, dry_run: bool) -> (usize, u64) {
+        let mut removed = 0;
+        let mut reclaimed_bytes = 0;
+
+        let shards = fs::read_dir(&self.root).into_iter().flatten().flatten();
+        for shard in shards {
+            for blob in fs::read_dir(shard.path()).into_iter().flatten().flatten() {
+                let hash = blob.file_name().to_string_lossy().into_owned();
+                if referenced.contains(&hash) {
+                    continue;
+                }
+                let size = blob.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+                if !dry_run {
+                    if let Err(e) = fs::remove_file(blob.path()) {
+                        warn!("Failed to remove blob {}: {}", blob.path().display(), e);
+                        continue;
+                    }
+                }
+                removed += 1;
+                reclaimed_bytes += size;
+            }
+            if !dry_run {
+                let _ = fs::remove_dir(shard.path());
+            }
+        }
+
+        (removed, reclaimed_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_identical_files_share_a_blob() {
+        let temp_dir = tempdir().unwrap();
+        let store = BlobStore::new(temp_dir.path().join("blobs"));
+        let cache_dir = temp_dir.path().join("gamedata");
+        fs::create_dir_all(cache_dir.join("cba")).unwrap();
+        fs::create_dir_all(cache_dir.join("ace")).unwrap();
+
+        let first = temp_dir.path().join("first.hpp");
+        let second = temp_dir.path().join("second.hpp");
+        fs::write(&first, "#define QUOTE(var) #var").unwrap();
+        fs::write(&second, "#define QUOTE(var) #var").unwrap();
+
+        let cba_macros = cache_dir.join("cba/script_macros.hpp");
+        let ace_macros = cache_dir.join("ace/script_macros.hpp");
+        let first_hash = store.store(&first, &cba_macros).unwrap();
+        let second_hash = store.store(&second, &ace_macros).unwrap();
+
+        assert_eq!(first_hash, second_hash);
+        assert!(!first.exists() && !second.exists());
+        assert_eq!(fs::read_to_string(&ace_macros).unwrap(), "#define QUOTE(var) #var");
+        assert_eq!(fs::read_dir(temp_dir.path().join("blobs")).unwrap().count(), 1);
+        assert!(store.blob_path(&first_hash).is_file());
+    }
+
+    #[test]
+    fn test_store_in_place() {
+        let temp_dir = tempdir().unwrap();
+        let store = BlobStore::new(temp_dir.path().join("blobs"));
+        let file = temp_dir.path().join("logo.paa");
+        fs::write(&file, "texture").unwrap();
+
+        let hash = store.store(&file, &file).unwrap();
+
+        assert_eq!(fs::read_to_string(&file).unwrap(), "texture");
+        assert!(store.blob_path(&hash).is_file());
+    }
+
+    #[test]
+    fn test_can_link_into() {
+        let temp_dir = tempdir().unwrap();
+        let store = BlobStore::new(temp_dir.path().join("blobs"));
+        let cache_dir = temp_dir.path().join("gamedata");
+        fs::create_dir_all(&cache_dir).unwrap();
+
+        assert!(store.can_link_into(&cache_dir));
+        assert!(!store.can_link_into(&temp_dir.path().join("missing")));
+
+        // The probe leaves nothing behind
+        assert_eq!(fs::read_dir(&cache_dir).unwrap().count(), 0);
+        assert_eq!(fs::read_dir(temp_dir.path().join("blobs")).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_remove_unreferenced() {
+        let temp_dir = tempdir().unwrap();
+        let store = BlobStore::new(temp_dir.path().join("blobs"));
+        let kept = temp_dir.path().join("kept.sqf");
+        let dropped = temp_dir.path().join("dropped.sqf");
+        fs::write(&kept, "kept").unwrap();
+        fs::write(&dropped, "dropped").unwrap();
+        let kept_hash = store.store(&kept, &kept).unwrap();
+        let dropped_hash = store.store(&dropped, &dropped).unwrap();
+        let referenced = HashSet::from([kept_hash.clone()]);
+
+        assert_eq!(store.remove_unreferenced(&referenced, true), (1, 7));
+        assert!(store.blob_path(&dropped_hash).is_file());
+
+        assert_eq!(store.remove_unreferenced(&referenced, false), (1, 7));
+        assert!(!store.blob_path(&dropped_hash).exists());
+        assert!(store.blob_path(&kept_hash).is_file());
+        assert!(kept.is_file());
+    }
+}
@@ -35,6 +35,10 @@ pub enum CacheError {
     #[error("Invalid configuration: {0}")]
     Configuration(String),
     
+    /// Error from the SQLite extraction state
+    #[error("Database error: {0}")]
+    Database(#[from] arma3_database::DatabaseError),
+    
     /// IO error wrapper
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
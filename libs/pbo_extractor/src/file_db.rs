@@ -1,12 +1,16 @@
 use std::path::{Path, PathBuf};
 use std::fs::{self, File};
-use std::io::{Read, Write};
-use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::collections::{HashMap, HashSet};
 use log::{debug, info, warn};
 use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 
-use crate::models::{PboMetadata, PboType};
+use pbo_tools::signatures::SignatureStatus;
+
+use crate::models::{ExtractionFailure, ExtractionStats, PboEntry, PboMetadata, PboType};
+use crate::mods::ModIdentity;
+use crate::store::{same_extensions, ExtractionStore};
 use crate::error::{Result, CacheError};
 
 /// Represents an extracted file in the database
@@ -23,6 +27,18 @@ pub struct ExtractedFileInfo {
     
     /// File extension
     pub extension: String,
+    
+    /// Whether the file is in the cache, or only listed in its PBO's entry table
+    #[serde(default = "default_extracted")]
+    pub extracted: bool,
+    
+    /// SHA-1 of the file contents, if the file is kept in the blob store
+    #[serde(default)]
+    pub content_hash: Option<String>,
+}
+
+fn default_extracted() -> bool {
+    true
 }
 
 /// Represents a failed extraction record
@@ -36,6 +52,55 @@ pub struct FailedExtraction {
     
     /// Timestamp of the failure
     pub timestamp: DateTime<Utc>,
+    
+    /// Path of the PBO that failed
+    #[serde(default)]
+    pub pbo_path: PathBuf,
+    
+    /// Error category, see [`ExtractionFailure::category`]
+    #[serde(default = "default_category")]
+    pub category: String,
+    
+    /// Whether the error is transient
+    #[serde(default)]
+    pub retryable: bool,
+    
+    /// Number of extraction attempts that failed in a row
+    #[serde(default = "default_attempts")]
+    pub attempts: u32,
+    
+    /// SHA-1 of the PBO contents at the time of the failure
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    
+    /// Version of the extractor that recorded the failure
+    #[serde(default)]
+    pub tool_version: Option<String>,
+}
+
+fn default_category() -> String {
+    "unknown".to_string()
+}
+
+fn default_attempts() -> u32 {
+    1
+}
+
+impl FailedExtraction {
+    /// Create a failure record, counting the attempts of an earlier failure of the same PBO
+    pub fn new(path: &Path, failure: ExtractionFailure, previous: Option<&FailedExtraction>) -> Self {
+        Self {
+            pbo_id: normalize_path(path),
+            error_message: failure.message,
+            timestamp: Utc::now(),
+            pbo_path: path.to_path_buf(),
+            category: failure.category,
+            retryable: failure.retryable,
+            attempts: previous.map_or(1, |previous| previous.attempts + 1),
+            content_hash: failure.content_hash,
+            tool_version: Some(crate::VERSION.to_string()),
+        }
+    }
 }
 
 /// Represents a PBO record in the database
@@ -67,6 +132,72 @@ pub struct PboRecord {
     
     /// Extracted files (relative paths)
     pub extracted_files: Vec<PathBuf>,
+    
+    /// Signature status, if it was checked
+    #[serde(default)]
+    pub signature_status: Option<SignatureStatus>,
+    
+    /// SHA-1 of the PBO contents, if it was hashed
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    
+    /// ID of an identical PBO whose extracted files this one shares
+    #[serde(default)]
+    pub duplicate_of: Option<String>,
+    
+    /// Mod the PBO belongs to, if it is part of an `@mod/addons` layout
+    #[serde(default)]
+    pub mod_identity: Option<ModIdentity>,
+    
+    /// Full file table of the PBO, including entries that were not extracted
+    #[serde(default)]
+    pub entries: Vec<PboEntry>,
+    
+    /// Time and data spent on the last extraction, unless it is a duplicate
+    #[serde(default)]
+    pub extraction_stats: Option<ExtractionStats>,
+    
+    /// SHA-1 of each extracted file kept in the blob store
+    #[serde(default)]
+    pub file_hashes: HashMap<PathBuf, String>,
+}
+
+impl PboRecord {
+    /// Find an entry of the PBO by its path, ignoring case and slash direction
+    pub fn find_entry(&self, entry_path: &str) -> Option<&PboEntry> {
+        let wanted = entry_path.replace('\\', "/");
+        self.entries.iter().find(|entry| entry.path.eq_ignore_ascii_case(&wanted))
+    }
+    
+    /// Create the record of a finished extraction
+    pub(crate) fn from_metadata(metadata: &PboMetadata) -> Self {
+        // Convert SystemTime to DateTime<Utc>
+        let last_modified = DateTime::<Utc>::from(metadata.last_modified);
+        let extraction_time = DateTime::<Utc>::from(metadata.extraction_time);
+        
+        Self {
+            id: normalize_path(&metadata.get_full_path()),
+            full_path: metadata.get_full_path(),
+            base_dir: if metadata.base_dir.as_os_str().is_empty() {
+                None
+            } else {
+                Some(metadata.base_dir.clone())
+            },
+            file_size: metadata.file_size,
+            last_modified,
+            extraction_time,
+            pbo_type: metadata.pbo_type,
+            used_extensions: metadata.used_extensions.clone(),
+            extracted_files: metadata.extracted_files.clone(),
+            signature_status: metadata.signature_status.clone(),
+            content_hash: metadata.content_hash.clone(),
+            duplicate_of: metadata.duplicate_of.as_deref().map(normalize_path),
+            mod_identity: metadata.mod_identity.clone(),
+            entries: metadata.entries.clone(),
+            extraction_stats: metadata.extraction_stats,
+            file_hashes: metadata.file_hashes.clone(),
+        }
+    }
 }
 
 /// Database container structure
@@ -82,7 +213,23 @@ pub struct FileDatabase {
     pub failed_extractions: HashMap<String, FailedExtraction>,
 }
 
+/// A change appended to the journal between saves of the database file
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum JournalEntry {
+    /// A PBO finished extracting
+    Extracted(Box<PboRecord>),
+    
+    /// A PBO failed to extract
+    Failed(FailedExtraction),
+}
+
 /// Manager for file-based database operations
+///
+/// Every finished or failed extraction is appended to a journal next to the
+/// database file and synced to disk, so an interrupted run keeps everything
+/// it completed. The journal is replayed on load and folded into the
+/// database file on the next [`FileDbManager::save`].
 #[derive(Debug)]
 pub struct FileDbManager {
     /// Path to the database file
@@ -99,6 +246,13 @@ pub fn normalize_path(path: &Path) -> String {
         .replace('\\', "/")
 }
 
+/// Path of the journal belonging to a database file
+fn journal_path(db_path: &Path) -> PathBuf {
+    let mut path = db_path.as_os_str().to_owned();
+    path.push(".journal");
+    PathBuf::from(path)
+}
+
 impl FileDbManager {
     /// Create a new file database manager, loading from db_path if it exists.
     pub fn new(db_path: &Path) -> Result<Self> {
@@ -130,11 +284,16 @@ impl FileDbManager {
             }
         };
         
-        let manager = Self {
+        let mut manager = Self {
             db_path: db_path.to_path_buf(),
             db,
         };
         
+        // Pick up what an interrupted run recorded after the last save
+        if manager.replay_journal()? > 0 {
+            manager.save(None)?;
+        }
+        
         info!("File database manager initialized successfully.");
         Ok(manager)
     }
@@ -164,10 +323,67 @@ impl FileDbManager {
             .map_err(|e| CacheError::IndexOperation(format!("Failed to parse database file '{}': {}", path.display(), e)))
     }
     
+    /// Apply the journal left next to the database file
+    ///
+    /// A crash can leave the last entry half written; it and anything after
+    /// it are ignored. Returns the number of entries applied.
+    fn replay_journal(&mut self) -> Result<usize> {
+        let path = journal_path(&self.db_path);
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(CacheError::IndexOperation(format!("Failed to open journal '{}': {}", path.display(), e))),
+        };
+        
+        let mut applied = 0;
+        for line in BufReader::new(file).lines() {
+            let line = line
+                .map_err(|e| CacheError::IndexOperation(format!("Failed to read journal '{}': {}", path.display(), e)))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<JournalEntry>(&line) {
+                Ok(JournalEntry::Extracted(record)) => self.apply_record(*record),
+                Ok(JournalEntry::Failed(failure)) => self.apply_failure(failure),
+                Err(e) => {
+                    warn!("Ignoring incomplete journal entry in {}: {}", path.display(), e);
+                    break;
+                }
+            }
+            applied += 1;
+        }
+        
+        if applied > 0 {
+            info!("Recovered {} entries from interrupted run in {}", applied, path.display());
+        }
+        Ok(applied)
+    }
+    
+    /// Append an entry to the journal and sync it to disk
+    fn append_journal(&self, entry: &JournalEntry) -> Result<()> {
+        let path = journal_path(&self.db_path);
+        let mut line = serde_json::to_vec(entry)
+            .map_err(|e| CacheError::IndexOperation(format!("Failed to serialize journal entry: {}", e)))?;
+        line.push(b'\n');
+        
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| CacheError::IndexOperation(format!("Failed to open journal '{}': {}", path.display(), e)))?;
+        file.write_all(&line)
+            .and_then(|_| file.sync_data())
+            .map_err(|e| CacheError::IndexOperation(format!("Failed to write journal '{}': {}", path.display(), e)))
+    }
+    
     /// Save the current database state to a JSON file.
     ///
-    /// If `target_path` is `None`, saves to the `db_path` configured during initialization.
+    /// If `target_path` is `None`, saves to the `db_path` configured during initialization
+    /// and clears the journal, whose entries are now part of the file.
     /// If `target_path` is `Some`, saves to the specified path.
+    ///
+    /// The file is written under a temporary name and renamed into place, so an
+    /// interrupted save leaves the previous version intact.
     pub fn save(&self, target_path: Option<&Path>) -> Result<()> {
         let path_to_save = target_path.unwrap_or(&self.db_path);
 
@@ -182,158 +398,137 @@ impl FileDbManager {
         let contents = serde_json::to_vec_pretty(&self.db)
             .map_err(|e| CacheError::IndexOperation(format!("Failed to serialize database: {}", e)))?;
 
-        let mut file = File::create(path_to_save)
-            .map_err(|e| CacheError::IndexOperation(format!("Failed to create database file at '{}': {}", path_to_save.display(), e)))?;
+        let mut temp_path = path_to_save.as_os_str().to_owned();
+        temp_path.push(".tmp");
+        let temp_path = PathBuf::from(temp_path);
+
+        let mut file = File::create(&temp_path)
+            .map_err(|e| CacheError::IndexOperation(format!("Failed to create database file at '{}': {}", temp_path.display(), e)))?;
 
         file.write_all(&contents)
-            .map_err(|e| CacheError::IndexOperation(format!("Failed to write database file at '{}': {}", path_to_save.display(), e)))?;
+            .and_then(|_| file.sync_all())
+            .map_err(|e| CacheError::IndexOperation(format!("Failed to write database file at '{}': {}", temp_path.display(), e)))?;
+
+        fs::rename(&temp_path, path_to_save)
+            .map_err(|e| CacheError::IndexOperation(format!("Failed to replace database file at '{}': {}", path_to_save.display(), e)))?;
+
+        if target_path.is_none() {
+            let journal = journal_path(&self.db_path);
+            if journal.exists() {
+                fs::remove_file(&journal)
+                    .map_err(|e| CacheError::IndexOperation(format!("Failed to clear journal '{}': {}", journal.display(), e)))?;
+            }
+        }
 
         debug!("Database saved successfully to {}", path_to_save.display());
         Ok(())
     }
     
-    /// Create PboRecord from PboMetadata
-    fn create_pbo_record(metadata: &PboMetadata) -> PboRecord {
-        // Convert SystemTime to DateTime<Utc>
-        let last_modified = DateTime::<Utc>::from(metadata.last_modified);
-        let extraction_time = DateTime::<Utc>::from(metadata.extraction_time);
-        
-        PboRecord {
-            id: normalize_path(&metadata.get_full_path()),
-            full_path: metadata.get_full_path(),
-            base_dir: if metadata.base_dir.as_os_str().is_empty() {
-                None
-            } else {
-                Some(metadata.base_dir.clone())
-            },
-            file_size: metadata.file_size,
-            last_modified,
-            extraction_time,
-            pbo_type: metadata.pbo_type,
-            used_extensions: metadata.used_extensions.clone(),
-            extracted_files: metadata.extracted_files.clone(),
-        }
-    }
-    
-    /// Check if a PBO needs to be extracted
-    pub fn needs_extraction(
-        &self,
-        path: &Path,
-        _pbo_type: PboType,
-        extensions: &[String],
-    ) -> Result<bool> {
-        if !path.exists() {
-            return Ok(true);
-        }
+    /// Apply a PBO record to the in-memory database
+    fn apply_record(&mut self, pbo_record: PboRecord) {
+        let pbo_id = pbo_record.id.clone();
         
-        // Normalize the path to use as ID
-        let pbo_id = normalize_path(path);
+        // A successful extraction supersedes any earlier failure
+        self.db.failed_extractions.remove(&pbo_id);
         
-        // Check if we have this PBO in the database
-        if let Some(record) = self.db.pbos.get(&pbo_id) {
-            // If the list of extensions has changed, we need to extract again
-            // Sort both lists to ensure order doesn't matter
-            let mut record_extensions = record.used_extensions.clone();
-            let mut current_extensions = extensions.to_vec();
+        // Update extracted files index. Duplicates share the files of the PBO
+        // that owns them, so the index keeps pointing at the owner.
+        if pbo_record.duplicate_of.is_none() {
+            for file_path in &pbo_record.extracted_files {
+                let path_str = file_path.to_string_lossy().to_string();
+                let extension = file_path.extension()
+                    .map(|ext| ext.to_string_lossy().to_string())
+                    .unwrap_or_default();
             
-            record_extensions.sort();
-            current_extensions.sort();
+                let file_info = ExtractedFileInfo {
+                    relative_path: file_path.clone(),
+                    pbo_id: pbo_id.clone(),
+                    extraction_time: pbo_record.extraction_time,
+                    extension,
+                    extracted: true,
+                    content_hash: pbo_record.file_hashes.get(file_path).cloned(),
+                };
             
-            if record_extensions != current_extensions {
-                debug!("Extensions changed for {}, needs extraction", path.display());
-                return Ok(true);
+                self.db.files.insert(path_str, file_info);
             }
-            
-            // Check if the file has been modified since last extraction
-            let metadata = std::fs::metadata(path)
-                .map_err(|e| CacheError::FileOperation(format!("Failed to get metadata: {}", e)))?;
-            
-            let current_modified = DateTime::<Utc>::from(metadata.modified()
-                .map_err(|e| CacheError::FileOperation(format!("Failed to get modified time: {}", e)))?);
-            
-            if current_modified > record.last_modified {
-                debug!("File modified since last extraction: {}", path.display());
-                return Ok(true);
+        }
+        
+        // Add to database
+        self.db.pbos.insert(pbo_id, pbo_record);
+    }
+    
+    /// Let the first duplicate of a removed PBO that stays own its extracted files
+    fn promote_duplicate(&mut self, pbo_id: &str, removed: &HashSet<String>) {
+        let Some(original) = self.db.pbos.get(pbo_id).cloned() else {
+            return;
+        };
+        let Some(heir_id) = self.db.pbos.values()
+            .filter(|pbo| pbo.duplicate_of.as_deref() == Some(pbo_id) && !removed.contains(&pbo.id))
+            .map(|pbo| pbo.id.clone())
+            .min()
+        else {
+            return;
+        };
+        
+        for pbo in self.db.pbos.values_mut() {
+            if pbo.duplicate_of.as_deref() == Some(pbo_id) {
+                pbo.duplicate_of = Some(heir_id.clone());
             }
-            
-            // No need to extract
-            debug!("PBO does not need extraction: {}", path.display());
-            Ok(false)
-        } else {
-            // Not in database, needs extraction
-            debug!("PBO not in database, needs extraction: {}", path.display());
-            Ok(true)
         }
+        
+        let mut heir = self.db.pbos[&heir_id].clone();
+        heir.duplicate_of = None;
+        heir.extracted_files = original.extracted_files;
+        heir.file_hashes = original.file_hashes;
+        debug!("Promoted {} to owner of the files of {}", heir_id, pbo_id);
+        self.apply_record(heir);
     }
     
+    /// Apply a failed extraction record to the in-memory database
+    fn apply_failure(&mut self, failed_extraction: FailedExtraction) {
+        self.db.failed_extractions.insert(failed_extraction.pbo_id.clone(), failed_extraction);
+    }
+}
+
+impl ExtractionStore for FileDbManager {
     /// Update metadata for a PBO
-    pub fn update_metadata(&mut self, metadata: PboMetadata) -> Result<()> {
+    fn update_metadata(&mut self, metadata: PboMetadata) -> Result<()> {
         // Convert to database record
-        let pbo_record = Self::create_pbo_record(&metadata);
-        let pbo_id = pbo_record.id.clone();
+        let pbo_record = PboRecord::from_metadata(&metadata);
         
-        // Add to database
-        self.db.pbos.insert(pbo_id.clone(), pbo_record);
-        
-        // Update extracted files index
-        for file_path in &metadata.extracted_files {
-            let path_str = file_path.to_string_lossy().to_string();
-            let extension = file_path.extension()
-                .map(|ext| ext.to_string_lossy().to_string())
-                .unwrap_or_default();
-            
-            let file_info = ExtractedFileInfo {
-                relative_path: file_path.clone(),
-                pbo_id: pbo_id.clone(),
-                extraction_time: DateTime::<Utc>::from(metadata.extraction_time),
-                extension,
-            };
-            
-            self.db.files.insert(path_str, file_info);
-        }
-        
-        // Save changes to disk
-        self.save(None)?;
+        // Record the change durably before applying it
+        self.append_journal(&JournalEntry::Extracted(Box::new(pbo_record.clone())))?;
+        self.apply_record(pbo_record);
         
         debug!("Updated metadata for PBO: {}", metadata.path.display());
         Ok(())
     }
     
     /// Record a failed extraction
-    pub fn add_failed_extraction(&mut self, path: &Path, error_message: String) -> Result<()> {
-        let pbo_id = normalize_path(path);
-        let now = Utc::now();
+    fn add_failed_extraction(&mut self, path: &Path, _pbo_type: PboType, failure: ExtractionFailure) -> Result<()> {
+        let previous = self.db.failed_extractions.get(&normalize_path(path));
+        let failed_extraction = FailedExtraction::new(path, failure, previous);
         
-        // Create failed extraction record
-        let failed_extraction = FailedExtraction {
-            pbo_id: pbo_id.clone(),
-            error_message,
-            timestamp: now,
-        };
-        
-        // Add to database
-        self.db.failed_extractions.insert(pbo_id, failed_extraction);
-        
-        // Save changes to disk
-        self.save(None)?;
+        // Record the failure durably before applying it
+        self.append_journal(&JournalEntry::Failed(failed_extraction.clone()))?;
+        self.apply_failure(failed_extraction);
         
         debug!("Recorded failed extraction for {}", path.display());
         Ok(())
     }
     
-    /// Check if a PBO has a failed extraction record
-    pub fn is_failed_extraction(&self, path: &Path) -> Result<Option<(String, String)>> {
-        let pbo_id = normalize_path(path);
-        
-        if let Some(failed) = self.db.failed_extractions.get(&pbo_id) {
-            Ok(Some((failed.timestamp.to_rfc3339(), failed.error_message.clone())))
-        } else {
-            Ok(None)
-        }
+    /// Get the failure record of a PBO
+    fn get_failed_extraction(&self, path: &Path) -> Result<Option<FailedExtraction>> {
+        Ok(self.db.failed_extractions.get(&normalize_path(path)).cloned())
+    }
+    
+    /// Get all failure records
+    fn get_failed_extractions(&self) -> Result<Vec<FailedExtraction>> {
+        Ok(self.db.failed_extractions.values().cloned().collect())
     }
     
     /// Find the source PBO for a file path
-    pub fn find_pbo_for_file(&self, file_path: &Path) -> Result<Option<PboRecord>> {
+    fn find_pbo_for_file(&self, file_path: &Path) -> Result<Option<PboRecord>> {
         // Try to match against relative paths in our file index
         let path_str = file_path.to_string_lossy().to_string();
         
@@ -357,7 +552,11 @@ impl FileDbManager {
     }
     
     /// Find all files with a specific extension
-    pub fn find_files_by_extension(&self, extension: &str) -> Result<Vec<ExtractedFileInfo>> {
+    ///
+    /// Entries that were never extracted are included from the PBO entry
+    /// tables with `extracted` set to false; see
+    /// [`ExtractionManager::materialize`](crate::ExtractionManager::materialize).
+    fn find_files_by_extension(&self, extension: &str) -> Result<Vec<ExtractedFileInfo>> {
         let mut result = Vec::new();
         
         for file_info in self.db.files.values() {
@@ -366,11 +565,171 @@ impl FileDbManager {
             }
         }
         
+        // Duplicates list the same entries as the PBO that owns their files
+        for record in self.db.pbos.values().filter(|record| record.duplicate_of.is_none()) {
+            let extracted: HashSet<String> = record.extracted_files.iter()
+                .map(|file| file.to_string_lossy().replace('\\', "/").to_lowercase())
+                .collect();
+            
+            for entry in &record.entries {
+                let matches = entry.extension().is_some_and(|ext| ext.eq_ignore_ascii_case(extension));
+                if matches && !extracted.contains(&entry.path.to_lowercase()) {
+                    result.push(ExtractedFileInfo {
+                        relative_path: PathBuf::from(&entry.path),
+                        pbo_id: record.id.clone(),
+                        extraction_time: record.extraction_time,
+                        extension: extension.to_string(),
+                        extracted: false,
+                        content_hash: None,
+                    });
+                }
+            }
+        }
+        
+        Ok(result)
+    }
+    
+    /// Record a file that was extracted from a PBO after its initial extraction
+    fn add_extracted_file(&mut self, path: &Path, relative_path: PathBuf, content_hash: Option<String>) -> Result<()> {
+        let mut record = self.db.pbos.get(&normalize_path(path))
+            .cloned()
+            .ok_or_else(|| CacheError::IndexOperation(format!("No record for PBO {}", path.display())))?;
+        
+        if let Some(content_hash) = content_hash {
+            record.file_hashes.insert(relative_path.clone(), content_hash);
+        }
+        if !record.extracted_files.contains(&relative_path) {
+            record.extracted_files.push(relative_path);
+        }
+        
+        self.append_journal(&JournalEntry::Extracted(Box::new(record.clone())))?;
+        self.apply_record(record);
+        Ok(())
+    }
+    
+    /// Get the record of a PBO by its ID
+    fn get_record_by_id(&self, pbo_id: &str) -> Result<Option<PboRecord>> {
+        Ok(self.db.pbos.get(pbo_id).cloned())
+    }
+    
+    /// Find an extracted PBO with the given contents and extraction settings
+    ///
+    /// Only PBOs that own their extracted files are considered, never other
+    /// duplicates.
+    fn find_by_content_hash(
+        &self,
+        content_hash: &str,
+        pbo_type: PboType,
+        extensions: &[String],
+    ) -> Result<Option<PboRecord>> {
+        let result = self.db.pbos.values()
+            .filter(|pbo| pbo.duplicate_of.is_none() && pbo.pbo_type == pbo_type)
+            .filter(|pbo| pbo.content_hash.as_deref() == Some(content_hash))
+            .filter(|pbo| same_extensions(&pbo.used_extensions, extensions))
+            .min_by(|a, b| a.id.cmp(&b.id))
+            .cloned();
+        
         Ok(result)
     }
     
+    /// Get the PBO records sharing the extracted files of another PBO
+    fn get_duplicates(&self, path: &Path) -> Result<Vec<PboRecord>> {
+        let pbo_id = normalize_path(path);
+        let mut result: Vec<PboRecord> = self.db.pbos.values()
+            .filter(|pbo| pbo.duplicate_of.as_deref() == Some(pbo_id.as_str()))
+            .cloned()
+            .collect();
+        result.sort_by(|a, b| a.id.cmp(&b.id));
+        
+        Ok(result)
+    }
+    
+    /// Store the current mod identity on the records of unchanged PBOs
+    ///
+    /// `mod.cpp` and `meta.cpp` can change without the PBOs changing, e.g. when
+    /// a workshop mod is renamed.
+    fn refresh_mod_identities(&mut self, mods: &HashMap<PathBuf, ModIdentity>, paths: &[PathBuf]) -> Result<usize> {
+        let mut refreshed = 0;
+        for path in paths {
+            let Some(record) = self.db.pbos.get_mut(&normalize_path(path)) else {
+                continue;
+            };
+            let mod_identity = mods.get(path);
+            if record.mod_identity.as_ref() != mod_identity {
+                record.mod_identity = mod_identity.cloned();
+                refreshed += 1;
+            }
+        }
+        
+        if refreshed > 0 {
+            self.save(None)?;
+            debug!("Refreshed mod identities of {} PBOs", refreshed);
+        }
+        Ok(refreshed)
+    }
+    
+    /// Get all records of PBOs belonging to the mod in the given folder
+    fn get_mod_records(&self, folder: &str) -> Result<Vec<PboRecord>> {
+        let mut records: Vec<PboRecord> = self.db.pbos.values()
+            .filter(|pbo| pbo.mod_identity.as_ref().is_some_and(|m| m.folder.eq_ignore_ascii_case(folder)))
+            .cloned()
+            .collect();
+        records.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(records)
+    }
+    
+    /// Move the stored timestamp of unchanged PBOs forward
+    ///
+    /// Called for PBOs that were found not to need extraction, so a timestamp
+    /// that moved without a content change is only hashed once.
+    fn refresh_modified_times(&mut self, paths: &[PathBuf]) -> Result<usize> {
+        let mut refreshed = 0;
+        for path in paths {
+            let Some(record) = self.db.pbos.get_mut(&normalize_path(path)) else {
+                continue;
+            };
+            let Ok(modified) = std::fs::metadata(path).and_then(|m| m.modified()) else {
+                continue;
+            };
+            let modified = DateTime::<Utc>::from(modified);
+            if modified > record.last_modified {
+                record.last_modified = modified;
+                refreshed += 1;
+            }
+        }
+        
+        if refreshed > 0 {
+            self.save(None)?;
+            debug!("Refreshed timestamps of {} unchanged PBOs", refreshed);
+        }
+        Ok(refreshed)
+    }
+    
+    /// Get the IDs of all PBOs with a failed extraction record
+    fn get_failed_extraction_ids(&self) -> Result<Vec<String>> {
+        Ok(self.db.failed_extractions.keys().cloned().collect())
+    }
+    
+    /// Remove PBO records together with their file index and failure entries
+    ///
+    /// Extracted files on disk are left alone.
+    fn remove_pbos(&mut self, pbo_ids: &HashSet<String>) -> Result<()> {
+        for pbo_id in pbo_ids {
+            self.promote_duplicate(pbo_id, pbo_ids);
+        }
+        self.db.pbos.retain(|id, _| !pbo_ids.contains(id));
+        self.db.failed_extractions.retain(|id, _| !pbo_ids.contains(id));
+        // Index entries of removed PBOs, or of PBOs that no longer exist at all
+        let pbos = &self.db.pbos;
+        self.db.files.retain(|_, file| pbos.contains_key(&file.pbo_id));
+        
+        self.save(None)?;
+        debug!("Removed {} PBOs from the database", pbo_ids.len());
+        Ok(())
+    }
+    
     /// Get all game data PBO records
-    pub fn get_game_data_metadata(&self) -> Result<Vec<PboRecord>> {
+    fn get_game_data_metadata(&self) -> Result<Vec<PboRecord>> {
         let result: Vec<PboRecord> = self.db.pbos.values()
             .filter(|pbo| pbo.pbo_type == PboType::GameData)
             .cloned()
@@ -379,8 +738,18 @@ impl FileDbManager {
         Ok(result)
     }
     
+    /// Get all PBO records whose signature was checked and is not valid
+    fn get_signature_issues(&self) -> Result<Vec<PboRecord>> {
+        let result: Vec<PboRecord> = self.db.pbos.values()
+            .filter(|pbo| matches!(&pbo.signature_status, Some(status) if !status.is_valid()))
+            .cloned()
+            .collect();
+        
+        Ok(result)
+    }
+    
     /// Get all mission PBO records
-    pub fn get_mission_metadata(&self) -> Result<Vec<PboRecord>> {
+    fn get_mission_metadata(&self) -> Result<Vec<PboRecord>> {
         let result: Vec<PboRecord> = self.db.pbos.values()
             .filter(|pbo| pbo.pbo_type == PboType::Mission)
             .cloned()
@@ -388,11 +757,17 @@ impl FileDbManager {
         
         Ok(result)
     }
+    
+    fn flush(&mut self) -> Result<()> {
+        self.save(None)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::RetryPolicy;
+    use crate::utils;
     use tempfile::tempdir;
     use std::io::Write;
     use std::time::{Duration, SystemTime};
@@ -449,8 +824,8 @@ mod tests {
         // Update metadata
         db_manager.update_metadata(metadata.clone()).unwrap();
         
-        // Verify the database was saved
-        assert!(db_path.exists());
+        // Verify the change was recorded on disk
+        assert!(journal_path(&db_path).exists());
         
         // Load the database again to verify persistence
         let db_manager2 = FileDbManager::new(&db_path).unwrap();
@@ -617,7 +992,7 @@ mod tests {
 
         // Add a failed extraction record
         let error_msg = "Extraction timed out".to_string();
-        db_manager.add_failed_extraction(&pbo_path, error_msg.clone()).unwrap();
+        db_manager.add_failed_extraction(&pbo_path, PboType::GameData, error_msg.clone().into()).unwrap();
 
         // Verify the record exists
         let failed_info = db_manager.is_failed_extraction(&pbo_path).unwrap();
@@ -631,6 +1006,57 @@ mod tests {
         let failed_info2 = db_manager2.is_failed_extraction(&pbo_path).unwrap();
         assert!(failed_info2.is_some());
         assert_eq!(failed_info2.unwrap().1, error_msg);
+
+        // A later successful extraction clears the failure
+        let metadata = create_test_metadata(
+            &pbo_path,
+            PboType::Mission,
+            vec![PathBuf::from("init.sqf")],
+            vec!["sqf".to_string()],
+        );
+        db_manager.update_metadata(metadata).unwrap();
+        assert!(db_manager.is_failed_extraction(&pbo_path).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_retry_policy() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("retry_test.db");
+        let mut db_manager = FileDbManager::new(&db_path).unwrap();
+        let policy = RetryPolicy::default();
+
+        let pbo_path = create_mock_pbo(temp_dir.path(), "timeout.pbo");
+        let content_hash = utils::hash_file(&pbo_path).ok();
+
+        // Transient failures are retried until they used up their attempts
+        for attempt in 1..=policy.max_attempts {
+            let failure = ExtractionFailure::new("Operation timed out", "timeout", true)
+                .with_content_hash(content_hash.clone());
+            db_manager.add_failed_extraction(&pbo_path, PboType::GameData, failure).unwrap();
+            let failed = db_manager.get_failed_extraction(&pbo_path).unwrap().unwrap();
+            assert_eq!(failed.attempts, attempt);
+            assert_eq!(policy.should_retry(&failed, &pbo_path), attempt < policy.max_attempts);
+        }
+
+        // Corrupt PBOs are not retried until their contents change
+        let corrupt_path = create_mock_pbo(temp_dir.path(), "corrupt.pbo");
+        let failure = ExtractionFailure::new("Invalid header", "corrupt", false)
+            .with_content_hash(utils::hash_file(&corrupt_path).ok());
+        db_manager.add_failed_extraction(&corrupt_path, PboType::GameData, failure).unwrap();
+        let failed = db_manager.get_failed_extraction(&corrupt_path).unwrap().unwrap();
+        assert!(!policy.should_retry(&failed, &corrupt_path));
+
+        fs::write(&corrupt_path, b"Repaired PBO content").unwrap();
+        assert!(policy.should_retry(&failed, &corrupt_path));
+        assert!(!RetryPolicy::never().should_retry(&failed, &corrupt_path));
+
+        // Failures recorded by another version of the extractor are retried
+        let mut old_failure = failed.clone();
+        old_failure.tool_version = Some("0.0.0".to_string());
+        fs::write(&corrupt_path, b"Mock PBO content").unwrap();
+        assert!(policy.should_retry(&old_failure, &corrupt_path));
+
+        assert_eq!(db_manager.get_failed_extractions().unwrap().len(), 2);
     }
 
     #[test]
@@ -668,6 +1094,44 @@ mod tests {
         assert!(txt_files.is_empty());
     }
 
+    #[test]
+    fn test_find_files_by_extension_includes_unextracted_entries() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("entries.db");
+        let mut db_manager = FileDbManager::new(&db_path).unwrap();
+
+        let pbo_path = create_mock_pbo(temp_dir.path(), "entries.pbo");
+        let mut metadata = create_test_metadata(
+            &pbo_path,
+            PboType::GameData,
+            vec![PathBuf::from("config.cpp")],
+            vec!["cpp".to_string()],
+        );
+        metadata.entries = ["config.bin", "functions/fn_init.sqf", "stringtable.xml"].iter()
+            .map(|path| PboEntry { path: path.to_string(), size: 10, timestamp: 0 })
+            .collect();
+        db_manager.update_metadata(metadata).unwrap();
+
+        // Entries that were never extracted are still found
+        let sqf_files = db_manager.find_files_by_extension("sqf").unwrap();
+        assert_eq!(sqf_files.len(), 1);
+        assert_eq!(sqf_files[0].relative_path, PathBuf::from("functions/fn_init.sqf"));
+        assert!(!sqf_files[0].extracted);
+
+        let cpp_files = db_manager.find_files_by_extension("cpp").unwrap();
+        assert_eq!(cpp_files.len(), 1);
+        assert!(cpp_files[0].extracted);
+
+        // Once extracted on demand the entry is reported only once
+        db_manager.add_extracted_file(&pbo_path, PathBuf::from("functions/fn_init.sqf"), None).unwrap();
+        let sqf_files = db_manager.find_files_by_extension("sqf").unwrap();
+        assert_eq!(sqf_files.len(), 1);
+        assert!(sqf_files[0].extracted);
+
+        let record = db_manager.get_record(&pbo_path).unwrap().unwrap();
+        assert!(record.find_entry("FUNCTIONS\\fn_init.sqf").is_some());
+    }
+
     #[test]
     fn test_get_metadata_by_type() {
         let temp_dir = tempdir().unwrap();
@@ -751,4 +1215,157 @@ mod tests {
         assert_eq!(updated_record.extracted_files[0], file2);
         assert_eq!(updated_record.extraction_time, DateTime::<Utc>::from(updated_metadata.extraction_time));
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_unchanged_content_skips_extraction() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("hash_test.db");
+        let mut db_manager = FileDbManager::new(&db_path).unwrap();
+        let extensions = vec!["sqf".to_string()];
+
+        let pbo_path = create_mock_pbo(temp_dir.path(), "hashed.pbo");
+        let mut metadata = create_test_metadata(&pbo_path, PboType::GameData, vec![], extensions.clone());
+        metadata.content_hash = Some(utils::hash_file(&pbo_path).unwrap());
+        db_manager.update_metadata(metadata).unwrap();
+
+        // Rewriting the same bytes only moves the timestamp
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        create_mock_pbo(temp_dir.path(), "hashed.pbo");
+        assert!(!db_manager.needs_extraction(&pbo_path, PboType::GameData, &extensions).unwrap());
+        assert_eq!(db_manager.refresh_modified_times(std::slice::from_ref(&pbo_path)).unwrap(), 1);
+        assert_eq!(db_manager.refresh_modified_times(std::slice::from_ref(&pbo_path)).unwrap(), 0);
+
+        // Same size, different contents
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        let mut file = File::create(&pbo_path).unwrap();
+        file.write_all(b"Mock PBO CONTENT").unwrap();
+        assert!(db_manager.needs_extraction(&pbo_path, PboType::GameData, &extensions).unwrap());
+    }
+
+    #[test]
+    fn test_duplicate_records() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("duplicates.db");
+        let mut db_manager = FileDbManager::new(&db_path).unwrap();
+        let extensions = vec!["sqf".to_string()];
+
+        let original_path = create_mock_pbo(temp_dir.path(), "original.pbo");
+        let duplicate_path = create_mock_pbo(temp_dir.path(), "duplicate.pbo");
+        let hash = utils::hash_file(&original_path).unwrap();
+
+        let mut original = create_test_metadata(
+            &original_path, PboType::GameData, vec![PathBuf::from("shared.sqf")], extensions.clone(),
+        );
+        original.content_hash = Some(hash.clone());
+        db_manager.update_metadata(original).unwrap();
+
+        let found = db_manager.find_by_content_hash(&hash, PboType::GameData, &extensions).unwrap().unwrap();
+        assert_eq!(found.full_path, original_path);
+        assert!(db_manager.find_by_content_hash(&hash, PboType::Mission, &extensions).unwrap().is_none());
+        assert!(db_manager.find_by_content_hash(&hash, PboType::GameData, &["hpp".to_string()]).unwrap().is_none());
+
+        let mut duplicate = create_test_metadata(
+            &duplicate_path, PboType::GameData, vec![PathBuf::from("shared.sqf")], extensions.clone(),
+        );
+        duplicate.content_hash = Some(hash.clone());
+        duplicate.duplicate_of = Some(original_path.clone());
+        db_manager.update_metadata(duplicate).unwrap();
+
+        // The shared file stays owned by the original
+        assert_eq!(db_manager.db.files["shared.sqf"].pbo_id, normalize_path(&original_path));
+        let duplicates = db_manager.get_duplicates(&original_path).unwrap();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].full_path, duplicate_path);
+        assert!(!db_manager.needs_extraction(&duplicate_path, PboType::GameData, &extensions).unwrap());
+
+        // Once the original changes, the duplicate needs its own copy
+        db_manager.db.pbos.get_mut(&normalize_path(&original_path)).unwrap().content_hash = Some("changed".to_string());
+        assert!(db_manager.needs_extraction(&duplicate_path, PboType::GameData, &extensions).unwrap());
+    }
+    
+    #[test]
+    fn test_removing_original_promotes_duplicate() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("promote.db");
+        let mut db_manager = FileDbManager::new(&db_path).unwrap();
+        let extensions = vec!["sqf".to_string()];
+        let shared = PathBuf::from("shared.sqf");
+
+        let original_path = create_mock_pbo(temp_dir.path(), "a_original.pbo");
+        let mut original = create_test_metadata(&original_path, PboType::GameData, vec![shared.clone()], extensions.clone());
+        original.file_hashes = HashMap::from([(shared.clone(), "c0ffee".to_string())]);
+        db_manager.update_metadata(original).unwrap();
+
+        let mut duplicate_paths = Vec::new();
+        for name in ["b_duplicate.pbo", "c_duplicate.pbo"] {
+            let path = create_mock_pbo(temp_dir.path(), name);
+            let mut duplicate = create_test_metadata(&path, PboType::GameData, vec![], extensions.clone());
+            duplicate.duplicate_of = Some(original_path.clone());
+            db_manager.update_metadata(duplicate).unwrap();
+            duplicate_paths.push(path);
+        }
+
+        db_manager.remove_pbos(&HashSet::from([normalize_path(&original_path)])).unwrap();
+
+        // The first duplicate owns the files now and the other one shares them
+        let heir = db_manager.get_record(&duplicate_paths[0]).unwrap().unwrap();
+        assert_eq!(heir.duplicate_of, None);
+        assert_eq!(heir.extracted_files, vec![shared.clone()]);
+        assert_eq!(heir.file_hashes[&shared], "c0ffee");
+        assert_eq!(db_manager.find_pbo_for_file(&shared).unwrap().unwrap().id, heir.id);
+        let other = db_manager.get_record(&duplicate_paths[1]).unwrap().unwrap();
+        assert_eq!(other.duplicate_of, Some(heir.id));
+    }
+    
+    #[test]
+    fn test_journal_survives_interrupted_run() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let pbo_path = create_mock_pbo(temp_dir.path(), "done.pbo");
+        let failed_path = create_mock_pbo(temp_dir.path(), "broken.pbo");
+        
+        // A run that records its progress but never gets to save the database file
+        {
+            let mut db_manager = FileDbManager::new(&db_path).unwrap();
+            let metadata = create_test_metadata(
+                &pbo_path,
+                PboType::GameData,
+                vec![PathBuf::from("addon/config.cpp")],
+                vec!["cpp".to_string()],
+            );
+            db_manager.update_metadata(metadata).unwrap();
+            db_manager.add_failed_extraction(&failed_path, PboType::GameData, "bad header".to_string().into()).unwrap();
+        }
+        assert!(!db_path.exists());
+        assert!(journal_path(&db_path).exists());
+        
+        // The next run picks up where it stopped and folds the journal into the file
+        let db_manager = FileDbManager::new(&db_path).unwrap();
+        assert!(!db_manager.needs_extraction(&pbo_path, PboType::GameData, &["cpp".to_string()]).unwrap());
+        assert!(db_manager.is_failed_extraction(&failed_path).unwrap().is_some());
+        assert!(db_manager.find_pbo_for_file(Path::new("addon/config.cpp")).unwrap().is_some());
+        assert!(db_path.exists());
+        assert!(!journal_path(&db_path).exists());
+    }
+    
+    #[test]
+    fn test_journal_ignores_torn_entry() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let pbo_path = create_mock_pbo(temp_dir.path(), "done.pbo");
+        
+        {
+            let mut db_manager = FileDbManager::new(&db_path).unwrap();
+            let metadata = create_test_metadata(&pbo_path, PboType::Mission, vec![], vec!["sqf".to_string()]);
+            db_manager.update_metadata(metadata).unwrap();
+        }
+        
+        // Simulate a crash in the middle of appending the next entry
+        let mut journal = fs::OpenOptions::new().append(true).open(journal_path(&db_path)).unwrap();
+        journal.write_all(b"{\"kind\":\"extracted\",\"id\":\"trunc").unwrap();
+        drop(journal);
+        
+        let db_manager = FileDbManager::new(&db_path).unwrap();
+        assert_eq!(db_manager.get_mission_metadata().unwrap().len(), 1);
+    }
+}
@@ -5,20 +5,41 @@
 //! - Extracting PBO contents to a cache location
 //! - Managing cache validity based on file modifications and extension filters
 //! - Handling both game data and mission PBOs
+//! - Recording the signature status of game data PBOs
+//! - Resolving cached files by game path through a virtual P drive
+//! - Optionally salvaging intact files from PBOs that fail extraction
+//! - Pruning cache entries of PBOs that disappeared from the source directories
+//! - Journaling finished PBOs so interrupted extractions resume where they stopped
+//! - Identifying the mod a PBO belongs to from `@mod/addons` layouts and `mod.cpp` / `meta.cpp`
+//! - Indexing the full entry table of every PBO and extracting other entries on demand
+//! - Keeping the extraction state in a JSON file or in the SQLite analysis database
+//! - Retrying failed extractions by policy and reviewing failures by error category
+//! - Recording the time and data spent extracting each PBO
+//! - Finding missions as PBOs or unpacked folders, including inside zip archives
+//! - Storing identical extracted files once in a content-addressed blob store
 
 pub mod manager;
 pub mod models;
 mod scanner;
+mod archives;
+mod blobs;
+pub mod mods;
 mod processor;
+mod signatures;
 pub mod file_db;
+pub mod store;
+pub mod sqlite_store;
 mod utils;
 pub mod error;
 
 // Public API exports
 pub use manager::ExtractionManager;
-pub use models::{PboMetadata, PboType, ExtractionConfig};
+pub use mods::ModIdentity;
+pub use models::{PboMetadata, PboType, PboEntry, ExtractionConfig, ExtractionFailure, ExtractionOutcome, ExtractionStats, MissionSource, PruneReport, RetryPolicy, StateBackend};
 pub use error::{CacheError, Result};
-pub use file_db::{FileDbManager, ExtractedFileInfo, PboRecord};
+pub use file_db::{FileDbManager, ExtractedFileInfo, FailedExtraction, PboRecord};
+pub use store::{ExtractionStore, open_store};
+pub use sqlite_store::SqliteStore;
 
 // Convenience re-exports of core functionality
 pub use manager::{extract_game_data, extract_mission};
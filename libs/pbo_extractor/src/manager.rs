@@ -1,24 +1,45 @@
 use std::path::{Path, PathBuf};
-use std::collections::HashMap;
-use log::info;
+use std::collections::{HashMap, HashSet};
+use log::{debug, info, warn};
+use rayon::prelude::*;
+use pbo_tools::ops::PboDigest;
+use pbo_tools::vfs::{self, VirtualPDrive};
+use walkdir::WalkDir;
 
-use crate::models::{ExtractionConfig, PboMetadata, PboType};
+use crate::archives;
+use crate::blobs::BlobStore;
+use crate::models::{ExtractionConfig, ExtractionFailure, ExtractionStats, MissionSource, PboMetadata, PboType, PruneReport};
 use crate::scanner::PboScanner;
-use crate::processor::PboProcessor;
-use crate::file_db::{FileDbManager, PboRecord, ExtractedFileInfo};
+use crate::processor::{ExtractedPbo, PboProcessor};
+use crate::file_db::{normalize_path, FailedExtraction, PboRecord, ExtractedFileInfo};
+use crate::mods::ModIdentity;
+use crate::store::{self, ExtractionStore};
+use crate::signatures;
 use crate::utils;
 use crate::error::{Result, CacheError};
 
+/// Directory below the cache holding the materialized virtual P drive
+const INCLUDE_ROOT_DIR: &str = "pdrive";
+
+/// A PBO paired with the identical PBO whose extracted files it shares
+type DuplicatePbo = (PathBuf, PathBuf);
+
 /// Manager for PBO extraction and caching
 pub struct ExtractionManager {
     /// Configuration for the extraction process
     config: ExtractionConfig,
     
-    /// The database manager for tracking cache state
-    db_manager: FileDbManager,
+    /// The store tracking cache state
+    db_manager: Box<dyn ExtractionStore>,
     
     /// The processor for extracting PBOs
     processor: PboProcessor,
+    
+    /// Time and data spent on each PBO extracted by this manager
+    extraction_stats: Vec<(PathBuf, ExtractionStats)>,
+    
+    /// Missions found by the last call to `process_all_missions`
+    mission_sources: Vec<MissionSource>,
 }
 
 /// Public functions for convenient extraction
@@ -47,26 +68,42 @@ impl ExtractionManager {
         utils::ensure_dir_exists(&config.mission_cache_dir)
             .map_err(|e| CacheError::CacheDirectory(format!("Failed to create mission cache directory: {}", e)))?;
         
-        // Create database manager
-        let db_manager = FileDbManager::new(&config.db_path)
+        // Open the configured extraction state
+        let db_manager = store::open_store(&config)
             .map_err(|e| CacheError::IndexOperation(format!("Failed to create database manager: {}", e)))?;
         
         // Create processor
-        let processor = PboProcessor::new(
+        let mut processor = PboProcessor::new(
             config.timeout,
             config.threads,
-        );
+        )
+        .with_salvage(config.salvage_failed);
+        
+        // Blobs are only worth keeping where the caches can link to them
+        let blob_store = BlobStore::new(config.cache_dir.join("blobs"));
+        let unlinkable = [&config.game_data_cache_dir, &config.mission_cache_dir].into_iter()
+            .find(|dir| !blob_store.can_link_into(dir));
+        match unlinkable {
+            None => processor = processor.with_blob_store(blob_store),
+            Some(dir) => warn!(
+                "Not using the blob store, {} cannot link to files in {}; is it on another file system?",
+                dir.display(),
+                config.cache_dir.join("blobs").display()
+            ),
+        }
         
         Ok(Self {
             config,
             db_manager,
             processor,
+            extraction_stats: Vec::new(),
+            mission_sources: Vec::new(),
         })
     }
     
-    /// Get a mutable reference to the database manager
-    pub fn get_db_manager(&mut self) -> &mut FileDbManager {
-        &mut self.db_manager
+    /// Get a mutable reference to the store tracking cache state
+    pub fn get_db_manager(&mut self) -> &mut dyn ExtractionStore {
+        self.db_manager.as_mut()
     }
     
     /// Helper method to determine if a PBO needs extraction
@@ -82,6 +119,7 @@ impl ExtractionManager {
         pbo_type: PboType, 
         extensions: &[String],
         base_dir: Option<&Path>,
+        digest: Option<&PboDigest>,
     ) -> Result<PboMetadata> {
         // Create metadata with base directory if provided
         let mut metadata = if let Some(base_dir) = base_dir {
@@ -112,6 +150,20 @@ impl ExtractionManager {
         // Add extracted files
         metadata.extracted_files = extracted_files.to_vec();
         
+        // Hash the contents so a later timestamp change alone does not trigger re-extraction
+        let digest = match digest {
+            Some(digest) => Some(digest.clone()),
+            None => Self::digest_pbo(pbo_path),
+        };
+        metadata.content_hash = digest.as_ref().map(|digest| digest.content_hash.clone());
+        
+        // Record whether addons are signed with one of their mod's keys
+        if pbo_type == PboType::GameData {
+            if let Some(digest) = &digest {
+                metadata.signature_status = Some(signatures::check_signature(pbo_path, digest));
+            }
+        }
+        
         Ok(metadata)
     }
     
@@ -140,60 +192,224 @@ impl ExtractionManager {
             needs_extraction,
         ).map_err(|e| CacheError::FileOperation(format!("Failed to filter PBOs: {}", e)))?;
         
+        // Remember the new timestamps of PBOs whose contents turned out unchanged
+        let to_extract_set: HashSet<&PathBuf> = pbos_to_extract.iter().collect();
+        let unchanged: Vec<PathBuf> = all_pbos.iter()
+            .filter(|path| !to_extract_set.contains(path))
+            .cloned()
+            .collect();
+        self.db_manager.refresh_modified_times(&unchanged)?;
+        
+        // Mods are identified from their folder layout and mod.cpp / meta.cpp
+        let mods = PboScanner::find_mods(&all_pbos);
+        self.db_manager.refresh_mod_identities(&mods, &unchanged)?;
+        
+        // PBOs that failed before are only attempted again as the retry policy allows
+        let pbos_to_extract = self.filter_failed(pbos_to_extract)?;
+        
         if pbos_to_extract.is_empty() {
             info!("No game data PBOs need extraction");
             return Ok(Vec::new());
         }
         
+        self.extract_game_data_pbos(pbos_to_extract, &mods).await
+    }
+    
+    /// Extract game data PBOs and record them along with their duplicates
+    ///
+    /// Returns the full paths of the files extracted.
+    async fn extract_game_data_pbos(
+        &mut self,
+        pbos_to_extract: Vec<PathBuf>,
+        mods: &HashMap<PathBuf, ModIdentity>,
+    ) -> Result<Vec<PathBuf>> {
         // Save cache_dir and extensions before the mutable borrow
         let cache_dir = self.config.game_data_cache_dir.clone();
         let extensions = self.config.game_data_extensions.clone();
         let verbose = self.config.verbose;
         
-        // Extract PBOs
+        // Identical PBOs, e.g. the same mod in two directories, are extracted once
+        let hashes = Self::hash_pbos(&pbos_to_extract);
+        let (pbos_to_extract, duplicates) = self.split_duplicates(
+            pbos_to_extract,
+            &hashes,
+            PboType::GameData,
+            &extensions,
+        )?;
+        
+        // Extract PBOs, recording each one in the index as soon as it is done
+        let game_data_dirs = &self.config.game_data_dirs;
+        let db_manager = self.db_manager.as_mut();
+        let run_stats = &mut self.extraction_stats;
         let extraction_results = self.processor.extract_pbos(
             pbos_to_extract,
             &cache_dir,
             extensions.clone(),
             verbose,
-            &mut |path, error| self.db_manager.add_failed_extraction(path, error),
+            &mut |path, outcome, stats| match outcome {
+                Ok(extracted) => {
+                    run_stats.push((path.to_path_buf(), *stats));
+                    let base_dir = Self::best_base_dir(game_data_dirs, path);
+                    let mut metadata = Self::create_metadata(
+                        path,
+                        &extracted.files,
+                        PboType::GameData,
+                        &extensions,
+                        base_dir.as_deref(),
+                        hashes.get(path),
+                    )?;
+                    metadata.mod_identity = mods.get(path).cloned();
+                    metadata.entries = extracted.entries.clone();
+                    metadata.extraction_stats = Some(*stats);
+                    metadata.file_hashes = extracted.file_hashes.clone();
+                    db_manager.update_metadata(metadata)
+                }
+                Err(failure) => {
+                    run_stats.push((path.to_path_buf(), *stats));
+                    let content_hash = hashes.get(path).map(|digest| digest.content_hash.clone());
+                    db_manager.add_failed_extraction(path, PboType::GameData, failure.with_content_hash(content_hash))
+                }
+            },
         ).await.map_err(|e| CacheError::PboExtraction {
             pbo_path: PathBuf::new(), // We don't know which PBO failed
             message: e.to_string(),
         })?;
         
-        // Update cache index
-        let mut all_extracted_files = Vec::new();
+        // Full paths of everything extracted in this run
+        let all_extracted_files: Vec<PathBuf> = extraction_results.iter()
+            .flat_map(|(_, extracted_files)| extracted_files.iter().map(|file| cache_dir.join(file)))
+            .collect();
         
-        for (pbo_path, extracted_files) in &extraction_results {
-            // Find the best base directory for this PBO
-            let base_dir = self.find_best_base_dir(pbo_path);
-                
-            // Create metadata with relative path if possible
-            let metadata = Self::create_metadata(
-                pbo_path,
-                extracted_files,
+        // Record the duplicates against the copy they share
+        for (duplicate_path, original_path) in &duplicates {
+            let Some(original) = self.db_manager.get_record(original_path)? else {
+                warn!("Skipping duplicate {} because {} was not extracted",
+                    duplicate_path.display(), original_path.display());
+                continue;
+            };
+            
+            let base_dir = Self::best_base_dir(&self.config.game_data_dirs, duplicate_path);
+            let mut metadata = Self::create_metadata(
+                duplicate_path,
+                &original.extracted_files,
                 PboType::GameData,
                 &extensions,
                 base_dir.as_deref(),
+                hashes.get(duplicate_path),
             )?;
+            metadata.duplicate_of = Some(original.full_path.clone());
+            metadata.mod_identity = mods.get(duplicate_path).cloned();
+            metadata.entries = original.entries.clone();
+            metadata.file_hashes = original.file_hashes.clone();
             
-            // Update database
-            self.db_manager.update_metadata(metadata.clone())?;
-            
-            // Add to result (full paths)
-            all_extracted_files.extend(metadata.get_full_extracted_paths(&cache_dir));
+            self.db_manager.update_metadata(metadata)?;
+            info!("{} is identical to {}, sharing its extracted files",
+                duplicate_path.display(), original.full_path.display());
         }
         
+        // Fold the journal of this run into the database file
+        self.db_manager.flush()?;
+        
         info!("Processed {} game data PBOs with {} extracted files", 
             extraction_results.len(), all_extracted_files.len());
             
         Ok(all_extracted_files)
     }
     
-    /// Find the best base directory for a PBO path from the configured dirs
-    fn find_best_base_dir(&self, pbo_path: &Path) -> Option<PathBuf> {
-        self.config.game_data_dirs.iter()
+    /// Leave out PBOs whose earlier failure the retry policy does not retry
+    ///
+    /// With `salvage_failed` every PBO that failed is attempted again.
+    fn filter_failed(&self, pbos: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
+        if self.config.salvage_failed {
+            return Ok(pbos);
+        }
+        
+        let mut to_extract = Vec::with_capacity(pbos.len());
+        let mut skipped = 0;
+        for pbo_path in pbos {
+            match self.db_manager.get_failed_extraction(&pbo_path)? {
+                Some(failure) if !self.config.retry_policy.should_retry(&failure, &pbo_path) => {
+                    debug!("Skipping {}, it failed {} times: {}",
+                        pbo_path.display(), failure.attempts, failure.error_message);
+                    skipped += 1;
+                }
+                _ => to_extract.push(pbo_path),
+            }
+        }
+        
+        if skipped > 0 {
+            info!("Skipping {} PBOs that failed to extract before", skipped);
+        }
+        Ok(to_extract)
+    }
+    
+    /// Hash the contents of PBOs in parallel
+    ///
+    /// The stored checksum is checked in the same read, so the signature check
+    /// does not read the PBO again. PBOs that cannot be read are left out and
+    /// treated as unique.
+    fn hash_pbos(pbos: &[PathBuf]) -> HashMap<PathBuf, PboDigest> {
+        pbos.par_iter()
+            .filter_map(|path| Self::digest_pbo(path).map(|digest| (path.clone(), digest)))
+            .collect()
+    }
+    
+    /// Hash a PBO and check its stored checksum in one read
+    fn digest_pbo(pbo_path: &Path) -> Option<PboDigest> {
+        pbo_tools::ops::digest_pbo(pbo_path)
+            .map_err(|e| warn!("Failed to hash {}: {}", pbo_path.display(), e))
+            .ok()
+    }
+    
+    /// Split PBOs into those to extract and duplicates of an already extracted or
+    /// earlier PBO with identical contents
+    ///
+    /// Duplicates are returned together with the path of the PBO whose extracted
+    /// files they share.
+    fn split_duplicates(
+        &self,
+        mut pbos: Vec<PathBuf>,
+        hashes: &HashMap<PathBuf, PboDigest>,
+        pbo_type: PboType,
+        extensions: &[String],
+    ) -> Result<(Vec<PathBuf>, Vec<DuplicatePbo>)> {
+        pbos.sort();
+        let mut owners: HashMap<String, PathBuf> = HashMap::new();
+        let mut to_extract = Vec::new();
+        let mut duplicates = Vec::new();
+        
+        for pbo_path in pbos {
+            let Some(hash) = hashes.get(&pbo_path).map(|digest| &digest.content_hash) else {
+                to_extract.push(pbo_path);
+                continue;
+            };
+            
+            if let Some(owner) = owners.get(hash) {
+                duplicates.push((pbo_path, owner.clone()));
+                continue;
+            }
+            
+            match self.db_manager.find_by_content_hash(hash, pbo_type, extensions)? {
+                Some(record) if record.id != normalize_path(&pbo_path) => {
+                    owners.insert(hash.clone(), record.full_path.clone());
+                    duplicates.push((pbo_path, record.full_path));
+                }
+                _ => {
+                    owners.insert(hash.clone(), pbo_path.clone());
+                    to_extract.push(pbo_path);
+                }
+            }
+        }
+        
+        if !duplicates.is_empty() {
+            info!("{} PBOs are duplicates of other PBOs and will not be extracted again", duplicates.len());
+        }
+        Ok((to_extract, duplicates))
+    }
+    
+    /// Find the best base directory for a PBO path from the given dirs
+    fn best_base_dir(dirs: &[PathBuf], pbo_path: &Path) -> Option<PathBuf> {
+        dirs.iter()
             .filter(|dir| pbo_path.starts_with(dir))
             .max_by_key(|dir| dir.as_os_str().len())
             .cloned()
@@ -218,13 +434,19 @@ impl ExtractionManager {
             return Ok(Vec::new());
         }
         
-        // Check if PBO extraction previously failed
-        if let Some((timestamp, message)) = self.db_manager.is_failed_extraction(mission_path)? {
-            return Err(CacheError::PboExtractionFailed {
-                pbo_path: mission_path.to_path_buf(),
-                timestamp,
-                message,
-            });
+        // Check if PBO extraction previously failed, unless salvage or the retry policy may recover it
+        if let Some(failure) = self.db_manager.get_failed_extraction(mission_path)? {
+            if self.config.salvage_failed {
+                info!("Retrying previously failed PBO with salvage: {}", mission_path.display());
+            } else if self.config.retry_policy.should_retry(&failure, mission_path) {
+                info!("Retrying previously failed PBO: {}", mission_path.display());
+            } else {
+                return Err(CacheError::PboExtractionFailed {
+                    pbo_path: mission_path.to_path_buf(),
+                    timestamp: failure.timestamp.to_rfc3339(),
+                    message: failure.error_message,
+                });
+            }
         }
         
         // Check if PBO needs extraction
@@ -257,73 +479,99 @@ impl ExtractionManager {
         let verbose = self.config.verbose;
         
         // Extract PBO
+        let mission_dirs = &self.config.mission_dirs;
+        let db_manager = self.db_manager.as_mut();
+        let run_stats = &mut self.extraction_stats;
         let extraction_results = self.processor.extract_pbos(
             vec![mission_path.to_path_buf()],
             &cache_dir,
             extensions.clone(),
             verbose,
-            &mut |path, error| self.db_manager.add_failed_extraction(path, error),
+            &mut |path, outcome, stats| {
+                run_stats.push((path.to_path_buf(), *stats));
+                Self::record_mission_outcome(db_manager, mission_dirs, &extensions, path, outcome, stats)
+            },
         ).await.map_err(|e| CacheError::PboExtraction {
             pbo_path: mission_path.to_path_buf(),
             message: e.to_string(),
         })?;
+        self.db_manager.flush()?;
         
-        if extraction_results.is_empty() {
+        let Some((_, extracted_files)) = extraction_results.first() else {
             return Ok(Vec::new());
-        }
-        
-        let (pbo_path, extracted_files) = &extraction_results[0];
-        
-        // Find the best base directory for this PBO
-        let base_dir = self.find_best_mission_base_dir(pbo_path);
-            
-        // Create metadata with relative path if possible
-        let metadata = Self::create_metadata(
-            pbo_path,
-            extracted_files,
-            PboType::Mission,
-            &extensions,
-            base_dir.as_deref(),
-        )?;
-        
-        // Update database
-        self.db_manager.update_metadata(metadata.clone())?;
+        };
         
         info!("Extracted {} files from mission PBO", extracted_files.len());
         
         // Return the full paths
-        Ok(metadata.get_full_extracted_paths(&cache_dir))
+        Ok(extracted_files.iter().map(|file| cache_dir.join(file)).collect())
     }
     
-    /// Find the best base directory for a mission PBO
-    fn find_best_mission_base_dir(&self, pbo_path: &Path) -> Option<PathBuf> {
-        self.config.mission_dirs.iter()
-            .filter(|dir| pbo_path.starts_with(dir))
-            .max_by_key(|dir| dir.as_os_str().len())
-            .cloned()
+    /// Record the outcome of a mission extraction in the index
+    fn record_mission_outcome(
+        db_manager: &mut dyn ExtractionStore,
+        mission_dirs: &[PathBuf],
+        extensions: &[String],
+        pbo_path: &Path,
+        outcome: std::result::Result<&ExtractedPbo, ExtractionFailure>,
+        stats: &ExtractionStats,
+    ) -> Result<()> {
+        match outcome {
+            Ok(extracted) => {
+                let base_dir = Self::best_base_dir(mission_dirs, pbo_path);
+                let mut metadata = Self::create_metadata(
+                    pbo_path,
+                    &extracted.files,
+                    PboType::Mission,
+                    extensions,
+                    base_dir.as_deref(),
+                    None,
+                )?;
+                metadata.entries = extracted.entries.clone();
+                metadata.extraction_stats = Some(*stats);
+                metadata.file_hashes = extracted.file_hashes.clone();
+                db_manager.update_metadata(metadata)
+            }
+            Err(failure) => {
+                let content_hash = utils::hash_file(pbo_path).ok();
+                db_manager.add_failed_extraction(pbo_path, PboType::Mission, failure.with_content_hash(content_hash))
+            }
+        }
     }
     
-    /// Process all mission PBOs
+    /// Process all missions
+    ///
+    /// Missions are PBOs or unpacked folders holding a `mission.sqm`, at any
+    /// depth below the mission directories. Zip archives found there are
+    /// unpacked into the cache and searched the same way. Unpacked folders
+    /// are not copied; their files matching the mission extensions are
+    /// returned in place. See [`mission_sources`](Self::mission_sources) for
+    /// where each mission came from. With `skip_extraction` nothing is
+    /// extracted or returned, but the missions are still found.
     pub async fn process_all_missions(
         &mut self,
         skip_extraction: bool
     ) -> Result<HashMap<PathBuf, Vec<PathBuf>>> {
         info!("Processing missions from {} directories", self.config.mission_dirs.len());
         
-        // Find all mission PBOs
-        let all_missions = PboScanner::find_pbos(&self.config.mission_dirs)
-            .map_err(|e| CacheError::FileOperation(format!("Failed to scan for PBOs: {}", e)))?;
-        info!("Found {} mission PBOs", all_missions.len());
-        
-        // Save mission cache dir for later use
-        let cache_dir = self.config.mission_cache_dir.clone();
-        
         // Skip extraction if requested
         if skip_extraction {
             info!("Skipping extraction as requested");
+            self.mission_sources = self.find_mission_sources()?;
             return Ok(HashMap::new());
         }
         
+        // Find all mission PBOs and folders, including those inside archives
+        let sources = self.find_mission_sources()?;
+        let all_missions: Vec<PathBuf> = sources.iter()
+            .filter(|source| !source.is_folder)
+            .map(|source| source.path.clone())
+            .collect();
+        info!("Found {} mission PBOs", all_missions.len());
+        
+        // Save mission cache dir for later use
+        let cache_dir = self.config.mission_cache_dir.clone();
+        
         // Find PBOs that need extraction
         let needs_extraction = |path: &Path| -> Result<bool> {
             self.needs_extraction(path, PboType::Mission, &self.config.mission_extensions)
@@ -333,93 +581,239 @@ impl ExtractionManager {
             &all_missions,
             needs_extraction,
         ).map_err(|e| CacheError::FileOperation(format!("Failed to filter PBOs: {}", e)))?;
+        let pbos_to_extract = self.filter_failed(pbos_to_extract)?;
         
-        if pbos_to_extract.is_empty() {
+        // First add the newly extracted PBOs
+        let mut results: HashMap<PathBuf, Vec<PathBuf>> = if pbos_to_extract.is_empty() {
             info!("No mission PBOs need extraction");
+            HashMap::new()
+        } else {
+            self.extract_missions(pbos_to_extract).await?
+                .into_iter()
+                .map(|(pbo_path, extracted_files)| {
+                    let full_paths = extracted_files.iter().map(|file| cache_dir.join(file)).collect();
+                    (pbo_path, full_paths)
+                })
+                .collect()
+        };
+        
+        // Then add any already extracted PBOs
+        let mission_models = self.db_manager.get_mission_metadata()?;
+        
+        for model in mission_models {
+            let path_buf = model.full_path.clone();
             
-            // Return already extracted missions
-            let mut results = HashMap::new();
+            if results.contains_key(&path_buf) {
+                continue; // Already processed
+            }
             
-            // Get all mission metadata from the database
-            let mission_models = self.db_manager.get_mission_metadata()?;
+            // Add to results with full paths
+            let full_paths: Vec<PathBuf> = model.extracted_files.iter()
+                .map(|path| cache_dir.join(path))
+                .collect();
             
-            for model in mission_models {
-                // Add to results with full paths
-                let cache_dir = self.config.mission_cache_dir.clone();
-                let full_paths: Vec<PathBuf> = model.extracted_files.iter()
-                    .map(|path| cache_dir.join(path))
-                    .collect();
-                
-                if !full_paths.is_empty() {
-                    results.insert(model.full_path, full_paths);
-                }
+            if !full_paths.is_empty() {
+                results.insert(path_buf, full_paths);
+            }
+        }
+        
+        // Unpacked mission folders are used where they are
+        for source in sources.iter().filter(|source| source.is_folder) {
+            let files = Self::mission_folder_files(&source.path, &self.config.mission_extensions);
+            if !files.is_empty() {
+                results.insert(source.path.clone(), files);
+            }
+        }
+        self.mission_sources = sources;
+        
+        info!("Processed {} missions", results.len());
+        Ok(results)
+    }
+    
+    /// Get the missions found by the last call to
+    /// [`process_all_missions`](Self::process_all_missions), ordered by path
+    ///
+    /// Each source can be turned into a [`Mission`](arma3_models::Mission)
+    /// whose `source_pbo` is the ID of its indexed PBO and whose
+    /// `source_archive` names the archive it came from.
+    pub fn mission_sources(&self) -> &[MissionSource] {
+        &self.mission_sources
+    }
+    
+    /// Directory in the cache where mission archives are unpacked
+    fn archives_dir(&self) -> PathBuf {
+        self.config.cache_dir.join("archives")
+    }
+    
+    /// Store in the cache holding the contents of extracted files
+    fn blob_store(&self) -> BlobStore {
+        BlobStore::new(self.config.cache_dir.join("blobs"))
+    }
+    
+    /// Find mission PBOs and folders in the mission directories and in the
+    /// zip archives found there
+    ///
+    /// Archives that cannot be unpacked are skipped with a warning.
+    fn find_mission_sources(&self) -> Result<Vec<MissionSource>> {
+        let mut sources = Self::scan_mission_dirs(&self.config.mission_dirs, None)?;
+        
+        let archives_dir = self.archives_dir();
+        for archive in PboScanner::find_archives(&self.config.mission_dirs)? {
+            match archives::unpack_archive(&archive, &archives_dir) {
+                Ok(unpacked) => sources.extend(Self::scan_mission_dirs(&[unpacked], Some(&archive))?),
+                Err(e) => warn!("Skipping mission archive {}: {}", archive.display(), e),
             }
-            
-            return Ok(results);
         }
         
-        // Save extensions and verbose settings before mutable borrow
+        sources.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(sources)
+    }
+    
+    /// Find mission PBOs and folders in the given directories
+    fn scan_mission_dirs(dirs: &[PathBuf], archive: Option<&Path>) -> Result<Vec<MissionSource>> {
+        let source = |path: PathBuf, is_folder: bool| MissionSource {
+            path,
+            is_folder,
+            archive: archive.map(Path::to_path_buf),
+        };
+        
+        let pbos = PboScanner::find_pbos(dirs)?;
+        let folders = PboScanner::find_mission_folders(dirs)?;
+        Ok(pbos.into_iter().map(|path| source(path, false))
+            .chain(folders.into_iter().map(|path| source(path, true)))
+            .collect())
+    }
+    
+    /// Files of an unpacked mission folder with one of the given extensions
+    fn mission_folder_files(folder: &Path, extensions: &[String]) -> Vec<PathBuf> {
+        WalkDir::new(folder)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .filter(|entry| {
+                entry.path().extension().is_some_and(|ext| {
+                    let ext = ext.to_string_lossy().to_lowercase();
+                    extensions.iter().any(|wanted| *wanted == ext)
+                })
+            })
+            .map(|entry| entry.into_path())
+            .collect()
+    }
+
+    /// Extract mission PBOs in parallel, recording each one in the index as soon as it is done
+    async fn extract_missions(&mut self, pbos_to_extract: Vec<PathBuf>) -> Result<Vec<(PathBuf, Vec<PathBuf>)>> {
+        // Save cache_dir and extensions before the mutable borrow
+        let cache_dir = self.config.mission_cache_dir.clone();
         let extensions = self.config.mission_extensions.clone();
         let verbose = self.config.verbose;
         
-        // Extract PBOs in parallel
+        let mission_dirs = &self.config.mission_dirs;
+        let db_manager = self.db_manager.as_mut();
+        let run_stats = &mut self.extraction_stats;
         let extraction_results = self.processor.extract_pbos(
             pbos_to_extract,
             &cache_dir,
             extensions.clone(),
             verbose,
-            &mut |path, error| self.db_manager.add_failed_extraction(path, error),
+            &mut |path, outcome, stats| {
+                run_stats.push((path.to_path_buf(), *stats));
+                Self::record_mission_outcome(db_manager, mission_dirs, &extensions, path, outcome, stats)
+            },
         ).await.map_err(|e| CacheError::PboExtraction {
             pbo_path: PathBuf::new(), // We don't know which PBO failed
             message: e.to_string(),
         })?;
+        self.db_manager.flush()?;
         
-        // Update cache index and build results
-        let mut results = HashMap::new();
+        Ok(extraction_results)
+    }
+    
+    /// Get the time and data spent on each PBO extracted by this manager, in
+    /// the order they finished
+    ///
+    /// Includes PBOs that failed, timed out or were cancelled; see
+    /// [`ExtractionStats::outcome`].
+    pub fn extraction_stats(&self) -> &[(PathBuf, ExtractionStats)] {
+        &self.extraction_stats
+    }
+    
+    /// Get the PBOs whose last extraction failed, ordered by path
+    pub fn failures(&self) -> Result<Vec<FailedExtraction>> {
+        let mut failures = self.db_manager.get_failed_extractions()?;
+        failures.sort_by(|a, b| a.pbo_id.cmp(&b.pbo_id));
+        Ok(failures)
+    }
+    
+    /// Extract PBOs that failed before again, regardless of the retry policy
+    ///
+    /// PBOs below a configured mission directory are extracted as missions,
+    /// all others as game data. Failures recorded by older versions only have
+    /// the normalized ID, so their PBO is looked up among the PBOs in the
+    /// configured directories. PBOs that cannot be found are skipped. Returns
+    /// the paths of the PBOs that extracted this time.
+    pub async fn retry_failures(&mut self, failures: &[FailedExtraction]) -> Result<Vec<PathBuf>> {
+        let (missions, game_data): (Vec<PathBuf>, Vec<PathBuf>) = self.resolve_failure_paths(failures)?
+            .into_iter()
+            .partition(|path| self.is_mission_path(path));
+        info!("Retrying {} failed PBOs", game_data.len() + missions.len());
         
-        // First add the newly extracted PBOs
-        for (pbo_path, extracted_files) in &extraction_results {
-            // Find the best base directory for this PBO
-            let base_dir = self.find_best_mission_base_dir(pbo_path);
-                
-            // Create metadata with relative path if possible
-            let metadata = Self::create_metadata(
-                pbo_path,
-                extracted_files,
-                PboType::Mission,
-                &extensions,
-                base_dir.as_deref(),
-            )?;
-            
-            // Update database
-            self.db_manager.update_metadata(metadata.clone())?;
-            
-            // Add to result (full paths)
-            results.insert(pbo_path.clone(), metadata.get_full_extracted_paths(&cache_dir));
+        if !game_data.is_empty() {
+            let mods = PboScanner::find_mods(&game_data);
+            self.extract_game_data_pbos(game_data.clone(), &mods).await?;
+        }
+        if !missions.is_empty() {
+            self.extract_missions(missions.clone()).await?;
         }
         
-        // Then add any already extracted PBOs
-        let mission_models = self.db_manager.get_mission_metadata()?;
-        
-        for model in mission_models {
-            let path_buf = model.full_path.clone();
-            
-            if results.contains_key(&path_buf) {
-                continue; // Already processed
+        let mut extracted = Vec::new();
+        for path in game_data.into_iter().chain(missions) {
+            if self.db_manager.get_failed_extraction(&path)?.is_none() {
+                extracted.push(path);
             }
-            
-            // Add to results with full paths
-            let full_paths: Vec<PathBuf> = model.extracted_files.iter()
-                .map(|path| cache_dir.join(path))
+        }
+        
+        info!("{} of the retried PBOs extracted", extracted.len());
+        Ok(extracted)
+    }
+
+    /// Find the PBOs of failure records, skipping those that no longer exist
+    fn resolve_failure_paths(&self, failures: &[FailedExtraction]) -> Result<Vec<PathBuf>> {
+        // Only scan the configured directories when some failure has no usable path
+        let scanned: HashMap<String, PathBuf> = if failures.iter().any(|failure| !failure.pbo_path.is_file()) {
+            let mut dirs: Vec<PathBuf> = self.config.game_data_dirs.iter()
+                .chain(&self.config.mission_dirs)
+                .cloned()
                 .collect();
-            
-            if !full_paths.is_empty() {
-                results.insert(path_buf, full_paths);
+            let archives_dir = self.archives_dir();
+            if archives_dir.is_dir() {
+                dirs.push(archives_dir);
+            }
+            PboScanner::find_pbos(&dirs)
+                .map_err(|e| CacheError::FileOperation(format!("Failed to scan for PBOs: {}", e)))?
+                .into_iter()
+                .map(|path| (normalize_path(&path), path))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        let mut paths = Vec::new();
+        for failure in failures {
+            if failure.pbo_path.is_file() {
+                paths.push(failure.pbo_path.clone());
+            } else if let Some(path) = scanned.get(&failure.pbo_id) {
+                paths.push(path.clone());
+            } else {
+                warn!("Skipping {}, the PBO no longer exists", failure.pbo_id);
             }
         }
-        
-        info!("Processed {} mission PBOs", results.len());
-        Ok(results)
+        Ok(paths)
+    }
+
+    /// Whether a PBO is below a mission directory or was unpacked from a mission archive
+    fn is_mission_path(&self, path: &Path) -> bool {
+        path.starts_with(self.archives_dir())
+            || self.config.mission_dirs.iter().any(|dir| path.starts_with(dir))
     }
 
     /// Update metadata in the database
@@ -433,7 +827,247 @@ impl ExtractionManager {
     }
     
     /// Find all files with a specific extension
+    ///
+    /// Includes entries that were not extracted; pass them to
+    /// [`materialize_file`](Self::materialize_file) to get them into the cache.
     pub fn find_files_by_extension(&self, extension: &str) -> Result<Vec<ExtractedFileInfo>> {
         self.db_manager.find_files_by_extension(extension)
     }
+    
+    /// Extract a single entry of an indexed PBO into the cache on demand
+    ///
+    /// Only the configured extensions are unpacked up front; any other entry
+    /// listed in the PBO's entry table is extracted from its source PBO here
+    /// and recorded like an eagerly extracted file. Returns the full path of
+    /// the file in the cache.
+    pub async fn materialize(&mut self, pbo_path: &Path, entry_path: &str) -> Result<PathBuf> {
+        let record = self.db_manager.get_record(pbo_path)?
+            .ok_or_else(|| CacheError::IndexOperation(format!("PBO is not indexed: {}", pbo_path.display())))?;
+        
+        // Duplicates share the files of the PBO that owns them
+        let record = match record.duplicate_of.clone() {
+            Some(original_id) => self.db_manager.get_record_by_id(&original_id)?.unwrap_or(record),
+            None => record,
+        };
+        
+        let entry = record.find_entry(entry_path)
+            .ok_or_else(|| CacheError::FileOperation(format!("{} has no entry {}", record.full_path.display(), entry_path)))?;
+        
+        let cache_dir = self.cache_dir_for(record.pbo_type);
+        let relative_path = PathBuf::from(&entry.path);
+        let full_path = cache_dir.join(&relative_path);
+        if record.extracted_files.contains(&relative_path) && full_path.is_file() {
+            return Ok(full_path);
+        }
+        
+        let (_, content_hash) = self.processor.extract_entry(&record.full_path, &entry.path, &cache_dir).await
+            .map_err(|e| CacheError::PboExtraction {
+                pbo_path: record.full_path.clone(),
+                message: e.to_string(),
+            })?;
+        self.db_manager.add_extracted_file(&record.full_path, relative_path, content_hash)?;
+        
+        Ok(full_path)
+    }
+    
+    /// Get a file returned by [`find_files_by_extension`](Self::find_files_by_extension)
+    /// into the cache, extracting it if it is not there yet
+    pub async fn materialize_file(&mut self, file: &ExtractedFileInfo) -> Result<PathBuf> {
+        let record = self.db_manager.get_record_by_id(&file.pbo_id)?
+            .ok_or_else(|| CacheError::IndexOperation(format!("PBO is not indexed: {}", file.pbo_id)))?;
+        
+        if file.extracted {
+            return Ok(self.cache_dir_for(record.pbo_type).join(&file.relative_path));
+        }
+        self.materialize(&record.full_path, &file.relative_path.to_string_lossy()).await
+    }
+    
+    /// Cache directory holding the files of a type of PBO
+    fn cache_dir_for(&self, pbo_type: PboType) -> PathBuf {
+        match pbo_type {
+            PboType::GameData => self.config.game_data_cache_dir.clone(),
+            PboType::Mission => self.config.mission_cache_dir.clone(),
+        }
+    }
+    
+    /// Get game data PBOs that are unsigned or fail signature verification
+    pub fn get_signature_issues(&self) -> Result<Vec<PboRecord>> {
+        self.db_manager.get_signature_issues()
+    }
+    
+    /// Get the records of PBOs belonging to the mod in the given folder, e.g. `@CBA_A3`
+    pub fn get_mod_records(&self, folder: &str) -> Result<Vec<PboRecord>> {
+        self.db_manager.get_mod_records(folder)
+    }
+
+    /// Remove cache entries of PBOs that no longer exist in the source directories
+    ///
+    /// The database is reconciled against the PBOs the scanner currently finds
+    /// in the configured game data and mission directories. Records of PBOs
+    /// that disappeared are dropped and their extracted files deleted, unless
+    /// another PBO still uses them. PBOs below a configured directory that is
+    /// missing entirely, such as an unmounted drive, are kept. Unpacked
+    /// copies of mission archives that disappeared are deleted as well, and so
+    /// are blobs no remaining PBO references. With `dry_run` nothing is
+    /// changed and the report lists what would be removed.
+    pub fn prune(&mut self, dry_run: bool) -> Result<PruneReport> {
+        info!("Pruning extraction cache{}", if dry_run { " (dry run)" } else { "" });
+        
+        let mut found = HashSet::new();
+        let mut unavailable_dirs = Vec::new();
+        for dirs in [&self.config.game_data_dirs, &self.config.mission_dirs] {
+            let pbos = PboScanner::find_pbos(dirs)
+                .map_err(|e| CacheError::FileOperation(format!("Failed to scan for PBOs: {}", e)))?;
+            found.extend(pbos.iter().map(|path| normalize_path(path)));
+            unavailable_dirs.extend(dirs.iter().filter(|dir| !dir.exists()).map(|dir| normalize_path(dir)));
+        }
+        
+        // PBOs unpacked from mission archives stay as long as their archive does
+        let archives_dir = self.archives_dir();
+        let archives_unavailable = self.config.mission_dirs.iter().any(|dir| !dir.exists());
+        if archives_unavailable {
+            unavailable_dirs.push(normalize_path(&archives_dir));
+        }
+        let unpacked_dirs: Vec<PathBuf> = PboScanner::find_archives(&self.config.mission_dirs)?
+            .iter()
+            .map(|archive| archives::unpack_dir(archive, &archives_dir))
+            .filter(|dir| dir.is_dir())
+            .collect();
+        let unpacked_pbos = PboScanner::find_pbos(&unpacked_dirs)?;
+        found.extend(unpacked_pbos.iter().map(|path| normalize_path(path)));
+        
+        let is_orphaned = |pbo_id: &str| {
+            !found.contains(pbo_id) && !unavailable_dirs.iter().any(|dir| pbo_id.starts_with(dir.as_str()))
+        };
+        
+        let mut records = self.db_manager.get_game_data_metadata()?;
+        records.extend(self.db_manager.get_mission_metadata()?);
+        let (orphaned, kept): (Vec<PboRecord>, Vec<PboRecord>) = records.into_iter()
+            .partition(|record| is_orphaned(&record.id));
+        
+        // Files still used by a remaining PBO, such as a duplicate, stay
+        let cache_dir_for = |pbo_type: PboType| match pbo_type {
+            PboType::GameData => &self.config.game_data_cache_dir,
+            PboType::Mission => &self.config.mission_cache_dir,
+        };
+        let in_use: HashSet<PathBuf> = kept.iter()
+            .flat_map(|record| record.extracted_files.iter().map(|file| cache_dir_for(record.pbo_type).join(file)))
+            .collect();
+        
+        let mut report = PruneReport { dry_run, ..Default::default() };
+        let mut removed_files = HashSet::new();
+        let mut removed_ids: HashSet<String> = self.db_manager.get_failed_extraction_ids()?
+            .into_iter()
+            .filter(|id| is_orphaned(id))
+            .collect();
+        
+        for record in orphaned {
+            let cache_dir = cache_dir_for(record.pbo_type);
+            for file in &record.extracted_files {
+                let path = cache_dir.join(file);
+                if in_use.contains(&path) || !removed_files.insert(path.clone()) {
+                    continue;
+                }
+                let Ok(metadata) = std::fs::metadata(&path) else {
+                    continue;
+                };
+                if !dry_run {
+                    if let Err(e) = std::fs::remove_file(&path) {
+                        warn!("Failed to remove {}: {}", path.display(), e);
+                        continue;
+                    }
+                    utils::remove_empty_parents(&path, cache_dir);
+                }
+                // Blob-backed files only free space once their blob goes
+                if !record.file_hashes.contains_key(file) {
+                    report.reclaimed_bytes += metadata.len();
+                }
+                report.removed_files.push(path);
+            }
+            removed_ids.insert(record.id);
+            report.removed_pbos.push(record.full_path);
+        }
+        
+        if !dry_run && !removed_ids.is_empty() {
+            self.db_manager.remove_pbos(&removed_ids)?;
+        }
+        
+        // Unpacked copies of archives that disappeared are no longer needed
+        if !dry_run && !archives_unavailable {
+            for entry in std::fs::read_dir(&archives_dir).into_iter().flatten().flatten() {
+                let path = entry.path();
+                if path.is_dir() && !unpacked_dirs.contains(&path) {
+                    match std::fs::remove_dir_all(&path) {
+                        Ok(()) => debug!("Removed unpacked archive {}", path.display()),
+                        Err(e) => warn!("Failed to remove {}: {}", path.display(), e),
+                    }
+                }
+            }
+        }
+        
+        // Blobs are shared between PBOs, so only those nothing kept refers to go
+        let referenced: HashSet<String> = kept.iter()
+            .flat_map(|record| record.file_hashes.values().cloned())
+            .collect();
+        let (removed_blobs, blob_bytes) = self.blob_store().remove_unreferenced(&referenced, dry_run);
+        report.removed_blobs = removed_blobs;
+        report.reclaimed_bytes += blob_bytes;
+        
+        info!("Pruned {} PBOs, {} files and {} blobs ({} bytes)",
+            report.removed_pbos.len(), report.removed_files.len(), report.removed_blobs, report.reclaimed_bytes);
+        Ok(report)
+    }
+
+    /// Build a virtual P drive from the extracted game data cache
+    ///
+    /// Each PBO's extracted files are mounted at the prefix read from the
+    /// source PBO, so game paths such as `\x\cba\addons\main\script_macros.hpp`
+    /// resolve to files in the cache. PBOs whose source is no longer readable
+    /// are skipped.
+    pub fn virtual_pdrive(&self) -> Result<VirtualPDrive> {
+        let cache_dir = &self.config.game_data_cache_dir;
+        let mut drive = VirtualPDrive::new();
+
+        for record in self.db_manager.get_game_data_metadata()? {
+            let prefix = match vfs::read_prefix(&record.full_path) {
+                Ok(prefix) => prefix.unwrap_or_default(),
+                Err(e) => {
+                    warn!("Skipping {} in virtual P drive: {}", record.full_path.display(), e);
+                    continue;
+                }
+            };
+            // Files may already be cached below their prefix, in which case the
+            // cache-relative path is the game path
+            let prefix_path = PathBuf::from(prefix.replace('\\', "/"));
+            let cached_under_prefix = !prefix.is_empty()
+                && record.extracted_files.iter().all(|file| file.starts_with(&prefix_path));
+            let mount_prefix = if cached_under_prefix { "" } else { prefix.as_str() };
+            drive.mount_files(mount_prefix, cache_dir, &record.extracted_files);
+        }
+
+        info!("Virtual P drive has {} files from the game data cache", drive.len());
+        Ok(drive)
+    }
+
+    /// Write the includable files of the virtual P drive below the cache
+    ///
+    /// The returned directory is laid out like a P drive and can be given to
+    /// the HPP parser as an include root, so configs resolve `#include`s of
+    /// headers from other addons. Files that have not changed since the last
+    /// call are kept, and files no longer on the drive are removed.
+    pub fn materialize_include_root(&self) -> Result<PathBuf> {
+        let include_root = self.config.cache_dir.join(INCLUDE_ROOT_DIR);
+
+        let drive = self.virtual_pdrive()?;
+        let written = drive.materialize(&include_root, vfs::INCLUDE_EXTENSIONS)
+            .map_err(|e| CacheError::FileOperation(format!("Failed to materialize virtual P drive: {}", e)))?;
+        let removed = drive.remove_stale(&include_root, vfs::INCLUDE_EXTENSIONS)
+            .map_err(|e| CacheError::FileOperation(format!("Failed to clean up virtual P drive: {}", e)))?;
+        if removed > 0 {
+            debug!("Removed {} stale include files from {}", removed, include_root.display());
+        }
+
+        info!("Wrote {} include files to {}", written, include_root.display());
+        Ok(include_root)
+    }
 } 
\ No newline at end of file
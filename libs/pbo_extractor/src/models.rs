@@ -1,6 +1,11 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 use serde::{Serialize, Deserialize};
+use pbo_tools::ops::PboOperationError;
+use pbo_tools::signatures::SignatureStatus;
+use crate::file_db::{normalize_path, FailedExtraction};
+use crate::mods::ModIdentity;
 use crate::error::{Result, CacheError};
 
 /// Type of PBO being processed
@@ -13,6 +18,27 @@ pub enum PboType {
     Mission,
 }
 
+/// An entry in a PBO's file table, whether or not it was extracted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PboEntry {
+    /// Path of the entry inside the PBO, using forward slashes
+    pub path: String,
+    
+    /// Uncompressed size in bytes
+    pub size: u64,
+    
+    /// Timestamp stored in the PBO header
+    pub timestamp: u64,
+}
+
+impl PboEntry {
+    /// Lowercased extension of the entry, if it has one
+    pub fn extension(&self) -> Option<String> {
+        Path::new(&self.path).extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+    }
+}
+
 /// Metadata about an extracted PBO
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PboMetadata {
@@ -39,6 +65,34 @@ pub struct PboMetadata {
     
     /// Type of PBO (game data or mission)
     pub pbo_type: PboType,
+    
+    /// Signature status, if it was checked
+    #[serde(default)]
+    pub signature_status: Option<SignatureStatus>,
+    
+    /// SHA-1 of the PBO contents, if it was hashed
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    
+    /// Identical PBO whose extracted files this one shares
+    #[serde(default)]
+    pub duplicate_of: Option<PathBuf>,
+    
+    /// Mod the PBO belongs to, if it is part of an `@mod/addons` layout
+    #[serde(default)]
+    pub mod_identity: Option<ModIdentity>,
+    
+    /// Full file table of the PBO, including entries that were not extracted
+    #[serde(default)]
+    pub entries: Vec<PboEntry>,
+    
+    /// Time and data spent extracting the PBO, unless it is a duplicate
+    #[serde(default)]
+    pub extraction_stats: Option<ExtractionStats>,
+    
+    /// SHA-1 of each extracted file kept in the blob store
+    #[serde(default)]
+    pub file_hashes: HashMap<PathBuf, String>,
 }
 
 impl PboMetadata {
@@ -61,6 +115,13 @@ impl PboMetadata {
             extracted_files: Vec::new(),
             used_extensions,
             pbo_type,
+            signature_status: None,
+            content_hash: None,
+            duplicate_of: None,
+            mod_identity: None,
+            entries: Vec::new(),
+            extraction_stats: None,
+            file_hashes: HashMap::new(),
         })
     }
     
@@ -91,6 +152,13 @@ impl PboMetadata {
             extracted_files: Vec::new(),
             used_extensions,
             pbo_type,
+            signature_status: None,
+            content_hash: None,
+            duplicate_of: None,
+            mod_identity: None,
+            entries: Vec::new(),
+            extraction_stats: None,
+            file_hashes: HashMap::new(),
         })
     }
     
@@ -103,38 +171,6 @@ impl PboMetadata {
         }
     }
     
-    /// Check if this PBO needs to be extracted again
-    pub fn needs_extraction(
-        &self, 
-        path: &Path, 
-        current_extensions: &[String]
-    ) -> Result<bool> {
-        // Check if file exists
-        if !path.exists() {
-            return Ok(true);
-        }
-        
-        // Check if metadata has changed
-        let metadata = std::fs::metadata(path)
-            .map_err(|e| CacheError::FileOperation(format!("Failed to get metadata for {}: {}", path.display(), e)))?;
-        let current_modified = metadata.modified()
-            .map_err(|e| CacheError::FileOperation(format!("Failed to get modified time: {}", e)))?;
-        let current_size = metadata.len();
-        
-        // Check if file was modified
-        if current_modified > self.last_modified || current_size != self.file_size {
-            return Ok(true);
-        }
-        
-        // Check if extensions have changed
-        if self.used_extensions != current_extensions {
-            return Ok(true);
-        }
-        
-        // No need to extract
-        Ok(false)
-    }
-
     /// Get full paths to extracted files by combining with a cache directory
     pub fn get_full_extracted_paths(&self, cache_dir: &Path) -> Vec<PathBuf> {
         self.extracted_files
@@ -144,6 +180,233 @@ impl PboMetadata {
     }
 }
 
+/// How the extraction of a PBO ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExtractionOutcome {
+    /// The PBO was extracted
+    #[default]
+    Extracted,
+    
+    /// The PBO could not be extracted
+    Failed,
+    
+    /// Extraction took longer than the configured timeout
+    TimedOut,
+    
+    /// The extraction task was cancelled or panicked before it finished
+    Cancelled,
+}
+
+/// Time and data spent extracting a single PBO
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ExtractionStats {
+    /// Wall time from taking an extraction slot until the files were in place, in milliseconds
+    pub duration_ms: u64,
+    
+    /// Size of the PBO that was read
+    pub bytes_read: u64,
+    
+    /// Number of files moved into the cache
+    pub entries_extracted: usize,
+    
+    /// How the extraction ended
+    pub outcome: ExtractionOutcome,
+}
+
+impl ExtractionStats {
+    /// Wall time of the extraction
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.duration_ms)
+    }
+    
+    /// Bytes read per second
+    pub fn throughput(&self) -> f64 {
+        if self.duration_ms == 0 {
+            return 0.0;
+        }
+        self.bytes_read as f64 * 1000.0 / self.duration_ms as f64
+    }
+}
+
+/// Where a mission found in the mission directories comes from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MissionSource {
+    /// Path of the mission PBO or unpacked mission folder
+    ///
+    /// Missions from an archive point into its unpacked copy in the cache.
+    pub path: PathBuf,
+    
+    /// Whether the mission is an unpacked folder rather than a PBO
+    pub is_folder: bool,
+    
+    /// Zip archive the mission was unpacked from
+    pub archive: Option<PathBuf>,
+}
+
+impl MissionSource {
+    /// Name of the mission, e.g. `co10_escape.Altis`
+    pub fn name(&self) -> String {
+        let name = if self.is_folder {
+            self.path.file_name()
+        } else {
+            self.path.file_stem()
+        };
+        name.map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+    }
+    
+    /// ID of the mission PBO, as recorded in the index
+    ///
+    /// PBOs from an archive are indexed under their unpacked copy. Unpacked
+    /// folders have none.
+    pub fn source_pbo(&self) -> Option<String> {
+        (!self.is_folder).then(|| normalize_path(&self.path))
+    }
+    
+    /// Normalized path of the archive the mission came from
+    pub fn source_archive(&self) -> Option<String> {
+        self.archive.as_deref().map(normalize_path)
+    }
+    
+    /// Create a mission from this source, without any parsed content
+    pub fn to_mission(&self) -> arma3_models::Mission {
+        let mut mission = arma3_models::Mission::new(self.name(), self.path.clone());
+        mission.source_pbo = self.source_pbo();
+        mission.source_archive = self.source_archive();
+        mission
+    }
+}
+
+/// Outcome of reconciling the cache with the source directories
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PruneReport {
+    /// Source PBOs that disappeared and were removed from the database
+    pub removed_pbos: Vec<PathBuf>,
+    
+    /// Cached files that were deleted
+    pub removed_files: Vec<PathBuf>,
+    
+    /// Total size of the deleted files in bytes
+    ///
+    /// Files kept in the blob store only count once their blob is deleted.
+    pub reclaimed_bytes: u64,
+    
+    /// Number of blobs that no remaining PBO references and were deleted
+    pub removed_blobs: usize,
+    
+    /// Whether nothing was actually changed
+    pub dry_run: bool,
+}
+
+/// Why a PBO could not be extracted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractionFailure {
+    /// Error message
+    pub message: String,
+    
+    /// Error category, see [`PboOperationError::category`]
+    pub category: String,
+    
+    /// Whether the error is transient, so another attempt may succeed
+    pub retryable: bool,
+    
+    /// SHA-1 of the PBO contents at the time of the failure
+    pub content_hash: Option<String>,
+}
+
+impl ExtractionFailure {
+    /// Create a failure with the given category
+    pub fn new(message: impl Into<String>, category: &str, retryable: bool) -> Self {
+        Self {
+            message: message.into(),
+            category: category.to_string(),
+            retryable,
+            content_hash: None,
+        }
+    }
+    
+    /// Create a failure from a PBO operation error
+    pub fn from_error(error: &PboOperationError) -> Self {
+        Self::new(error.to_string(), error.category(), error.is_retryable())
+    }
+    
+    /// Attach the hash of the PBO that failed
+    pub fn with_content_hash(mut self, content_hash: Option<String>) -> Self {
+        self.content_hash = content_hash;
+        self
+    }
+}
+
+impl From<String> for ExtractionFailure {
+    fn from(message: String) -> Self {
+        Self::new(message, "unknown", false)
+    }
+}
+
+/// When PBOs that failed to extract are attempted again
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Number of attempts after which a retryable failure is no longer retried
+    pub max_attempts: u32,
+    
+    /// Retry when the PBO's contents changed since it failed
+    pub retry_on_change: bool,
+    
+    /// Retry when the failure was recorded by another version of the extractor
+    pub retry_on_new_version: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            retry_on_change: true,
+            retry_on_new_version: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry on its own; failures are only attempted again on request
+    pub fn never() -> Self {
+        Self {
+            max_attempts: 0,
+            retry_on_change: false,
+            retry_on_new_version: false,
+        }
+    }
+    
+    /// Check whether a PBO that failed should be attempted again
+    pub fn should_retry(&self, failure: &FailedExtraction, pbo_path: &Path) -> bool {
+        if self.retry_on_new_version && failure.tool_version.as_deref() != Some(crate::VERSION) {
+            return true;
+        }
+        
+        if self.retry_on_change {
+            if let Some(hash) = &failure.content_hash {
+                if crate::utils::hash_file(pbo_path).is_ok_and(|current| &current != hash) {
+                    return true;
+                }
+            }
+        }
+        
+        failure.retryable && failure.attempts < self.max_attempts
+    }
+}
+
+/// Where the extraction state is stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StateBackend {
+    /// JSON file database with a journal, see [`crate::FileDbManager`]
+    #[default]
+    File,
+    
+    /// SQLite database shared with the analysis tools, see [`crate::SqliteStore`]
+    Sqlite,
+}
+
 /// Configuration for PBO extraction and caching
 #[derive(Debug, Clone)]
 pub struct ExtractionConfig {
@@ -179,6 +442,15 @@ pub struct ExtractionConfig {
     
     /// Path to the database file
     pub db_path: PathBuf,
+    
+    /// Storage used for the extraction state at `db_path`
+    pub state_backend: StateBackend,
+    
+    /// When PBOs that failed before are extracted again
+    pub retry_policy: RetryPolicy,
+    
+    /// Whether to salvage intact entries from PBOs that fail normal extraction
+    pub salvage_failed: bool,
 }
 
 impl ExtractionConfig {
@@ -199,6 +471,9 @@ impl ExtractionConfig {
             timeout: 400,
             verbose: false,
             db_path: cache_dir.join("cache.db"),
+            state_backend: StateBackend::default(),
+            retry_policy: RetryPolicy::default(),
+            salvage_failed: false,
         }
     }
     
@@ -249,4 +524,22 @@ impl ExtractionConfig {
         self.db_path = db_path;
         self
     }
+    
+    /// Set the storage used for the extraction state
+    pub fn with_state_backend(mut self, state_backend: StateBackend) -> Self {
+        self.state_backend = state_backend;
+        self
+    }
+    
+    /// Set when PBOs that failed before are extracted again
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+    
+    /// Salvage previously failed or corrupt PBOs instead of skipping them
+    pub fn with_salvage_failed(mut self, salvage_failed: bool) -> Self {
+        self.salvage_failed = salvage_failed;
+        self
+    }
 }
\ No newline at end of file
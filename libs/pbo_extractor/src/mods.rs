@@ -0,0 +1,257 @@
+//! Mod folder detection and `mod.cpp` / `meta.cpp` metadata
+//!
+//! Mods are distributed as `@ModName/addons/*.pbo`, with a `mod.cpp` holding
+//! the display name and, for Steam Workshop downloads, a `meta.cpp` holding the
+//! workshop id and publish timestamp.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use log::{debug, warn};
+use serde::{Serialize, Deserialize};
+use pbo_tools::rapify::{self, RapEntry, RapValue};
+
+/// Identity of the mod a PBO belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModIdentity {
+    /// Name of the mod folder, e.g. `@CBA_A3`
+    pub folder: String,
+
+    /// Display name from `mod.cpp` or `meta.cpp`, or the folder name without `@`
+    pub name: String,
+
+    /// Path of the mod folder
+    pub root: PathBuf,
+
+    /// Steam Workshop id from `meta.cpp`
+    #[serde(default)]
+    pub published_id: Option<u64>,
+
+    /// Publish timestamp from `meta.cpp`, as stored by the launcher
+    #[serde(default)]
+    pub timestamp: Option<u64>,
+}
+
+impl ModIdentity {
+    /// Read the identity of the mod in `root`
+    ///
+    /// Missing or unreadable `mod.cpp` and `meta.cpp` files only mean less
+    /// information; the folder name is always available.
+    pub fn read(root: &Path) -> Self {
+        let folder = root.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mod_cpp = read_cpp_values(&root.join("mod.cpp")).unwrap_or_default();
+        let meta_cpp = read_cpp_values(&root.join("meta.cpp")).unwrap_or_default();
+
+        let name = mod_cpp.get("name")
+            .or_else(|| meta_cpp.get("name"))
+            .filter(|name| !name.trim().is_empty())
+            .cloned()
+            .unwrap_or_else(|| folder.trim_start_matches('@').to_string());
+
+        Self {
+            name,
+            root: root.to_path_buf(),
+            published_id: meta_cpp.get("publishedid").and_then(|id| id.parse().ok()),
+            timestamp: meta_cpp.get("timestamp").and_then(|ts| ts.parse().ok()),
+            folder,
+        }
+    }
+}
+
+/// Find the mod folder a PBO belongs to
+///
+/// A PBO is part of a mod when it sits in `<mod>/addons` and the mod folder
+/// either starts with `@` or contains a `mod.cpp` or `meta.cpp`. The game's
+/// own `addons` folder has neither and is not treated as a mod.
+pub fn mod_root(pbo_path: &Path) -> Option<&Path> {
+    let addons_dir = pbo_path.parent()?;
+    let is_addons = addons_dir.file_name()
+        .is_some_and(|name| name.eq_ignore_ascii_case("addons"));
+    if !is_addons {
+        return None;
+    }
+
+    let root = addons_dir.parent()?;
+    let is_mod = root.file_name().is_some_and(|name| name.to_string_lossy().starts_with('@'))
+        || root.join("mod.cpp").is_file()
+        || root.join("meta.cpp").is_file();
+    is_mod.then_some(root)
+}
+
+/// Read the top level `key = value;` entries of a `mod.cpp` or `meta.cpp`
+///
+/// Keys are lowercased and strings are unquoted. Rapified files are decoded
+/// first.
+fn read_cpp_values(path: &Path) -> Option<HashMap<String, String>> {
+    let data = std::fs::read(path).ok()?;
+
+    if rapify::is_rapified(&data) {
+        let config = match rapify::derapify(&data) {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("Failed to decode {}: {}", path.display(), e);
+                return None;
+            }
+        };
+        let values = config.root.entries.iter()
+            .filter_map(|entry| match entry {
+                RapEntry::Value { name, value } => Some((name.to_lowercase(), rap_value_text(value))),
+                _ => None,
+            })
+            .collect();
+        return Some(values);
+    }
+
+    let values = parse_cpp_values(&String::from_utf8_lossy(&data));
+    debug!("Read {} values from {}", values.len(), path.display());
+    Some(values)
+}
+
+fn rap_value_text(value: &RapValue) -> String {
+    match value {
+        RapValue::String(s) | RapValue::Variable(s) => s.clone(),
+        RapValue::Float(f) => f.to_string(),
+        RapValue::Int(i) => i.to_string(),
+        RapValue::Int64(i) => i.to_string(),
+        RapValue::Array(_) => String::new(),
+    }
+}
+
+/// Parse the top level scalar entries of a text config
+///
+/// Entries inside classes, arrays and comments are skipped.
+fn parse_cpp_values(text: &str) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    let mut statement = String::new();
+    let mut depth = 0usize;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                // Copy the whole string, including `""` escapes
+                statement.push(c);
+                while let Some(c) = chars.next() {
+                    statement.push(c);
+                    if c == '"' {
+                        if chars.peek() == Some(&'"') {
+                            statement.push(chars.next().unwrap_or('"'));
+                        } else {
+                            break;
+                        }
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = '\0';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '{' => {
+                depth += 1;
+                statement.clear();
+            }
+            '}' => {
+                depth = depth.saturating_sub(1);
+                statement.clear();
+            }
+            ';' => {
+                if depth == 0 {
+                    if let Some((key, value)) = statement.split_once('=') {
+                        let key = key.trim();
+                        if !key.is_empty() && !key.ends_with("[]") && !key.contains(char::is_whitespace) {
+                            values.insert(key.to_lowercase(), unquote(value.trim()));
+                        }
+                    }
+                }
+                statement.clear();
+            }
+            _ => statement.push(c),
+        }
+    }
+
+    values
+}
+
+fn unquote(value: &str) -> String {
+    match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(inner) => inner.replace("\"\"", "\""),
+        None => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_cpp_values() {
+        let values = parse_cpp_values(r#"
+            // Shown in the launcher
+            name = "Community Base ""Addons""";
+            picture = "logo_ca.paa";
+            actionName = "Website";
+            publishedid = 450814997;
+            class Nested { name = "ignored"; };
+            tooltips[] = {"a; b"};
+            /* timestamp = 1; */
+            timestamp = 5249751130849005548;
+        "#);
+
+        assert_eq!(values.get("name").map(String::as_str), Some("Community Base \"Addons\""));
+        assert_eq!(values.get("actionname").map(String::as_str), Some("Website"));
+        assert_eq!(values.get("publishedid").map(String::as_str), Some("450814997"));
+        assert_eq!(values.get("timestamp").map(String::as_str), Some("5249751130849005548"));
+        assert!(!values.contains_key("tooltips"));
+    }
+
+    #[test]
+    fn test_mod_identity() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path().join("@CBA_A3");
+        std::fs::create_dir_all(root.join("addons")).unwrap();
+        std::fs::write(root.join("mod.cpp"), "name = \"Community Base Addons\";").unwrap();
+        std::fs::write(root.join("meta.cpp"), "protocol = 1;\npublishedid = 450814997;\nname = \"CBA_A3\";\ntimestamp = 5249751130849005548;").unwrap();
+
+        let pbo_path = root.join("addons").join("cba_main.pbo");
+        assert_eq!(mod_root(&pbo_path), Some(root.as_path()));
+
+        let identity = ModIdentity::read(&root);
+        assert_eq!(identity.folder, "@CBA_A3");
+        assert_eq!(identity.name, "Community Base Addons");
+        assert_eq!(identity.published_id, Some(450814997));
+        assert_eq!(identity.timestamp, Some(5249751130849005548));
+    }
+
+    #[test]
+    fn test_mod_root_requires_mod_folder() {
+        let temp_dir = tempdir().unwrap();
+
+        // The game's own addons folder is not a mod
+        let game_addons = temp_dir.path().join("Arma 3").join("addons");
+        std::fs::create_dir_all(&game_addons).unwrap();
+        assert_eq!(mod_root(&game_addons.join("data_f.pbo")), None);
+
+        // Loose PBOs are not part of a mod
+        assert_eq!(mod_root(&temp_dir.path().join("loose.pbo")), None);
+
+        // Without mod.cpp the name falls back to the folder
+        let identity = ModIdentity::read(&temp_dir.path().join("@ace"));
+        assert_eq!(identity.name, "ace");
+        assert_eq!(identity.published_id, None);
+    }
+}
@@ -3,19 +3,50 @@ use std::fs;
 use anyhow::Result;
 use log::{info, warn, error};
 use tokio::task;
-use tempfile::tempdir;
+use tokio::sync::mpsc;
 use walkdir::WalkDir;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use pbo_tools::core::api::{PboApi, PboApiOps};
-use std::collections::HashSet;
+use pbo_tools::core::constants::RAPIFIED_TEXT_EXTENSIONS;
+use pbo_tools::fs::derapify_file;
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+use crate::blobs::BlobStore;
+use crate::models::{ExtractionFailure, ExtractionOutcome, ExtractionStats, PboEntry};
+
+/// Directory inside a cache directory where PBOs are extracted before their
+/// files are moved into place
+const STAGING_DIR: &str = ".staging";
+
+/// Files kept from an extracted PBO, along with its full entry table
+#[derive(Debug, Clone, Default)]
+pub struct ExtractedPbo {
+    /// Cache-relative paths of the extracted files
+    pub files: Vec<PathBuf>,
+    
+    /// Every entry of the PBO, including those that were filtered out
+    pub entries: Vec<PboEntry>,
+    
+    /// SHA-1 of each extracted file, when they are kept in a blob store
+    pub file_hashes: HashMap<PathBuf, String>,
+}
 
 /// Handles extraction of PBO files to the cache
 pub struct PboProcessor {
     /// API for interacting with PBO files
     pbo_api: PboApi,
     
+    /// Timeout in seconds for each PBO operation
+    timeout: u64,
+    
     /// Number of threads to use for extraction
     threads: usize,
+    
+    /// Whether to salvage intact entries when normal extraction fails
+    salvage: bool,
+    
+    /// Store that extracted files are moved into and linked from
+    blob_store: Option<BlobStore>,
 }
 
 impl PboProcessor {
@@ -25,9 +56,45 @@ impl PboProcessor {
             .with_timeout(timeout as u32)
             .build();
             
-        Self { pbo_api, threads }
+        Self { pbo_api, timeout, threads, salvage: false, blob_store: None }
+    }
+    
+    /// Fall back to salvaging intact entries when normal extraction fails
+    pub fn with_salvage(mut self, salvage: bool) -> Self {
+        self.salvage = salvage;
+        self
+    }
+    
+    /// Keep extracted files in a content-addressed store, linking them into the cache
+    pub fn with_blob_store(mut self, blob_store: BlobStore) -> Self {
+        self.blob_store = Some(blob_store);
+        self
+    }
+    
+    /// Move a finished file into place at `target`
+    ///
+    /// Returns the hash of the file when it is kept in the blob store.
+    fn place_file(blob_store: Option<&BlobStore>, source: &Path, target: &Path) -> std::io::Result<Option<String>> {
+        match blob_store {
+            Some(blob_store) => blob_store.store(source, target).map(Some),
+            None => fs::rename(source, target).map(|_| None),
+        }
     }
  
+    /// Discard the staging area left behind by an interrupted run
+    ///
+    /// Files only leave the staging area once they are complete, so anything
+    /// still in it is half-written output.
+    fn discard_staging(cache_dir: &Path) -> Result<PathBuf> {
+        let staging_dir = cache_dir.join(STAGING_DIR);
+        if staging_dir.exists() {
+            warn!("Discarding partial output of an interrupted extraction in {}", staging_dir.display());
+            fs::remove_dir_all(&staging_dir)?;
+        }
+        fs::create_dir_all(&staging_dir)?;
+        Ok(staging_dir)
+    }
+
     /// Catalog all files in a directory recursively
     fn catalog_files(dir: &Path) -> Result<Vec<PathBuf>> {
         let files: Vec<PathBuf> = WalkDir::new(dir)
@@ -42,16 +109,21 @@ impl PboProcessor {
     }
     
     /// Extract multiple PBOs in parallel
+    ///
+    /// `on_complete` is called as soon as each PBO has finished, with the
+    /// cache-relative paths of its files and its entry table or the error
+    /// that stopped it, and the time and data spent on it, so progress can
+    /// be recorded before the whole batch is done.
     pub async fn extract_pbos<F>(
         &self,
         pbos: Vec<PathBuf>,
         cache_dir: &Path,
         extensions: Vec<String>,
         _verbose: bool,
-        on_failed_extraction: &mut F,
+        on_complete: &mut F,
     ) -> Result<Vec<(PathBuf, Vec<PathBuf>)>> 
     where
-        F: FnMut(&Path, String) -> crate::error::Result<()>
+        F: FnMut(&Path, std::result::Result<&ExtractedPbo, ExtractionFailure>, &ExtractionStats) -> crate::error::Result<()>
     {
         if pbos.is_empty() {
             return Ok(Vec::new());
@@ -63,11 +135,10 @@ impl PboProcessor {
         // Convert original extensions to HashSet for efficient lookup later
         let original_extensions_set: HashSet<String> = extensions.iter().cloned().collect();
 
-        // Shared result collection
-        let results = Arc::new(Mutex::new(Vec::with_capacity(pbos.len())));
-        
-        // Create a mutex for tracking failures
-        let failures = Arc::new(Mutex::new(Vec::new()));
+        let staging_dir = Self::discard_staging(cache_dir)?;
+
+        // Each task reports its outcome as soon as it is done
+        let (outcome_tx, mut outcome_rx) = mpsc::unbounded_channel();
 
         // Limit concurrency
         let semaphore = Arc::new(tokio::sync::Semaphore::new(self.threads));
@@ -79,27 +150,44 @@ impl PboProcessor {
             let cache_dir = cache_dir.to_path_buf();
             let extensions = extensions.clone();
             let original_extensions_set = original_extensions_set.clone();
-            let results = Arc::clone(&results);
-            let failures = Arc::clone(&failures);
+            let staging_dir = staging_dir.clone();
+            let outcome_tx = outcome_tx.clone();
             let semaphore_clone = Arc::clone(&semaphore);
-            let processor = PboApi::new(60); // Create a new instance with 60 second timeout (TODO: Make PboApi Clone-able)
+            let salvage = self.salvage;
+            let blob_store = self.blob_store.clone();
+            let processor = PboApi::new(self.timeout as u32); // PboApi is not Clone, so each task gets its own
 
+            let task_path = pbo_path.clone();
             let task = task::spawn(async move {
                 // Acquire semaphore permit
                 let _permit = semaphore_clone.acquire().await.unwrap();
 
+                // Time spent waiting for a permit is not part of the PBO's extraction
+                let started = Instant::now();
+                let bytes_read = fs::metadata(&pbo_path).map(|metadata| metadata.len()).unwrap_or(0);
+                let stats = |entries_extracted: usize, outcome: ExtractionOutcome| ExtractionStats {
+                    duration_ms: started.elapsed().as_millis() as u64,
+                    bytes_read,
+                    entries_extracted,
+                    outcome,
+                };
+
                 // Common error handling function to reduce duplication
-                let record_failure = |error_msg: String| {
-                    error!("Error processing PBO {}: {}", pbo_path.display(), error_msg);
-                    let mut failures_guard = failures.lock().unwrap();
-                    failures_guard.push((pbo_path.clone(), error_msg));
+                let record_failure = |failure: ExtractionFailure| {
+                    error!("Error processing PBO {}: {}", pbo_path.display(), failure.message);
+                    let outcome = if failure.category == "timeout" {
+                        ExtractionOutcome::TimedOut
+                    } else {
+                        ExtractionOutcome::Failed
+                    };
+                    let _ = outcome_tx.send((pbo_path.clone(), Err(failure), stats(0, outcome)));
                 };
 
-                // Extract the PBO to a temporary directory
-                let temp_dir = match tempdir() {
+                // Extract the PBO to a staging directory on the same file system as the cache
+                let temp_dir = match tempfile::Builder::new().prefix("extract-").tempdir_in(&staging_dir) {
                     Ok(dir) => dir,
                     Err(e) => {
-                        record_failure(format!("Failed to create temporary directory: {}", e));
+                        record_failure(ExtractionFailure::new(format!("Failed to create temporary directory: {}", e), "io", true));
                         return;
                     }
                 };
@@ -129,8 +217,33 @@ impl PboProcessor {
                 // -----------------------------------------
 
                 // Try to extract the PBO using the new filtered API
-                match processor.extract_filtered(&pbo_path, temp_dir.path(), &filter_pattern).await {
-                    Ok(_) => {
+                let extraction = match processor.extract_filtered(&pbo_path, temp_dir.path(), &filter_pattern).await {
+                    Ok(()) => Ok(()),
+                    Err(e) if salvage => {
+                        warn!("Extraction of {} failed ({}), attempting salvage", pbo_path.display(), e);
+                        match processor.salvage(&pbo_path, temp_dir.path(), &filter_pattern).await {
+                            Ok(report) if !report.recovered.is_empty() => {
+                                for lost in &report.lost {
+                                    warn!("Lost {} from {}: {}", lost.file_path, pbo_path.display(), lost.reason);
+                                }
+                                info!("Salvaged {} files from {}", report.recovered.len(), pbo_path.display());
+                                Ok(())
+                            },
+                            Ok(_) => Err(ExtractionFailure {
+                                message: format!("{} (salvage recovered no files)", e),
+                                ..ExtractionFailure::from_error(&e)
+                            }),
+                            Err(salvage_error) => Err(ExtractionFailure {
+                                message: format!("{} (salvage failed: {})", e, salvage_error),
+                                ..ExtractionFailure::from_error(&e)
+                            }),
+                        }
+                    },
+                    Err(e) => Err(ExtractionFailure::from_error(&e)),
+                };
+
+                match extraction {
+                    Ok(()) => {
                         // Add a small delay to ensure file handles are released
                         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
                         
@@ -138,6 +251,7 @@ impl PboProcessor {
                         match tokio::task::spawn_blocking(move || PboProcessor::catalog_files(&temp_dir_path)).await {
                             Ok(Ok(extracted_files)) => {
                                 let mut cache_paths = Vec::new();
+                                let mut file_hashes = HashMap::new();
                                 let temp_path_str = temp_dir.path().to_string_lossy();
 
                                 // --- Post-Extraction Filtering Loop ---
@@ -202,14 +316,24 @@ impl PboProcessor {
                                         }
                                     }
 
-                                    // Copy the file (from original temp path to final target path)
+                                    // Binarized configs and missions are decoded to text so the parsers can read them
+                                    if rename_to_cpp || RAPIFIED_TEXT_EXTENSIONS.contains(&effective_extension.as_str()) {
+                                        if let Err(e) = derapify_file(file_path) {
+                                            warn!("Failed to derapify {}: {}", file_path.display(), e);
+                                        }
+                                    }
+
+                                    // Move the finished file into place, so the cache never holds a partial file
                                     let mut retries = 3;
                                     let mut last_error = None;
 
                                     while retries > 0 {
-                                        match fs::copy(file_path, &target_path) {
-                                            Ok(_) => {
+                                        match Self::place_file(blob_store.as_ref(), file_path, &target_path) {
+                                            Ok(hash) => {
                                                 // Store the final relative path (e.g., .../config.cpp)
+                                                if let Some(hash) = hash {
+                                                    file_hashes.insert(final_rel_path.clone(), hash);
+                                                }
                                                 cache_paths.push(final_rel_path);
                                                 last_error = None; // Clear error on success
                                                 break;
@@ -225,60 +349,133 @@ impl PboProcessor {
                                     }
 
                                     if let Some(e) = last_error {
-                                         warn!("Failed to copy {} to {} after 3 retries: {}",
+                                         warn!("Failed to move {} to {} after 3 retries: {}",
                                              file_path.display(), target_path.display(), e);
                                     }
                                 } // --- End Post-Extraction Filtering Loop ---
 
-                                // Store the result (filtered paths)
-                                if !cache_paths.is_empty() { // Only store if we actually kept some files
-                                    let mut result_guard = results.lock().unwrap();
-                                    result_guard.push((pbo_path, cache_paths));
-                                }
+                                // Keep the full entry table so other files can be extracted on demand
+                                let entries = match processor.list_contents(&pbo_path).await {
+                                    Ok(files) => files.into_iter()
+                                        .map(|file| PboEntry {
+                                            path: file.file_path,
+                                            size: file.size,
+                                            timestamp: file.timestamp,
+                                        })
+                                        .collect(),
+                                    Err(e) => {
+                                        warn!("Failed to list entries of {}: {}", pbo_path.display(), e);
+                                        Vec::new()
+                                    }
+                                };
+
+                                // Report the result (filtered paths)
+                                let stats = stats(cache_paths.len(), ExtractionOutcome::Extracted);
+                                let _ = outcome_tx.send((pbo_path, Ok(ExtractedPbo { files: cache_paths, entries, file_hashes }), stats));
                             },
                             Ok(Err(e)) => {
-                                record_failure(format!("Failed to catalog extracted files: {}", e));
+                                record_failure(ExtractionFailure::new(format!("Failed to catalog extracted files: {}", e), "io", true));
                             },
                             Err(e) => {
-                                record_failure(format!("Task failure when cataloging files: {}", e));
+                                record_failure(ExtractionFailure::new(format!("Task failure when cataloging files: {}", e), "io", true));
                             }
                         }
                     },
-                    Err(e) => {
-                        record_failure(format!("Extraction error: {}", e));
+                    Err(failure) => {
+                        record_failure(ExtractionFailure {
+                            message: format!("Extraction error: {}", failure.message),
+                            ..failure
+                        });
                     }
                 }
             });
 
-            tasks.push(task);
+            tasks.push((task_path, task));
         }
 
-        // Wait for all tasks to complete
-        for task in tasks {
-            let _ = task.await;
-        }
+        // Record each outcome as it arrives; the channel closes once every task is done.
+        // A failed record is returned once the running tasks have finished, so they never
+        // outlive the staging directory.
+        drop(outcome_tx);
+        let mut final_results = Vec::new();
+        let mut record_error = None;
+        while let Some((pbo_path, outcome, stats)) = outcome_rx.recv().await {
+            let recorded = match &outcome {
+                Ok(extracted) => on_complete(&pbo_path, Ok(extracted), &stats),
+                Err(failure) => on_complete(&pbo_path, Err(failure.clone()), &stats),
+            };
+            if let Err(e) = recorded {
+                error!("Failed to record extraction result for {}: {}", pbo_path.display(), e);
+                record_error.get_or_insert(e);
+            }
 
-        // Process failures and update the index using the callback
-        let failures = Arc::try_unwrap(failures)
-            .expect("Failed to unwrap failures")
-            .into_inner()
-            .expect("Failed to get inner failures");
+            // Only return PBOs we actually kept some files from
+            if let Ok(extracted) = outcome {
+                if !extracted.files.is_empty() {
+                    final_results.push((pbo_path, extracted.files));
+                }
+            }
+        }
 
-        for (path, error_message) in failures {
-            if let Err(e) = on_failed_extraction(&path, error_message) {
-                warn!("Failed to record extraction failure for {}: {}", path.display(), e);
+        // A task that was cancelled or panicked never reported its outcome
+        for (pbo_path, task) in tasks {
+            if let Err(e) = task.await {
+                error!("Extraction task for {} did not finish: {}", pbo_path.display(), e);
+                let failure = ExtractionFailure::new(format!("Extraction task did not finish: {}", e), "cancelled", true);
+                let stats = ExtractionStats { outcome: ExtractionOutcome::Cancelled, ..Default::default() };
+                if let Err(e) = on_complete(&pbo_path, Err(failure), &stats) {
+                    error!("Failed to record extraction result for {}: {}", pbo_path.display(), e);
+                    record_error.get_or_insert(e);
+                }
             }
         }
+        let _ = fs::remove_dir_all(&staging_dir);
 
-        // Return the collected results
-        let final_results = Arc::try_unwrap(results)
-            .expect("Failed to unwrap results")
-            .into_inner()
-            .expect("Failed to get inner results");
+        if let Some(e) = record_error {
+            return Err(e.into());
+        }
 
         info!("Successfully processed {} PBOs ({} might have failed or yielded no matching files)", initial_pbo_count, initial_pbo_count - final_results.len());
         Ok(final_results)
     }
+
+    /// Extract a single entry of a PBO into the cache
+    ///
+    /// The entry keeps its path inside the PBO, so it lands where a full
+    /// extraction would have put it. Returns the cache-relative path and, when
+    /// a blob store is used, the hash of the file.
+    pub async fn extract_entry(&self, pbo_path: &Path, entry_path: &str, cache_dir: &Path) -> Result<(PathBuf, Option<String>)> {
+        // Entry names come from the PBO header and must stay inside the cache
+        if !pbo_tools::is_safe_entry_path(entry_path) {
+            anyhow::bail!("Refusing to extract unsafe entry path {} from {}", entry_path, pbo_path.display());
+        }
+        let relative_path = PathBuf::from(entry_path.replace('\\', "/"));
+        let staging_dir = cache_dir.join(STAGING_DIR);
+        fs::create_dir_all(&staging_dir)?;
+        let temp_dir = tempfile::Builder::new().prefix("entry-").tempdir_in(&staging_dir)?;
+        let staged_path = temp_dir.path().join(&relative_path);
+
+        self.pbo_api.extract_file(pbo_path, entry_path, &staged_path).await?;
+
+        // Rapified mission files are decoded like in a full extraction
+        let extension = relative_path.extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if RAPIFIED_TEXT_EXTENSIONS.contains(&extension.as_str()) {
+            if let Err(e) = derapify_file(&staged_path) {
+                warn!("Failed to derapify {}: {}", staged_path.display(), e);
+            }
+        }
+
+        let target_path = cache_dir.join(&relative_path);
+        if let Some(parent) = target_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let hash = Self::place_file(self.blob_store.as_ref(), &staged_path, &target_path)?;
+
+        info!("Extracted {} from {} on demand", entry_path, pbo_path.display());
+        Ok((relative_path, hash))
+    }
 }
 
 #[cfg(test)]
@@ -310,8 +507,10 @@ mod tests {
         
         // Track failed extractions
         let mut failed_pbo_paths = Vec::new();
-        let mut on_failed_extraction = |path: &Path, _error: String| {
-            failed_pbo_paths.push(path.to_path_buf());
+        let mut on_complete = |path: &Path, outcome: std::result::Result<&ExtractedPbo, ExtractionFailure>, _stats: &ExtractionStats| {
+            if outcome.is_err() {
+                failed_pbo_paths.push(path.to_path_buf());
+            }
             Ok(())
         };
         
@@ -327,11 +526,106 @@ mod tests {
             &cache_dir,
             extensions.clone(),
             false,
-            &mut on_failed_extraction
+            &mut on_complete
         ).await;
         
         // The extraction itself will fail because these aren't real PBOs,
         // but we can verify that failures were recorded correctly
         assert!(results.is_err() || failed_pbo_paths.len() > 0);
     }
+
+    #[tokio::test]
+    async fn test_failure_stats() {
+        let temp_dir = tempdir().unwrap();
+        let pbo_path = create_mock_pbo(temp_dir.path());
+
+        let processor = PboProcessor::new(30, 1);
+        let mut recorded = Vec::new();
+        processor.extract_pbos(
+            vec![pbo_path.clone()],
+            &temp_dir.path().join("cache"),
+            vec!["sqf".to_string()],
+            false,
+            &mut |_path: &Path, outcome: std::result::Result<&ExtractedPbo, ExtractionFailure>, stats: &ExtractionStats| {
+                recorded.push((outcome.is_ok(), *stats));
+                Ok(())
+            },
+        ).await.unwrap();
+
+        // Not a real PBO, so nothing is extracted
+        let (extracted, stats) = recorded[0];
+        assert!(!extracted);
+        assert_eq!(stats.outcome, ExtractionOutcome::Failed);
+        assert_eq!(stats.bytes_read, b"PBO content".len() as u64);
+        assert_eq!(stats.entries_extracted, 0);
+    }
+
+    #[tokio::test]
+    async fn test_discards_leftover_staging() {
+        let temp_dir = tempdir().unwrap();
+        let cache_dir = temp_dir.path().join("cache");
+        let pbo_path = create_mock_pbo(temp_dir.path());
+
+        // Simulate a run that was killed halfway through writing a file
+        let leftover = cache_dir.join(STAGING_DIR).join("extract-1234").join("config.cpp");
+        fs::create_dir_all(leftover.parent().unwrap()).unwrap();
+        fs::write(&leftover, b"class CfgPatches {").unwrap();
+
+        let processor = PboProcessor::new(30, 1);
+        let mut completed = Vec::new();
+        processor.extract_pbos(
+            vec![pbo_path.clone()],
+            &cache_dir,
+            vec!["cpp".to_string()],
+            false,
+            &mut |path: &Path, _outcome: std::result::Result<&ExtractedPbo, ExtractionFailure>, _stats: &ExtractionStats| {
+                completed.push(path.to_path_buf());
+                Ok(())
+            },
+        ).await.unwrap();
+
+        assert_eq!(completed, vec![pbo_path]);
+        assert!(!cache_dir.join(STAGING_DIR).exists());
+        assert!(!cache_dir.join("config.cpp").exists());
+    }
+
+    #[tokio::test]
+    async fn test_record_error_is_returned() {
+        let temp_dir = tempdir().unwrap();
+        let cache_dir = temp_dir.path().join("cache");
+        let pbo_path = create_mock_pbo(temp_dir.path());
+
+        let processor = PboProcessor::new(30, 1);
+        let result = processor.extract_pbos(
+            vec![pbo_path],
+            &cache_dir,
+            vec!["sqf".to_string()],
+            false,
+            &mut |_path: &Path, _outcome: std::result::Result<&ExtractedPbo, ExtractionFailure>, _stats: &ExtractionStats| {
+                Err(crate::error::CacheError::IndexOperation("journal is read-only".to_string()))
+            },
+        ).await;
+
+        // The journal failure is not swallowed, and the staging area is still cleaned up
+        let error = result.unwrap_err();
+        assert!(error.to_string().contains("journal is read-only"));
+        assert!(!cache_dir.join(STAGING_DIR).exists());
+    }
+
+    #[tokio::test]
+    async fn test_extract_entry_rejects_unsafe_path() {
+        let temp_dir = tempdir().unwrap();
+        let cache_dir = temp_dir.path().join("cache");
+        let pbo_path = create_mock_pbo(temp_dir.path());
+
+        let processor = PboProcessor::new(30, 1);
+        for entry_path in [r"..\escape.sqf", "/abs/escape.sqf", r"C:\escape.sqf"] {
+            let error = processor.extract_entry(&pbo_path, entry_path, &cache_dir).await.unwrap_err();
+            assert!(error.to_string().contains("unsafe entry path"));
+        }
+
+        // Rejected before anything is staged
+        assert!(!cache_dir.exists());
+        assert!(!temp_dir.path().join("escape.sqf").exists());
+    }
 }
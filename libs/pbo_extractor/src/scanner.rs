@@ -1,8 +1,10 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 use log::{debug, info, warn};
 use rayon::prelude::*;
 use crate::error::Result;
+use crate::mods::{self, ModIdentity};
 
 /// Scanner for finding PBO files in directories
 pub struct PboScanner;
@@ -12,14 +14,56 @@ impl PboScanner {
     pub fn find_pbos(dirs: &[PathBuf]) -> Result<Vec<PathBuf>> {
         debug!("Scanning {} directories for PBO files", dirs.len());
         
-        let pbos: Vec<PathBuf> = dirs.par_iter()
+        let pbos = Self::find_files(dirs, |path| {
+            path.extension().is_some_and(|ext| ext == "pbo")
+        });
+        
+        info!("Found {} PBO files", pbos.len());
+        Ok(pbos)
+    }
+    
+    /// Find all zip archives in the given directories
+    pub fn find_archives(dirs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+        debug!("Scanning {} directories for archives", dirs.len());
+        
+        let archives = Self::find_files(dirs, |path| {
+            path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+        });
+        
+        info!("Found {} archives", archives.len());
+        Ok(archives)
+    }
+    
+    /// Find unpacked missions in the given directories
+    ///
+    /// An unpacked mission is a folder such as `co10_escape.Altis` holding a
+    /// `mission.sqm`.
+    pub fn find_mission_folders(dirs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+        debug!("Scanning {} directories for mission folders", dirs.len());
+        
+        let sqm_files = Self::find_files(dirs, |path| {
+            path.file_name().is_some_and(|name| name.eq_ignore_ascii_case("mission.sqm"))
+        });
+        let folders: Vec<PathBuf> = sqm_files.into_iter()
+            .filter_map(|sqm| sqm.parent().map(Path::to_path_buf))
+            .collect();
+        
+        info!("Found {} mission folders", folders.len());
+        Ok(folders)
+    }
+    
+    /// Find the files in the given directories and their subdirectories that match `filter`
+    fn find_files<F>(dirs: &[PathBuf], filter: F) -> Vec<PathBuf>
+    where
+        F: Fn(&Path) -> bool + Sync,
+    {
+        dirs.par_iter()
             .filter_map(|dir| {
                 if !dir.exists() {
                     warn!("Directory does not exist: {}", dir.display());
                     return None;
                 }
                 
-                // Find all .pbo files
                 let mut files = Vec::new();
                 for entry in WalkDir::new(dir)
                     .follow_links(true)
@@ -27,9 +71,8 @@ impl PboScanner {
                     .filter_map(|e| e.ok())
                 {
                     let path = entry.path();
-                    if path.is_file() && 
-                       path.extension().is_some_and(|ext| ext == "pbo") {
-                        debug!("Found PBO: {}", path.display());
+                    if path.is_file() && filter(path) {
+                        debug!("Found: {}", path.display());
                         files.push(path.to_path_buf());
                     }
                 }
@@ -37,10 +80,28 @@ impl PboScanner {
                 Some(files)
             })
             .flatten()
-            .collect();
+            .collect()
+    }
+    
+    /// Find the mod each PBO belongs to
+    ///
+    /// PBOs outside an `<mod>/addons` layout are left out. Each mod folder's
+    /// `mod.cpp` and `meta.cpp` are read once.
+    pub fn find_mods(pbos: &[PathBuf]) -> HashMap<PathBuf, ModIdentity> {
+        let mut identities: HashMap<&Path, ModIdentity> = HashMap::new();
+        let mut mods = HashMap::new();
         
-        info!("Found {} PBO files", pbos.len());
-        Ok(pbos)
+        for pbo_path in pbos {
+            let Some(root) = mods::mod_root(pbo_path) else {
+                continue;
+            };
+            let identity = identities.entry(root)
+                .or_insert_with(|| ModIdentity::read(root));
+            mods.insert(pbo_path.clone(), identity.clone());
+        }
+        
+        debug!("Found {} mods for {} PBOs", identities.len(), pbos.len());
+        mods
     }
     
     /// Filter PBOs that need extraction based on the provided function
@@ -114,6 +175,40 @@ mod tests {
         assert!(!pbos.contains(&non_pbo));
     }
     
+    #[test]
+    fn test_find_archives_and_mission_folders() {
+        let temp_dir = tempdir().unwrap();
+        let nested = temp_dir.path().join("community").join("tvt");
+        std::fs::create_dir_all(nested.join("tvt_raid.Stratis")).unwrap();
+        std::fs::write(nested.join("tvt_raid.Stratis").join("mission.sqm"), "version = 54;").unwrap();
+        std::fs::write(nested.join("tvt_raid.Stratis").join("init.sqf"), "").unwrap();
+        let archive = create_test_pbo(&nested, "pack.ZIP");
+        create_test_pbo(temp_dir.path(), "co10_escape.Altis.pbo");
+        
+        let dirs = vec![temp_dir.path().to_path_buf()];
+        
+        assert_eq!(PboScanner::find_archives(&dirs).unwrap(), vec![archive]);
+        assert_eq!(PboScanner::find_mission_folders(&dirs).unwrap(), vec![nested.join("tvt_raid.Stratis")]);
+    }
+    
+    #[test]
+    fn test_find_mods() {
+        let temp_dir = tempdir().unwrap();
+        let addons = temp_dir.path().join("@ace").join("addons");
+        std::fs::create_dir_all(&addons).unwrap();
+        std::fs::write(temp_dir.path().join("@ace").join("mod.cpp"), "name = \"ACE3\";").unwrap();
+        let ace_common = create_test_pbo(&addons, "ace_common.pbo");
+        let ace_medical = create_test_pbo(&addons, "ace_medical.pbo");
+        let loose = create_test_pbo(temp_dir.path(), "loose.pbo");
+        
+        let mods = PboScanner::find_mods(&[ace_common.clone(), ace_medical.clone(), loose.clone()]);
+        
+        assert_eq!(mods.len(), 2);
+        assert_eq!(mods[&ace_common].name, "ACE3");
+        assert_eq!(mods[&ace_medical].folder, "@ace");
+        assert!(!mods.contains_key(&loose));
+    }
+    
     #[test]
     fn test_filter_needs_extraction() {
         let temp_dir = tempdir().unwrap();
@@ -0,0 +1,85 @@
+use std::path::{Path, PathBuf};
+use log::{debug, warn};
+use pbo_tools::ops::PboDigest;
+use pbo_tools::signatures::{self, BiPublicKey, BiSignature, SignatureStatus};
+
+/// Check the signature of a game data PBO against the keys shipped with its mod
+///
+/// PBOs are expected to live in `<mod>/addons`, with the mod's public keys in
+/// `<mod>/keys`. Any signature that verifies against one of those keys makes
+/// the PBO valid; otherwise the first problem found is reported. `digest` is
+/// the PBO's digest taken while hashing it, so its checksum is not read again.
+pub fn check_signature(pbo_path: &Path, digest: &PboDigest) -> SignatureStatus {
+    let signature_paths = signatures::find_signatures(pbo_path);
+    if signature_paths.is_empty() {
+        return SignatureStatus::Unsigned;
+    }
+
+    let keys = load_mod_keys(pbo_path);
+    let mut first_problem = None;
+
+    for signature_path in &signature_paths {
+        let status = match BiSignature::from_file(signature_path) {
+            Ok(signature) => verify_with_keys(pbo_path, &signature, &keys, digest),
+            Err(e) => SignatureStatus::Invalid { reason: e.to_string() },
+        };
+        debug!("Signature {} for {}: {}", signature_path.display(), pbo_path.display(), status);
+
+        if status.is_valid() {
+            return status;
+        }
+        first_problem.get_or_insert(status);
+    }
+
+    first_problem.unwrap_or(SignatureStatus::Unsigned)
+}
+
+fn verify_with_keys(pbo_path: &Path, signature: &BiSignature, keys: &[BiPublicKey], digest: &PboDigest) -> SignatureStatus {
+    let Some(key) = keys.iter().find(|key| key.authority.eq_ignore_ascii_case(&signature.authority)) else {
+        return SignatureStatus::UnknownKey { authority: signature.authority.clone() };
+    };
+
+    signatures::verify_pbo_digest(pbo_path, signature, key, digest)
+        .unwrap_or_else(|e| SignatureStatus::Invalid { reason: e.to_string() })
+}
+
+/// Load every `.bikey` from the `keys` folder of the mod containing the PBO
+fn load_mod_keys(pbo_path: &Path) -> Vec<BiPublicKey> {
+    let Some(keys_dir) = find_keys_dir(pbo_path) else {
+        return Vec::new();
+    };
+
+    let Ok(entries) = std::fs::read_dir(&keys_dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .map(|ext| ext.eq_ignore_ascii_case("bikey"))
+                .unwrap_or(false)
+        })
+        .filter_map(|path| match BiPublicKey::from_file(&path) {
+            Ok(key) => Some(key),
+            Err(e) => {
+                warn!("Failed to read key {}: {}", path.display(), e);
+                None
+            }
+        })
+        .collect()
+}
+
+fn find_keys_dir(pbo_path: &Path) -> Option<PathBuf> {
+    let mod_dir = pbo_path.parent()?.parent()?;
+    std::fs::read_dir(mod_dir).ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.is_dir()
+                && path.file_name()
+                    .map(|name| name.eq_ignore_ascii_case("keys"))
+                    .unwrap_or(false)
+        })
+}
@@ -0,0 +1,629 @@
+//! Extraction state in the SQLite analysis database
+//!
+//! Records live in the `pbo_files`, `extracted_files`, `pbo_entries` and
+//! `failed_extractions` tables of [`arma3_database`], so the analysis tools
+//! can join what they find to the PBOs it came from, and file lookups go
+//! through the database indexes.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+
+use arma3_database::{Cache, CacheConfig, DatabaseManager, ExtractedFile, ModInfo, PboEntryModel, PboModel};
+use arma3_database::FailedExtraction as FailureModel;
+
+use crate::error::{CacheError, Result};
+use crate::file_db::{normalize_path, ExtractedFileInfo, FailedExtraction, PboRecord};
+use crate::models::{ExtractionConfig, ExtractionFailure, PboEntry, PboMetadata, PboType};
+use crate::mods::ModIdentity;
+use crate::store::{same_extensions, ExtractionStore};
+
+/// Extraction state stored in an [`arma3_database`] SQLite database
+///
+/// Records are keyed by the same IDs as the file database, see
+/// [`normalize_path`].
+#[derive(Clone)]
+pub struct SqliteStore {
+    db: DatabaseManager,
+}
+
+impl SqliteStore {
+    /// Open the database at the configured `db_path`, creating it if needed
+    pub fn new(config: &ExtractionConfig) -> Result<Self> {
+        let db = DatabaseManager::with_config(CacheConfig::with_cache_dirs(
+            config.db_path.clone(),
+            config.cache_dir.clone(),
+            config.game_data_cache_dir.clone(),
+            config.mission_cache_dir.clone(),
+        ))?;
+        Ok(Self::with_database(db))
+    }
+
+    /// Use an already open database
+    pub fn with_database(db: DatabaseManager) -> Self {
+        Self { db }
+    }
+
+    /// Get the underlying database
+    pub fn database(&self) -> &DatabaseManager {
+        &self.db
+    }
+
+    fn cache(&self) -> Cache<'_> {
+        Cache::new(&self.db)
+    }
+
+    /// Convert a record to the database model
+    fn to_model(record: &PboRecord) -> Result<PboModel> {
+        let signature_status = record.signature_status.as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| CacheError::IndexOperation(format!("Failed to serialize signature status: {}", e)))?;
+        let extraction_stats = record.extraction_stats.as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| CacheError::IndexOperation(format!("Failed to serialize extraction stats: {}", e)))?;
+
+        Ok(PboModel {
+            id: record.id.clone(),
+            full_path: record.full_path.clone(),
+            base_dir: record.base_dir.clone(),
+            file_size: record.file_size,
+            last_modified: record.last_modified,
+            extraction_time: record.extraction_time,
+            pbo_type: to_database_type(record.pbo_type),
+            mod_info: record.mod_identity.as_ref().map(to_mod_info),
+            used_extensions: record.used_extensions.clone(),
+            content_hash: record.content_hash.clone(),
+            duplicate_of: record.duplicate_of.clone(),
+            signature_status,
+            extraction_stats,
+        })
+    }
+
+    /// Convert a database model to a record, loading its files and entries
+    fn to_record(&self, pbo: PboModel) -> Result<PboRecord> {
+        let cache = self.cache();
+        let files = cache.get_extracted_files(owner_id(&pbo))?;
+        let entries = cache.get_entries(&pbo.id)?;
+        Ok(Self::build_record(pbo, files, entries))
+    }
+
+    /// Convert database models to records, loading all their files and entries at once
+    fn to_records(&self, pbos: Vec<PboModel>) -> Result<Vec<PboRecord>> {
+        let cache = self.cache();
+        let owner_ids: Vec<String> = pbos.iter()
+            .map(|pbo| owner_id(pbo).to_string())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let pbo_ids: Vec<String> = pbos.iter().map(|pbo| pbo.id.clone()).collect();
+        let files = cache.get_extracted_files_for(&owner_ids)?;
+        let mut entries = cache.get_entries_for(&pbo_ids)?;
+
+        Ok(pbos.into_iter()
+            .map(|pbo| {
+                let files = files.get(owner_id(&pbo)).cloned().unwrap_or_default();
+                let entries = entries.remove(&pbo.id).unwrap_or_default();
+                Self::build_record(pbo, files, entries)
+            })
+            .collect())
+    }
+
+    /// Convert a database model with its files and entries to a record
+    fn build_record(pbo: PboModel, files: Vec<ExtractedFile>, entries: Vec<PboEntryModel>) -> PboRecord {
+        let mut file_hashes = HashMap::new();
+        let extracted_files = files
+            .into_iter()
+            .map(|file| {
+                if let Some(content_hash) = file.content_hash {
+                    file_hashes.insert(file.relative_path.clone(), content_hash);
+                }
+                file.relative_path
+            })
+            .collect();
+
+        let entries = entries
+            .into_iter()
+            .map(|entry| PboEntry {
+                path: entry.path,
+                size: entry.size,
+                timestamp: entry.timestamp,
+            })
+            .collect();
+
+        let signature_status = pbo.signature_status.as_deref().and_then(|status| {
+            serde_json::from_str(status)
+                .inspect_err(|e| warn!("Ignoring unreadable signature status of {}: {}", pbo.id, e))
+                .ok()
+        });
+        let extraction_stats = pbo.extraction_stats.as_deref().and_then(|stats| {
+            serde_json::from_str(stats)
+                .inspect_err(|e| warn!("Ignoring unreadable extraction stats of {}: {}", pbo.id, e))
+                .ok()
+        });
+
+        // Mods are laid out as `<root>/addons/<pbo>`
+        let mod_identity = pbo.mod_info.map(|mod_info| ModIdentity {
+            root: pbo.full_path.parent()
+                .and_then(Path::parent)
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+            folder: mod_info.folder,
+            name: mod_info.name,
+            published_id: mod_info.published_id,
+            timestamp: mod_info.timestamp,
+        });
+
+        PboRecord {
+            id: pbo.id,
+            full_path: pbo.full_path,
+            base_dir: pbo.base_dir,
+            file_size: pbo.file_size,
+            last_modified: pbo.last_modified,
+            extraction_time: pbo.extraction_time,
+            pbo_type: to_extractor_type(pbo.pbo_type),
+            used_extensions: pbo.used_extensions,
+            extracted_files,
+            signature_status,
+            content_hash: pbo.content_hash,
+            duplicate_of: pbo.duplicate_of,
+            mod_identity,
+            entries,
+            extraction_stats,
+            file_hashes,
+        }
+    }
+}
+
+/// ID of the PBO whose files a PBO uses; duplicates share the files of the PBO that owns them
+fn owner_id(pbo: &PboModel) -> &str {
+    pbo.duplicate_of.as_deref().unwrap_or(&pbo.id)
+}
+
+fn to_database_type(pbo_type: PboType) -> arma3_database::PboType {
+    match pbo_type {
+        PboType::GameData => arma3_database::PboType::GameData,
+        PboType::Mission => arma3_database::PboType::Mission,
+    }
+}
+
+fn to_extractor_type(pbo_type: arma3_database::PboType) -> PboType {
+    match pbo_type {
+        arma3_database::PboType::GameData => PboType::GameData,
+        arma3_database::PboType::Mission => PboType::Mission,
+    }
+}
+
+fn to_mod_info(mod_identity: &ModIdentity) -> ModInfo {
+    ModInfo {
+        folder: mod_identity.folder.clone(),
+        name: mod_identity.name.clone(),
+        published_id: mod_identity.published_id,
+        timestamp: mod_identity.timestamp,
+    }
+}
+
+fn to_failure_model(failed: FailedExtraction) -> FailureModel {
+    FailureModel {
+        pbo_id: failed.pbo_id,
+        timestamp: failed.timestamp,
+        error_message: failed.error_message,
+        pbo_path: failed.pbo_path,
+        category: failed.category,
+        retryable: failed.retryable,
+        attempts: failed.attempts,
+        content_hash: failed.content_hash,
+        tool_version: failed.tool_version,
+    }
+}
+
+fn to_failed_extraction(failed: FailureModel) -> FailedExtraction {
+    FailedExtraction {
+        pbo_id: failed.pbo_id,
+        error_message: failed.error_message,
+        timestamp: failed.timestamp,
+        pbo_path: failed.pbo_path,
+        category: failed.category,
+        retryable: failed.retryable,
+        attempts: failed.attempts,
+        content_hash: failed.content_hash,
+        tool_version: failed.tool_version,
+    }
+}
+
+impl ExtractionStore for SqliteStore {
+    fn update_metadata(&mut self, metadata: PboMetadata) -> Result<()> {
+        let record = PboRecord::from_metadata(&metadata);
+        let pbo = Self::to_model(&record)?;
+
+        // Duplicates keep pointing at the files of the PBO that owns them
+        let extracted_files: Vec<ExtractedFile> = if record.duplicate_of.is_none() {
+            record.extracted_files.iter()
+                .map(|path| ExtractedFile::new(&record.id, path)
+                    .with_content_hash(record.file_hashes.get(path).cloned()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let entries: Vec<PboEntryModel> = record.entries.iter()
+            .map(|entry| PboEntryModel {
+                pbo_id: record.id.clone(),
+                path: entry.path.clone(),
+                size: entry.size,
+                timestamp: entry.timestamp,
+            })
+            .collect();
+
+        self.cache().record_extraction(&pbo, &extracted_files, &entries)?;
+
+        debug!("Updated metadata for PBO: {}", metadata.path.display());
+        Ok(())
+    }
+
+    fn add_failed_extraction(&mut self, path: &Path, pbo_type: PboType, failure: ExtractionFailure) -> Result<()> {
+        let previous = self.get_failed_extraction(path)?;
+        let failed = FailedExtraction::new(path, failure, previous.as_ref());
+
+        // A PBO that never extracted gets a record without files for the failure to reference;
+        // having no extensions, it is extracted again like a PBO without a record
+        let now = Utc::now();
+        let file_metadata = std::fs::metadata(path).ok();
+        let last_modified = file_metadata.as_ref()
+            .and_then(|metadata| metadata.modified().ok())
+            .map_or(now, DateTime::<Utc>::from);
+        let mut pbo = PboModel::new(
+            path,
+            to_database_type(pbo_type),
+            file_metadata.map_or(0, |metadata| metadata.len()),
+            last_modified,
+            now,
+        );
+        pbo.id = failed.pbo_id.clone();
+        self.cache().record_failure(&pbo, &to_failure_model(failed))?;
+
+        debug!("Recorded failed extraction for {}", path.display());
+        Ok(())
+    }
+
+    fn get_failed_extraction(&self, path: &Path) -> Result<Option<FailedExtraction>> {
+        let failed = self.cache().get_failed_extraction(&normalize_path(path))?;
+        Ok(failed.map(to_failed_extraction))
+    }
+
+    fn get_failed_extractions(&self) -> Result<Vec<FailedExtraction>> {
+        let failed = self.cache().get_failed_extractions()?;
+        Ok(failed.into_iter().map(to_failed_extraction).collect())
+    }
+
+    fn find_pbo_for_file(&self, file_path: &Path) -> Result<Option<PboRecord>> {
+        self.cache().find_pbo_by_file_path(file_path)?
+            .map(|pbo| self.to_record(pbo))
+            .transpose()
+    }
+
+    fn find_files_by_extension(&self, extension: &str) -> Result<Vec<ExtractedFileInfo>> {
+        let cache = self.cache();
+        let mut extraction_times: HashMap<String, DateTime<Utc>> = HashMap::new();
+        let mut extraction_time = |pbo_id: &str| -> Result<DateTime<Utc>> {
+            if let Some(time) = extraction_times.get(pbo_id) {
+                return Ok(*time);
+            }
+            let time = cache.get(pbo_id)?
+                .map(|pbo| pbo.extraction_time)
+                .unwrap_or_else(Utc::now);
+            extraction_times.insert(pbo_id.to_string(), time);
+            Ok(time)
+        };
+
+        let mut result = Vec::new();
+
+        for file in cache.find_files_by_extension(extension)? {
+            result.push(ExtractedFileInfo {
+                extraction_time: extraction_time(&file.pbo_id)?,
+                relative_path: file.relative_path,
+                pbo_id: file.pbo_id,
+                extension: extension.to_string(),
+                extracted: true,
+                content_hash: file.content_hash,
+            });
+        }
+
+        for entry in cache.find_unextracted_entries(extension)? {
+            result.push(ExtractedFileInfo {
+                extraction_time: extraction_time(&entry.pbo_id)?,
+                relative_path: PathBuf::from(entry.path),
+                pbo_id: entry.pbo_id,
+                extension: extension.to_string(),
+                extracted: false,
+                content_hash: None,
+            });
+        }
+
+        Ok(result)
+    }
+
+    fn add_extracted_file(&mut self, path: &Path, relative_path: PathBuf, content_hash: Option<String>) -> Result<()> {
+        let pbo_id = normalize_path(path);
+        let cache = self.cache();
+        if cache.get(&pbo_id)?.is_none() {
+            return Err(CacheError::IndexOperation(format!("No record for PBO {}", path.display())));
+        }
+
+        let relative_path = relative_path.to_string_lossy().replace('\\', "/");
+        let already_recorded = cache.get_extracted_files(&pbo_id)?
+            .iter()
+            .any(|file| file.relative_path == Path::new(&relative_path));
+        if !already_recorded {
+            cache.add_extracted_files(&[ExtractedFile::new(pbo_id, relative_path).with_content_hash(content_hash)])?;
+        }
+        Ok(())
+    }
+
+    fn get_record_by_id(&self, pbo_id: &str) -> Result<Option<PboRecord>> {
+        self.cache().get(pbo_id)?
+            .map(|pbo| self.to_record(pbo))
+            .transpose()
+    }
+
+    fn find_by_content_hash(
+        &self,
+        content_hash: &str,
+        pbo_type: PboType,
+        extensions: &[String],
+    ) -> Result<Option<PboRecord>> {
+        self.cache().find_by_content_hash(content_hash, to_database_type(pbo_type))?
+            .into_iter()
+            .find(|pbo| same_extensions(&pbo.used_extensions, extensions))
+            .map(|pbo| self.to_record(pbo))
+            .transpose()
+    }
+
+    fn get_duplicates(&self, path: &Path) -> Result<Vec<PboRecord>> {
+        let duplicates = self.cache().get_duplicates(&normalize_path(path))?;
+        self.to_records(duplicates)
+    }
+
+    fn refresh_mod_identities(&mut self, mods: &HashMap<PathBuf, ModIdentity>, paths: &[PathBuf]) -> Result<usize> {
+        let cache = self.cache();
+        let mut refreshed = 0;
+        for path in paths {
+            let Some(pbo) = cache.get(&normalize_path(path))? else {
+                continue;
+            };
+            let mod_info = mods.get(path).map(to_mod_info);
+            if pbo.mod_info != mod_info {
+                cache.update_mod_info(&pbo.id, mod_info.as_ref())?;
+                refreshed += 1;
+            }
+        }
+
+        if refreshed > 0 {
+            debug!("Refreshed mod identities of {} PBOs", refreshed);
+        }
+        Ok(refreshed)
+    }
+
+    fn get_mod_records(&self, folder: &str) -> Result<Vec<PboRecord>> {
+        let pbos = self.cache().get_by_mod(folder)?;
+        self.to_records(pbos)
+    }
+
+    fn refresh_modified_times(&mut self, paths: &[PathBuf]) -> Result<usize> {
+        let cache = self.cache();
+        let mut refreshed = 0;
+        for path in paths {
+            let Some(pbo) = cache.get(&normalize_path(path))? else {
+                continue;
+            };
+            let Ok(modified) = std::fs::metadata(path).and_then(|m| m.modified()) else {
+                continue;
+            };
+            let modified = DateTime::<Utc>::from(modified);
+            if modified > pbo.last_modified {
+                cache.update_last_modified(&pbo.id, modified)?;
+                refreshed += 1;
+            }
+        }
+
+        if refreshed > 0 {
+            debug!("Refreshed timestamps of {} unchanged PBOs", refreshed);
+        }
+        Ok(refreshed)
+    }
+
+    fn get_failed_extraction_ids(&self) -> Result<Vec<String>> {
+        Ok(self.cache().get_failed_extraction_ids()?)
+    }
+
+    fn remove_pbos(&mut self, pbo_ids: &HashSet<String>) -> Result<()> {
+        let cache = self.cache();
+        for pbo_id in pbo_ids {
+            let heir = cache.get_duplicates(pbo_id)?
+                .into_iter()
+                .map(|duplicate| duplicate.id)
+                .filter(|id| !pbo_ids.contains(id))
+                .min();
+            if let Some(heir_id) = heir {
+                cache.promote_duplicate(pbo_id, &heir_id)?;
+            }
+        }
+
+        let pbo_ids: Vec<String> = pbo_ids.iter().cloned().collect();
+        cache.delete(&pbo_ids)?;
+
+        debug!("Removed {} PBOs from the database", pbo_ids.len());
+        Ok(())
+    }
+
+    fn get_game_data_metadata(&self) -> Result<Vec<PboRecord>> {
+        let pbos = self.cache().get_by_type(arma3_database::PboType::GameData)?;
+        self.to_records(pbos)
+    }
+
+    fn get_mission_metadata(&self) -> Result<Vec<PboRecord>> {
+        let pbos = self.cache().get_by_type(arma3_database::PboType::Mission)?;
+        self.to_records(pbos)
+    }
+
+    fn get_signature_issues(&self) -> Result<Vec<PboRecord>> {
+        let mut records = self.get_game_data_metadata()?;
+        records.extend(self.get_mission_metadata()?);
+        records.retain(|pbo| matches!(&pbo.signature_status, Some(status) if !status.is_valid()));
+        Ok(records)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        // Every change is committed as it is made
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ExtractionOutcome, ExtractionStats};
+    use tempfile::tempdir;
+
+    fn create_store(dir: &Path) -> SqliteStore {
+        let config = ExtractionConfig::new(dir.join("cache"))
+            .with_db_path(dir.join("analysis.db"));
+        SqliteStore::new(&config).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let temp_dir = tempdir().unwrap();
+        let mut store = create_store(temp_dir.path());
+
+        let pbo_path = temp_dir.path().join("@CBA_A3").join("addons").join("Main.pbo");
+        std::fs::create_dir_all(pbo_path.parent().unwrap()).unwrap();
+        std::fs::write(&pbo_path, vec![0u8; 1024]).unwrap();
+        let mut metadata = PboMetadata::new(
+            pbo_path.clone(),
+            PboType::GameData,
+            vec!["cpp".to_string()],
+        ).unwrap();
+        metadata.extracted_files = vec![PathBuf::from("x/cba/main/config.cpp")];
+        metadata.file_hashes = HashMap::from([(PathBuf::from("x/cba/main/config.cpp"), "c0ffee".to_string())]);
+        metadata.entries = vec![
+            PboEntry { path: "x/cba/main/config.cpp".to_string(), size: 10, timestamp: 0 },
+            PboEntry { path: "x/cba/main/logo.paa".to_string(), size: 20, timestamp: 0 },
+        ];
+        metadata.mod_identity = Some(ModIdentity::read(&temp_dir.path().join("@CBA_A3")));
+        metadata.extraction_stats = Some(ExtractionStats {
+            duration_ms: 250,
+            bytes_read: 1024,
+            entries_extracted: 1,
+            outcome: ExtractionOutcome::Extracted,
+        });
+        store.update_metadata(metadata.clone()).unwrap();
+
+        let record = store.get_record(&pbo_path).unwrap().unwrap();
+        assert_eq!(record.id, normalize_path(&pbo_path));
+        assert_eq!(record.file_size, 1024);
+        assert!(!store.needs_extraction(&pbo_path, PboType::GameData, &["cpp".to_string()]).unwrap());
+        assert_eq!(record.extracted_files, vec![PathBuf::from("x/cba/main/config.cpp")]);
+        assert_eq!(record.entries.len(), 2);
+        assert_eq!(record.mod_identity.as_ref().map(|m| m.folder.as_str()), Some("@CBA_A3"));
+        assert_eq!(record.mod_identity.map(|m| m.root), Some(temp_dir.path().join("@CBA_A3")));
+        assert_eq!(record.extraction_stats, metadata.extraction_stats);
+        assert_eq!(record.file_hashes, metadata.file_hashes);
+
+        // Looked up through the extracted files index
+        let found = store.find_pbo_for_file(Path::new("x/cba/main/config.cpp")).unwrap().unwrap();
+        assert_eq!(found.id, record.id);
+
+        // Entries that were not extracted are listed until they are
+        let paa_files = store.find_files_by_extension("paa").unwrap();
+        assert_eq!(paa_files.len(), 1);
+        assert!(!paa_files[0].extracted);
+
+        store.add_extracted_file(&pbo_path, PathBuf::from("x/cba/main/logo.paa"), Some("beef".to_string())).unwrap();
+        let paa_files = store.find_files_by_extension("paa").unwrap();
+        assert_eq!(paa_files.len(), 1);
+        assert!(paa_files[0].extracted);
+        assert_eq!(paa_files[0].content_hash.as_deref(), Some("beef"));
+    }
+
+    #[test]
+    fn test_failed_extraction() {
+        let temp_dir = tempdir().unwrap();
+        let mut store = create_store(temp_dir.path());
+
+        let pbo_path = temp_dir.path().join("broken.pbo");
+        std::fs::write(&pbo_path, b"not a pbo").unwrap();
+        store.add_failed_extraction(&pbo_path, PboType::GameData, ExtractionFailure::new("Bad header", "corrupt", false)).unwrap();
+        store.add_failed_extraction(&pbo_path, PboType::GameData, ExtractionFailure::new("Bad header", "corrupt", false)).unwrap();
+
+        let failed = store.get_failed_extraction(&pbo_path).unwrap().unwrap();
+        assert_eq!(failed.category, "corrupt");
+        assert_eq!(failed.attempts, 2);
+        assert_eq!(failed.pbo_path, pbo_path);
+        assert_eq!(failed.tool_version.as_deref(), Some(crate::VERSION));
+
+        // The failure references a record without files, which still needs extracting
+        let record = store.get_record(&pbo_path).unwrap().unwrap();
+        assert_eq!(record.pbo_type, PboType::GameData);
+        assert!(record.extracted_files.is_empty());
+        assert!(store.needs_extraction(&pbo_path, PboType::GameData, &["cpp".to_string()]).unwrap());
+        let foreign_key_violations: i64 = store.database().with_connection(|conn| {
+            Ok(conn.query_row("SELECT COUNT(*) FROM pragma_foreign_key_check", [], |row| row.get(0))?)
+        }).unwrap();
+        assert_eq!(foreign_key_violations, 0);
+
+        let (_, message) = store.is_failed_extraction(&pbo_path).unwrap().unwrap();
+        assert_eq!(message, "Bad header");
+        assert_eq!(store.get_failed_extraction_ids().unwrap(), vec![normalize_path(&pbo_path)]);
+
+        // A later successful extraction clears the failure
+        let metadata = PboMetadata::new(pbo_path.clone(), PboType::GameData, vec!["cpp".to_string()]).unwrap();
+        store.update_metadata(metadata).unwrap();
+        assert!(store.is_failed_extraction(&pbo_path).unwrap().is_none());
+
+        store.remove_pbos(&HashSet::from([normalize_path(&pbo_path)])).unwrap();
+        assert!(store.get_record(&pbo_path).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_removing_original_promotes_duplicate() {
+        let temp_dir = tempdir().unwrap();
+        let mut store = create_store(temp_dir.path());
+        let shared = PathBuf::from("x/cba/main/config.cpp");
+
+        let mut paths = Vec::new();
+        for name in ["a_original.pbo", "b_duplicate.pbo", "c_duplicate.pbo"] {
+            let path = temp_dir.path().join(name);
+            std::fs::write(&path, b"same contents").unwrap();
+            let mut metadata = PboMetadata::new(path.clone(), PboType::GameData, vec!["cpp".to_string()]).unwrap();
+            match paths.first() {
+                None => {
+                    metadata.extracted_files = vec![shared.clone()];
+                    metadata.file_hashes = HashMap::from([(shared.clone(), "c0ffee".to_string())]);
+                }
+                Some(original) => metadata.duplicate_of = Some(PathBuf::clone(original)),
+            }
+            store.update_metadata(metadata).unwrap();
+            paths.push(path);
+        }
+
+        store.remove_pbos(&HashSet::from([normalize_path(&paths[0])])).unwrap();
+
+        // The first duplicate owns the files now and the other one shares them
+        let heir = store.get_record(&paths[1]).unwrap().unwrap();
+        assert_eq!(heir.duplicate_of, None);
+        assert_eq!(heir.extracted_files, vec![shared.clone()]);
+        assert_eq!(heir.file_hashes[&shared], "c0ffee");
+        assert_eq!(store.find_pbo_for_file(&shared).unwrap().unwrap().id, heir.id);
+        let other = store.get_record(&paths[2]).unwrap().unwrap();
+        assert_eq!(other.duplicate_of, Some(heir.id.clone()));
+        assert_eq!(other.extracted_files, vec![shared.clone()]);
+
+        // Listing loads the shared files the same way
+        for record in store.get_game_data_metadata().unwrap() {
+            assert_eq!(record.extracted_files, vec![shared.clone()]);
+            assert_eq!(record.file_hashes[&shared], "c0ffee");
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! Storage of extraction state
+//!
+//! The extraction manager records what it extracted through
+//! [`ExtractionStore`]. Two implementations exist: the JSON file database in
+//! [`crate::file_db`] and the SQLite database shared with the analysis tools in
+//! [`crate::sqlite_store`].
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+use log::debug;
+
+use crate::error::{CacheError, Result};
+use crate::file_db::{normalize_path, ExtractedFileInfo, FailedExtraction, FileDbManager, PboRecord};
+use crate::models::{ExtractionConfig, ExtractionFailure, PboMetadata, PboType, StateBackend};
+use crate::sqlite_store::SqliteStore;
+use crate::mods::ModIdentity;
+use crate::utils;
+
+/// Persistent record of extracted and failed PBOs
+///
+/// PBO IDs are the paths normalized by [`normalize_path`].
+pub trait ExtractionStore: Send + Sync {
+    /// Record a finished extraction of a PBO, replacing any earlier record
+    fn update_metadata(&mut self, metadata: PboMetadata) -> Result<()>;
+
+    /// Record a failed extraction of a PBO of type `pbo_type`
+    ///
+    /// An earlier failure of the same PBO is replaced and its attempts are
+    /// carried over.
+    fn add_failed_extraction(&mut self, path: &Path, pbo_type: PboType, failure: ExtractionFailure) -> Result<()>;
+
+    /// Get the failure record of a PBO
+    fn get_failed_extraction(&self, path: &Path) -> Result<Option<FailedExtraction>>;
+
+    /// Get all failure records
+    fn get_failed_extractions(&self) -> Result<Vec<FailedExtraction>>;
+
+    /// Find the source PBO for a file path
+    fn find_pbo_for_file(&self, file_path: &Path) -> Result<Option<PboRecord>>;
+
+    /// Find all files with a specific extension
+    ///
+    /// Entries that were never extracted are included from the PBO entry
+    /// tables with `extracted` set to false; see
+    /// [`ExtractionManager::materialize`](crate::ExtractionManager::materialize).
+    fn find_files_by_extension(&self, extension: &str) -> Result<Vec<ExtractedFileInfo>>;
+
+    /// Record a file that was extracted from a PBO after its initial extraction
+    ///
+    /// `content_hash` is the SHA-1 of the file when it is kept in the blob store.
+    fn add_extracted_file(&mut self, path: &Path, relative_path: PathBuf, content_hash: Option<String>) -> Result<()>;
+
+    /// Get the record of a PBO by its ID
+    fn get_record_by_id(&self, pbo_id: &str) -> Result<Option<PboRecord>>;
+
+    /// Find an extracted PBO with the given contents and extraction settings
+    ///
+    /// Only PBOs that own their extracted files are considered, never other
+    /// duplicates.
+    fn find_by_content_hash(
+        &self,
+        content_hash: &str,
+        pbo_type: PboType,
+        extensions: &[String],
+    ) -> Result<Option<PboRecord>>;
+
+    /// Get the PBO records sharing the extracted files of another PBO
+    fn get_duplicates(&self, path: &Path) -> Result<Vec<PboRecord>>;
+
+    /// Store the current mod identity on the records of unchanged PBOs
+    ///
+    /// `mod.cpp` and `meta.cpp` can change without the PBOs changing, e.g. when
+    /// a workshop mod is renamed.
+    fn refresh_mod_identities(&mut self, mods: &HashMap<PathBuf, ModIdentity>, paths: &[PathBuf]) -> Result<usize>;
+
+    /// Get all records of PBOs belonging to the mod in the given folder
+    fn get_mod_records(&self, folder: &str) -> Result<Vec<PboRecord>>;
+
+    /// Move the stored timestamp of unchanged PBOs forward
+    ///
+    /// Called for PBOs that were found not to need extraction, so a timestamp
+    /// that moved without a content change is only hashed once.
+    fn refresh_modified_times(&mut self, paths: &[PathBuf]) -> Result<usize>;
+
+    /// Get the IDs of all PBOs with a failed extraction record
+    fn get_failed_extraction_ids(&self) -> Result<Vec<String>>;
+
+    /// Remove PBO records together with their file index and failure entries
+    ///
+    /// Extracted files on disk are left alone.
+    ///
+    /// When a removed PBO has duplicates that stay, the first of them takes
+    /// over its extracted files and the others become its duplicates.
+    fn remove_pbos(&mut self, pbo_ids: &HashSet<String>) -> Result<()>;
+
+    /// Get all game data PBO records
+    fn get_game_data_metadata(&self) -> Result<Vec<PboRecord>>;
+
+    /// Get all mission PBO records
+    fn get_mission_metadata(&self) -> Result<Vec<PboRecord>>;
+
+    /// Get all PBO records whose signature was checked and is not valid
+    fn get_signature_issues(&self) -> Result<Vec<PboRecord>>;
+
+    /// Make everything recorded so far durable
+    ///
+    /// Called at the end of each extraction run.
+    fn flush(&mut self) -> Result<()>;
+
+    /// Check if a PBO has a failed extraction record
+    ///
+    /// Returns the RFC 3339 timestamp and the error message of the failure.
+    fn is_failed_extraction(&self, path: &Path) -> Result<Option<(String, String)>> {
+        Ok(self.get_failed_extraction(path)?
+            .map(|failed| (failed.timestamp.to_rfc3339(), failed.error_message)))
+    }
+
+    /// Get the record of a PBO by its path
+    fn get_record(&self, path: &Path) -> Result<Option<PboRecord>> {
+        self.get_record_by_id(&normalize_path(path))
+    }
+
+    /// Check if a PBO needs to be extracted
+    fn needs_extraction(
+        &self,
+        path: &Path,
+        _pbo_type: PboType,
+        extensions: &[String],
+    ) -> Result<bool> {
+        if !path.exists() {
+            return Ok(true);
+        }
+
+        // Check if we have this PBO in the database
+        let Some(record) = self.get_record(path)? else {
+            // Not in database, needs extraction
+            debug!("PBO not in database, needs extraction: {}", path.display());
+            return Ok(true);
+        };
+
+        // If the list of extensions has changed, we need to extract again
+        if !same_extensions(&record.used_extensions, extensions) {
+            debug!("Extensions changed for {}, needs extraction", path.display());
+            return Ok(true);
+        }
+
+        // A duplicate is only current while the copy it shares still has its contents
+        if let Some(original_id) = &record.duplicate_of {
+            let shared = self.get_record_by_id(original_id)?
+                .is_some_and(|original| original.content_hash == record.content_hash);
+            if !shared {
+                debug!("Original of duplicate {} changed or is gone, needs extraction", path.display());
+                return Ok(true);
+            }
+        }
+
+        // Check if the file has been modified since last extraction
+        let metadata = std::fs::metadata(path)
+            .map_err(|e| CacheError::FileOperation(format!("Failed to get metadata: {}", e)))?;
+
+        let current_modified = DateTime::<Utc>::from(metadata.modified()
+            .map_err(|e| CacheError::FileOperation(format!("Failed to get modified time: {}", e)))?);
+
+        if metadata.len() != record.file_size {
+            debug!("File size changed since last extraction: {}", path.display());
+            return Ok(true);
+        }
+
+        if current_modified > record.last_modified {
+            // Re-downloads touch the timestamp without changing the contents
+            let unchanged = match &record.content_hash {
+                Some(hash) => utils::hash_file(path).is_ok_and(|current| &current == hash),
+                None => false,
+            };
+            if !unchanged {
+                debug!("File modified since last extraction: {}", path.display());
+                return Ok(true);
+            }
+            debug!("Timestamp changed but contents are identical: {}", path.display());
+        }
+
+        // No need to extract
+        debug!("PBO does not need extraction: {}", path.display());
+        Ok(false)
+    }
+}
+
+/// Open the extraction state configured by `state_backend` at `db_path`
+pub fn open_store(config: &ExtractionConfig) -> Result<Box<dyn ExtractionStore>> {
+    match config.state_backend {
+        StateBackend::File => Ok(Box::new(FileDbManager::new(&config.db_path)?)),
+        StateBackend::Sqlite => Ok(Box::new(SqliteStore::new(config)?)),
+    }
+}
+
+/// Compare extension lists ignoring order
+pub(crate) fn same_extensions(a: &[String], b: &[String]) -> bool {
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    a.sort();
+    b.sort();
+    a == b
+}
@@ -1,6 +1,8 @@
 use std::path::Path;
-use std::fs;
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
 use anyhow::{Result, Context};
+use sha1::{Digest, Sha1};
 
 /// Ensure a directory exists, creating it if necessary
 pub fn ensure_dir_exists(path: &Path) -> Result<()> {
@@ -9,4 +11,33 @@ pub fn ensure_dir_exists(path: &Path) -> Result<()> {
             .context(format!("Failed to create directory: {}", path.display()))?;
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Remove the now empty directories between a deleted file and `root`
+pub fn remove_empty_parents(path: &Path, root: &Path) {
+    let mut current = path.parent();
+    while let Some(dir) = current {
+        if dir == root || !dir.starts_with(root) || fs::remove_dir(dir).is_err() {
+            break;
+        }
+        current = dir.parent();
+    }
+}
+
+/// Hash the contents of a file as a lowercase SHA-1 hex string
+pub fn hash_file(path: &Path) -> Result<String> {
+    let file = File::open(path)
+        .context(format!("Failed to open file for hashing: {}", path.display()))?;
+    let mut reader = BufReader::new(file);
+    let mut hasher = Sha1::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)
+            .context(format!("Failed to read file for hashing: {}", path.display()))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
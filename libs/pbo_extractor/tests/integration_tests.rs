@@ -10,9 +10,13 @@ use std::collections::HashSet;
 use arma3_extractor::{
     ExtractionConfig,
     ExtractionManager,
+    FailedExtraction,
     PboMetadata,
     PboType,
+    RetryPolicy,
+    StateBackend,
 };
+use pbo_tools::signatures::SignatureStatus;
 
 /// Helper function to get the base path of the fixtures directory
 fn get_fixtures_dir() -> PathBuf {
@@ -59,6 +63,9 @@ fn create_test_config() -> (tempfile::TempDir, ExtractionConfig) {
         timeout: 30,
         verbose: false,
         db_path: cache_dir.join("cache.db"),
+        state_backend: StateBackend::File,
+        retry_policy: RetryPolicy::default(),
+        salvage_failed: false,
     };
     
     (temp_dir, config)
@@ -129,7 +136,7 @@ fn test_file_db_manager_integration() -> Result<()> {
     assert_eq!(game_data.len(), 1);
     
     // Record a failed extraction and check it's recorded
-    db_manager.add_failed_extraction(&pbo_path, "Test integration failure".to_string())?;
+    db_manager.add_failed_extraction(&pbo_path, PboType::GameData, "Test integration failure".to_string().into())?;
     
     let failed_result = db_manager.is_failed_extraction(&pbo_path)?;
     assert!(failed_result.is_some());
@@ -404,7 +411,207 @@ async fn test_extract_headgear_pumpkin_fixture() -> Result<()> {
     assert_eq!(extracted_files_set, expected_full_paths,
                "The set of extracted files does not match the expected set.");
 
-    // Optional: Further check db state if needed
+    // The addon is signed with the key shipped in the mod's keys folder
+    let records = manager.get_db_manager().get_game_data_metadata()?;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].signature_status, Some(SignatureStatus::Valid));
+
+    // The addon is attributed to its mod folder and the name from mod.cpp
+    let mod_identity = records[0].mod_identity.as_ref().expect("PBO should belong to a mod");
+    assert_eq!(mod_identity.folder, "@tc_headgear_pumpkin");
+    assert_eq!(mod_identity.name, "Tyen Customs - Pumpkin Headgear");
+    assert_eq!(manager.get_mod_records("@tc_headgear_pumpkin")?.len(), 1);
+
+    // Cached files resolve by game path through the addon's prefix
+    let drive = manager.virtual_pdrive()?;
+    assert!(drive.exists(r"\tc\headgear_pumpkin\config.cpp"));
+    assert!(drive.exists(r"\tc\headgear_pumpkin\data\pumpkin_halloween_co.paa"));
+    assert!(manager.get_signature_issues()?.is_empty());
+
+    // Only includable files are written to the include root
+    let include_root = manager.materialize_include_root()?;
+    assert!(include_root.join("tc/headgear_pumpkin/config.cpp").is_file());
+    assert!(!include_root.join("tc/headgear_pumpkin/data/pumpkin_halloween_co.paa").exists());
+
+    // Unchanged files are not rewritten and stale ones are dropped
+    let config_cpp = include_root.join("tc/headgear_pumpkin/config.cpp");
+    let modified = std::fs::metadata(&config_cpp)?.modified()?;
+    std::fs::write(include_root.join("stale.hpp"), "")?;
+    manager.materialize_include_root()?;
+    assert_eq!(std::fs::metadata(&config_cpp)?.modified()?, modified);
+    assert!(!include_root.join("stale.hpp").exists());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_identical_pbos_are_extracted_once() -> Result<()> {
+    let (temp_dir, mut config) = create_test_config();
+    let source = get_fixtures_dir().join("modfolder/@tc_headgear_pumpkin/addons/headgear_pumpkin.pbo");
+
+    // The same addon installed in two game data directories
+    let first_dir = temp_dir.path().join("first/addons");
+    let second_dir = temp_dir.path().join("second/addons");
+    for dir in [&first_dir, &second_dir] {
+        std::fs::create_dir_all(dir)?;
+        std::fs::copy(&source, dir.join("headgear_pumpkin.pbo"))?;
+    }
+    config.game_data_dirs = vec![first_dir.clone(), second_dir.clone()];
+    config.game_data_extensions = vec!["cpp".to_string(), "rvmat".to_string()];
+
+    let mut manager = ExtractionManager::new(config.clone())?;
+    let extracted = manager.process_game_data(false).await?;
+    assert_eq!(extracted.len(), 3);
+
+    // Both source paths are recorded, the second sharing the first one's files
+    let mut records = manager.get_db_manager().get_game_data_metadata()?;
+    records.sort_by(|a, b| a.full_path.cmp(&b.full_path));
+    assert_eq!(records.len(), 2);
+    assert!(records[0].content_hash.is_some());
+    assert_eq!(records[0].content_hash, records[1].content_hash);
+    assert_eq!(records[0].duplicate_of, None);
+    assert_eq!(records[1].duplicate_of, Some(records[0].id.clone()));
+    assert_eq!(records[0].extracted_files, records[1].extracted_files);
+
+    // A re-download with identical contents only moves the timestamp
+    thread::sleep(Duration::from_millis(10));
+    std::fs::copy(&source, first_dir.join("headgear_pumpkin.pbo"))?;
+    let mut manager = ExtractionManager::new(config)?;
+    assert!(manager.process_game_data(false).await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_prune_removes_vanished_pbos() -> Result<()> {
+    let (temp_dir, mut config) = create_test_config();
+    let source = get_fixtures_dir().join("modfolder/@tc_headgear_pumpkin/addons/headgear_pumpkin.pbo");
+    let addons_dir = temp_dir.path().join("mod/addons");
+    std::fs::create_dir_all(&addons_dir)?;
+    let pbo_path = addons_dir.join("headgear_pumpkin.pbo");
+    std::fs::copy(&source, &pbo_path)?;
+    config.game_data_dirs = vec![addons_dir.clone()];
+    config.game_data_extensions = vec!["cpp".to_string(), "rvmat".to_string()];
+
+    let mut manager = ExtractionManager::new(config.clone())?;
+    let extracted = manager.process_game_data(false).await?;
+    assert_eq!(extracted.len(), 3);
+
+    // Nothing to prune while the PBO is still there
+    let report = manager.prune(false)?;
+    assert!(report.removed_pbos.is_empty());
+    assert!(report.removed_files.is_empty());
+
+    // The mod is unsubscribed
+    std::fs::remove_file(&pbo_path)?;
+
+    let report = manager.prune(true)?;
+    assert!(report.dry_run);
+    assert_eq!(report.removed_pbos, vec![pbo_path.clone()]);
+    assert_eq!(report.removed_files.len(), 3);
+    assert!(report.reclaimed_bytes > 0);
+    assert!(extracted.iter().all(|file| file.exists()));
+    assert_eq!(manager.get_db_manager().get_game_data_metadata()?.len(), 1);
+
+    let report = manager.prune(false)?;
+    assert_eq!(report.removed_files.len(), 3);
+    assert!(extracted.iter().all(|file| !file.exists()));
+    assert!(manager.get_db_manager().get_game_data_metadata()?.is_empty());
+    assert!(manager.find_files_by_extension("rvmat")?.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_extracted_files_are_kept_in_blob_store() -> Result<()> {
+    let (temp_dir, mut config) = create_test_config();
+    let source = get_fixtures_dir().join("modfolder/@tc_headgear_pumpkin/addons/headgear_pumpkin.pbo");
+    let addons_dir = temp_dir.path().join("mod/addons");
+    std::fs::create_dir_all(&addons_dir)?;
+    let pbo_path = addons_dir.join("headgear_pumpkin.pbo");
+    std::fs::copy(&source, &pbo_path)?;
+    config.game_data_dirs = vec![addons_dir.clone()];
+    config.game_data_extensions = vec!["cpp".to_string(), "rvmat".to_string()];
+
+    let mut manager = ExtractionManager::new(config.clone())?;
+    manager.process_game_data(false).await?;
+
+    // Every extracted file is recorded with the hash of the blob holding it
+    let records = manager.get_db_manager().get_game_data_metadata()?;
+    assert_eq!(records[0].file_hashes.len(), 3);
+    let blobs_dir = config.cache_dir.join("blobs");
+    for file in manager.find_files_by_extension("rvmat")? {
+        let hash = file.content_hash.expect("extracted file should have a hash");
+        let blob = blobs_dir.join(&hash[..2]).join(&hash);
+        let cached = config.game_data_cache_dir.join(&file.relative_path);
+        assert_eq!(std::fs::read(&blob)?, std::fs::read(&cached)?);
+    }
+
+    // Blobs go once no PBO refers to them any more
+    std::fs::remove_file(&pbo_path)?;
+    let report = manager.prune(false)?;
+    assert_eq!(report.removed_blobs, 3);
+    assert!(report.reclaimed_bytes > 0);
+    assert_eq!(std::fs::read_dir(&blobs_dir)?.count(), 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_prune_keeps_pbos_of_unavailable_directories() -> Result<()> {
+    let (temp_dir, mut config) = create_test_config();
+    let source = get_fixtures_dir().join("modfolder/@tc_headgear_pumpkin/addons/headgear_pumpkin.pbo");
+    let mod_dir = temp_dir.path().join("mod");
+    let addons_dir = mod_dir.join("addons");
+    std::fs::create_dir_all(&addons_dir)?;
+    std::fs::copy(&source, addons_dir.join("headgear_pumpkin.pbo"))?;
+    config.game_data_dirs = vec![addons_dir.clone()];
+    config.game_data_extensions = vec!["cpp".to_string()];
+
+    let mut manager = ExtractionManager::new(config)?;
+    manager.process_game_data(false).await?;
+
+    // A whole source directory going away looks like an unmounted drive
+    std::fs::remove_dir_all(&mod_dir)?;
+    let report = manager.prune(false)?;
+    assert!(report.removed_pbos.is_empty());
+    assert_eq!(manager.get_db_manager().get_game_data_metadata()?.len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_materialize_entries_on_demand() -> Result<()> {
+    let (temp_dir, mut config) = create_test_config();
+    let source = get_fixtures_dir().join("modfolder/@tc_headgear_pumpkin/addons/headgear_pumpkin.pbo");
+    let addons_dir = temp_dir.path().join("mod/addons");
+    std::fs::create_dir_all(&addons_dir)?;
+    let pbo_path = addons_dir.join("headgear_pumpkin.pbo");
+    std::fs::copy(&source, &pbo_path)?;
+    config.game_data_dirs = vec![addons_dir.clone()];
+    config.game_data_extensions = vec!["cpp".to_string()];
+
+    let mut manager = ExtractionManager::new(config.clone())?;
+    let extracted = manager.process_game_data(false).await?;
+    assert_eq!(extracted.len(), 1);
+
+    // Only the config was unpacked, but the whole entry table is indexed
+    let record = manager.get_db_manager().get_record(&pbo_path)?.unwrap();
+    assert!(record.find_entry("logo.paa").is_some());
+    let textures = manager.find_files_by_extension("paa")?;
+    assert!(!textures.is_empty());
+    assert!(textures.iter().all(|file| !file.extracted));
+
+    let logo = textures.iter().find(|file| file.relative_path == PathBuf::from("logo.paa")).unwrap();
+    let logo_path = manager.materialize_file(logo).await?;
+    assert_eq!(logo_path, config.game_data_cache_dir.join("logo.paa"));
+    assert!(logo_path.is_file());
+
+    // The materialized file is recorded and survives a restart
+    let manager = ExtractionManager::new(config)?;
+    let textures = manager.find_files_by_extension("paa")?;
+    assert!(textures.iter().any(|file| file.relative_path == PathBuf::from("logo.paa") && file.extracted));
+    assert!(manager.find_pbo_for_file(&PathBuf::from("logo.paa"))?.is_some());
 
     Ok(())
 }
@@ -492,3 +699,96 @@ async fn test_extract_joust_mission_fixture() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_extract_missions_from_archive() -> Result<()> {
+    let (temp_dir, mut config) = create_test_config();
+    let pbo_bytes = std::fs::read(get_fixtures_dir().join("missions/adv48_Joust.VR.pbo"))?;
+
+    // A community bundle with a packed and an unpacked mission side by side
+    let bundle_dir = temp_dir.path().join("downloads/community");
+    std::fs::create_dir_all(&bundle_dir)?;
+    let archive_path = bundle_dir.join("bundle.zip");
+    let mut zip = zip::ZipWriter::new(File::create(&archive_path)?);
+    let options = zip::write::SimpleFileOptions::default();
+    zip.start_file("packed/adv48_Joust.VR.pbo", options)?;
+    zip.write_all(&pbo_bytes)?;
+    zip.start_file("tvt_raid.Stratis/mission.sqm", options)?;
+    zip.write_all(b"version = 54;")?;
+    zip.start_file("tvt_raid.Stratis/init.sqf", options)?;
+    zip.write_all(b"hint \"raid\";")?;
+    zip.finish()?;
+
+    config.mission_dirs = vec![temp_dir.path().join("downloads")];
+    config.mission_extensions = vec!["sqf".to_string(), "sqm".to_string()];
+
+    let mut manager = ExtractionManager::new(config.clone())?;
+    let results = manager.process_all_missions(false).await?;
+    assert_eq!(results.len(), 2);
+
+    let sources = manager.mission_sources().to_vec();
+    assert_eq!(sources.len(), 2);
+    let pbo_source = sources.iter().find(|source| !source.is_folder).unwrap();
+    let folder_source = sources.iter().find(|source| source.is_folder).unwrap();
+    assert!(pbo_source.path.starts_with(config.cache_dir.join("archives")));
+    assert!(results[&pbo_source.path].contains(&config.mission_cache_dir.join("adv48_Joust.VR/mission.sqm")));
+    assert_eq!(results[&folder_source.path].len(), 2);
+
+    // Both missions name the archive they came from
+    let archive_id = archive_path.to_string_lossy().to_lowercase().replace('\\', "/");
+    for source in &sources {
+        assert_eq!(source.archive.as_ref(), Some(&archive_path));
+        assert_eq!(source.to_mission().source_archive, Some(archive_id.clone()));
+    }
+
+    // Only the packed mission has a source PBO, the record of its unpacked copy
+    let record = manager.get_db_manager().get_record(&pbo_source.path)?.unwrap();
+    assert_eq!(pbo_source.to_mission().source_pbo, Some(record.id));
+    assert_eq!(folder_source.to_mission().source_pbo, None);
+    assert_eq!(folder_source.to_mission().name, "tvt_raid.Stratis");
+    assert_eq!(pbo_source.to_mission().name, "adv48_Joust.VR");
+
+    // The unpacked PBO is not pruned while its archive exists, but is once it is gone
+    assert!(manager.prune(false)?.removed_pbos.is_empty());
+    std::fs::remove_file(&archive_path)?;
+    assert_eq!(manager.prune(false)?.removed_pbos, vec![pbo_source.path.clone()]);
+    assert!(!pbo_source.path.exists());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_retry_legacy_failures_by_id() -> Result<()> {
+    let (temp_dir, mut config) = create_test_config();
+    let source = get_fixtures_dir().join("modfolder/@tc_headgear_pumpkin/addons/headgear_pumpkin.pbo");
+    let addons_dir = temp_dir.path().join("Mod/Addons");
+    std::fs::create_dir_all(&addons_dir)?;
+    let pbo_path = addons_dir.join("Headgear_Pumpkin.pbo");
+    std::fs::copy(&source, &pbo_path)?;
+    config.game_data_dirs = vec![addons_dir.clone()];
+    config.game_data_extensions = vec!["cpp".to_string()];
+
+    // Older versions only recorded the normalized ID of the PBO
+    let legacy = |pbo_id: String| FailedExtraction {
+        pbo_id,
+        error_message: "Timed out".to_string(),
+        timestamp: chrono::Utc::now(),
+        pbo_path: PathBuf::new(),
+        category: "timeout".to_string(),
+        retryable: true,
+        attempts: 1,
+        content_hash: None,
+        tool_version: None,
+    };
+    let failures = vec![
+        legacy(pbo_path.to_string_lossy().to_lowercase().replace('\\', "/")),
+        legacy(addons_dir.join("gone.pbo").to_string_lossy().to_lowercase().replace('\\', "/")),
+    ];
+
+    let mut manager = ExtractionManager::new(config)?;
+    let extracted = manager.retry_failures(&failures).await?;
+    assert_eq!(extracted, vec![pbo_path.clone()]);
+    assert!(manager.get_db_manager().get_record(&pbo_path)?.is_some());
+
+    Ok(())
+}
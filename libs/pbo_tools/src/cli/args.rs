@@ -1,5 +1,5 @@
 use std::path::PathBuf;
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -14,10 +14,11 @@ pub struct Cli {
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
-    /// List contents of PBO file
+    /// List contents of PBO files
     List {
-        /// Path to PBO file
-        pbo_path: PathBuf,
+        /// Paths to PBO files
+        #[arg(required = true)]
+        pbo_paths: Vec<PathBuf>,
 
         /// Use brief directory-style output listing
         #[arg(short, long)]
@@ -26,6 +27,10 @@ pub enum Commands {
         /// Enable verbose output
         #[arg(short, long)]
         verbose: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
     },
     /// Extract PBO file contents
     Extract {
@@ -53,16 +58,123 @@ pub enum Commands {
     },
     /// Show PBO properties and metadata
     Properties {
-        /// Path to PBO file
-        pbo_path: PathBuf,
+        /// Paths to PBO files
+        #[arg(required = true)]
+        pbo_paths: Vec<PathBuf>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
     },
     /// Validate PBO file integrity
     Validate {
-        /// Path to PBO file
-        pbo_path: PathBuf,
+        /// Paths to PBO files
+        #[arg(required = true)]
+        pbo_paths: Vec<PathBuf>,
+
+        /// Enable verbose output
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// Also verify every entry's declared size against the data block
+        #[arg(short, long)]
+        deep: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// Pack a directory into a PBO file
+    Pack {
+        /// Directory to pack
+        source_dir: PathBuf,
+
+        /// Output PBO file
+        output_path: PathBuf,
+
+        /// Prefix header extension (defaults to $PBOPREFIX$ if present)
+        #[arg(long)]
+        prefix: Option<String>,
+
+        /// Product header extension
+        #[arg(long)]
+        product: Option<String>,
+
+        /// Version header extension
+        #[arg(long = "pbo-version")]
+        version: Option<String>,
+
+        /// Glob pattern of files to exclude (can be repeated)
+        #[arg(short, long)]
+        exclude: Vec<String>,
 
         /// Enable verbose output
         #[arg(short, long)]
         verbose: bool,
     },
+    /// Verify a PBO's signature against a public key
+    VerifySign {
+        /// Path to PBO file
+        pbo_path: PathBuf,
+
+        /// Public key (.bikey) the PBO should be signed with
+        key_path: PathBuf,
+
+        /// Signature file (defaults to the .bisign files next to the PBO)
+        #[arg(short, long)]
+        signature: Option<PathBuf>,
+    },
+    /// Compare two versions of a PBO
+    Diff {
+        /// Path to the older PBO file
+        old_pbo: PathBuf,
+
+        /// Path to the newer PBO file
+        new_pbo: PathBuf,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// Search the contents of PBO entries
+    Grep {
+        /// Regular expression to search for
+        pattern: String,
+
+        /// PBO files or directories to search recursively
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+
+        /// Only search entries matching this glob pattern (e.g. "*.sqf,*.hpp")
+        #[arg(short, long)]
+        glob: Option<String>,
+
+        /// Ignore case when matching
+        #[arg(short, long)]
+        ignore_case: bool,
+    },
+    /// Recover the intact files from a corrupt or truncated PBO
+    Salvage {
+        /// Path to PBO file
+        pbo_path: PathBuf,
+
+        /// Output directory
+        output_dir: PathBuf,
+
+        /// Only recover files matching this pattern (e.g. "*.cpp,*.hpp")
+        #[arg(short, long)]
+        filter: Option<String>,
+    },
+}
+
+/// Output format of the inspection commands
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human readable text
+    #[default]
+    Text,
+    /// A single JSON array with one record per PBO
+    Json,
+    /// One JSON record per line, written as each PBO is processed
+    Ndjson,
 }
@@ -1,27 +1,56 @@
-use std::path::PathBuf;
-use crate::ops::PboOperationResult;
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use crate::ops::{
+    ClassChangeKind, EntryChangeKind, PboGrepOptions, PboOperationError, PboOperationResult, PboPackOptions,
+};
 use crate::core::api::PboApiOps;
+use crate::signatures;
+use super::args::OutputFormat;
 
-/// List contents of a PBO file
-pub async fn list_contents(api: &dyn PboApiOps, pbo_path: &PathBuf, verbose: bool) -> PboOperationResult<()> {
-    let files = api.list_contents(pbo_path).await?;
-    
-    if verbose {
-        println!("Found {} files in PBO:", files.len());
-        for file in files {
-            println!("  {:<40} {:>10} bytes  {} ({})", 
-                     file.file_path, 
-                     file.size, 
-                     file.mime_type,
-                     file.timestamp);
+/// List contents of PBO files
+pub async fn list_contents(
+    api: &dyn PboApiOps,
+    pbo_paths: &[PathBuf],
+    verbose: bool,
+    format: OutputFormat,
+) -> PboOperationResult<()> {
+    let mut records = RecordWriter::new(format, pbo_paths.len());
+
+    for pbo_path in pbo_paths {
+        let result = api.list_contents(pbo_path).await;
+        if format != OutputFormat::Text {
+            records.write(pbo_path, result)?;
+            continue;
+        }
+
+        let files = match result {
+            Ok(files) => files,
+            Err(e) => {
+                records.text_failure(pbo_path, e);
+                continue;
+            }
+        };
+
+        if pbo_paths.len() > 1 {
+            println!("{}:", pbo_path.display());
         }
-    } else {
-        for file in files {
-            println!("{}", file.file_path);
+        if verbose {
+            println!("Found {} files in PBO:", files.len());
+            for file in files {
+                println!("  {:<40} {:>10} bytes  {} ({})", 
+                         file.file_path, 
+                         file.size, 
+                         file.mime_type,
+                         file.timestamp);
+            }
+        } else {
+            for file in files {
+                println!("{}", file.file_path);
+            }
         }
     }
     
-    Ok(())
+    records.finish()
 }
 
 /// Extract contents from a PBO file
@@ -56,80 +85,406 @@ pub async fn extract_contents(
 }
 
 /// Show PBO properties and metadata
-pub async fn show_properties(api: &dyn PboApiOps, pbo_path: &PathBuf) -> PboOperationResult<()> {
-    let properties = api.get_properties(pbo_path).await?;
-    
-    println!("PBO Properties for: {}", pbo_path.display());
-    println!("  File Count: {}", properties.file_count);
-    println!("  Total Size: {} bytes", properties.total_size);
-    println!("  Compression Ratio: {:.1}%", properties.compression_ratio() * 100.0);
-    
-    if let Some(version) = &properties.version {
-        println!("  Version: {}", version);
+pub async fn show_properties(api: &dyn PboApiOps, pbo_paths: &[PathBuf], format: OutputFormat) -> PboOperationResult<()> {
+    let mut records = RecordWriter::new(format, pbo_paths.len());
+
+    for pbo_path in pbo_paths {
+        let result = api.get_properties(pbo_path).await;
+        if format != OutputFormat::Text {
+            records.write(pbo_path, result)?;
+            continue;
+        }
+
+        let properties = match result {
+            Ok(properties) => properties,
+            Err(e) => {
+                records.text_failure(pbo_path, e);
+                continue;
+            }
+        };
+
+        println!("PBO Properties for: {}", pbo_path.display());
+        println!("  File Count: {}", properties.file_count);
+        println!("  Total Size: {} bytes", properties.total_size);
+        println!("  Compression Ratio: {:.1}%", properties.compression_ratio() * 100.0);
+
+        if let Some(version) = &properties.version {
+            println!("  Version: {}", version);
+        }
+
+        if let Some(author) = &properties.author {
+            println!("  Author: {}", author);
+        }
+
+        if let Some(prefix) = &properties.prefix {
+            println!("  Prefix: {}", prefix);
+        }
+
+        if let Some(checksum) = &properties.checksum {
+            println!("  Checksum: {}", checksum);
+        }
+
+        if !properties.custom_properties.is_empty() {
+            println!("  Custom Properties:");
+            for (key, value) in &properties.custom_properties {
+                if key != "version" && key != "author" && key != "prefix" {
+                    println!("    {}: {}", key, value);
+                }
+            }
+        }
     }
     
-    if let Some(author) = &properties.author {
-        println!("  Author: {}", author);
-    }
+    records.finish()
+}
+
+/// Validate PBO files
+pub async fn validate_pbo(
+    api: &dyn PboApiOps,
+    pbo_paths: &[PathBuf],
+    verbose: bool,
+    deep: bool,
+    format: OutputFormat,
+) -> PboOperationResult<()> {
+    let mut records = RecordWriter::new(format, pbo_paths.len());
+
+    for pbo_path in pbo_paths {
+        let result = if deep {
+            api.validate_pbo_deep(pbo_path).await
+        } else {
+            api.validate_pbo(pbo_path).await
+        };
+        if format != OutputFormat::Text {
+            records.write(pbo_path, result)?;
+            continue;
+        }
+
+        let validation = match result {
+            Ok(validation) => validation,
+            Err(e) => {
+                records.text_failure(pbo_path, e);
+                continue;
+            }
+        };
+
+        if validation.is_valid {
+            println!("✓ PBO file is valid: {}", pbo_path.display());
+        } else {
+            println!("✗ PBO file has issues: {}", pbo_path.display());
+        }
+
+        if verbose || !validation.is_valid {
+            println!("Validation Results:");
+            println!("  Files sorted: {}", if validation.files_sorted { "✓" } else { "✗" });
     
-    if let Some(prefix) = &properties.prefix {
-        println!("  Prefix: {}", prefix);
-    }
+            if let Some(checksum_valid) = validation.checksum_valid {
+                println!("  Checksum valid: {}", if checksum_valid { "✓" } else { "✗" });
+            }
     
-    if let Some(checksum) = &properties.checksum {
-        println!("  Checksum: {}", checksum);
-    }
+            if !validation.errors.is_empty() {
+                println!("  Errors ({}):", validation.errors.len());
+                for error in &validation.errors {
+                    println!("    ✗ {}", error.message);
+                    if let Some(file_path) = &error.file_path {
+                        println!("      File: {}", file_path);
+                    }
+                }
+            }
     
-    if !properties.custom_properties.is_empty() {
-        println!("  Custom Properties:");
-        for (key, value) in &properties.custom_properties {
-            if key != "version" && key != "author" && key != "prefix" {
-                println!("    {}: {}", key, value);
+            if !validation.warnings.is_empty() {
+                println!("  Warnings ({}):", validation.warnings.len());
+                for warning in &validation.warnings {
+                    println!("    ⚠ {}", warning.message);
+                    if let Some(file_path) = &warning.file_path {
+                        println!("      File: {}", file_path);
+                    }
+                }
             }
         }
     }
     
+    records.finish()
+}
+
+/// Pack a directory into a PBO file
+pub async fn pack_directory(
+    api: &dyn PboApiOps,
+    source_dir: &PathBuf,
+    output_path: &PathBuf,
+    options: &PboPackOptions,
+    verbose: bool,
+) -> PboOperationResult<()> {
+    let summary = api.pack(source_dir, output_path, options).await?;
+
+    if verbose {
+        for file in &summary.files {
+            println!("  {}", file);
+        }
+    }
+
+    println!("✓ Packed {} files ({} bytes) into {}",
+             summary.files.len(),
+             summary.total_size,
+             summary.output_path.display());
+    if summary.excluded_count > 0 {
+        println!("  Excluded: {} files", summary.excluded_count);
+    }
+    println!("  Checksum: {}", summary.checksum);
+
     Ok(())
 }
 
-/// Validate a PBO file
-pub async fn validate_pbo(api: &dyn PboApiOps, pbo_path: &PathBuf, verbose: bool) -> PboOperationResult<()> {
-    let validation = api.validate_pbo(pbo_path).await?;
-    
-    if validation.is_valid {
-        println!("✓ PBO file is valid: {}", pbo_path.display());
-    } else {
-        println!("✗ PBO file has issues: {}", pbo_path.display());
+/// Verify a PBO's signature against a public key
+///
+/// Fails when the PBO is unsigned or any signature does not verify, so the
+/// command exits non-zero.
+pub async fn verify_signature(
+    api: &dyn PboApiOps,
+    pbo_path: &PathBuf,
+    key_path: &PathBuf,
+    signature: Option<PathBuf>,
+) -> PboOperationResult<()> {
+    let signature_paths = match signature {
+        Some(path) => vec![path],
+        None => signatures::find_signatures(pbo_path),
+    };
+
+    if signature_paths.is_empty() {
+        println!("✗ PBO is unsigned: {}", pbo_path.display());
+        return Err(PboOperationError::validation_failed(format!("PBO is unsigned: {}", pbo_path.display())));
     }
-    
-    if verbose || !validation.is_valid {
-        println!("Validation Results:");
-        println!("  Files sorted: {}", if validation.files_sorted { "✓" } else { "✗" });
-        
-        if let Some(checksum_valid) = validation.checksum_valid {
-            println!("  Checksum valid: {}", if checksum_valid { "✓" } else { "✗" });
-        }
-        
-        if !validation.errors.is_empty() {
-            println!("  Errors ({}):", validation.errors.len());
-            for error in &validation.errors {
-                println!("    ✗ {}", error.message);
-                if let Some(file_path) = &error.file_path {
-                    println!("      File: {}", file_path);
+
+    let mut invalid_count = 0;
+    for signature_path in &signature_paths {
+        let verification = api.verify_signature(pbo_path, signature_path, key_path).await?;
+        let mark = if verification.status.is_valid() {
+            "✓"
+        } else {
+            invalid_count += 1;
+            "✗"
+        };
+        println!("{} {} ({} {}): {}",
+                 mark,
+                 pbo_path.display(),
+                 verification.authority,
+                 verification.version,
+                 verification.status);
+        println!("  Signature: {}", signature_path.display());
+    }
+
+    if invalid_count > 0 {
+        return Err(PboOperationError::validation_failed(format!(
+            "{} of {} signatures failed verification for {}",
+            invalid_count,
+            signature_paths.len(),
+            pbo_path.display()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Compare two versions of a PBO
+pub async fn diff_pbos(
+    api: &dyn PboApiOps,
+    old_pbo: &PathBuf,
+    new_pbo: &PathBuf,
+    format: OutputFormat,
+) -> PboOperationResult<()> {
+    let diff = api.diff(old_pbo, new_pbo).await?;
+
+    let serialize_error = |e: serde_json::Error| PboOperationError::unknown(format!("serializing diff: {}", e));
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&diff).map_err(serialize_error)?);
+            return Ok(());
+        }
+        OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string(&diff).map_err(serialize_error)?);
+            return Ok(());
+        }
+        OutputFormat::Text => {}
+    }
+
+    if diff.is_empty() {
+        println!("✓ PBOs are identical");
+        return Ok(());
+    }
+
+    println!("Comparing {} -> {}", old_pbo.display(), new_pbo.display());
+    println!("  Added: {}, Removed: {}, Modified: {}",
+             diff.count(EntryChangeKind::Added),
+             diff.count(EntryChangeKind::Removed),
+             diff.count(EntryChangeKind::Modified));
+
+    for entry in &diff.entries {
+        match entry.kind {
+            EntryChangeKind::Added => println!("+ {} ({} bytes)", entry.path, entry.new_size.unwrap_or(0)),
+            EntryChangeKind::Removed => println!("- {} ({} bytes)", entry.path, entry.old_size.unwrap_or(0)),
+            EntryChangeKind::Modified => {
+                let mut details = Vec::new();
+                if entry.old_size != entry.new_size {
+                    details.push(format!("size {} -> {}", entry.old_size.unwrap_or(0), entry.new_size.unwrap_or(0)));
+                }
+                if entry.old_timestamp != entry.new_timestamp {
+                    details.push("timestamp".to_string());
                 }
+                if entry.content_changed() {
+                    details.push("content".to_string());
+                }
+                println!("~ {} ({})", entry.path, details.join(", "));
             }
         }
-        
-        if !validation.warnings.is_empty() {
-            println!("  Warnings ({}):", validation.warnings.len());
-            for warning in &validation.warnings {
-                println!("    ⚠ {}", warning.message);
-                if let Some(file_path) = &warning.file_path {
-                    println!("      File: {}", file_path);
+    }
+
+    for config in &diff.configs {
+        println!();
+        println!("Classes in {}:", config.path);
+        if let Some(error) = &config.error {
+            println!("  ⚠ {}", error);
+            continue;
+        }
+        if config.changes.is_empty() {
+            println!("  (no class changes)");
+        }
+        for change in &config.changes {
+            match &change.kind {
+                ClassChangeKind::Added { parent } => match parent {
+                    Some(parent) => println!("+ class {}: {}", change.class_path, parent),
+                    None => println!("+ class {}", change.class_path),
+                },
+                ClassChangeKind::Removed { .. } => println!("- class {}", change.class_path),
+                ClassChangeKind::ParentChanged { old, new } => println!("~ class {}: parent {} -> {}",
+                    change.class_path,
+                    old.as_deref().unwrap_or("(none)"),
+                    new.as_deref().unwrap_or("(none)")),
+                ClassChangeKind::PropertyAdded { name, value } => {
+                    println!("+ {}.{} = {}", change.class_path, name, value)
+                }
+                ClassChangeKind::PropertyRemoved { name, value } => {
+                    println!("- {}.{} = {}", change.class_path, name, value)
+                }
+                ClassChangeKind::PropertyChanged { name, old, new } => {
+                    println!("~ {}.{}: {} -> {}", change.class_path, name, old, new)
                 }
             }
         }
     }
-    
+
+    Ok(())
+}
+
+/// Search entry contents of PBOs and print matches as `pbo:entry:line: text`
+pub async fn grep(
+    api: &dyn PboApiOps,
+    pattern: &str,
+    paths: &[PathBuf],
+    glob: Option<String>,
+    ignore_case: bool,
+) -> PboOperationResult<()> {
+    let mut options = PboGrepOptions::new(pattern).ignore_case(ignore_case);
+    if let Some(glob) = glob {
+        options = options.with_path_glob(glob);
+    }
+
+    api.grep(paths, &options, &mut |found| {
+        println!("{}:{}:{}: {}", found.pbo_path.display(), found.entry, found.line_number, found.line.trim_end());
+    }).await?;
+
     Ok(())
 }
+
+/// Recover the intact files from a damaged PBO and report what was lost
+pub async fn salvage(
+    api: &dyn PboApiOps,
+    pbo_path: &PathBuf,
+    output_dir: &PathBuf,
+    filter: Option<String>,
+) -> PboOperationResult<()> {
+    let filter = filter.unwrap_or_else(|| "*".to_string());
+    let report = api.salvage(pbo_path, output_dir, &filter).await?;
+
+    println!("Recovered {} files to {}", report.recovered.len(), output_dir.display());
+    if report.checksum_valid == Some(false) {
+        println!("⚠ Stored checksum does not match the PBO contents");
+    }
+    if let Some(error) = &report.header_error {
+        println!("✗ Header table is damaged: {}", error);
+    }
+    for lost in &report.lost {
+        println!("✗ {} ({})", lost.file_path, lost.reason);
+    }
+    if report.is_complete() {
+        println!("✓ All files recovered");
+    }
+
+    Ok(())
+}
+
+/// Structured output record for one PBO of a batch
+#[derive(Debug, Serialize)]
+struct PboRecord<'a, T: Serialize> {
+    pbo_path: &'a Path,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Writes per-PBO results in the requested output format
+///
+/// A failing PBO does not stop the batch. Its error is written in place of
+/// the result and the command fails once every PBO has been processed.
+struct RecordWriter {
+    format: OutputFormat,
+    batch_size: usize,
+    records: Vec<serde_json::Value>,
+    failures: Vec<PboOperationError>,
+}
+
+impl RecordWriter {
+    fn new(format: OutputFormat, batch_size: usize) -> Self {
+        Self { format, batch_size, records: Vec::new(), failures: Vec::new() }
+    }
+
+    /// Emit the result for one PBO as a JSON record
+    fn write<T: Serialize>(&mut self, pbo_path: &Path, result: PboOperationResult<T>) -> PboOperationResult<()> {
+        let record = match result {
+            Ok(value) => PboRecord { pbo_path, result: Some(value), error: None },
+            Err(e) => {
+                let record = PboRecord { pbo_path, result: None, error: Some(e.to_string()) };
+                self.failures.push(e);
+                record
+            }
+        };
+
+        let serialize_error = |e: serde_json::Error| PboOperationError::unknown(format!("serializing output: {}", e));
+        match self.format {
+            OutputFormat::Ndjson => println!("{}", serde_json::to_string(&record).map_err(serialize_error)?),
+            _ => self.records.push(serde_json::to_value(&record).map_err(serialize_error)?),
+        }
+        Ok(())
+    }
+
+    /// Report a failed PBO in text mode
+    fn text_failure(&mut self, pbo_path: &Path, error: PboOperationError) {
+        if self.batch_size > 1 {
+            eprintln!("✗ {}: {}", pbo_path.display(), error);
+        }
+        self.failures.push(error);
+    }
+
+    /// Flush buffered output and fail if any PBO failed
+    fn finish(mut self) -> PboOperationResult<()> {
+        if self.format == OutputFormat::Json {
+            let output = serde_json::to_string_pretty(&self.records)
+                .map_err(|e| PboOperationError::unknown(format!("serializing output: {}", e)))?;
+            println!("{}", output);
+        }
+
+        match self.failures.len() {
+            0 => Ok(()),
+            1 if self.batch_size == 1 => Err(self.failures.remove(0)),
+            failed => Err(PboOperationError::unknown(format!("{} of {} PBOs failed", failed, self.batch_size))),
+        }
+    }
+}
@@ -3,7 +3,7 @@ pub mod commands;
 
 use log::debug;
 use crate::core::api::{PboApi, PboApiOps};
-use crate::ops::PboOperationResult;
+use crate::ops::{PboOperationResult, PboPackOptions};
 use self::args::Commands;
 
 pub struct CliProcessor {
@@ -23,21 +23,42 @@ impl CliProcessor {
     pub async fn process_command(&self, command: Commands) -> PboOperationResult<()> {
         debug!("Processing command: {:?}", command);
         match command {
-            Commands::List { pbo_path, brief: _, verbose } => {
-                debug!("Listing contents of PBO: {}", pbo_path.display());
-                commands::list_contents(&self.api, &pbo_path, verbose).await
+            Commands::List { pbo_paths, brief: _, verbose, format } => {
+                debug!("Listing contents of {} PBO(s)", pbo_paths.len());
+                commands::list_contents(&self.api, &pbo_paths, verbose, format).await
             }
             Commands::Extract { pbo_path, output_dir, filter, keep_pbo_name: _, verbose, ignore_warnings: _ } => {
                 debug!("Extracting from PBO: {} to {}", pbo_path.display(), output_dir.display());
                 commands::extract_contents(&self.api, &pbo_path, &output_dir, filter, verbose).await
             }
-            Commands::Properties { pbo_path } => {
-                debug!("Getting properties for PBO: {}", pbo_path.display());
-                commands::show_properties(&self.api, &pbo_path).await
+            Commands::Properties { pbo_paths, format } => {
+                debug!("Getting properties for {} PBO(s)", pbo_paths.len());
+                commands::show_properties(&self.api, &pbo_paths, format).await
             }
-            Commands::Validate { pbo_path, verbose } => {
-                debug!("Validating PBO: {}", pbo_path.display());
-                commands::validate_pbo(&self.api, &pbo_path, verbose).await
+            Commands::Validate { pbo_paths, verbose, deep, format } => {
+                debug!("Validating {} PBO(s)", pbo_paths.len());
+                commands::validate_pbo(&self.api, &pbo_paths, verbose, deep, format).await
+            }
+            Commands::Pack { source_dir, output_path, prefix, product, version, exclude, verbose } => {
+                debug!("Packing {} into {}", source_dir.display(), output_path.display());
+                let options = PboPackOptions { prefix, product, version, exclude };
+                commands::pack_directory(&self.api, &source_dir, &output_path, &options, verbose).await
+            }
+            Commands::VerifySign { pbo_path, key_path, signature } => {
+                debug!("Verifying signature of PBO: {}", pbo_path.display());
+                commands::verify_signature(&self.api, &pbo_path, &key_path, signature).await
+            }
+            Commands::Diff { old_pbo, new_pbo, format } => {
+                debug!("Diffing {} against {}", old_pbo.display(), new_pbo.display());
+                commands::diff_pbos(&self.api, &old_pbo, &new_pbo, format).await
+            }
+            Commands::Grep { pattern, paths, glob, ignore_case } => {
+                debug!("Searching {} path(s) for '{}'", paths.len(), pattern);
+                commands::grep(&self.api, &pattern, &paths, glob, ignore_case).await
+            }
+            Commands::Salvage { pbo_path, output_dir, filter } => {
+                debug!("Salvaging {} to {}", pbo_path.display(), output_dir.display());
+                commands::salvage(&self.api, &pbo_path, &output_dir, filter).await
             }
         }
     }
@@ -46,6 +67,7 @@ impl CliProcessor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::args::OutputFormat;
     use std::path::PathBuf;
     use tempfile::tempdir;
     use crate::test_utils;
@@ -56,11 +78,45 @@ mod tests {
         let cli = CliProcessor::new(10);
         let test_pbo = test_utils::get_test_pbo_path();
         let result = cli.process_command(Commands::List { 
-            pbo_path: test_pbo,
+            pbo_paths: vec![test_pbo],
+            brief: false,
+            verbose: false,
+            format: OutputFormat::Text,
+        }).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_cli_structured_output_batch() {
+        test_utils::setup();
+        let cli = CliProcessor::new(10);
+        let data_dir = test_utils::get_test_data_dir();
+        let pbo_paths = vec![data_dir.join("mirrorform.pbo"), data_dir.join("headgear_pumpkin.pbo")];
+
+        let result = cli.process_command(Commands::List {
+            pbo_paths: pbo_paths.clone(),
             brief: false,
             verbose: false,
+            format: OutputFormat::Ndjson,
+        }).await;
+        assert!(result.is_ok());
+
+        let result = cli.process_command(Commands::Properties {
+            pbo_paths: pbo_paths.clone(),
+            format: OutputFormat::Json,
         }).await;
         assert!(result.is_ok());
+
+        // One bad path fails the batch after the other PBOs were reported
+        let mut with_missing = pbo_paths;
+        with_missing.push(PathBuf::from("nonexistent.pbo"));
+        let result = cli.process_command(Commands::Validate {
+            pbo_paths: with_missing,
+            verbose: false,
+            deep: true,
+            format: OutputFormat::Json,
+        }).await;
+        assert!(result.is_err());
     }
 
     #[tokio::test]
@@ -81,6 +137,105 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_cli_pack_command() {
+        test_utils::setup();
+        let cli = CliProcessor::new(10);
+        let source_dir = tempdir().unwrap();
+        std::fs::write(source_dir.path().join("config.cpp"), "class CfgPatches {};").unwrap();
+        let output_dir = tempdir().unwrap();
+        let output_path = output_dir.path().join("packed.pbo");
+
+        let result = cli.process_command(Commands::Pack {
+            source_dir: source_dir.path().to_path_buf(),
+            output_path: output_path.clone(),
+            prefix: Some("test\\packed".to_string()),
+            product: None,
+            version: None,
+            exclude: vec!["*.bak".to_string()],
+            verbose: false,
+        }).await;
+        assert!(result.is_ok());
+        assert!(output_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_cli_verify_sign_command() {
+        test_utils::setup();
+        let cli = CliProcessor::new(10);
+        let data_dir = test_utils::get_test_data_dir();
+
+        let result = cli.process_command(Commands::VerifySign {
+            pbo_path: data_dir.join("headgear_pumpkin.pbo"),
+            key_path: data_dir.join("tc_arma.bikey"),
+            signature: None,
+        }).await;
+        assert!(result.is_ok());
+
+        // A signature checked against another PBO does not verify
+        let result = cli.process_command(Commands::VerifySign {
+            pbo_path: data_dir.join("mirrorform.pbo"),
+            key_path: data_dir.join("tc_arma.bikey"),
+            signature: Some(data_dir.join("headgear_pumpkin.pbo.tc_arma.bisign")),
+        }).await;
+        assert!(result.is_err());
+
+        // mirrorform.pbo has no signature next to it
+        let result = cli.process_command(Commands::VerifySign {
+            pbo_path: data_dir.join("mirrorform.pbo"),
+            key_path: data_dir.join("tc_arma.bikey"),
+            signature: None,
+        }).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cli_grep_command() {
+        test_utils::setup();
+        let cli = CliProcessor::new(10);
+        let data_dir = test_utils::get_test_data_dir();
+
+        let result = cli.process_command(Commands::Grep {
+            pattern: "cfgpatches".to_string(),
+            paths: vec![data_dir.clone()],
+            glob: Some("*.bin,*.cpp".to_string()),
+            ignore_case: true,
+        }).await;
+        assert!(result.is_ok());
+
+        let result = cli.process_command(Commands::Grep {
+            pattern: "(unclosed".to_string(),
+            paths: vec![data_dir],
+            glob: None,
+            ignore_case: false,
+        }).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cli_salvage_command() {
+        test_utils::setup();
+        let cli = CliProcessor::new(10);
+        let temp_dir = tempdir().unwrap();
+        let test_pbo = test_utils::get_test_data_dir().join("mirrorform.pbo");
+
+        let result = cli.process_command(Commands::Salvage {
+            pbo_path: test_pbo.clone(),
+            output_dir: temp_dir.path().to_path_buf(),
+            filter: Some("*.bin".to_string()),
+        }).await;
+        assert!(result.is_ok());
+        assert!(temp_dir.path().join("config.bin").exists());
+        assert!(!temp_dir.path().join("logo.paa").exists());
+
+        let result = cli.process_command(Commands::Salvage {
+            pbo_path: test_pbo,
+            output_dir: temp_dir.path().to_path_buf(),
+            filter: Some(" ".to_string()),
+        }).await;
+        assert!(result.is_err());
+    }
+
     #[tokio::test]
     async fn test_cli_with_invalid_paths() {
         test_utils::setup();
@@ -88,9 +243,10 @@ mod tests {
         let invalid_pbo = PathBuf::from("nonexistent.pbo");
         
         let result = cli.process_command(Commands::List { 
-            pbo_path: invalid_pbo.clone(),
+            pbo_paths: vec![invalid_pbo.clone()],
             brief: false,
             verbose: false,
+            format: OutputFormat::Text,
         }).await;
         assert!(result.is_err());
 
@@ -1,13 +1,15 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use std::sync::Arc;
 use log::{debug, warn};
 use crate::ops::{
-    PboOperations, HemttPboOperations, PboFileInfo, PboProperties, PboValidation,
-    PboOperationResult, PboOperationError
+    PboOperations, HemttPboOperations, GrepMatch, PboDiff, PboFileInfo, PboGrepOptions, PboPackOptions,
+    PboPackSummary, PboProperties, PboSalvageReport, PboValidation, PboOperationResult, PboOperationError
 };
+use crate::ops::traits::PboOperationsExt;
+use crate::signatures::SignatureVerification;
 use super::config::PboConfig;
-use super::constants::DEFAULT_TIMEOUT;
+use super::constants::{COMMON_PBO_EXTENSIONS, DEFAULT_TIMEOUT};
 
 /// Core trait defining operations available for PBO files using modern HEMTT backend.
 /// 
@@ -56,6 +58,9 @@ pub trait PboApiOps {
     
     /// Validate a PBO file for integrity and correctness
     async fn validate_pbo(&self, pbo_path: &Path) -> PboOperationResult<PboValidation>;
+
+    /// Validate a PBO file and verify every entry's size against the data block
+    async fn validate_pbo_deep(&self, pbo_path: &Path) -> PboOperationResult<PboValidation>;
     
     /// Read a file from a PBO into memory
     async fn read_file(&self, pbo_path: &Path, file_path: &str) -> PboOperationResult<Vec<u8>>;
@@ -65,6 +70,28 @@ pub trait PboApiOps {
     
     /// Get information about a specific file in a PBO
     async fn get_file_info(&self, pbo_path: &Path, file_path: &str) -> PboOperationResult<Option<PboFileInfo>>;
+
+    /// Build a PBO from the contents of a directory
+    async fn pack(&self, source_dir: &Path, output_path: &Path, options: &PboPackOptions) -> PboOperationResult<PboPackSummary>;
+
+    /// Verify a PBO against a `.bisign` signature and `.bikey` key
+    async fn verify_signature(&self, pbo_path: &Path, signature_path: &Path, key_path: &Path) -> PboOperationResult<SignatureVerification>;
+
+    /// Compare two versions of a PBO
+    async fn diff(&self, old_pbo: &Path, new_pbo: &Path) -> PboOperationResult<PboDiff>;
+
+    /// Search entry contents of PBOs, walking directories for PBO files
+    ///
+    /// Matches are passed to `on_match` as they are found; the total count is returned.
+    async fn grep(
+        &self,
+        paths: &[PathBuf],
+        options: &PboGrepOptions,
+        on_match: &mut (dyn FnMut(GrepMatch) + Send),
+    ) -> PboOperationResult<usize>;
+
+    /// Extract the intact entries of a corrupt or truncated PBO
+    async fn salvage(&self, pbo_path: &Path, output_dir: &Path, filter: &str) -> PboOperationResult<PboSalvageReport>;
 }
 
 /// Main API for working with PBO files using native HEMTT backend.
@@ -227,6 +254,13 @@ impl PboApiOps for PboApi {
         self.with_timeout(self.pbo_ops.validate_pbo(pbo_path)).await
     }
 
+    async fn validate_pbo_deep(&self, pbo_path: &Path) -> PboOperationResult<PboValidation> {
+        debug!("Deep validating PBO: {}", pbo_path.display());
+        self.validate_pbo_path(pbo_path)?;
+        
+        self.with_timeout(self.pbo_ops.validate_pbo_deep(pbo_path)).await
+    }
+
     async fn read_file(&self, pbo_path: &Path, file_path: &str) -> PboOperationResult<Vec<u8>> {
         debug!("Reading file '{}' from {}", file_path, pbo_path.display());
         self.validate_pbo_path(pbo_path)?;
@@ -247,6 +281,77 @@ impl PboApiOps for PboApi {
         
         self.with_timeout(self.pbo_ops.get_file_info(pbo_path, file_path)).await
     }
+
+    async fn pack(&self, source_dir: &Path, output_path: &Path, options: &PboPackOptions) -> PboOperationResult<PboPackSummary> {
+        debug!("Packing {} into {}", source_dir.display(), output_path.display());
+        let has_pbo_extension = output_path.extension()
+            .map(|ext| COMMON_PBO_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()))
+            .unwrap_or(false);
+        if !has_pbo_extension {
+            return Err(PboOperationError::invalid_format(
+                format!("Output does not have a valid PBO extension: {}", output_path.display())
+            ));
+        }
+
+        self.with_timeout(self.pbo_ops.pack(source_dir, output_path, options)).await
+    }
+
+    async fn verify_signature(&self, pbo_path: &Path, signature_path: &Path, key_path: &Path) -> PboOperationResult<SignatureVerification> {
+        debug!("Verifying signature {} for {}", signature_path.display(), pbo_path.display());
+        self.validate_pbo_path(pbo_path)?;
+        
+        self.with_timeout(self.pbo_ops.verify_signature(pbo_path, signature_path, key_path)).await
+    }
+
+    async fn diff(&self, old_pbo: &Path, new_pbo: &Path) -> PboOperationResult<PboDiff> {
+        debug!("Diffing {} against {}", old_pbo.display(), new_pbo.display());
+        self.validate_pbo_path(old_pbo)?;
+        self.validate_pbo_path(new_pbo)?;
+        
+        self.with_timeout(self.pbo_ops.diff(old_pbo, new_pbo)).await
+    }
+
+    async fn grep(
+        &self,
+        paths: &[PathBuf],
+        options: &PboGrepOptions,
+        on_match: &mut (dyn FnMut(GrepMatch) + Send),
+    ) -> PboOperationResult<usize> {
+        debug!("Searching {} path(s) for '{}'", paths.len(), options.pattern);
+        crate::ops::grep::build_regex(options)?;
+        for path in paths {
+            if !path.exists() {
+                return Err(PboOperationError::file_not_found(path));
+            }
+            if !path.is_dir() {
+                self.validate_pbo_path(path)?;
+            }
+        }
+
+        let mut found = 0;
+        for pbo_path in crate::ops::grep::collect_pbos(paths) {
+            // A broken PBO in a directory tree should not stop the search
+            match self.with_timeout(self.pbo_ops.grep(&pbo_path, options, &mut *on_match)).await {
+                Ok(count) => found += count,
+                Err(e) => warn!("Failed to search {}: {}", pbo_path.display(), e),
+            }
+        }
+
+        Ok(found)
+    }
+
+    async fn salvage(&self, pbo_path: &Path, output_dir: &Path, filter: &str) -> PboOperationResult<PboSalvageReport> {
+        debug!("Salvaging files from {} to {} with filter '{}'",
+               pbo_path.display(), output_dir.display(), filter);
+        self.validate_pbo_path(pbo_path)?;
+        self.ensure_output_dir(output_dir)?;
+
+        if filter.trim().is_empty() {
+            return Err(PboOperationError::invalid_path("Filter cannot be empty"));
+        }
+
+        self.with_timeout(self.pbo_ops.salvage(pbo_path, filter, output_dir)).await
+    }
 }
 
 /// Builder for creating customized PboApi instances.
@@ -46,4 +46,12 @@ pub const DEFAULT_MAX_RETRIES: u32 = 3;
 pub const COMMON_PBO_EXTENSIONS: &[&str] = &["pbo", "xbo", "ifa"];
 
 /// Common binary file extensions that may need conversion
-pub const BINARY_EXTENSIONS: &[&str] = &["bin", "binpbo", "binconfig"];
\ No newline at end of file
+pub const BINARY_EXTENSIONS: &[&str] = &["bin", "binpbo", "binconfig"];
+
+/// Text file extensions that may be stored rapified and are decoded in place
+pub const RAPIFIED_TEXT_EXTENSIONS: &[&str] = &["sqm", "ext"];
+
+/// Asset extensions whose contents are never searched by grep
+pub const BINARY_ASSET_EXTENSIONS: &[&str] = &[
+    "paa", "pac", "p3d", "rtm", "wrp", "wss", "ogg", "wav", "lip", "jpg", "png", "tga", "fxy", "bisurf",
+];
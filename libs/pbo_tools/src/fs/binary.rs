@@ -3,6 +3,8 @@ use log::{debug, info};
 use crate::ops::{PboOperationResult, PboOperationError};
 use std::fs;
 use crate::core::config::PboConfig;
+use crate::core::constants::RAPIFIED_TEXT_EXTENSIONS;
+use crate::rapify::{derapify, is_rapified, rapify, RapConfig};
 
 pub fn convert_binary_file(input: &Path, output: &Path) -> PboOperationResult<()> {
     debug!("Converting binary file from {:?} to {:?}", input, output);
@@ -22,17 +24,70 @@ pub fn convert_binary_file(input: &Path, output: &Path) -> PboOperationResult<()
         return Err(err);
     }
 
-    debug!("Renaming file");
-    fs::rename(input, output).map_err(|e| {
-        let err = PboOperationError::io_error("renaming file", e);
-        debug!("Error during rename: {}", err);
-        err
+    let data = fs::read(input).map_err(|e| {
+        PboOperationError::io_error("reading binary file", e)
     })?;
 
+    if is_rapified(&data) {
+        debug!("Derapifying file");
+        let config = derapify(&data)?;
+        fs::write(output, config.to_text()).map_err(|e| {
+            PboOperationError::io_error("writing derapified file", e)
+        })?;
+        if input != output {
+            fs::remove_file(input).map_err(|e| {
+                PboOperationError::io_error("removing binary file", e)
+            })?;
+        }
+    } else {
+        debug!("Renaming file");
+        fs::rename(input, output).map_err(|e| {
+            let err = PboOperationError::io_error("renaming file", e);
+            debug!("Error during rename: {}", err);
+            err
+        })?;
+    }
+
     info!("Successfully converted {:?} to {:?}", input, output);
     Ok(())
 }
 
+/// Decode a rapified text file (such as a binarized `mission.sqm`) in place
+///
+/// Returns `true` if the file was rapified and has been rewritten as text.
+pub fn derapify_file(path: &Path) -> PboOperationResult<bool> {
+    let data = fs::read(path).map_err(|e| {
+        PboOperationError::io_error("reading file", e)
+    })?;
+
+    if !is_rapified(&data) {
+        return Ok(false);
+    }
+
+    debug!("Derapifying {:?} in place", path);
+    let config = derapify(&data)?;
+    fs::write(path, config.to_text()).map_err(|e| {
+        PboOperationError::io_error("writing derapified file", e)
+    })?;
+    Ok(true)
+}
+
+/// Write a config tree to disk as a rapified file (e.g. `config.bin`)
+pub fn write_rapified_file(config: &RapConfig, output: &Path) -> PboOperationResult<()> {
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            PboOperationError::io_error("creating parent directory", e)
+        })?;
+    }
+
+    fs::write(output, rapify(config)).map_err(|e| {
+        PboOperationError::io_error("writing rapified file", e)
+    })?;
+
+    info!("Wrote rapified config to {:?}", output);
+    Ok(())
+}
+
 pub fn process_binary_files(source_dir: &Path, config: &PboConfig) -> PboOperationResult<()> {
     if !source_dir.is_dir() {
         debug!("Source directory {:?} is not a directory", source_dir);
@@ -66,6 +121,8 @@ pub fn process_binary_files(source_dir: &Path, config: &PboConfig) -> PboOperati
                 
                 let new_path = path.with_file_name(format!("{}.{}", stem, ext));
                 convert_binary_file(&path, &new_path)?;
+            } else if is_rapifiable_text(&path) {
+                derapify_file(&path)?;
             } else {
                 debug!("No mapping found for {}, skipping", name);
             }
@@ -76,4 +133,11 @@ pub fn process_binary_files(source_dir: &Path, config: &PboConfig) -> PboOperati
     Ok(())
 }
 
+fn is_rapifiable_text(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| RAPIFIED_TEXT_EXTENSIONS.iter().any(|r| r.eq_ignore_ascii_case(e)))
+        .unwrap_or(false)
+}
+
 // Remove duplicated tests since they are covered in binary_handling.rs
@@ -2,6 +2,6 @@ mod binary;
 mod temp;
 mod traits;
 
-pub use binary::{convert_binary_file, process_binary_files};
+pub use binary::{convert_binary_file, derapify_file, process_binary_files, write_rapified_file};
 pub use temp::TempFileManager;
 pub use traits::FileOperation;
\ No newline at end of file
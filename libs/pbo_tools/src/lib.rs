@@ -2,6 +2,9 @@ pub mod cli;
 pub mod core;
 pub mod fs;
 pub mod ops;
+pub mod rapify;
+pub mod signatures;
+pub mod vfs;
 #[cfg(test)]
 pub mod test_utils;
 
@@ -13,7 +16,7 @@ pub use core::{
 };
 pub use ops::{
     PboOperations, PboFileInfo, PboProperties, PboValidation,
-    PboOperationError, PboOperationResult, HemttPboOperations,
+    PboOperationError, PboOperationResult, HemttPboOperations, is_safe_entry_path,
 };
 
 /// Version of the library
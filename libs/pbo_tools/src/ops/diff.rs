@@ -0,0 +1,409 @@
+//! Comparison of two PBOs, down to config classes
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::Path;
+use gamedata_scanner_models::{GameClass, PropertyValue};
+use log::{debug, warn};
+use parser_hpp::AdvancedProjectParser;
+use sha1::{Digest, Sha1};
+
+use super::error::{PboOperationError, PboOperationResult};
+use super::salvage::is_safe_entry_path;
+use super::traits::PboOperations;
+use super::types::{
+    ClassChange, ClassChangeKind, ConfigDiff, EntryChangeKind, EntryDiff, PboDiff, PboFileInfo,
+};
+use crate::rapify;
+
+/// File names that get a class-level diff
+const CONFIG_FILES: &[&str] = &["config.cpp", "config.bin"];
+
+/// Extensions copied next to a config so its `#include`s resolve
+const INCLUDE_EXTENSIONS: &[&str] = &["hpp", "h", "inc"];
+
+/// Compare the entries of two PBOs and diff the configs that changed
+pub(crate) async fn diff_pbos<O: PboOperations + ?Sized>(
+    ops: &O,
+    old_pbo: &Path,
+    new_pbo: &Path,
+) -> PboOperationResult<PboDiff> {
+    let old_files = index_entries(ops.list_contents(old_pbo).await?);
+    let new_files = index_entries(ops.list_contents(new_pbo).await?);
+
+    let mut entries = Vec::new();
+    let mut changed_configs = Vec::new();
+
+    for (key, old) in &old_files {
+        let Some(new) = new_files.get(key) else {
+            entries.push(EntryDiff {
+                path: old.file_path.clone(),
+                kind: EntryChangeKind::Removed,
+                old_size: Some(old.size),
+                new_size: None,
+                old_timestamp: Some(old.timestamp),
+                new_timestamp: None,
+                old_hash: Some(hash_entry(ops, old_pbo, &old.file_path).await?),
+                new_hash: None,
+            });
+            continue;
+        };
+
+        let old_hash = hash_entry(ops, old_pbo, &old.file_path).await?;
+        let new_hash = hash_entry(ops, new_pbo, &new.file_path).await?;
+        if old.size == new.size && old.timestamp == new.timestamp && old_hash == new_hash {
+            continue;
+        }
+
+        if old_hash != new_hash && is_config_file(&new.file_path) {
+            changed_configs.push((old.file_path.clone(), new.file_path.clone()));
+        }
+        entries.push(EntryDiff {
+            path: new.file_path.clone(),
+            kind: EntryChangeKind::Modified,
+            old_size: Some(old.size),
+            new_size: Some(new.size),
+            old_timestamp: Some(old.timestamp),
+            new_timestamp: Some(new.timestamp),
+            old_hash: Some(old_hash),
+            new_hash: Some(new_hash),
+        });
+    }
+
+    for (key, new) in &new_files {
+        if old_files.contains_key(key) {
+            continue;
+        }
+        entries.push(EntryDiff {
+            path: new.file_path.clone(),
+            kind: EntryChangeKind::Added,
+            old_size: None,
+            new_size: Some(new.size),
+            old_timestamp: None,
+            new_timestamp: Some(new.timestamp),
+            old_hash: None,
+            new_hash: Some(hash_entry(ops, new_pbo, &new.file_path).await?),
+        });
+    }
+    entries.sort_by(|a, b| a.path.to_lowercase().cmp(&b.path.to_lowercase()));
+
+    let mut configs = Vec::new();
+    for (old_config, new_config) in changed_configs {
+        debug!("Diffing config classes of {}", new_config);
+        let old_classes = load_config_classes(ops, old_pbo, &old_config, &old_files).await;
+        let new_classes = load_config_classes(ops, new_pbo, &new_config, &new_files).await;
+
+        let diff = match (old_classes, new_classes) {
+            (Ok(old_classes), Ok(new_classes)) => ConfigDiff {
+                path: new_config,
+                changes: diff_classes(&old_classes, &new_classes),
+                error: None,
+            },
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("Cannot diff classes of {}: {}", new_config, e);
+                ConfigDiff {
+                    path: new_config,
+                    changes: Vec::new(),
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+        configs.push(diff);
+    }
+
+    Ok(PboDiff {
+        old_path: old_pbo.to_path_buf(),
+        new_path: new_pbo.to_path_buf(),
+        entries,
+        configs,
+    })
+}
+
+/// Index entries by lowercase path, since entry lookups in PBOs ignore case
+fn index_entries(files: Vec<PboFileInfo>) -> BTreeMap<String, PboFileInfo> {
+    files.into_iter()
+        .map(|file| (file.file_path.to_lowercase(), file))
+        .collect()
+}
+
+async fn hash_entry<O: PboOperations + ?Sized>(
+    ops: &O,
+    pbo_path: &Path,
+    file_path: &str,
+) -> PboOperationResult<String> {
+    let mut reader = ops.get_file_reader(pbo_path, file_path).await?;
+    let mut hasher = Sha1::new();
+    let mut buffer = [0u8; 8192];
+    loop {
+        let read = reader.read(&mut buffer)
+            .map_err(|e| PboOperationError::io_error("hashing PBO entry", e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+fn is_config_file(file_path: &str) -> bool {
+    let file_name = file_path.rsplit('/').next().unwrap_or(file_path);
+    CONFIG_FILES.iter().any(|name| file_name.eq_ignore_ascii_case(name))
+}
+
+/// Parse a config from a PBO with the HEMTT based parser
+///
+/// The config and any headers it may include are written to a scratch
+/// directory first; rapified configs are decoded to text.
+async fn load_config_classes<O: PboOperations + ?Sized>(
+    ops: &O,
+    pbo_path: &Path,
+    config_path: &str,
+    files: &BTreeMap<String, PboFileInfo>,
+) -> PboOperationResult<Vec<GameClass>> {
+    let scratch = tempfile::tempdir()
+        .map_err(|e| PboOperationError::io_error("creating scratch directory", e))?;
+
+    for file in files.values() {
+        let is_header = file.extension()
+            .map(|ext| INCLUDE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+            .unwrap_or(false);
+        if is_header {
+            if !is_safe_entry_path(&file.file_path) {
+                warn!("Skipping header with unsafe path '{}'", file.file_path);
+                continue;
+            }
+            let data = ops.read_file(pbo_path, &file.file_path).await?;
+            write_scratch_file(scratch.path(), &file.file_path, &data)?;
+        }
+    }
+
+    let data = ops.read_file(pbo_path, config_path).await?;
+    let text = if rapify::is_rapified(&data) {
+        rapify::derapify(&data)?.to_text().into_bytes()
+    } else {
+        data
+    };
+    // Rapified configs are stored as config.bin but parsed as text
+    let relative = config_path.rsplit_once('.')
+        .map(|(stem, _)| format!("{}.cpp", stem))
+        .unwrap_or_else(|| config_path.to_string());
+    write_scratch_file(scratch.path(), &relative, &text)?;
+
+    let parser = AdvancedProjectParser::new(scratch.path(), None)
+        .map_err(|e| PboOperationError::validation_failed(format!("cannot set up config parser: {}", e)))?;
+    let (classes, warnings) = parser.parse_file(Path::new(&relative))
+        .map_err(|e| PboOperationError::validation_failed(format!("cannot parse {}: {}", config_path, e)))?;
+    for warning in warnings {
+        debug!("Parser warning in {}: {} {}", config_path, warning.code, warning.message);
+    }
+
+    Ok(classes)
+}
+
+fn write_scratch_file(root: &Path, file_path: &str, data: &[u8]) -> PboOperationResult<()> {
+    if !is_safe_entry_path(file_path) {
+        return Err(PboOperationError::invalid_path(file_path));
+    }
+    let path = root.join(file_path.replace('\\', "/"));
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| PboOperationError::io_error("creating scratch directory", e))?;
+    }
+    std::fs::write(&path, data)
+        .map_err(|e| PboOperationError::io_error("writing scratch file", e))
+}
+
+/// Flattened view of a class used for comparison
+struct FlatClass {
+    path: String,
+    parent: Option<String>,
+    properties: BTreeMap<String, (String, String)>,
+}
+
+/// Compare two parsed configs class by class
+pub(crate) fn diff_classes(old: &[GameClass], new: &[GameClass]) -> Vec<ClassChange> {
+    let old_flat = flatten(old);
+    let new_flat = flatten(new);
+    let mut changes = Vec::new();
+
+    for (key, old_class) in &old_flat {
+        let Some(new_class) = new_flat.get(key) else {
+            changes.push(ClassChange {
+                class_path: old_class.path.clone(),
+                kind: ClassChangeKind::Removed { parent: old_class.parent.clone() },
+            });
+            continue;
+        };
+
+        let same_parent = match (&old_class.parent, &new_class.parent) {
+            (Some(old), Some(new)) => old.eq_ignore_ascii_case(new),
+            (old, new) => old == new,
+        };
+        if !same_parent {
+            changes.push(ClassChange {
+                class_path: new_class.path.clone(),
+                kind: ClassChangeKind::ParentChanged {
+                    old: old_class.parent.clone(),
+                    new: new_class.parent.clone(),
+                },
+            });
+        }
+
+        for (prop_key, (name, old_value)) in &old_class.properties {
+            let kind = match new_class.properties.get(prop_key) {
+                None => ClassChangeKind::PropertyRemoved { name: name.clone(), value: old_value.clone() },
+                Some((new_name, new_value)) if new_value != old_value => ClassChangeKind::PropertyChanged {
+                    name: new_name.clone(),
+                    old: old_value.clone(),
+                    new: new_value.clone(),
+                },
+                Some(_) => continue,
+            };
+            changes.push(ClassChange { class_path: new_class.path.clone(), kind });
+        }
+        for (prop_key, (name, value)) in &new_class.properties {
+            if !old_class.properties.contains_key(prop_key) {
+                changes.push(ClassChange {
+                    class_path: new_class.path.clone(),
+                    kind: ClassChangeKind::PropertyAdded { name: name.clone(), value: value.clone() },
+                });
+            }
+        }
+    }
+
+    for (key, new_class) in &new_flat {
+        if !old_flat.contains_key(key) {
+            changes.push(ClassChange {
+                class_path: new_class.path.clone(),
+                kind: ClassChangeKind::Added { parent: new_class.parent.clone() },
+            });
+        }
+    }
+
+    changes.sort_by(|a, b| a.class_path.to_lowercase().cmp(&b.class_path.to_lowercase()));
+    changes
+}
+
+/// Flatten the class tree into full class paths keyed case-insensitively
+fn flatten(classes: &[GameClass]) -> HashMap<String, FlatClass> {
+    let mut flat = HashMap::new();
+    for class in classes.iter().filter(|c| c.container_class.is_none()) {
+        flatten_class(class, None, &mut flat);
+    }
+    flat
+}
+
+fn flatten_class(class: &GameClass, container: Option<&str>, flat: &mut HashMap<String, FlatClass>) {
+    if class.is_forward_declaration {
+        return;
+    }
+
+    let path = match container {
+        Some(container) => format!("{}/{}", container, class.name),
+        None => class.name.clone(),
+    };
+
+    let mut properties = BTreeMap::new();
+    for property in &class.properties {
+        match &property.value {
+            PropertyValue::Class(nested) => flatten_class(nested, Some(&path), flat),
+            value => {
+                properties.insert(
+                    property.name.to_lowercase(),
+                    (property.name.clone(), format_value(value)),
+                );
+            }
+        }
+    }
+
+    flat.insert(path.to_lowercase(), FlatClass {
+        path,
+        parent: class.parent.clone(),
+        properties,
+    });
+}
+
+fn format_value(value: &PropertyValue) -> String {
+    match value {
+        PropertyValue::String(s) => format!("\"{}\"", s),
+        PropertyValue::Number(n) => n.to_string(),
+        PropertyValue::Float(f) => f.to_string(),
+        PropertyValue::Array(items) => format!(
+            "{{{}}}",
+            items.iter().map(format_value).collect::<Vec<_>>().join(", ")
+        ),
+        PropertyValue::Append(appended) => format!("+= {}", format_value(appended)),
+        PropertyValue::Expression(expression) => expression.clone(),
+        PropertyValue::Delete => "delete".to_string(),
+        PropertyValue::Class(class) => format!("class {}", class.name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use gamedata_scanner_models::ClassProperty;
+
+    fn class(name: &str, parent: Option<&str>, properties: Vec<ClassProperty>) -> GameClass {
+        let mut class = GameClass::new(name.to_string(), parent.map(String::from), PathBuf::from("config.cpp"));
+        class.properties = properties;
+        class
+    }
+
+    fn property(name: &str, value: PropertyValue) -> ClassProperty {
+        ClassProperty { name: name.to_string(), value, location: None }
+    }
+
+    #[test]
+    fn test_diff_classes() {
+        let old = vec![class("CfgWeapons", None, vec![
+            property("Rifle", PropertyValue::Class(Box::new(class("Rifle", Some("RifleCore"), vec![
+                property("scope", PropertyValue::Number(1)),
+                property("displayName", PropertyValue::String("Rifle".to_string())),
+            ])))),
+            property("Old", PropertyValue::Class(Box::new(class("Old", None, vec![])))),
+        ])];
+        let new = vec![class("CfgWeapons", None, vec![
+            property("Rifle", PropertyValue::Class(Box::new(class("Rifle", Some("Rifle_Base_F"), vec![
+                property("scope", PropertyValue::Number(2)),
+                property("magazines", PropertyValue::Array(vec!["mag".into()])),
+            ])))),
+            property("New", PropertyValue::Class(Box::new(class("New", Some("Rifle"), vec![])))),
+        ])];
+
+        let changes = diff_classes(&old, &new);
+        let kinds: Vec<_> = changes.iter().map(|c| (c.class_path.as_str(), &c.kind)).collect();
+
+        assert!(kinds.contains(&("CfgWeapons/New", &ClassChangeKind::Added { parent: Some("Rifle".to_string()) })));
+        assert!(kinds.contains(&("CfgWeapons/Old", &ClassChangeKind::Removed { parent: None })));
+        assert!(kinds.contains(&("CfgWeapons/Rifle", &ClassChangeKind::ParentChanged {
+            old: Some("RifleCore".to_string()),
+            new: Some("Rifle_Base_F".to_string()),
+        })));
+        assert!(kinds.contains(&("CfgWeapons/Rifle", &ClassChangeKind::PropertyChanged {
+            name: "scope".to_string(),
+            old: "1".to_string(),
+            new: "2".to_string(),
+        })));
+        assert!(kinds.contains(&("CfgWeapons/Rifle", &ClassChangeKind::PropertyRemoved {
+            name: "displayName".to_string(),
+            value: "\"Rifle\"".to_string(),
+        })));
+        assert!(kinds.contains(&("CfgWeapons/Rifle", &ClassChangeKind::PropertyAdded {
+            name: "magazines".to_string(),
+            value: "{\"mag\"}".to_string(),
+        })));
+        assert_eq!(changes.len(), 6);
+    }
+
+    #[test]
+    fn test_identical_configs_have_no_changes() {
+        let config = vec![class("CfgPatches", None, vec![
+            property("addon", PropertyValue::Class(Box::new(class("addon", None, vec![
+                property("units", PropertyValue::Array(vec![])),
+            ])))),
+        ])];
+        assert!(diff_classes(&config, &config).is_empty());
+    }
+}
@@ -189,6 +189,25 @@ impl PboOperationError {
         }
     }
 
+    /// Get the error category, used to group failures for review
+    pub fn category(&self) -> &'static str {
+        match self {
+            Self::FileNotFound { .. } | Self::InvalidPath { .. } => "missing",
+            Self::InvalidFormat { .. }
+            | Self::ValidationFailed { .. }
+            | Self::ChecksumMismatch { .. }
+            | Self::HemttError(_) => "corrupt",
+            Self::ExtractionFailed { .. } | Self::FileNotFoundInPbo { .. } => "extraction",
+            Self::IoError { .. } => "io",
+            Self::PermissionDenied { .. } => "permission",
+            Self::UnsupportedOperation { .. } => "unsupported",
+            Self::ResourceExhausted { .. } => "resources",
+            Self::Timeout { .. } => "timeout",
+            Self::MultipleErrors { errors, .. } => errors.first().map_or("unknown", |e| e.category()),
+            Self::Unknown { .. } => "unknown",
+        }
+    }
+
     /// Check if the error is critical (requires immediate attention)
     pub fn is_critical(&self) -> bool {
         match self {
@@ -287,6 +306,18 @@ mod tests {
         assert!(!format_error.is_retryable());
     }
 
+    #[test]
+    fn test_error_category() {
+        assert_eq!(PboOperationError::timeout("extract").category(), "timeout");
+        assert_eq!(PboOperationError::invalid_format("bad header").category(), "corrupt");
+
+        let multiple = PboOperationError::multiple_errors(vec![
+            PboOperationError::permission_denied("write"),
+            PboOperationError::timeout("extract"),
+        ]);
+        assert_eq!(multiple.category(), "permission");
+    }
+
     #[test]
     fn test_error_is_critical() {
         let validation_error = PboOperationError::validation_failed("validation failed");
@@ -0,0 +1,163 @@
+//! Line search over PBO entries without extracting them
+
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+use std::path::{Path, PathBuf};
+use regex::bytes::{Regex, RegexBuilder};
+use walkdir::WalkDir;
+
+use super::error::{PboOperationError, PboOperationResult};
+use super::types::{GrepMatch, PboFileInfo, PboGrepOptions};
+use super::hemtt_backend::HemttPboOperations;
+use crate::core::constants::{BINARY_ASSET_EXTENSIONS, COMMON_PBO_EXTENSIONS};
+use crate::rapify;
+
+/// Bytes inspected when deciding whether an entry is binary
+const BINARY_SNIFF_LEN: usize = 8192;
+
+/// Compile the search pattern
+///
+/// Matching works on raw bytes with ASCII semantics for classes such as `\w`,
+/// since entries are not guaranteed to be valid UTF-8.
+pub(crate) fn build_regex(options: &PboGrepOptions) -> PboOperationResult<Regex> {
+    RegexBuilder::new(&options.pattern)
+        .unicode(false)
+        .case_insensitive(options.ignore_case)
+        .build()
+        .map_err(|e| PboOperationError::invalid_format(format!("invalid search pattern: {}", e)))
+}
+
+/// Whether an entry should be read at all
+pub(crate) fn should_search(file: &PboFileInfo, options: &PboGrepOptions) -> bool {
+    if let Some(glob) = &options.path_glob {
+        return HemttPboOperations::matches_pattern(&file.file_path, glob);
+    }
+    !file.extension()
+        .map(|ext| BINARY_ASSET_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Search the contents of a single entry line by line
+///
+/// Plain text entries are read one line at a time and each match is passed
+/// to `on_match` as it is found. Rapified entries are decoded first, so line
+/// numbers refer to the decoded text. Other binary entries are skipped.
+/// Returns the number of matches.
+pub(crate) fn search_entry(
+    regex: &Regex,
+    pbo_path: &Path,
+    entry: &str,
+    mut reader: impl Read,
+    on_match: &mut (dyn FnMut(GrepMatch) + Send),
+) -> io::Result<usize> {
+    let mut head = Vec::new();
+    reader.by_ref().take(BINARY_SNIFF_LEN as u64).read_to_end(&mut head)?;
+
+    if rapify::is_rapified(&head) {
+        let mut data = head;
+        reader.read_to_end(&mut data)?;
+        return match rapify::derapify(&data) {
+            Ok(config) => search_lines(regex, pbo_path, entry, config.to_text().as_bytes(), on_match),
+            Err(_) => Ok(0),
+        };
+    }
+
+    if head.contains(&0) {
+        return Ok(0);
+    }
+
+    search_lines(regex, pbo_path, entry, BufReader::new(Cursor::new(head).chain(reader)), on_match)
+}
+
+fn search_lines(
+    regex: &Regex,
+    pbo_path: &Path,
+    entry: &str,
+    mut reader: impl BufRead,
+    on_match: &mut (dyn FnMut(GrepMatch) + Send),
+) -> io::Result<usize> {
+    let mut line = Vec::new();
+    let mut line_number = 0;
+    let mut found = 0;
+
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        line_number += 1;
+
+        let text = line.strip_suffix(b"\n").unwrap_or(&line);
+        let text = text.strip_suffix(b"\r").unwrap_or(text);
+        if regex.is_match(text) {
+            found += 1;
+            on_match(GrepMatch {
+                pbo_path: pbo_path.to_path_buf(),
+                entry: entry.to_string(),
+                line_number,
+                line: String::from_utf8_lossy(text).into_owned(),
+            });
+        }
+    }
+
+    Ok(found)
+}
+
+/// Expand the given paths into PBO files, walking directories recursively
+pub(crate) fn collect_pbos(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut pbos = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            pbos.push(path.clone());
+            continue;
+        }
+
+        let mut found: Vec<PathBuf> = WalkDir::new(path)
+            .follow_links(true)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| entry.into_path())
+            .filter(|path| {
+                path.extension()
+                    .map(|ext| COMMON_PBO_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()))
+                    .unwrap_or(false)
+            })
+            .collect();
+        found.sort();
+        pbos.extend(found);
+    }
+    pbos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_entry() {
+        let options = PboGrepOptions::new(r"define\s+GVAR").ignore_case(true);
+        let regex = build_regex(&options).unwrap();
+        let data: &[u8] = b"// header\r\n#DEFINE  GVAR(x) x\r\nclass A {};\n";
+
+        let mut matches = Vec::new();
+        let found = search_entry(&regex, Path::new("addon.pbo"), "script_macros.hpp", data, &mut |m| matches.push(m)).unwrap();
+        assert_eq!(found, 1);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line_number, 2);
+        assert_eq!(matches[0].line, "#DEFINE  GVAR(x) x");
+    }
+
+    #[test]
+    fn test_binary_entries_are_skipped() {
+        let regex = build_regex(&PboGrepOptions::new("abc")).unwrap();
+        let data: &[u8] = b"abc\0def";
+        let mut matches = Vec::new();
+        assert_eq!(search_entry(&regex, Path::new("addon.pbo"), "data.bin", data, &mut |m| matches.push(m)).unwrap(), 0);
+        assert!(matches.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert!(build_regex(&PboGrepOptions::new("(unclosed")).is_err());
+    }
+}
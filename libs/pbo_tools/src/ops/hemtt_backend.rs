@@ -10,9 +10,11 @@ use log::{debug, trace, warn};
 
 use super::traits::PboOperations;
 use super::types::{
-    PboFileInfo, PboProperties, PboValidation, ValidationIssue, ValidationIssueType, ValidationSeverity
+    PboDiff, PboFileInfo, PboPackOptions, PboPackSummary, PboProperties, PboSalvageReport, PboValidation,
+    ValidationIssue, ValidationIssueType, ValidationSeverity
 };
 use super::error::{PboOperationResult, PboOperationError};
+use crate::signatures::{self, BiPublicKey, BiSignature, SignatureVerification};
 
 /// HEMTT-based PBO operations implementation
 /// 
@@ -50,7 +52,18 @@ impl HemttPboOperations {
     }
 
     /// Check if a file path matches a glob pattern
-    fn matches_pattern(file_path: &str, pattern: &str) -> bool {
+    ///
+    /// A comma separated list such as `"*.cpp,*.bin"` (as built by the
+    /// extractor from its extension list) matches if any entry matches.
+    pub(crate) fn matches_pattern(file_path: &str, pattern: &str) -> bool {
+        if pattern.contains(',') {
+            return pattern
+                .split(',')
+                .map(str::trim)
+                .filter(|p| !p.is_empty())
+                .any(|p| Self::matches_pattern(file_path, p));
+        }
+
         // Simple glob pattern matching
         // In a production implementation, you'd want to use a proper glob library
         if pattern == "*" || pattern == "**" {
@@ -59,11 +72,11 @@ impl HemttPboOperations {
 
         // Handle basic patterns
         if pattern.contains('*') {
-            let pattern_regex = pattern
-                .replace("**", ".*")
-                .replace("*", "[^/]*")
-                .replace("?", ".");
-            
+            let pattern_regex = regex::escape(pattern)
+                .replace(r"\*\*", ".*")
+                .replace(r"\*", "[^/]*")
+                .replace(r"\?", ".");
+
             if let Ok(regex) = regex::Regex::new(&format!("^{}$", pattern_regex)) {
                 return regex.is_match(file_path);
             }
@@ -405,29 +418,8 @@ impl PboOperations for HemttPboOperations {
             }
         }
 
-        // Validate checksum
-        match pbo.gen_checksum() {
-            Ok(calculated_checksum) => {
-                let stored_checksum = pbo.checksum();
-                if calculated_checksum.as_bytes() == stored_checksum.as_bytes() {
-                    validation.checksum_valid = Some(true);
-                } else {
-                    validation.checksum_valid = Some(false);
-                    validation.add_error(
-                        ValidationIssueType::ChecksumMismatch,
-                        "Stored checksum does not match calculated checksum".to_string(),
-                        None,
-                    );
-                }
-            }
-            Err(e) => {
-                validation.add_warning(
-                    ValidationIssueType::ChecksumMismatch,
-                    format!("Cannot calculate checksum: {}", e),
-                    None,
-                );
-            }
-        }
+        // Validate checksum by recomputing the trailing SHA-1
+        super::integrity::check_checksum(pbo_path, &mut validation);
 
         // Check for required prefix
         let properties = pbo.properties();
@@ -551,6 +543,72 @@ impl PboOperations for HemttPboOperations {
             Ok(None)
         }
     }
+
+    async fn validate_pbo_deep(&self, pbo_path: &Path) -> PboOperationResult<PboValidation> {
+        let mut validation = self.validate_pbo(pbo_path).await?;
+        if !pbo_path.exists() {
+            return Ok(validation);
+        }
+
+        debug!("Verifying entry sizes of PBO: {}", pbo_path.display());
+        super::integrity::check_entry_sizes(pbo_path, &mut validation);
+        Ok(validation)
+    }
+
+    async fn pack(
+        &self,
+        source_dir: &Path,
+        output_path: &Path,
+        options: &PboPackOptions,
+    ) -> PboOperationResult<PboPackSummary> {
+        debug!("Packing {} into {}", source_dir.display(), output_path.display());
+        super::pack::pack_directory(source_dir, output_path, options)
+    }
+
+    async fn verify_signature(
+        &self,
+        pbo_path: &Path,
+        signature_path: &Path,
+        key_path: &Path,
+    ) -> PboOperationResult<SignatureVerification> {
+        debug!("Verifying {} against {}", pbo_path.display(), signature_path.display());
+        if !pbo_path.exists() {
+            return Err(PboOperationError::file_not_found(pbo_path));
+        }
+
+        let key = BiPublicKey::from_file(key_path)?;
+        let signature = BiSignature::from_file(signature_path)?;
+        let status = signatures::verify_pbo(pbo_path, &signature, &key)?;
+
+        Ok(SignatureVerification {
+            pbo_path: pbo_path.to_path_buf(),
+            signature_path: signature_path.to_path_buf(),
+            authority: signature.authority,
+            version: signature.version,
+            status,
+        })
+    }
+
+    async fn diff(&self, old_pbo: &Path, new_pbo: &Path) -> PboOperationResult<PboDiff> {
+        debug!("Diffing {} against {}", old_pbo.display(), new_pbo.display());
+        super::diff::diff_pbos(self, old_pbo, new_pbo).await
+    }
+
+    async fn salvage(
+        &self,
+        pbo_path: &Path,
+        filter: &str,
+        output_dir: &Path,
+    ) -> PboOperationResult<PboSalvageReport> {
+        debug!("Salvaging files from {} to {} with filter '{}'",
+               pbo_path.display(), output_dir.display(), filter);
+
+        if !pbo_path.exists() {
+            return Err(PboOperationError::file_not_found(pbo_path));
+        }
+
+        super::salvage::salvage_pbo(pbo_path, filter, output_dir)
+    }
 }
 
 #[cfg(test)]
@@ -571,5 +629,23 @@ mod tests {
         assert!(format!("{:?}", ops).contains("HemttPboOperations"));
     }
 
+    #[test]
+    fn test_matches_pattern() {
+        assert!(HemttPboOperations::matches_pattern("config.bin", "*.bin"));
+        assert!(!HemttPboOperations::matches_pattern("configxbin", "*.bin"));
+        assert!(!HemttPboOperations::matches_pattern("data/config.bin", "*.bin"));
+        assert!(HemttPboOperations::matches_pattern("data/sub/a.paa", "data/**"));
+        assert!(!HemttPboOperations::matches_pattern("other/a.paa", "data/*"));
+    }
+
+    #[test]
+    fn test_matches_pattern_list() {
+        assert!(HemttPboOperations::matches_pattern("config.bin", "*.cpp,*.bin"));
+        assert!(HemttPboOperations::matches_pattern("config.cpp", "*.cpp, *.bin"));
+        assert!(HemttPboOperations::matches_pattern("config.bin", "*.cpp,,*.bin"));
+        assert!(!HemttPboOperations::matches_pattern("model.p3d", "*.cpp,*.bin"));
+        assert!(!HemttPboOperations::matches_pattern("Config.CPP", "*.cpp,*.bin"));
+    }
+
     // Note: Actual functionality tests will be added when the implementation is complete
 }
\ No newline at end of file
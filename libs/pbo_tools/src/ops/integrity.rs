@@ -0,0 +1,374 @@
+//! Native integrity checks on the raw PBO layout
+//!
+//! These checks read the file directly rather than going through HEMTT so
+//! that truncated or inconsistent PBOs can still be inspected.
+
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use sha1::{Digest, Sha1};
+
+use super::error::{PboOperationError, PboOperationResult};
+use super::types::{PboValidation, ValidationIssueType};
+
+/// Mime value of the header extension entry ("Vers" stored little endian)
+const MIME_VERSION: u32 = 0x5665_7273;
+
+/// Mime value of a compressed entry ("Cprs" stored little endian)
+pub(crate) const MIME_COMPRESSED: u32 = 0x4370_7273;
+
+/// Size of the trailing checksum block (a zero byte followed by a SHA-1 digest)
+const CHECKSUM_TRAILER_LEN: u64 = 21;
+
+/// Declared layout of a single entry
+#[derive(Debug, Clone)]
+pub(crate) struct EntryLayout {
+    pub name: String,
+    pub mime: u32,
+    pub original_size: u32,
+    pub data_size: u32,
+    pub data_offset: u64,
+}
+
+/// Declared layout of a PBO as read from its header
+#[derive(Debug, Clone)]
+pub(crate) struct PboLayout {
+    /// Header extensions (`prefix`, `product`, ...) in file order
+    pub extensions: Vec<(String, String)>,
+    pub entries: Vec<EntryLayout>,
+    /// Offset of the first data byte
+    pub data_start: u64,
+    /// Offset just past the last declared data byte
+    pub data_end: u64,
+    /// Total length of the file
+    pub file_len: u64,
+}
+
+impl PboLayout {
+    /// Whether the file has room for a checksum trailer after the data block
+    pub fn has_checksum_trailer(&self) -> bool {
+        self.file_len == self.data_end + CHECKSUM_TRAILER_LEN
+    }
+
+    /// Value of a header extension, matched case-insensitively
+    pub fn extension(&self, key: &str) -> Option<&str> {
+        self.extensions.iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Read the header table of a PBO without touching the data block
+pub(crate) fn read_layout(pbo_path: &Path) -> PboOperationResult<PboLayout> {
+    match read_layout_partial(pbo_path)? {
+        (layout, None) => Ok(layout),
+        (_, Some(e)) => Err(e),
+    }
+}
+
+/// Read as much of the header table as possible
+///
+/// Returns the layout of the entries read before the table became unreadable,
+/// together with the error that stopped it. When the table is incomplete the
+/// start of the data block, and with it every data offset, is unknown.
+///
+/// # Errors
+///
+/// Returns an error only if the file cannot be opened.
+pub(crate) fn read_layout_partial(pbo_path: &Path) -> PboOperationResult<(PboLayout, Option<PboOperationError>)> {
+    let file = File::open(pbo_path)
+        .map_err(|e| PboOperationError::io_error("opening PBO file", e))?;
+    let file_len = file.metadata()
+        .map_err(|e| PboOperationError::io_error("reading PBO metadata", e))?
+        .len();
+    let mut reader = BufReader::new(file);
+    let mut position = 0u64;
+    let mut extensions = Vec::new();
+    let mut entries = Vec::new();
+
+    let header_error = read_header_table(&mut reader, &mut position, &mut extensions, &mut entries).err();
+
+    let data_start = position;
+    let mut offset = data_start;
+    for entry in &mut entries {
+        entry.data_offset = offset;
+        offset += entry.data_size as u64;
+    }
+
+    let layout = PboLayout {
+        extensions,
+        entries,
+        data_start,
+        data_end: offset,
+        file_len,
+    };
+    Ok((layout, header_error))
+}
+
+fn read_header_table<R: Read>(
+    reader: &mut R,
+    position: &mut u64,
+    extensions: &mut Vec<(String, String)>,
+    entries: &mut Vec<EntryLayout>,
+) -> PboOperationResult<()> {
+    let mut first = true;
+
+    loop {
+        let name = read_cstring(reader, position)?;
+        let mut fields = [0u32; 5];
+        for field in fields.iter_mut() {
+            *field = read_u32(reader, position)?;
+        }
+        let [mime, original_size, _reserved, _timestamp, data_size] = fields;
+
+        if first && name.is_empty() && mime == MIME_VERSION {
+            // Header extensions, terminated by an empty key
+            loop {
+                let key = read_cstring(reader, position)?;
+                if key.is_empty() {
+                    break;
+                }
+                let value = read_cstring(reader, position)?;
+                extensions.push((key, value));
+            }
+            first = false;
+            continue;
+        }
+        first = false;
+
+        if name.is_empty() {
+            return Ok(());
+        }
+
+        entries.push(EntryLayout {
+            name,
+            mime,
+            original_size,
+            data_size,
+            data_offset: 0,
+        });
+    }
+}
+
+/// Recompute the trailing SHA-1 of a PBO and compare it with the stored one
+///
+/// Returns the hex digest when the checksum matches.
+///
+/// # Errors
+///
+/// Returns `ChecksumMismatch` if the stored and calculated digests differ,
+/// and `InvalidFormat` if the file has no checksum trailer.
+pub(crate) fn verify_checksum(pbo_path: &Path) -> PboOperationResult<String> {
+    let mut file = File::open(pbo_path)
+        .map_err(|e| PboOperationError::io_error("opening PBO file", e))?;
+    let file_len = file.metadata()
+        .map_err(|e| PboOperationError::io_error("reading PBO metadata", e))?
+        .len();
+
+    if file_len < CHECKSUM_TRAILER_LEN {
+        return Err(PboOperationError::invalid_format("file is too small to hold a checksum"));
+    }
+
+    let hashed_len = file_len - CHECKSUM_TRAILER_LEN;
+    let mut trailer = [0u8; CHECKSUM_TRAILER_LEN as usize];
+    file.seek(SeekFrom::Start(hashed_len))
+        .and_then(|_| file.read_exact(&mut trailer))
+        .map_err(|e| PboOperationError::io_error("reading PBO checksum", e))?;
+    if trailer[0] != 0 {
+        return Err(PboOperationError::invalid_format("PBO has no checksum trailer"));
+    }
+
+    file.seek(SeekFrom::Start(0))
+        .map_err(|e| PboOperationError::io_error("seeking PBO file", e))?;
+    let mut hasher = Sha1::new();
+    let mut limited = BufReader::new(file).take(hashed_len);
+    std::io::copy(&mut limited, &mut hasher)
+        .map_err(|e| PboOperationError::io_error("hashing PBO file", e))?;
+    let calculated = hasher.finalize();
+
+    if calculated.as_slice() != &trailer[1..] {
+        return Err(PboOperationError::checksum_mismatch(pbo_path));
+    }
+
+    Ok(calculated.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Digests taken in a single read of a PBO
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PboDigest {
+    /// SHA-1 of the whole file, identifying its contents
+    pub content_hash: String,
+    /// Whether the stored checksum matches the contents, `None` without a checksum trailer
+    pub checksum_valid: Option<bool>,
+    /// The stored checksum, kept only when it matches
+    pub(crate) checksum: Option<[u8; 20]>,
+}
+
+/// Hash a PBO and check its stored SHA-1 checksum in a single read
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read.
+pub fn digest_pbo(pbo_path: &Path) -> PboOperationResult<PboDigest> {
+    let mut file = File::open(pbo_path)
+        .map_err(|e| PboOperationError::io_error("opening PBO file", e))?;
+    let file_len = file.metadata()
+        .map_err(|e| PboOperationError::io_error("reading PBO metadata", e))?
+        .len();
+    let hashed_len = file_len.saturating_sub(CHECKSUM_TRAILER_LEN);
+
+    let mut content = Sha1::new();
+    let mut checksum = Sha1::new();
+    let mut trailer = Vec::new();
+    let mut position = 0u64;
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)
+            .map_err(|e| PboOperationError::io_error("hashing PBO file", e))?;
+        if read == 0 {
+            break;
+        }
+        let chunk = &buffer[..read];
+        content.update(chunk);
+
+        // Everything before the trailer is covered by the stored checksum
+        let covered = hashed_len.saturating_sub(position).min(read as u64) as usize;
+        checksum.update(&chunk[..covered]);
+        trailer.extend_from_slice(&chunk[covered..]);
+        position += read as u64;
+    }
+
+    let calculated: [u8; 20] = checksum.finalize().into();
+    let checksum_valid = match trailer.split_first() {
+        Some((0, stored)) if trailer.len() == CHECKSUM_TRAILER_LEN as usize => Some(stored == calculated),
+        _ => None,
+    };
+
+    Ok(PboDigest {
+        content_hash: content.finalize().iter().map(|b| format!("{:02x}", b)).collect(),
+        checksum_valid,
+        checksum: (checksum_valid == Some(true)).then_some(calculated),
+    })
+}
+
+/// Record the result of the checksum pass on a validation result
+pub(crate) fn check_checksum(pbo_path: &Path, validation: &mut PboValidation) {
+    match verify_checksum(pbo_path) {
+        Ok(_) => {
+            validation.checksum_valid = Some(true);
+        }
+        Err(PboOperationError::ChecksumMismatch { .. }) => {
+            validation.checksum_valid = Some(false);
+            validation.add_error(
+                ValidationIssueType::ChecksumMismatch,
+                "Stored SHA-1 checksum does not match the PBO contents".to_string(),
+                None,
+            );
+        }
+        Err(e) => {
+            validation.add_warning(
+                ValidationIssueType::ChecksumMismatch,
+                format!("Cannot verify checksum: {}", e),
+                None,
+            );
+        }
+    }
+}
+
+/// Verify every entry's declared size against the data block
+pub(crate) fn check_entry_sizes(pbo_path: &Path, validation: &mut PboValidation) {
+    let layout = match read_layout(pbo_path) {
+        Ok(layout) => layout,
+        Err(e) => {
+            validation.add_critical_error(
+                ValidationIssueType::CorruptedHeader,
+                format!("Cannot read PBO header table: {}", e),
+                None,
+            );
+            return;
+        }
+    };
+
+    // Without a trailer the data block runs to the end of the file
+    let data_limit = if layout.file_len >= layout.data_end + CHECKSUM_TRAILER_LEN {
+        layout.file_len - CHECKSUM_TRAILER_LEN
+    } else {
+        layout.file_len
+    };
+
+    for entry in &layout.entries {
+        let entry_end = entry.data_offset + entry.data_size as u64;
+        if entry_end > data_limit {
+            validation.add_error(
+                ValidationIssueType::FileSizeMismatch,
+                format!(
+                    "Entry declares {} bytes at offset {} but the data block ends at {}",
+                    entry.data_size, entry.data_offset, data_limit
+                ),
+                Some(entry.name.replace('\\', "/")),
+            );
+        }
+        if entry.mime == MIME_COMPRESSED && entry.original_size < entry.data_size {
+            validation.add_warning(
+                ValidationIssueType::FileSizeMismatch,
+                format!(
+                    "Compressed entry is larger than its declared original size ({} > {})",
+                    entry.data_size, entry.original_size
+                ),
+                Some(entry.name.replace('\\', "/")),
+            );
+        }
+    }
+
+    if layout.data_end < data_limit {
+        validation.add_error(
+            ValidationIssueType::FileSizeMismatch,
+            format!(
+                "{} bytes of data are not covered by any entry",
+                data_limit - layout.data_end
+            ),
+            None,
+        );
+    } else if layout.data_end > layout.file_len {
+        validation.add_critical_error(
+            ValidationIssueType::Corruption,
+            format!(
+                "PBO is truncated: entries declare {} bytes of data but only {} are present",
+                layout.data_end - layout.data_start,
+                layout.file_len.saturating_sub(layout.data_start)
+            ),
+            None,
+        );
+    }
+}
+
+fn read_u32<R: Read>(reader: &mut R, position: &mut u64) -> PboOperationResult<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf).map_err(|e| {
+        PboOperationError::invalid_format(format!("header table ends unexpectedly at offset {}: {}", position, e))
+    })?;
+    *position += 4;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_cstring<R: Read>(reader: &mut R, position: &mut u64) -> PboOperationResult<String> {
+    let mut bytes = Vec::new();
+    let mut byte = [0u8; 1];
+    loop {
+        reader.read_exact(&mut byte).map_err(|e| {
+            PboOperationError::invalid_format(format!("header table ends unexpectedly at offset {}: {}", position, e))
+        })?;
+        *position += 1;
+        if byte[0] == 0 {
+            break;
+        }
+        bytes.push(byte[0]);
+        if bytes.len() > 1024 {
+            return Err(PboOperationError::invalid_format(format!(
+                "unterminated header string at offset {}",
+                position
+            )));
+        }
+    }
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
@@ -8,8 +8,19 @@ pub mod types;
 pub mod error;
 pub mod traits;
 pub mod hemtt_backend;
+mod diff;
+pub(crate) mod grep;
+pub(crate) mod integrity;
+mod pack;
+mod salvage;
 
-pub use types::{PboFileInfo, PboProperties, PboValidation};
+pub use types::{
+    ClassChange, ClassChangeKind, ConfigDiff, EntryChangeKind, EntryDiff, GrepMatch, LostEntry,
+    PboDiff, PboFileInfo, PboGrepOptions, PboPackOptions, PboPackSummary, PboProperties,
+    PboSalvageReport, PboValidation,
+};
 pub use error::{PboOperationError, PboOperationResult};
 pub use traits::PboOperations;
+pub use salvage::is_safe_entry_path;
+pub use integrity::{digest_pbo, PboDigest};
 pub use hemtt_backend::HemttPboOperations;
\ No newline at end of file
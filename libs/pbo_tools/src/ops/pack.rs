@@ -0,0 +1,227 @@
+//! Native PBO writer backing the pack operation
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use log::{debug, trace};
+use sha1::{Digest, Sha1};
+use tempfile::NamedTempFile;
+use walkdir::WalkDir;
+
+use super::error::{PboOperationError, PboOperationResult};
+use super::hemtt_backend::HemttPboOperations;
+use super::types::{PboPackOptions, PboPackSummary};
+
+/// Name of the file used by most tools to carry the PBO prefix
+const PREFIX_FILE: &str = "$PBOPREFIX$";
+
+/// Mime value of the header extension entry ("Vers" stored little endian)
+const MIME_VERSION: &[u8; 4] = b"sreV";
+
+/// Writes `data` through to the inner writer while hashing it
+struct HashingWriter<W: Write> {
+    inner: W,
+    hasher: Sha1,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+struct PackEntry {
+    source: PathBuf,
+    pbo_path: String,
+    size: u32,
+}
+
+/// Build a PBO from the contents of `source_dir`
+///
+/// Files are ordered case-insensitively by path and written with zero
+/// timestamps so packing the same directory twice yields identical bytes.
+pub(crate) fn pack_directory(
+    source_dir: &Path,
+    output_path: &Path,
+    options: &PboPackOptions,
+) -> PboOperationResult<PboPackSummary> {
+    if !source_dir.is_dir() {
+        return Err(PboOperationError::file_not_found(source_dir));
+    }
+
+    let prefix = match &options.prefix {
+        Some(prefix) => Some(prefix.clone()),
+        None => read_prefix_file(source_dir)?,
+    };
+
+    let output_abs = output_path.canonicalize().ok();
+    let mut entries = Vec::new();
+    let mut excluded_count = 0;
+
+    for entry in WalkDir::new(source_dir).follow_links(true) {
+        let entry = entry.map_err(|e| {
+            PboOperationError::io_error("walking source directory", e.into())
+        })?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let relative = entry.path().strip_prefix(source_dir)
+            .map_err(|_| PboOperationError::invalid_path(entry.path().display().to_string()))?;
+        let pbo_path = relative.to_string_lossy().replace('\\', "/");
+
+        if pbo_path.eq_ignore_ascii_case(PREFIX_FILE) {
+            continue;
+        }
+        // Never pack the output into itself when writing inside the source directory
+        if output_abs.is_some() && entry.path().canonicalize().ok() == output_abs {
+            continue;
+        }
+        if options.exclude.iter().any(|pattern| HemttPboOperations::matches_pattern(&pbo_path, pattern)) {
+            trace!("Excluding {}", pbo_path);
+            excluded_count += 1;
+            continue;
+        }
+
+        let len = entry.metadata()
+            .map_err(|e| PboOperationError::io_error("reading file metadata", e.into()))?
+            .len();
+        let size = u32::try_from(len).map_err(|_| {
+            PboOperationError::invalid_format(format!("{} is too large for a PBO ({} bytes)", pbo_path, len))
+        })?;
+
+        entries.push(PackEntry {
+            source: entry.path().to_path_buf(),
+            pbo_path,
+            size,
+        });
+    }
+
+    entries.sort_by(|a, b| {
+        a.pbo_path.to_lowercase()
+            .cmp(&b.pbo_path.to_lowercase())
+            .then_with(|| a.pbo_path.cmp(&b.pbo_path))
+    });
+
+    let parent = match output_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    std::fs::create_dir_all(parent)
+        .map_err(|e| PboOperationError::io_error("creating output directory", e))?;
+
+    // Write next to the output and rename into place, so a failed pack
+    // never leaves a truncated PBO behind or clobbers an existing one
+    let temp = NamedTempFile::new_in(parent)
+        .map_err(|e| PboOperationError::io_error("creating temporary PBO file", e))?;
+    let mut writer = HashingWriter {
+        inner: BufWriter::new(temp.as_file()),
+        hasher: Sha1::new(),
+    };
+
+    write_pbo(&mut writer, &entries, prefix.as_deref(), options)
+        .map_err(|e| PboOperationError::io_error("writing PBO file", e))?;
+
+    let HashingWriter { mut inner, hasher } = writer;
+    let digest = hasher.finalize();
+    inner.write_all(&[0])
+        .and_then(|_| inner.write_all(&digest))
+        .and_then(|_| inner.flush())
+        .map_err(|e| PboOperationError::io_error("writing PBO checksum", e))?;
+    drop(inner);
+
+    temp.persist(output_path)
+        .map_err(|e| PboOperationError::io_error("moving PBO file into place", e.error))?;
+
+    let checksum: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    debug!("Packed {} files into {} ({})", entries.len(), output_path.display(), checksum);
+
+    Ok(PboPackSummary {
+        output_path: output_path.to_path_buf(),
+        total_size: entries.iter().map(|e| e.size as u64).sum(),
+        files: entries.into_iter().map(|e| e.pbo_path).collect(),
+        excluded_count,
+        checksum,
+    })
+}
+
+fn write_pbo<W: Write>(
+    writer: &mut W,
+    entries: &[PackEntry],
+    prefix: Option<&str>,
+    options: &PboPackOptions,
+) -> std::io::Result<()> {
+    // Header extensions
+    writer.write_all(&[0])?;
+    writer.write_all(MIME_VERSION)?;
+    writer.write_all(&[0u8; 16])?;
+    let extensions = [
+        ("prefix", prefix),
+        ("product", options.product.as_deref()),
+        ("version", options.version.as_deref()),
+    ];
+    for (key, value) in extensions {
+        if let Some(value) = value {
+            write_cstring(writer, key)?;
+            write_cstring(writer, value)?;
+        }
+    }
+    writer.write_all(&[0])?;
+
+    // File headers
+    for entry in entries {
+        write_cstring(writer, &entry.pbo_path.replace('/', "\\"))?;
+        writer.write_all(&0u32.to_le_bytes())?; // mime
+        writer.write_all(&0u32.to_le_bytes())?; // original size, unused for uncompressed data
+        writer.write_all(&0u32.to_le_bytes())?; // reserved
+        writer.write_all(&0u32.to_le_bytes())?; // timestamp
+        writer.write_all(&entry.size.to_le_bytes())?;
+    }
+    writer.write_all(&[0u8; 21])?;
+
+    // File data
+    for entry in entries {
+        let mut file = File::open(&entry.source)?;
+        let copied = std::io::copy(&mut file, writer)?;
+        if copied != entry.size as u64 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!("{} changed size while packing", entry.source.display()),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn write_cstring<W: Write>(writer: &mut W, s: &str) -> std::io::Result<()> {
+    writer.write_all(s.as_bytes())?;
+    writer.write_all(&[0])
+}
+
+/// Read the prefix from a `$PBOPREFIX$` file, accepting both the bare and `prefix=` forms
+fn read_prefix_file(source_dir: &Path) -> PboOperationResult<Option<String>> {
+    let path = source_dir.join(PREFIX_FILE);
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| PboOperationError::io_error("reading prefix file", e))?;
+    for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        match line.split_once('=') {
+            Some((key, value)) if key.trim().eq_ignore_ascii_case("prefix") => {
+                return Ok(Some(value.trim().to_string()));
+            }
+            Some(_) => continue,
+            None => return Ok(Some(line.to_string())),
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,127 @@
+//! Recovery of intact entries from corrupt or truncated PBOs
+//!
+//! Salvage reads the raw header table instead of going through HEMTT, so a bad
+//! checksum trailer, a truncated data block or a damaged entry does not stop
+//! the remaining entries from being extracted.
+
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Component, Path};
+use log::{debug, warn};
+
+use super::error::{PboOperationError, PboOperationResult};
+use super::hemtt_backend::HemttPboOperations;
+use super::integrity::{self, MIME_COMPRESSED};
+use super::types::{LostEntry, PboSalvageReport};
+
+/// Extract every entry matching `filter` whose data range is intact
+///
+/// # Errors
+///
+/// Returns an error if the PBO cannot be opened or the output directory
+/// cannot be created. Problems with individual entries are reported as lost
+/// entries instead.
+pub(crate) fn salvage_pbo(pbo_path: &Path, filter: &str, output_dir: &Path) -> PboOperationResult<PboSalvageReport> {
+    let (layout, header_error) = integrity::read_layout_partial(pbo_path)?;
+    std::fs::create_dir_all(output_dir)
+        .map_err(|e| PboOperationError::io_error("creating output directory", e))?;
+
+    let checksum_valid = match integrity::verify_checksum(pbo_path) {
+        Ok(_) => Some(true),
+        Err(PboOperationError::ChecksumMismatch { .. }) => Some(false),
+        Err(_) => None,
+    };
+
+    let mut report = PboSalvageReport {
+        pbo_path: pbo_path.to_path_buf(),
+        recovered: Vec::new(),
+        lost: Vec::new(),
+        header_error: header_error.map(|e| e.to_string()),
+        checksum_valid,
+    };
+
+    let mut reader = BufReader::new(
+        File::open(pbo_path).map_err(|e| PboOperationError::io_error("opening PBO file", e))?,
+    );
+
+    for entry in &layout.entries {
+        let file_path = entry.name.replace('\\', "/");
+        if !HemttPboOperations::matches_pattern(&file_path, filter) {
+            continue;
+        }
+
+        let lost = |reason: String| LostEntry { file_path: file_path.clone(), reason };
+
+        // Without the end of the header table the data block cannot be located
+        if report.header_error.is_some() {
+            report.lost.push(lost("header table is incomplete, data offset is unknown".to_string()));
+            continue;
+        }
+        if !is_safe_entry_path(&file_path) {
+            report.lost.push(lost("entry name is not a valid relative path".to_string()));
+            continue;
+        }
+        let entry_end = entry.data_offset + entry.data_size as u64;
+        if entry_end > layout.file_len {
+            report.lost.push(lost(format!(
+                "data ends at offset {} but the file is only {} bytes",
+                entry_end, layout.file_len
+            )));
+            continue;
+        }
+        if entry.mime == MIME_COMPRESSED {
+            report.lost.push(lost("compressed entries cannot be salvaged".to_string()));
+            continue;
+        }
+
+        let mut data = vec![0u8; entry.data_size as usize];
+        if let Err(e) = reader.seek(SeekFrom::Start(entry.data_offset)).and_then(|_| reader.read_exact(&mut data)) {
+            report.lost.push(lost(format!("reading entry data failed: {}", e)));
+            continue;
+        }
+
+        let output_path = output_dir.join(&file_path);
+        let written = output_path.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&output_path, &data));
+        match written {
+            Ok(()) => report.recovered.push(file_path),
+            Err(e) => {
+                warn!("Failed to write salvaged entry {}: {}", output_path.display(), e);
+                report.lost.push(lost(format!("writing entry failed: {}", e)));
+            }
+        }
+    }
+
+    debug!("Salvaged {} entries from {} ({} lost)",
+           report.recovered.len(), pbo_path.display(), report.lost.len());
+    Ok(report)
+}
+
+/// Whether a PBO entry name can be written below an output directory
+///
+/// Entry names come from untrusted headers. Names that are empty, absolute,
+/// or contain `..` or drive components would escape the directory and are
+/// rejected. Both `\` and `/` separators are accepted.
+pub fn is_safe_entry_path(file_path: &str) -> bool {
+    let file_path = file_path.replace('\\', "/");
+    !file_path.is_empty()
+        && !file_path.contains(':')
+        && Path::new(&file_path).components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_safe_entry_path() {
+        assert!(is_safe_entry_path("data/texture.paa"));
+        assert!(!is_safe_entry_path("../outside.txt"));
+        assert!(!is_safe_entry_path("/etc/passwd"));
+        assert!(!is_safe_entry_path(""));
+        assert!(is_safe_entry_path(r"addons\main\config.cpp"));
+        assert!(!is_safe_entry_path(r"..\..\outside.txt"));
+        assert!(!is_safe_entry_path(r"C:\Windows\evil.dll"));
+    }
+}
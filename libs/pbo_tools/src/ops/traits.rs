@@ -4,8 +4,12 @@ use std::path::Path;
 use std::io::Read;
 use async_trait::async_trait;
 
-use super::types::{PboFileInfo, PboProperties, PboValidation};
-use super::error::PboOperationResult;
+use super::types::{
+    GrepMatch, PboDiff, PboFileInfo, PboGrepOptions, PboPackOptions, PboPackSummary, PboProperties,
+    PboSalvageReport, PboValidation,
+};
+use super::error::{PboOperationError, PboOperationResult};
+use crate::signatures::SignatureVerification;
 
 /// Main trait for PBO operations
 /// 
@@ -182,6 +186,26 @@ pub trait PboOperations: Send + Sync {
     /// - Critical I/O errors occur during validation
     async fn validate_pbo(&self, pbo_path: &Path) -> PboOperationResult<PboValidation>;
 
+    /// Validate a PBO file and verify its data block in depth
+    /// 
+    /// Runs all checks of `validate_pbo` and additionally verifies every
+    /// entry's declared size against the data block, detecting truncated
+    /// files and data not covered by any entry.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `pbo_path` - Path to the PBO file
+    /// 
+    /// # Returns
+    /// 
+    /// A `PboValidation` struct containing validation results
+    /// 
+    /// # Errors
+    /// 
+    /// Returns an error if:
+    /// - Critical I/O errors occur during validation
+    async fn validate_pbo_deep(&self, pbo_path: &Path) -> PboOperationResult<PboValidation>;
+
     /// Read a file from a PBO into memory
     /// 
     /// Returns the file contents as a byte vector. Use this method when you
@@ -269,6 +293,114 @@ pub trait PboOperations: Send + Sync {
         pbo_path: &Path,
         file_path: &str,
     ) -> PboOperationResult<Option<PboFileInfo>>;
+
+    /// Build a PBO from the contents of a directory
+    /// 
+    /// Files are written in a deterministic order with the requested header
+    /// extensions (`prefix`, `product`, `version`) and the trailing SHA-1
+    /// checksum. If no prefix is given, a `$PBOPREFIX$` file in the source
+    /// directory is used instead.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `source_dir` - Directory whose contents should be packed
+    /// * `output_path` - Destination path for the PBO file
+    /// * `options` - Header extensions and exclusion patterns
+    /// 
+    /// # Returns
+    /// 
+    /// A `PboPackSummary` describing the written PBO
+    /// 
+    /// # Errors
+    /// 
+    /// Returns an error if:
+    /// - The source directory cannot be found or read
+    /// - A file is too large to be stored in a PBO
+    /// - The output file cannot be written
+    async fn pack(
+        &self,
+        source_dir: &Path,
+        output_path: &Path,
+        options: &PboPackOptions,
+    ) -> PboOperationResult<PboPackSummary>;
+
+    /// Verify a PBO against a `.bisign` signature and a `.bikey` public key
+    /// 
+    /// Both v2 and v3 signatures are checked entirely offline. A signature made
+    /// with a different key is reported as a key mismatch rather than an error.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `pbo_path` - Path to the PBO file
+    /// * `signature_path` - Path to the `.bisign` file
+    /// * `key_path` - Path to the `.bikey` file the PBO is expected to be signed with
+    /// 
+    /// # Returns
+    /// 
+    /// A `SignatureVerification` describing the signature and its status
+    /// 
+    /// # Errors
+    /// 
+    /// Returns an error if:
+    /// - Any of the files cannot be found or read
+    /// - The key or signature is malformed
+    async fn verify_signature(
+        &self,
+        pbo_path: &Path,
+        signature_path: &Path,
+        key_path: &Path,
+    ) -> PboOperationResult<SignatureVerification>;
+
+    /// Compare two versions of a PBO
+    /// 
+    /// Entries are matched case-insensitively by path and reported as added,
+    /// removed or modified (by size, timestamp or SHA-1 of their contents).
+    /// Config files whose contents changed also get a class-level diff.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `old_pbo` - Path to the older PBO
+    /// * `new_pbo` - Path to the newer PBO
+    /// 
+    /// # Returns
+    /// 
+    /// A `PboDiff` listing the changed entries and config classes
+    /// 
+    /// # Errors
+    /// 
+    /// Returns an error if:
+    /// - Either PBO cannot be found or opened
+    /// - An entry cannot be read
+    async fn diff(&self, old_pbo: &Path, new_pbo: &Path) -> PboOperationResult<PboDiff>;
+
+    /// Extract the intact entries of a corrupt or truncated PBO
+    /// 
+    /// The header table is read tolerantly and every entry matching the
+    /// filter whose data lies within the file is extracted, regardless of the
+    /// checksum trailer. Entries that cannot be recovered are listed in the
+    /// report with the reason.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `pbo_path` - Path to the PBO file
+    /// * `filter` - Glob pattern to match files (e.g., "*.cpp", "config.*")
+    /// * `output_dir` - Directory where recovered files should be written
+    /// 
+    /// # Returns
+    /// 
+    /// A `PboSalvageReport` listing the recovered and lost entries
+    /// 
+    /// # Errors
+    /// 
+    /// Returns an error if:
+    /// - The PBO file cannot be found or opened
+    /// - The output directory cannot be created
+    async fn salvage(
+        &self,
+        pbo_path: &Path,
+        filter: &str,
+        output_dir: &Path,
+    ) -> PboOperationResult<PboSalvageReport>;
 }
 
 /// Extension trait providing convenience methods for PBO operations
@@ -405,6 +537,52 @@ pub trait PboOperationsExt: PboOperations {
             Ok(files.iter().any(|file| file.file_path.contains(pattern)))
         }
     }
+
+    /// Search the contents of a PBO's entries line by line
+    /// 
+    /// Entries are streamed without extracting the PBO and each matching line
+    /// is passed to `on_match` as soon as it is found. Rapified entries are
+    /// decoded to text before searching, and binary assets are skipped unless
+    /// explicitly selected by the path glob.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `pbo_path` - Path to the PBO file
+    /// * `options` - Search pattern and entry filters
+    /// * `on_match` - Receives matching lines in PBO entry order
+    /// 
+    /// # Returns
+    /// 
+    /// The number of matching lines
+    /// 
+    /// # Errors
+    /// 
+    /// Returns an error if the pattern is not a valid regular expression or
+    /// the PBO cannot be listed. Entries that fail to read are skipped.
+    async fn grep(
+        &self,
+        pbo_path: &Path,
+        options: &PboGrepOptions,
+        on_match: &mut (dyn FnMut(GrepMatch) + Send),
+    ) -> PboOperationResult<usize> {
+        let regex = super::grep::build_regex(options)?;
+        let files = self.list_contents(pbo_path).await?;
+        let mut found = 0;
+
+        for file in files.iter().filter(|file| super::grep::should_search(file, options)) {
+            let searched = match self.get_file_reader(pbo_path, &file.file_path).await {
+                Ok(reader) => super::grep::search_entry(&regex, pbo_path, &file.file_path, reader, on_match)
+                    .map_err(|e| PboOperationError::io_error("reading PBO entry", e)),
+                Err(e) => Err(e),
+            };
+            match searched {
+                Ok(count) => found += count,
+                Err(e) => log::warn!("Failed to read '{}': {}", file.file_path, e),
+            }
+        }
+
+        Ok(found)
+    }
 }
 
 // Blanket implementation of PboOperationsExt for all types that implement PboOperations
@@ -468,6 +646,10 @@ mod tests {
             Ok(PboValidation::new(pbo_path.to_path_buf()))
         }
 
+        async fn validate_pbo_deep(&self, pbo_path: &Path) -> PboOperationResult<PboValidation> {
+            Ok(PboValidation::new(pbo_path.to_path_buf()))
+        }
+
         async fn read_file(&self, _pbo_path: &Path, _file_path: &str) -> PboOperationResult<Vec<u8>> {
             Ok(b"test content".to_vec())
         }
@@ -495,6 +677,22 @@ mod tests {
                 Ok(None)
             }
         }
+
+        async fn pack(&self, _source_dir: &Path, _output_path: &Path, _options: &PboPackOptions) -> PboOperationResult<PboPackSummary> {
+            Err(PboOperationError::unsupported_operation("pack"))
+        }
+
+        async fn verify_signature(&self, _pbo_path: &Path, _signature_path: &Path, _key_path: &Path) -> PboOperationResult<SignatureVerification> {
+            Err(PboOperationError::unsupported_operation("verify_signature"))
+        }
+
+        async fn diff(&self, _old_pbo: &Path, _new_pbo: &Path) -> PboOperationResult<PboDiff> {
+            Err(PboOperationError::unsupported_operation("diff"))
+        }
+
+        async fn salvage(&self, _pbo_path: &Path, _filter: &str, _output_dir: &Path) -> PboOperationResult<PboSalvageReport> {
+            Err(PboOperationError::unsupported_operation("salvage"))
+        }
     }
 
     #[tokio::test]
@@ -527,4 +725,23 @@ mod tests {
         assert_eq!(summary.get("cpp"), Some(&1));
         assert_eq!(summary.get("paa"), Some(&1));
     }
+
+    #[tokio::test]
+    async fn test_grep_skips_binary_assets() {
+        let ops = MockPboOperations;
+        let path = Path::new("test.pbo");
+
+        let mut matches = Vec::new();
+        let found = ops.grep(path, &PboGrepOptions::new("CONTENT").ignore_case(true), &mut |m| matches.push(m)).await.unwrap();
+        assert_eq!(found, 1);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].entry, "config.cpp");
+        assert_eq!(matches[0].line_number, 1);
+
+        let options = PboGrepOptions::new("content").with_path_glob("*.paa");
+        let mut matches = Vec::new();
+        ops.grep(path, &options, &mut |m| matches.push(m)).await.unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].entry, "data/texture.paa");
+    }
 }
\ No newline at end of file
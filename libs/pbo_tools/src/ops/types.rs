@@ -141,6 +141,242 @@ impl Default for PboProperties {
     }
 }
 
+/// Options for building a PBO from a directory
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PboPackOptions {
+    /// Prefix header extension (falls back to a `$PBOPREFIX$` file in the source directory)
+    pub prefix: Option<String>,
+    /// Product header extension
+    pub product: Option<String>,
+    /// Version header extension
+    pub version: Option<String>,
+    /// Glob patterns of files to leave out of the PBO
+    pub exclude: Vec<String>,
+}
+
+impl PboPackOptions {
+    /// Create empty pack options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the prefix header extension
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Set the product header extension
+    pub fn with_product(mut self, product: impl Into<String>) -> Self {
+        self.product = Some(product.into());
+        self
+    }
+
+    /// Set the version header extension
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Add a glob pattern of files to exclude
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.exclude.push(pattern.into());
+        self
+    }
+}
+
+/// Result of salvaging the intact entries of a damaged PBO
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PboSalvageReport {
+    /// Path of the salvaged PBO
+    pub pbo_path: PathBuf,
+    /// Entries written to the output directory (using forward slashes)
+    pub recovered: Vec<String>,
+    /// Entries matching the filter that could not be recovered
+    pub lost: Vec<LostEntry>,
+    /// Why the header table could not be read to the end, if it couldn't
+    pub header_error: Option<String>,
+    /// Whether the stored SHA-1 matches, `None` if it could not be checked
+    pub checksum_valid: Option<bool>,
+}
+
+impl PboSalvageReport {
+    /// Whether every entry of the PBO was recovered
+    pub fn is_complete(&self) -> bool {
+        self.header_error.is_none() && self.lost.is_empty()
+    }
+}
+
+/// An entry that could not be salvaged from a damaged PBO
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LostEntry {
+    /// Path of the entry within the PBO (using forward slashes)
+    pub file_path: String,
+    /// Why the entry could not be recovered
+    pub reason: String,
+}
+
+/// Options for searching the contents of PBO entries
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PboGrepOptions {
+    /// Regular expression matched against each line
+    pub pattern: String,
+    /// Glob pattern (or comma-separated patterns) restricting the searched entries
+    pub path_glob: Option<String>,
+    /// Match without regard to ASCII case
+    pub ignore_case: bool,
+}
+
+impl PboGrepOptions {
+    /// Create search options for a pattern
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Only search entries whose path matches the glob pattern
+    pub fn with_path_glob(mut self, glob: impl Into<String>) -> Self {
+        self.path_glob = Some(glob.into());
+        self
+    }
+
+    /// Set whether matching ignores case
+    pub fn ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+}
+
+/// A line inside a PBO entry matching a search pattern
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GrepMatch {
+    /// Path of the PBO containing the entry
+    pub pbo_path: PathBuf,
+    /// Path of the entry within the PBO
+    pub entry: String,
+    /// Line number of the match (1-based, in decoded text for rapified entries)
+    pub line_number: usize,
+    /// Contents of the matching line without its line ending
+    pub line: String,
+}
+
+/// Result of packing a directory into a PBO
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PboPackSummary {
+    /// Path of the written PBO
+    pub output_path: PathBuf,
+    /// Paths of the packed files in PBO order (using forward slashes)
+    pub files: Vec<String>,
+    /// Number of files skipped by exclusion patterns
+    pub excluded_count: usize,
+    /// Total size of the packed file data
+    pub total_size: u64,
+    /// SHA-1 checksum written at the end of the PBO
+    pub checksum: String,
+}
+
+/// Differences between two versions of a PBO
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PboDiff {
+    /// Path of the older PBO
+    pub old_path: PathBuf,
+    /// Path of the newer PBO
+    pub new_path: PathBuf,
+    /// Entries that were added, removed or modified, ordered by path
+    pub entries: Vec<EntryDiff>,
+    /// Class-level differences of config files present in both PBOs
+    pub configs: Vec<ConfigDiff>,
+}
+
+impl PboDiff {
+    /// Whether the two PBOs have identical entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.configs.is_empty()
+    }
+
+    /// Count entries with the given kind of change
+    pub fn count(&self, kind: EntryChangeKind) -> usize {
+        self.entries.iter().filter(|e| e.kind == kind).count()
+    }
+}
+
+/// Kind of change to a PBO entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// Change to a single PBO entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryDiff {
+    /// Path of the entry within the PBO (using forward slashes)
+    pub path: String,
+    /// Kind of change
+    pub kind: EntryChangeKind,
+    /// Size in the older PBO
+    pub old_size: Option<u64>,
+    /// Size in the newer PBO
+    pub new_size: Option<u64>,
+    /// Timestamp in the older PBO
+    pub old_timestamp: Option<u64>,
+    /// Timestamp in the newer PBO
+    pub new_timestamp: Option<u64>,
+    /// SHA-1 of the contents in the older PBO
+    pub old_hash: Option<String>,
+    /// SHA-1 of the contents in the newer PBO
+    pub new_hash: Option<String>,
+}
+
+impl EntryDiff {
+    /// Whether the contents of a modified entry differ
+    pub fn content_changed(&self) -> bool {
+        self.old_hash != self.new_hash
+    }
+}
+
+/// Class-level differences of a config file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigDiff {
+    /// Path of the config file within the PBO
+    pub path: String,
+    /// Changes ordered by class path
+    pub changes: Vec<ClassChange>,
+    /// Set when either version could not be parsed
+    pub error: Option<String>,
+}
+
+/// Change to a config class, identified by its path (e.g. `CfgWeapons/arifle_MX_F`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassChange {
+    /// Slash separated path of the class
+    pub class_path: String,
+    /// What changed
+    pub kind: ClassChangeKind,
+}
+
+/// Kind of change to a config class
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum ClassChangeKind {
+    /// Class was added
+    Added { parent: Option<String> },
+    /// Class was removed
+    Removed { parent: Option<String> },
+    /// Class now inherits from a different parent
+    ParentChanged { old: Option<String>, new: Option<String> },
+    /// Property was added
+    PropertyAdded { name: String, value: String },
+    /// Property was removed
+    PropertyRemoved { name: String, value: String },
+    /// Property value changed
+    PropertyChanged { name: String, old: String, new: String },
+}
+
 /// Validation result for a PBO file
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PboValidation {
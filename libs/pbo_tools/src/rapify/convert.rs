@@ -0,0 +1,301 @@
+//! Conversions from parsed configs into the rapify tree
+
+use gamedata_scanner_models::{GameClass, PropertyValue};
+use hemtt_config::{Class, Config as HemttConfig, Item, Number as HemttNumber, Property, Value};
+
+use super::{RapClass, RapConfig, RapEntry, RapValue};
+use crate::ops::{PboOperationError, PboOperationResult};
+
+impl RapConfig {
+    /// Build a config from a list of scanned classes
+    ///
+    /// Only top-level classes (those without a container) are used as roots,
+    /// nested classes are taken from the `PropertyValue::Class` properties of
+    /// their container so they are not duplicated. Like the conversion from a
+    /// parsed config, this fails on values that cannot be rapified as written,
+    /// such as unevaluated expressions.
+    pub fn from_game_classes(classes: &[GameClass]) -> PboOperationResult<Self> {
+        let entries = classes
+            .iter()
+            .filter(|class| class.container_class.is_none())
+            .map(game_class_entry)
+            .collect::<PboOperationResult<_>>()?;
+
+        Ok(Self {
+            root: RapClass { parent: None, entries },
+            enums: Vec::new(),
+        })
+    }
+}
+
+impl TryFrom<&HemttConfig> for RapConfig {
+    type Error = PboOperationError;
+
+    /// Convert a parsed config, failing on anything that cannot be rapified
+    /// as written (unevaluated expressions, invalid values, missing
+    /// semicolons, enums) rather than producing a config that differs from the source
+    fn try_from(config: &HemttConfig) -> PboOperationResult<Self> {
+        Ok(Self {
+            root: RapClass {
+                parent: None,
+                entries: hemtt_entries(&config.0)?,
+            },
+            enums: Vec::new(),
+        })
+    }
+}
+
+fn game_class_entry(class: &GameClass) -> PboOperationResult<RapEntry> {
+    if class.is_forward_declaration {
+        return Ok(RapEntry::Extern(class.name.clone()));
+    }
+
+    let entries = class
+        .properties
+        .iter()
+        .map(|property| match &property.value {
+            PropertyValue::Class(nested) => game_class_entry(nested),
+            PropertyValue::Delete => Ok(RapEntry::Delete(property.name.clone())),
+            PropertyValue::Array(items) => Ok(RapEntry::Array {
+                name: property.name.clone(),
+                items: game_items(&property.name, items)?,
+                append: false,
+            }),
+            PropertyValue::Append(appended) => match &**appended {
+                PropertyValue::Array(items) => Ok(RapEntry::Array {
+                    name: property.name.clone(),
+                    items: game_items(&property.name, items)?,
+                    append: true,
+                }),
+                _ => Err(PboOperationError::invalid_format(format!(
+                    "property '{}' appends something other than an array",
+                    property.name
+                ))),
+            },
+            value => Ok(RapEntry::Value {
+                name: property.name.clone(),
+                value: game_value(&property.name, value)?,
+            }),
+        })
+        .collect::<PboOperationResult<_>>()?;
+
+    Ok(RapEntry::Class {
+        name: class.name.clone(),
+        class: RapClass {
+            parent: class.parent.clone(),
+            entries,
+        },
+    })
+}
+
+fn integer_value(n: i64) -> RapValue {
+    match i32::try_from(n) {
+        Ok(i) => RapValue::Int(i),
+        Err(_) => RapValue::Int64(n),
+    }
+}
+
+/// Rapify a value, `name` is the property it belongs to for error messages
+fn game_value(name: &str, value: &PropertyValue) -> PboOperationResult<RapValue> {
+    match value {
+        PropertyValue::String(s) => Ok(RapValue::String(s.clone())),
+        PropertyValue::Number(n) => Ok(integer_value(*n)),
+        PropertyValue::Float(f) => Ok(RapValue::Float(*f as f32)),
+        PropertyValue::Array(items) => Ok(RapValue::Array(game_items(name, items)?)),
+        PropertyValue::Expression(_) => Err(PboOperationError::invalid_format(format!(
+            "property '{}' holds an unevaluated expression",
+            name
+        ))),
+        PropertyValue::Append(_) | PropertyValue::Delete | PropertyValue::Class(_) => {
+            Err(PboOperationError::invalid_format(format!(
+                "array '{}' has an item that cannot be rapified",
+                name
+            )))
+        }
+    }
+}
+
+fn game_items(name: &str, items: &[PropertyValue]) -> PboOperationResult<Vec<RapValue>> {
+    items.iter().map(|item| game_value(name, item)).collect()
+}
+
+fn hemtt_entries(properties: &[Property]) -> PboOperationResult<Vec<RapEntry>> {
+    let mut entries = Vec::new();
+    for property in properties {
+        match property {
+            Property::Entry { name, value, .. } => {
+                let name = name.as_str().to_string();
+                match value {
+                    Value::Array(array) | Value::UnexpectedArray(array) => entries.push(RapEntry::Array {
+                        items: hemtt_items(&name, &array.items)?,
+                        name,
+                        append: array.expand,
+                    }),
+                    _ => {
+                        let value = hemtt_value(&name, value)?;
+                        entries.push(RapEntry::Value { name, value });
+                    }
+                }
+            }
+            Property::Class(Class::Local { name, parent, properties, .. }) => {
+                entries.push(RapEntry::Class {
+                    name: name.as_str().to_string(),
+                    class: RapClass {
+                        parent: parent.as_ref().map(|p| p.as_str().to_string()),
+                        entries: hemtt_entries(properties)?,
+                    },
+                });
+            }
+            Property::Class(Class::External { name, .. }) => {
+                entries.push(RapEntry::Extern(name.as_str().to_string()));
+            }
+            Property::Class(Class::Root { properties, .. }) => {
+                entries.extend(hemtt_entries(properties)?);
+            }
+            Property::Delete(name) => {
+                entries.push(RapEntry::Delete(name.as_str().to_string()));
+            }
+            // Missing semicolons and enum blocks have no rapified form here
+            _ => {
+                return Err(PboOperationError::invalid_format(
+                    "config contains an entry that cannot be rapified (missing semicolon or enum)",
+                ));
+            }
+        }
+    }
+    Ok(entries)
+}
+
+fn hemtt_number(number: &HemttNumber) -> RapValue {
+    match number {
+        HemttNumber::Int32 { value, .. } => RapValue::Int(*value),
+        HemttNumber::Int64 { value, .. } => RapValue::Int64(*value),
+        HemttNumber::Float32 { value, .. } => RapValue::Float(*value),
+    }
+}
+
+fn hemtt_value(name: &str, value: &Value) -> PboOperationResult<RapValue> {
+    match value {
+        Value::Str(s) => Ok(RapValue::String(s.value().to_string())),
+        Value::Number(n) => Ok(hemtt_number(n)),
+        Value::Macro(m) => Ok(RapValue::Variable(m.to_string())),
+        Value::Array(array) | Value::UnexpectedArray(array) => Ok(RapValue::Array(hemtt_items(name, &array.items)?)),
+        Value::Expression(_) => Err(PboOperationError::invalid_format(format!(
+            "property '{}' holds an unevaluated expression",
+            name
+        ))),
+        Value::Invalid(_) => Err(PboOperationError::invalid_format(format!(
+            "property '{}' has an invalid value",
+            name
+        ))),
+    }
+}
+
+fn hemtt_items(name: &str, items: &[Item]) -> PboOperationResult<Vec<RapValue>> {
+    items
+        .iter()
+        .map(|item| match item {
+            Item::Str(s) => Ok(RapValue::String(s.value().to_string())),
+            Item::Number(n) => Ok(hemtt_number(n)),
+            Item::Macro(m) => Ok(RapValue::Variable(m.to_string())),
+            Item::Array(items) => Ok(RapValue::Array(hemtt_items(name, items)?)),
+            Item::Invalid(_) => Err(PboOperationError::invalid_format(format!(
+                "array '{}' has an invalid item",
+                name
+            ))),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gamedata_scanner_models::ClassProperty;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_from_game_classes() {
+        let mut nested = GameClass::with_container(
+            "ItemInfo".to_string(),
+            None,
+            PathBuf::from("config.cpp"),
+            Some("Shim_Item".to_string()),
+        );
+        nested.add_property("mass".to_string(), PropertyValue::Number(40));
+
+        let mut item = GameClass::new("Shim_Item".to_string(), Some("ItemCore".to_string()), PathBuf::from("config.cpp"));
+        item.add_property("displayName".to_string(), PropertyValue::String("Shim".to_string()));
+        item.add_property(
+            "allowedSlots".to_string(),
+            PropertyValue::Array(vec![PropertyValue::Number(901), "901".into(), PropertyValue::Float(0.5), "text".into()]),
+        );
+        item.properties.push(ClassProperty {
+            name: "ItemInfo".to_string(),
+            value: PropertyValue::Class(Box::new(nested.clone())),
+            location: None,
+        });
+
+        let config = RapConfig::from_game_classes(&[item, nested]).unwrap();
+        assert_eq!(config.root.entries.len(), 1, "Nested classes should not be emitted at the root");
+
+        let item = config.root.class("Shim_Item").unwrap();
+        assert_eq!(item.parent.as_deref(), Some("ItemCore"));
+        assert_eq!(
+            item.entries[1],
+            RapEntry::Array {
+                name: "allowedSlots".to_string(),
+                items: vec![
+                    RapValue::Int(901),
+                    RapValue::String("901".to_string()),
+                    RapValue::Float(0.5),
+                    RapValue::String("text".to_string()),
+                ],
+                append: false,
+            }
+        );
+        assert!(item.class("ItemInfo").is_some());
+    }
+
+    #[test]
+    fn test_from_game_classes_keeps_floats_nested_arrays_and_appends() {
+        let mut item = GameClass::new("Shim_Item".to_string(), None, PathBuf::from("config.cpp"));
+        item.add_property("mass".to_string(), PropertyValue::Float(12.5));
+        item.add_property(
+            "weaponPoolAvailable".to_string(),
+            PropertyValue::Array(vec![
+                PropertyValue::Array(vec!["arifle_MX_F".into(), PropertyValue::Number(2)]),
+            ]),
+        );
+        item.add_property(
+            "magazineWell".to_string(),
+            PropertyValue::Append(Box::new(PropertyValue::Array(vec!["CBA_556x45_STANAG".into()]))),
+        );
+
+        let config = RapConfig::from_game_classes(&[item.clone()]).unwrap();
+        let rapified = config.root.class("Shim_Item").unwrap();
+
+        assert_eq!(rapified.entries.len(), 3);
+        assert_eq!(rapified.entries[0], RapEntry::Value { name: "mass".to_string(), value: RapValue::Float(12.5) });
+        assert_eq!(
+            rapified.entries[1],
+            RapEntry::Array {
+                name: "weaponPoolAvailable".to_string(),
+                items: vec![RapValue::Array(vec![RapValue::String("arifle_MX_F".to_string()), RapValue::Int(2)])],
+                append: false,
+            }
+        );
+        assert_eq!(
+            rapified.entries[2],
+            RapEntry::Array {
+                name: "magazineWell".to_string(),
+                items: vec![RapValue::String("CBA_556x45_STANAG".to_string())],
+                append: true,
+            }
+        );
+
+        // Unevaluated expressions cannot be rapified
+        item.add_property("scope".to_string(), PropertyValue::Expression("__EVAL(1 + 1)".to_string()));
+        let err = RapConfig::from_game_classes(&[item]).unwrap_err();
+        assert!(err.to_string().contains("scope"), "{}", err);
+    }
+}
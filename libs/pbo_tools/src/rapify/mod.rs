@@ -0,0 +1,100 @@
+//! Rapified (binarized) config support
+//!
+//! Arma stores `config.bin`, binarized `mission.sqm` and `description.ext` files
+//! in the "raP" format. This module decodes that format into a small config tree
+//! which can be written back out as text config that the HPP parser understands,
+//! or encoded back into a rapified file.
+
+mod convert;
+mod reader;
+mod text;
+mod writer;
+
+use serde::{Deserialize, Serialize};
+
+pub use reader::derapify;
+pub use writer::rapify;
+
+/// Signature found at the start of every rapified file
+pub const RAP_SIGNATURE: &[u8; 4] = b"\0raP";
+
+/// Check whether a buffer contains a rapified config
+pub fn is_rapified(data: &[u8]) -> bool {
+    data.starts_with(RAP_SIGNATURE)
+}
+
+/// A decoded rapified config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RapConfig {
+    /// The root class body (has no name and no parent)
+    pub root: RapClass,
+    /// Enum definitions stored at the end of the file
+    pub enums: Vec<(String, i32)>,
+}
+
+/// Body of a class within a rapified config
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RapClass {
+    /// Name of the class this body inherits from
+    pub parent: Option<String>,
+    /// Entries of the class in file order
+    pub entries: Vec<RapEntry>,
+}
+
+/// A single entry within a class body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RapEntry {
+    /// Nested class definition (`class Name: Parent { ... };`)
+    Class { name: String, class: RapClass },
+    /// Scalar property (`name = value;`)
+    Value { name: String, value: RapValue },
+    /// Array property (`name[] = {...};` or `name[] += {...};`)
+    Array {
+        name: String,
+        items: Vec<RapValue>,
+        append: bool,
+    },
+    /// External class reference (`class Name;`)
+    Extern(String),
+    /// Class deletion (`delete Name;`)
+    Delete(String),
+}
+
+/// A value stored in a property or array
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RapValue {
+    /// Quoted string
+    String(String),
+    /// 32-bit float
+    Float(f32),
+    /// 32-bit integer
+    Int(i32),
+    /// 64-bit integer
+    Int64(i64),
+    /// Unquoted variable or expression
+    Variable(String),
+    /// Nested array
+    Array(Vec<RapValue>),
+}
+
+impl RapEntry {
+    /// Name of the entry
+    pub fn name(&self) -> &str {
+        match self {
+            RapEntry::Class { name, .. }
+            | RapEntry::Value { name, .. }
+            | RapEntry::Array { name, .. } => name,
+            RapEntry::Extern(name) | RapEntry::Delete(name) => name,
+        }
+    }
+}
+
+impl RapClass {
+    /// Find a nested class body by name (case-insensitive, like the game)
+    pub fn class(&self, name: &str) -> Option<&RapClass> {
+        self.entries.iter().find_map(|entry| match entry {
+            RapEntry::Class { name: n, class } if n.eq_ignore_ascii_case(name) => Some(class),
+            _ => None,
+        })
+    }
+}
@@ -0,0 +1,346 @@
+//! Decoder for the rapified config format
+
+use std::collections::HashSet;
+use log::trace;
+
+use crate::ops::{PboOperationError, PboOperationResult};
+use super::{is_rapified, RapClass, RapConfig, RapEntry, RapValue};
+
+/// Deepest class nesting accepted before the file is considered corrupt
+const MAX_CLASS_DEPTH: usize = 128;
+
+/// Windows-1252 characters for bytes 0x80-0x9F, the range where it differs
+/// from Latin-1. Undefined bytes map to the C1 control of the same value.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20ac}', '\u{81}', '\u{201a}', '\u{192}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2c6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8d}', '\u{17d}', '\u{8f}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2dc}', '\u{2122}', '\u{161}', '\u{203a}', '\u{153}', '\u{9d}', '\u{17e}', '\u{178}',
+];
+
+/// Decode bytes as Windows-1252
+fn decode_windows_1252(bytes: &[u8]) -> String {
+    bytes.iter()
+        .map(|&b| match b {
+            0x80..=0x9f => WINDOWS_1252_HIGH[(b - 0x80) as usize],
+            _ => b as char,
+        })
+        .collect()
+}
+
+/// Decode a rapified config from its raw bytes
+pub fn derapify(data: &[u8]) -> PboOperationResult<RapConfig> {
+    if !is_rapified(data) {
+        return Err(PboOperationError::invalid_format("missing raP signature"));
+    }
+
+    let mut reader = RapReader::new(data);
+    reader.seek(12)?;
+    let enum_offset = reader.read_u32()? as usize;
+
+    let mut visited = HashSet::new();
+    let root = reader.read_class_body(16, 0, &mut visited)?;
+
+    // Enums are optional, some tools write a zero offset instead of an empty table
+    let mut enums = Vec::new();
+    if enum_offset != 0 && enum_offset + 4 <= data.len() {
+        reader.seek(enum_offset)?;
+        let count = reader.read_u32()?;
+        for _ in 0..count {
+            let name = reader.read_cstring()?;
+            let value = reader.read_i32()?;
+            enums.push((name, value));
+        }
+    }
+
+    Ok(RapConfig { root, enums })
+}
+
+struct RapReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> RapReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn seek(&mut self, pos: usize) -> PboOperationResult<()> {
+        if pos > self.data.len() {
+            return Err(PboOperationError::invalid_format(format!(
+                "offset {} is beyond the end of the file ({} bytes)",
+                pos,
+                self.data.len()
+            )));
+        }
+        self.pos = pos;
+        Ok(())
+    }
+
+    fn take(&mut self, len: usize) -> PboOperationResult<&'a [u8]> {
+        let end = self.pos.checked_add(len).filter(|end| *end <= self.data.len()).ok_or_else(|| {
+            PboOperationError::invalid_format(format!("unexpected end of data at offset {}", self.pos))
+        })?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> PboOperationResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_u32(&mut self) -> PboOperationResult<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_i32(&mut self) -> PboOperationResult<i32> {
+        Ok(self.read_u32()? as i32)
+    }
+
+    fn read_i64(&mut self) -> PboOperationResult<i64> {
+        let bytes = self.take(8)?;
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(bytes);
+        Ok(i64::from_le_bytes(buf))
+    }
+
+    fn read_f32(&mut self) -> PboOperationResult<f32> {
+        Ok(f32::from_bits(self.read_u32()?))
+    }
+
+    fn read_cstring(&mut self) -> PboOperationResult<String> {
+        let rest = &self.data[self.pos..];
+        let len = rest.iter().position(|b| *b == 0).ok_or_else(|| {
+            PboOperationError::invalid_format(format!("unterminated string at offset {}", self.pos))
+        })?;
+        let bytes = self.take(len)?;
+        self.pos += 1; // Skip the terminator
+        // Configs are usually UTF-8, but older addons use Windows-1252
+        Ok(match std::str::from_utf8(bytes) {
+            Ok(s) => s.to_string(),
+            Err(_) => decode_windows_1252(bytes),
+        })
+    }
+
+    fn read_compressed_int(&mut self) -> PboOperationResult<u32> {
+        let mut value = 0u32;
+        for shift in (0..35).step_by(7) {
+            let byte = self.read_u8()?;
+            value |= u32::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(PboOperationError::invalid_format(format!(
+            "compressed integer too long at offset {}",
+            self.pos
+        )))
+    }
+
+    fn read_class_body(
+        &mut self,
+        offset: usize,
+        depth: usize,
+        visited: &mut HashSet<usize>,
+    ) -> PboOperationResult<RapClass> {
+        if depth > MAX_CLASS_DEPTH {
+            return Err(PboOperationError::invalid_format("class nesting is too deep"));
+        }
+        if !visited.insert(offset) {
+            return Err(PboOperationError::invalid_format(format!(
+                "class body at offset {} is referenced more than once",
+                offset
+            )));
+        }
+
+        self.seek(offset)?;
+        let parent = self.read_cstring()?;
+        let count = self.read_compressed_int()?;
+        trace!("Reading class body at {} with {} entries", offset, count);
+
+        let mut class = RapClass {
+            parent: if parent.is_empty() { None } else { Some(parent) },
+            entries: Vec::new(),
+        };
+
+        for _ in 0..count {
+            let entry_type = self.read_u8()?;
+            let entry = match entry_type {
+                0 => {
+                    let name = self.read_cstring()?;
+                    let body_offset = self.read_u32()? as usize;
+                    let resume = self.pos;
+                    let body = self.read_class_body(body_offset, depth + 1, visited)?;
+                    self.pos = resume;
+                    RapEntry::Class { name, class: body }
+                }
+                1 => {
+                    let value_type = self.read_u8()?;
+                    let name = self.read_cstring()?;
+                    let value = self.read_value(value_type)?;
+                    RapEntry::Value { name, value }
+                }
+                2 => {
+                    let name = self.read_cstring()?;
+                    let items = self.read_array(depth)?;
+                    RapEntry::Array { name, items, append: false }
+                }
+                3 => RapEntry::Extern(self.read_cstring()?),
+                4 => RapEntry::Delete(self.read_cstring()?),
+                5 => {
+                    let flags = self.read_u32()?;
+                    let name = self.read_cstring()?;
+                    let items = self.read_array(depth)?;
+                    RapEntry::Array { name, items, append: flags & 1 == 1 }
+                }
+                other => {
+                    return Err(PboOperationError::invalid_format(format!(
+                        "unknown entry type {} at offset {}",
+                        other,
+                        self.pos - 1
+                    )));
+                }
+            };
+            class.entries.push(entry);
+        }
+
+        Ok(class)
+    }
+
+    fn read_value(&mut self, value_type: u8) -> PboOperationResult<RapValue> {
+        match value_type {
+            0 => Ok(RapValue::String(self.read_cstring()?)),
+            1 => Ok(RapValue::Float(self.read_f32()?)),
+            2 => Ok(RapValue::Int(self.read_i32()?)),
+            4 => Ok(RapValue::Variable(self.read_cstring()?)),
+            6 => Ok(RapValue::Int64(self.read_i64()?)),
+            other => Err(PboOperationError::invalid_format(format!(
+                "unknown value type {} at offset {}",
+                other,
+                self.pos
+            ))),
+        }
+    }
+
+    fn read_array(&mut self, depth: usize) -> PboOperationResult<Vec<RapValue>> {
+        if depth > MAX_CLASS_DEPTH {
+            return Err(PboOperationError::invalid_format("array nesting is too deep"));
+        }
+        let count = self.read_compressed_int()?;
+        let mut items = Vec::new();
+        for _ in 0..count {
+            let item_type = self.read_u8()?;
+            let item = if item_type == 3 {
+                RapValue::Array(self.read_array(depth + 1)?)
+            } else {
+                self.read_value(item_type)?
+            };
+            items.push(item);
+        }
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hand-assembled file equivalent to:
+    /// `class CfgPatches { class test { units[] = {"a"}; requiredVersion = 0.5; }; }; delete Old;`
+    fn sample() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(b"\0raP\0\0\0\0\x08\0\0\0");
+        data.extend_from_slice(&0u32.to_le_bytes()); // enum offset, patched below
+        // Root body at 16
+        data.push(0); // no parent
+        data.push(2); // two entries
+        data.push(0);
+        data.extend_from_slice(b"CfgPatches\0");
+        let cfg_patches_offset_pos = data.len();
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.push(4);
+        data.extend_from_slice(b"Old\0");
+        // CfgPatches body
+        let cfg_patches_offset = data.len() as u32;
+        data[cfg_patches_offset_pos..cfg_patches_offset_pos + 4].copy_from_slice(&cfg_patches_offset.to_le_bytes());
+        data.push(0);
+        data.push(1);
+        data.push(0);
+        data.extend_from_slice(b"test\0");
+        let test_offset_pos = data.len();
+        data.extend_from_slice(&0u32.to_le_bytes());
+        // test body
+        let test_offset = data.len() as u32;
+        data[test_offset_pos..test_offset_pos + 4].copy_from_slice(&test_offset.to_le_bytes());
+        data.push(0);
+        data.push(2);
+        data.push(2);
+        data.extend_from_slice(b"units\0");
+        data.push(1);
+        data.push(0);
+        data.extend_from_slice(b"a\0");
+        data.push(1);
+        data.push(1);
+        data.extend_from_slice(b"requiredVersion\0");
+        data.extend_from_slice(&0.5f32.to_le_bytes());
+        // Enums
+        let enum_offset = data.len() as u32;
+        data[12..16].copy_from_slice(&enum_offset.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_derapify_sample() {
+        let config = derapify(&sample()).unwrap();
+        assert_eq!(config.root.entries.len(), 2);
+        assert_eq!(config.root.entries[1], RapEntry::Delete("Old".to_string()));
+
+        let test = config.root.class("cfgpatches").unwrap().class("test").unwrap();
+        assert_eq!(
+            test.entries[0],
+            RapEntry::Array {
+                name: "units".to_string(),
+                items: vec![RapValue::String("a".to_string())],
+                append: false,
+            }
+        );
+        assert_eq!(
+            test.entries[1],
+            RapEntry::Value {
+                name: "requiredVersion".to_string(),
+                value: RapValue::Float(0.5),
+            }
+        );
+        assert!(config.enums.is_empty());
+    }
+
+    #[test]
+    fn test_derapify_rejects_text() {
+        assert!(derapify(b"class CfgPatches {};").is_err());
+    }
+
+    #[test]
+    fn test_derapify_truncated() {
+        let data = sample();
+        assert!(derapify(&data[..data.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn test_cstring_falls_back_to_windows_1252() {
+        let mut reader = RapReader::new(b"\x80 caf\xe9 \x93q\x94\0");
+        assert_eq!(reader.read_cstring().unwrap(), "\u{20ac} caf\u{e9} \u{201c}q\u{201d}");
+
+        let mut reader = RapReader::new("caf\u{e9}\0".as_bytes());
+        assert_eq!(reader.read_cstring().unwrap(), "caf\u{e9}");
+    }
+
+    #[test]
+    fn test_compressed_int() {
+        let mut reader = RapReader::new(&[0x80 | 0x2c, 0x02]);
+        assert_eq!(reader.read_compressed_int().unwrap(), 300);
+    }
+}
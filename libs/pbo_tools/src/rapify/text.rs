@@ -0,0 +1,121 @@
+//! Text output for decoded configs
+
+use std::fmt::Write;
+
+use super::{RapClass, RapConfig, RapEntry, RapValue};
+
+impl RapConfig {
+    /// Render the config as text in the same layout CfgConvert produces
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        if !self.enums.is_empty() {
+            out.push_str("enum\n{\n");
+            for (name, value) in &self.enums {
+                let _ = writeln!(out, "\t{} = {},", name, value);
+            }
+            out.push_str("};\n");
+        }
+        write_entries(&mut out, &self.root, 0);
+        out
+    }
+}
+
+fn write_entries(out: &mut String, class: &RapClass, depth: usize) {
+    let indent = "\t".repeat(depth);
+    for entry in &class.entries {
+        match entry {
+            RapEntry::Class { name, class } => {
+                match &class.parent {
+                    Some(parent) => {
+                        let _ = writeln!(out, "{}class {}: {}", indent, name, parent);
+                    }
+                    None => {
+                        let _ = writeln!(out, "{}class {}", indent, name);
+                    }
+                }
+                let _ = writeln!(out, "{}{{", indent);
+                write_entries(out, class, depth + 1);
+                let _ = writeln!(out, "{}}};", indent);
+            }
+            RapEntry::Value { name, value } => {
+                let _ = writeln!(out, "{}{} = {};", indent, name, format_value(value));
+            }
+            RapEntry::Array { name, items, append } => {
+                let op = if *append { "+=" } else { "=" };
+                let _ = writeln!(out, "{}{}[] {} {};", indent, name, op, format_array(items));
+            }
+            RapEntry::Extern(name) => {
+                let _ = writeln!(out, "{}class {};", indent, name);
+            }
+            RapEntry::Delete(name) => {
+                let _ = writeln!(out, "{}delete {};", indent, name);
+            }
+        }
+    }
+}
+
+fn format_array(items: &[RapValue]) -> String {
+    let items: Vec<String> = items.iter().map(format_value).collect();
+    format!("{{{}}}", items.join(","))
+}
+
+fn format_value(value: &RapValue) -> String {
+    match value {
+        RapValue::String(s) => format!("\"{}\"", s.replace('"', "\"\"")),
+        RapValue::Float(f) => format!("{}", f),
+        RapValue::Int(i) => i.to_string(),
+        RapValue::Int64(i) => i.to_string(),
+        RapValue::Variable(v) => v.clone(),
+        RapValue::Array(items) => format_array(items),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_text() {
+        let config = RapConfig {
+            root: RapClass {
+                parent: None,
+                entries: vec![
+                    RapEntry::Class {
+                        name: "CfgVehicles".to_string(),
+                        class: RapClass {
+                            parent: None,
+                            entries: vec![
+                                RapEntry::Extern("Man".to_string()),
+                                RapEntry::Class {
+                                    name: "Soldier".to_string(),
+                                    class: RapClass {
+                                        parent: Some("Man".to_string()),
+                                        entries: vec![
+                                            RapEntry::Value {
+                                                name: "displayName".to_string(),
+                                                value: RapValue::String("The \"Best\"".to_string()),
+                                            },
+                                            RapEntry::Array {
+                                                name: "weapons".to_string(),
+                                                items: vec![
+                                                    RapValue::String("arifle".to_string()),
+                                                    RapValue::Array(vec![RapValue::Int(1), RapValue::Float(0.25)]),
+                                                ],
+                                                append: true,
+                                            },
+                                        ],
+                                    },
+                                },
+                            ],
+                        },
+                    },
+                    RapEntry::Delete("Old".to_string()),
+                ],
+            },
+            enums: vec![],
+        };
+
+        let expected = "class CfgVehicles\n{\n\tclass Man;\n\tclass Soldier: Man\n\t{\n\t\tdisplayName = \"The \"\"Best\"\"\";\n\t\tweapons[] += {\"arifle\",{1,0.25}};\n\t};\n};\ndelete Old;\n";
+        assert_eq!(config.to_text(), expected);
+    }
+}
@@ -0,0 +1,228 @@
+//! Encoder for the rapified config format
+
+use super::{RapClass, RapConfig, RapEntry, RapValue, RAP_SIGNATURE};
+
+/// Encode a config tree into rapified bytes
+///
+/// Class bodies are written after the entries of their parent, depth-first,
+/// matching the layout produced by the game tools.
+pub fn rapify(config: &RapConfig) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(RAP_SIGNATURE);
+    out.extend_from_slice(&0u32.to_le_bytes());
+    out.extend_from_slice(&8u32.to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes()); // Enum offset, patched below
+
+    write_class_body(&mut out, &config.root);
+
+    let enum_offset = out.len() as u32;
+    out[12..16].copy_from_slice(&enum_offset.to_le_bytes());
+    out.extend_from_slice(&(config.enums.len() as u32).to_le_bytes());
+    for (name, value) in &config.enums {
+        write_cstring(&mut out, name);
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+
+    out
+}
+
+fn write_class_body(out: &mut Vec<u8>, class: &RapClass) {
+    write_cstring(out, class.parent.as_deref().unwrap_or(""));
+    write_compressed_int(out, class.entries.len() as u32);
+
+    // Offsets of nested class bodies are only known once the entries are written
+    let mut pending = Vec::new();
+    for entry in &class.entries {
+        match entry {
+            RapEntry::Class { name, class } => {
+                out.push(0);
+                write_cstring(out, name);
+                pending.push((out.len(), class));
+                out.extend_from_slice(&0u32.to_le_bytes());
+            }
+            RapEntry::Value { name, value } => match value {
+                RapValue::Array(items) => {
+                    // A scalar holding an array is stored as a plain array entry
+                    out.push(2);
+                    write_cstring(out, name);
+                    write_array(out, items);
+                }
+                _ => {
+                    out.push(1);
+                    out.push(value_type(value));
+                    write_cstring(out, name);
+                    write_value(out, value);
+                }
+            },
+            RapEntry::Array { name, items, append } => {
+                if *append {
+                    out.push(5);
+                    out.extend_from_slice(&1u32.to_le_bytes());
+                } else {
+                    out.push(2);
+                }
+                write_cstring(out, name);
+                write_array(out, items);
+            }
+            RapEntry::Extern(name) => {
+                out.push(3);
+                write_cstring(out, name);
+            }
+            RapEntry::Delete(name) => {
+                out.push(4);
+                write_cstring(out, name);
+            }
+        }
+    }
+
+    // Each body ends with the offset just past its nested bodies
+    let end_pos = out.len();
+    out.extend_from_slice(&0u32.to_le_bytes());
+
+    for (offset_pos, child) in pending {
+        let offset = out.len() as u32;
+        out[offset_pos..offset_pos + 4].copy_from_slice(&offset.to_le_bytes());
+        write_class_body(out, child);
+    }
+
+    let end = out.len() as u32;
+    out[end_pos..end_pos + 4].copy_from_slice(&end.to_le_bytes());
+}
+
+fn write_array(out: &mut Vec<u8>, items: &[RapValue]) {
+    write_compressed_int(out, items.len() as u32);
+    for item in items {
+        match item {
+            RapValue::Array(nested) => {
+                out.push(3);
+                write_array(out, nested);
+            }
+            _ => {
+                out.push(value_type(item));
+                write_value(out, item);
+            }
+        }
+    }
+}
+
+fn value_type(value: &RapValue) -> u8 {
+    match value {
+        RapValue::String(_) => 0,
+        RapValue::Float(_) => 1,
+        RapValue::Int(_) => 2,
+        RapValue::Array(_) => 3,
+        RapValue::Variable(_) => 4,
+        RapValue::Int64(_) => 6,
+    }
+}
+
+fn write_value(out: &mut Vec<u8>, value: &RapValue) {
+    match value {
+        RapValue::String(s) | RapValue::Variable(s) => write_cstring(out, s),
+        RapValue::Float(f) => out.extend_from_slice(&f.to_le_bytes()),
+        RapValue::Int(i) => out.extend_from_slice(&i.to_le_bytes()),
+        RapValue::Int64(i) => out.extend_from_slice(&i.to_le_bytes()),
+        RapValue::Array(items) => write_array(out, items),
+    }
+}
+
+fn write_cstring(out: &mut Vec<u8>, s: &str) {
+    out.extend_from_slice(s.as_bytes());
+    out.push(0);
+}
+
+fn write_compressed_int(out: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rapify::derapify;
+
+    fn sample() -> RapConfig {
+        RapConfig {
+            root: RapClass {
+                parent: None,
+                entries: vec![
+                    RapEntry::Class {
+                        name: "CfgPatches".to_string(),
+                        class: RapClass {
+                            parent: None,
+                            entries: vec![RapEntry::Class {
+                                name: "shim".to_string(),
+                                class: RapClass {
+                                    parent: None,
+                                    entries: vec![
+                                        RapEntry::Array {
+                                            name: "requiredAddons".to_string(),
+                                            items: vec![RapValue::String("A3_Data_F".to_string())],
+                                            append: false,
+                                        },
+                                        RapEntry::Value {
+                                            name: "requiredVersion".to_string(),
+                                            value: RapValue::Float(0.1),
+                                        },
+                                    ],
+                                },
+                            }],
+                        },
+                    },
+                    RapEntry::Class {
+                        name: "CfgWeapons".to_string(),
+                        class: RapClass {
+                            parent: None,
+                            entries: vec![
+                                RapEntry::Extern("ItemCore".to_string()),
+                                RapEntry::Class {
+                                    name: "Shim_Item".to_string(),
+                                    class: RapClass {
+                                        parent: Some("ItemCore".to_string()),
+                                        entries: vec![
+                                            RapEntry::Value {
+                                                name: "scope".to_string(),
+                                                value: RapValue::Int(2),
+                                            },
+                                            RapEntry::Array {
+                                                name: "magazines".to_string(),
+                                                items: vec![RapValue::Array(vec![
+                                                    RapValue::Variable("true".to_string()),
+                                                    RapValue::Int64(1 << 40),
+                                                ])],
+                                                append: true,
+                                            },
+                                        ],
+                                    },
+                                },
+                                RapEntry::Delete("Old_Item".to_string()),
+                            ],
+                        },
+                    },
+                ],
+            },
+            enums: vec![("destructNo".to_string(), 0)],
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let config = sample();
+        let bytes = rapify(&config);
+        assert_eq!(derapify(&bytes).unwrap(), config);
+    }
+
+    #[test]
+    fn test_compressed_int() {
+        let mut out = Vec::new();
+        write_compressed_int(&mut out, 300);
+        assert_eq!(out, vec![0xac, 0x02]);
+    }
+}
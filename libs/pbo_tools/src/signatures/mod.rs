@@ -0,0 +1,314 @@
+//! BI signature support (`.bikey` / `.bisign`)
+//!
+//! Keys and signatures are parsed natively and verified offline against the
+//! PBO on disk. Both v2 and v3 signatures are supported.
+
+mod verify;
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+use crate::ops::{PboOperationError, PboOperationResult};
+
+pub use verify::{find_signatures, verify_pbo, verify_pbo_digest};
+
+/// Header of the key blob embedded in `.bikey` and `.bisign` files
+const KEY_BLOB_HEADER: [u8; 8] = [0x06, 0x02, 0x00, 0x00, 0x00, 0x24, 0x00, 0x00];
+
+/// Magic of the RSA public key structure
+const RSA1_MAGIC: &[u8; 4] = b"RSA1";
+
+/// Signature format version stored in a `.bisign`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BiSignVersion {
+    V2,
+    V3,
+}
+
+impl BiSignVersion {
+    fn from_u32(value: u32) -> PboOperationResult<Self> {
+        match value {
+            2 => Ok(Self::V2),
+            3 => Ok(Self::V3),
+            other => Err(PboOperationError::invalid_format(format!(
+                "unsupported signature version {}",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for BiSignVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::V2 => write!(f, "v2"),
+            Self::V3 => write!(f, "v3"),
+        }
+    }
+}
+
+/// Public key read from a `.bikey`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BiPublicKey {
+    /// Name of the signing authority
+    pub authority: String,
+    /// Key size in bits
+    pub length: u32,
+    /// Public exponent
+    pub exponent: u32,
+    /// Modulus
+    pub modulus: BigUint,
+}
+
+impl BiPublicKey {
+    /// Parse a key from the contents of a `.bikey`
+    pub fn from_bytes(data: &[u8]) -> PboOperationResult<Self> {
+        let mut reader = ByteReader::new(data);
+        let key = Self::read(&mut reader)?;
+        if !reader.is_empty() {
+            return Err(PboOperationError::invalid_format("trailing data after key"));
+        }
+        Ok(key)
+    }
+
+    /// Read and parse a `.bikey` file
+    pub fn from_file(path: &Path) -> PboOperationResult<Self> {
+        let data = read_file(path)?;
+        Self::from_bytes(&data)
+    }
+
+    /// Size of the key in bytes
+    pub fn size(&self) -> usize {
+        (self.length / 8) as usize
+    }
+
+    fn read(reader: &mut ByteReader) -> PboOperationResult<Self> {
+        let authority = reader.cstring()?;
+        let blob_len = reader.u32()? as usize;
+        let mut blob = ByteReader::new(reader.bytes(blob_len)?);
+
+        if blob.bytes(KEY_BLOB_HEADER.len())? != KEY_BLOB_HEADER {
+            return Err(PboOperationError::invalid_format("unexpected key blob header"));
+        }
+        if blob.bytes(RSA1_MAGIC.len())? != RSA1_MAGIC {
+            return Err(PboOperationError::invalid_format("key blob is not an RSA1 public key"));
+        }
+        let length = blob.u32()?;
+        if length == 0 || length % 8 != 0 {
+            return Err(PboOperationError::invalid_format(format!("invalid key length {}", length)));
+        }
+        let exponent = blob.u32()?;
+        let modulus = BigUint::from_bytes_le(blob.bytes((length / 8) as usize)?);
+
+        Ok(Self {
+            authority,
+            length,
+            exponent,
+            modulus,
+        })
+    }
+}
+
+/// Signature read from a `.bisign`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BiSignature {
+    /// Name of the signing authority
+    pub authority: String,
+    /// Public key the signature was made with
+    pub key: BiPublicKey,
+    /// Signature format version
+    pub version: BiSignVersion,
+    /// Signature over the PBO checksum
+    pub sig1: BigUint,
+    /// Signature over the checksum, file names and prefix
+    pub sig2: BigUint,
+    /// Signature over the file contents, file names and prefix
+    pub sig3: BigUint,
+}
+
+impl BiSignature {
+    /// Parse a signature from the contents of a `.bisign`
+    pub fn from_bytes(data: &[u8]) -> PboOperationResult<Self> {
+        let mut reader = ByteReader::new(data);
+        let key = BiPublicKey::read(&mut reader)?;
+        let sig1 = reader.length_prefixed()?;
+        let version = BiSignVersion::from_u32(reader.u32()?)?;
+        let sig2 = reader.length_prefixed()?;
+        let sig3 = reader.length_prefixed()?;
+
+        Ok(Self {
+            authority: key.authority.clone(),
+            key,
+            version,
+            sig1: BigUint::from_bytes_le(sig1),
+            sig2: BigUint::from_bytes_le(sig2),
+            sig3: BigUint::from_bytes_le(sig3),
+        })
+    }
+
+    /// Read and parse a `.bisign` file
+    pub fn from_file(path: &Path) -> PboOperationResult<Self> {
+        let data = read_file(path)?;
+        Self::from_bytes(&data)
+    }
+}
+
+/// Outcome of checking a PBO's signature
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SignatureStatus {
+    /// The signature matches the PBO and the key
+    Valid,
+    /// The signature was made with a different key
+    KeyMismatch { expected: String, found: String },
+    /// The signature does not match the PBO contents
+    Invalid { reason: String },
+    /// No signature was found for the PBO
+    Unsigned,
+    /// A signature was found but no key to check it against
+    UnknownKey { authority: String },
+}
+
+impl SignatureStatus {
+    /// Whether the PBO is signed by the expected key
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Self::Valid)
+    }
+}
+
+impl fmt::Display for SignatureStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Valid => write!(f, "valid"),
+            Self::KeyMismatch { expected, found } => {
+                write!(f, "signed with '{}' but expected '{}'", found, expected)
+            }
+            Self::Invalid { reason } => write!(f, "invalid: {}", reason),
+            Self::Unsigned => write!(f, "unsigned"),
+            Self::UnknownKey { authority } => write!(f, "no key found for '{}'", authority),
+        }
+    }
+}
+
+/// Result of verifying a PBO against a signature and key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureVerification {
+    /// Path of the verified PBO
+    pub pbo_path: PathBuf,
+    /// Path of the `.bisign` that was checked
+    pub signature_path: PathBuf,
+    /// Authority named in the signature
+    pub authority: String,
+    /// Signature format version
+    pub version: BiSignVersion,
+    /// Verification outcome
+    pub status: SignatureStatus,
+}
+
+fn read_file(path: &Path) -> PboOperationResult<Vec<u8>> {
+    if !path.exists() {
+        return Err(PboOperationError::file_not_found(path));
+    }
+    std::fs::read(path).map_err(|e| PboOperationError::io_error(format!("reading {}", path.display()), e))
+}
+
+/// Bounds-checked little endian reader
+struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    fn bytes(&mut self, len: usize) -> PboOperationResult<&'a [u8]> {
+        let end = self.pos.checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| {
+                PboOperationError::invalid_format(format!("unexpected end of data at offset {}", self.pos))
+            })?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> PboOperationResult<u32> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn length_prefixed(&mut self) -> PboOperationResult<&'a [u8]> {
+        let len = self.u32()? as usize;
+        self.bytes(len)
+    }
+
+    fn cstring(&mut self) -> PboOperationResult<String> {
+        let rest = &self.data[self.pos.min(self.data.len())..];
+        let len = rest.iter().position(|b| *b == 0).ok_or_else(|| {
+            PboOperationError::invalid_format(format!("unterminated string at offset {}", self.pos))
+        })?;
+        let s = String::from_utf8_lossy(&rest[..len]).into_owned();
+        self.pos += len + 1;
+        Ok(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_bytes(authority: &str, modulus: &[u8]) -> Vec<u8> {
+        let mut blob = KEY_BLOB_HEADER.to_vec();
+        blob.extend_from_slice(RSA1_MAGIC);
+        blob.extend_from_slice(&((modulus.len() * 8) as u32).to_le_bytes());
+        blob.extend_from_slice(&65537u32.to_le_bytes());
+        blob.extend_from_slice(modulus);
+
+        let mut out = authority.as_bytes().to_vec();
+        out.push(0);
+        out.extend_from_slice(&(blob.len() as u32).to_le_bytes());
+        out.extend_from_slice(&blob);
+        out
+    }
+
+    #[test]
+    fn test_parse_key() {
+        let key = BiPublicKey::from_bytes(&key_bytes("test", &[0x01, 0x02, 0x03, 0x04])).unwrap();
+        assert_eq!(key.authority, "test");
+        assert_eq!(key.length, 32);
+        assert_eq!(key.exponent, 65537);
+        assert_eq!(key.modulus, BigUint::from(0x0403_0201u32));
+    }
+
+    #[test]
+    fn test_parse_signature() {
+        let mut data = key_bytes("test", &[0x01, 0x02, 0x03, 0x04]);
+        for (i, sig) in [[1u8, 0, 0, 0], [2, 0, 0, 0], [3, 0, 0, 0]].iter().enumerate() {
+            if i == 1 {
+                data.extend_from_slice(&3u32.to_le_bytes());
+            }
+            data.extend_from_slice(&4u32.to_le_bytes());
+            data.extend_from_slice(sig);
+        }
+
+        let signature = BiSignature::from_bytes(&data).unwrap();
+        assert_eq!(signature.authority, "test");
+        assert_eq!(signature.version, BiSignVersion::V3);
+        assert_eq!(signature.sig1, BigUint::from(1u32));
+        assert_eq!(signature.sig3, BigUint::from(3u32));
+    }
+
+    #[test]
+    fn test_truncated_key_rejected() {
+        let data = key_bytes("test", &[0x01, 0x02, 0x03, 0x04]);
+        assert!(BiPublicKey::from_bytes(&data[..data.len() - 2]).is_err());
+    }
+}
@@ -0,0 +1,226 @@
+//! Verification of a PBO against a BI signature
+
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use log::debug;
+use num_bigint::BigUint;
+use sha1::{Digest, Sha1};
+
+use super::{BiPublicKey, BiSignVersion, BiSignature, SignatureStatus};
+use crate::ops::integrity;
+use crate::ops::{PboDigest, PboOperationError, PboOperationResult};
+
+/// DER prefix of a SHA-1 `DigestInfo` used in the PKCS#1 v1.5 padding
+const SHA1_DIGEST_INFO: [u8; 15] = [
+    0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00, 0x04, 0x14,
+];
+
+/// Extensions whose contents are covered by a v3 signature
+const V3_HASHED_EXTENSIONS: &[&str] = &[
+    "sqf", "inc", "bikb", "ext", "fsm", "sqm", "hpp", "cfg", "sqs", "h", "sqfc",
+];
+
+/// Extensions whose contents are left out of a v2 signature
+const V2_SKIPPED_EXTENSIONS: &[&str] = &[
+    "paa", "jpg", "p3d", "tga", "rvmat", "lip", "ogg", "wss", "png", "rtm", "pac", "fxy", "wrp",
+];
+
+/// Find the `.bisign` files sitting next to a PBO (`<name>.pbo.<authority>.bisign`)
+pub fn find_signatures(pbo_path: &Path) -> Vec<PathBuf> {
+    let (Some(dir), Some(file_name)) = (pbo_path.parent(), pbo_path.file_name()) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}.", file_name.to_string_lossy().to_lowercase());
+
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut signatures: Vec<PathBuf> = read_dir
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+            path.is_file() && name.starts_with(&prefix) && name.ends_with(".bisign")
+        })
+        .collect();
+    signatures.sort();
+    signatures
+}
+
+/// Verify a PBO against a signature made with `key`
+///
+/// Returns `KeyMismatch` when the signature was made with a different key and
+/// `Invalid` when any of the three signed hashes does not match the PBO.
+///
+/// # Errors
+///
+/// Returns an error if the PBO cannot be read.
+pub fn verify_pbo(
+    pbo_path: &Path,
+    signature: &BiSignature,
+    key: &BiPublicKey,
+) -> PboOperationResult<SignatureStatus> {
+    if signature.key != *key {
+        return Ok(key_mismatch(signature, key));
+    }
+    verify_pbo_digest(pbo_path, signature, key, &integrity::digest_pbo(pbo_path)?)
+}
+
+/// Verify a PBO against a signature using a digest taken earlier
+///
+/// Callers that already read the PBO through [`digest_pbo`](crate::ops::digest_pbo)
+/// avoid reading the whole file again for the checksum.
+///
+/// # Errors
+///
+/// Returns an error if the PBO cannot be read.
+pub fn verify_pbo_digest(
+    pbo_path: &Path,
+    signature: &BiSignature,
+    key: &BiPublicKey,
+    digest: &PboDigest,
+) -> PboOperationResult<SignatureStatus> {
+    if signature.key != *key {
+        return Ok(key_mismatch(signature, key));
+    }
+
+    let layout = integrity::read_layout(pbo_path)?;
+    if !layout.has_checksum_trailer() {
+        return Ok(invalid("PBO has no checksum trailer"));
+    }
+    let checksum = match (digest.checksum_valid, digest.checksum) {
+        (Some(true), Some(checksum)) => checksum,
+        (Some(false), _) => return Ok(invalid("PBO checksum does not match its contents")),
+        _ => return Ok(invalid("PBO has no checksum trailer")),
+    };
+
+    let mut entries: Vec<_> = layout.entries.iter().filter(|e| e.data_size > 0).collect();
+    entries.sort_by_key(|e| e.name.to_lowercase());
+
+    let name_hash = {
+        let mut hasher = Sha1::new();
+        for entry in &entries {
+            hasher.update(entry.name.replace('/', "\\").to_lowercase().as_bytes());
+        }
+        hasher.finalize()
+    };
+
+    let prefix = match layout.extension("prefix") {
+        Some(prefix) if !prefix.is_empty() && !prefix.ends_with('\\') => format!("{}\\", prefix),
+        Some(prefix) => prefix.to_string(),
+        None => String::new(),
+    };
+
+    let file_hash = hash_files(pbo_path, &entries, signature.version)?;
+
+    let hash1 = checksum;
+    let hash2 = Sha1::new()
+        .chain_update(hash1)
+        .chain_update(name_hash)
+        .chain_update(prefix.as_bytes())
+        .finalize();
+    let hash3 = Sha1::new()
+        .chain_update(file_hash)
+        .chain_update(name_hash)
+        .chain_update(prefix.as_bytes())
+        .finalize();
+
+    let checks = [
+        (&signature.sig1, hash1.as_slice(), "checksum"),
+        (&signature.sig2, hash2.as_slice(), "file names"),
+        (&signature.sig3, hash3.as_slice(), "file contents"),
+    ];
+    for (sig, hash, what) in checks {
+        if !signature_matches(key, sig, hash) {
+            debug!("Signature over {} does not match for {}", what, pbo_path.display());
+            return Ok(invalid(format!("signature over the {} does not match", what)));
+        }
+    }
+
+    Ok(SignatureStatus::Valid)
+}
+
+fn key_mismatch(signature: &BiSignature, key: &BiPublicKey) -> SignatureStatus {
+    SignatureStatus::KeyMismatch {
+        expected: key.authority.clone(),
+        found: signature.authority.clone(),
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> SignatureStatus {
+    SignatureStatus::Invalid { reason: reason.into() }
+}
+
+/// Hash the contents of the entries covered by the given signature version
+fn hash_files(
+    pbo_path: &Path,
+    entries: &[&integrity::EntryLayout],
+    version: BiSignVersion,
+) -> PboOperationResult<[u8; 20]> {
+    let mut reader = BufReader::new(
+        File::open(pbo_path).map_err(|e| PboOperationError::io_error("opening PBO file", e))?,
+    );
+    let mut hasher = Sha1::new();
+    let mut hashed_any = false;
+
+    for entry in entries {
+        let file_name = entry.name.rsplit(['\\', '/']).next().unwrap_or(&entry.name);
+        let extension = file_name.rsplit_once('.')
+            .map(|(_, ext)| ext.to_lowercase())
+            .unwrap_or_default();
+        let covered = match version {
+            BiSignVersion::V2 => !V2_SKIPPED_EXTENSIONS.contains(&extension.as_str()),
+            BiSignVersion::V3 => V3_HASHED_EXTENSIONS.contains(&extension.as_str()),
+        };
+        if !covered {
+            continue;
+        }
+
+        reader.seek(SeekFrom::Start(entry.data_offset))
+            .map_err(|e| PboOperationError::io_error("seeking PBO file", e))?;
+        let mut limited = (&mut reader).take(entry.data_size as u64);
+        let copied = std::io::copy(&mut limited, &mut hasher)
+            .map_err(|e| PboOperationError::io_error("hashing PBO entry", e))?;
+        if copied != entry.data_size as u64 {
+            return Err(PboOperationError::invalid_format(format!(
+                "entry {} is truncated",
+                entry.name
+            )));
+        }
+        hashed_any = true;
+    }
+
+    if !hashed_any {
+        hasher.update(match version {
+            BiSignVersion::V2 => b"nothing".as_slice(),
+            BiSignVersion::V3 => b"gnihton".as_slice(),
+        });
+    }
+
+    Ok(hasher.finalize().into())
+}
+
+/// Check an RSA PKCS#1 v1.5 signature over a SHA-1 digest
+fn signature_matches(key: &BiPublicKey, signature: &BigUint, hash: &[u8]) -> bool {
+    let size = key.size();
+    let padding_len = SHA1_DIGEST_INFO.len() + hash.len() + 3;
+    if size < padding_len + 8 {
+        return false;
+    }
+
+    let mut expected = Vec::with_capacity(size);
+    expected.extend_from_slice(&[0x00, 0x01]);
+    expected.resize(size - SHA1_DIGEST_INFO.len() - hash.len() - 1, 0xff);
+    expected.push(0x00);
+    expected.extend_from_slice(&SHA1_DIGEST_INFO);
+    expected.extend_from_slice(hash);
+
+    let decrypted = signature.modpow(&BigUint::from(key.exponent), &key.modulus).to_bytes_be();
+    if decrypted.len() > size {
+        return false;
+    }
+    let mut padded = vec![0u8; size - decrypted.len()];
+    padded.extend_from_slice(&decrypted);
+    padded == expected
+}
@@ -0,0 +1,523 @@
+//! Virtual P-drive built from PBO prefixes
+//!
+//! The game addresses files by their path on a virtual P drive, such as
+//! `\x\cba\addons\main\script_macros.hpp`: the `prefix` header extension of
+//! the PBO holding the file followed by the entry's path inside it. This
+//! module indexes PBOs, or directories extracted from them, by those paths so
+//! they can be resolved and read without unpacking everything to a real P
+//! drive.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use hemtt_pbo::ReadablePbo;
+use log::{debug, trace, warn};
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::ops::integrity;
+use crate::ops::{is_safe_entry_path, PboOperationError, PboOperationResult};
+
+/// Name of the file declaring the prefix of an unpacked addon directory
+pub const PBOPREFIX_FILE: &str = "$PBOPREFIX$";
+
+/// Extensions that can be the target of a preprocessor `#include`
+pub const INCLUDE_EXTENSIONS: &[&str] = &["hpp", "h", "hh", "hxx", "inc", "sqh", "cpp", "ext"];
+
+/// Where the contents of a virtual file live
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VfsSource {
+    /// An entry inside a PBO
+    Pbo {
+        /// Path of the PBO on disk
+        pbo_path: PathBuf,
+        /// Name of the entry as stored in the PBO header
+        entry: String,
+    },
+    /// A file on disk, e.g. from an extraction cache
+    File(PathBuf),
+}
+
+/// A file on the virtual P drive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VfsEntry {
+    /// Game path without the leading backslash, in its original case
+    pub game_path: String,
+    /// Where the file's contents are read from
+    pub source: VfsSource,
+}
+
+/// A PBO or directory mounted on the virtual P drive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VfsMount {
+    /// Prefix the source is mounted at (may be empty)
+    pub prefix: String,
+    /// PBO file or directory that was mounted
+    pub source: PathBuf,
+    /// Number of files the mount added to the drive
+    pub file_count: usize,
+}
+
+/// Index of game paths to the PBO entries or files providing them
+///
+/// Paths are matched case-insensitively and with either slash direction. When
+/// two mounts provide the same path, the one mounted first is kept.
+///
+/// # Example
+///
+/// ```no_run
+/// use pbo_tools::vfs::VirtualPDrive;
+/// use std::path::Path;
+///
+/// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut drive = VirtualPDrive::new();
+/// drive.mount_pbos_in(Path::new("@CBA_A3/addons"));
+///
+/// if drive.exists(r"\x\cba\addons\main\script_macros.hpp") {
+///     let data = drive.read(r"\x\cba\addons\main\script_macros.hpp")?;
+///     println!("{} bytes", data.len());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct VirtualPDrive {
+    entries: HashMap<String, VfsEntry>,
+    mounts: Vec<VfsMount>,
+}
+
+impl VirtualPDrive {
+    /// Create an empty drive
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mount a PBO at the prefix stored in its header
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the PBO header cannot be read.
+    pub fn mount_pbo(&mut self, pbo_path: &Path) -> PboOperationResult<&VfsMount> {
+        let layout = integrity::read_layout(pbo_path)?;
+        let prefix = layout.extension("prefix").unwrap_or_default().to_string();
+
+        let files = layout.entries.iter().map(|entry| {
+            let source = VfsSource::Pbo {
+                pbo_path: pbo_path.to_path_buf(),
+                entry: entry.name.clone(),
+            };
+            (entry.name.as_str(), source)
+        });
+        let file_count = self.insert_all(&prefix, files);
+
+        debug!("Mounted {} at \\{} ({} files)", pbo_path.display(), prefix, file_count);
+        Ok(self.push_mount(prefix, pbo_path, file_count))
+    }
+
+    /// Mount every PBO found below a directory
+    ///
+    /// PBOs that cannot be read are skipped with a warning. Returns the number
+    /// of PBOs mounted.
+    pub fn mount_pbos_in(&mut self, dir: &Path) -> usize {
+        let mut mounted = 0;
+        for pbo_path in crate::ops::grep::collect_pbos(&[dir.to_path_buf()]) {
+            match self.mount_pbo(&pbo_path) {
+                Ok(_) => mounted += 1,
+                Err(e) => warn!("Failed to mount {}: {}", pbo_path.display(), e),
+            }
+        }
+        mounted
+    }
+
+    /// Mount an unpacked addon directory
+    ///
+    /// The prefix is taken from `prefix` if given, otherwise from a
+    /// `$PBOPREFIX$` file in the directory, and is empty if neither exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory does not exist or the prefix file
+    /// cannot be read.
+    pub fn mount_directory(&mut self, dir: &Path, prefix: Option<&str>) -> PboOperationResult<&VfsMount> {
+        if !dir.is_dir() {
+            return Err(PboOperationError::file_not_found(dir));
+        }
+
+        let prefix = match prefix {
+            Some(prefix) => prefix.to_string(),
+            None => read_prefix_file(dir)?.unwrap_or_default(),
+        };
+
+        let files: Vec<PathBuf> = WalkDir::new(dir)
+            .follow_links(true)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file() && entry.file_name() != PBOPREFIX_FILE)
+            .filter_map(|entry| entry.path().strip_prefix(dir).ok().map(Path::to_path_buf))
+            .collect();
+        let file_count = self.mount_files(&prefix, dir, &files);
+
+        debug!("Mounted directory {} at \\{} ({} files)", dir.display(), prefix, file_count);
+        Ok(self.push_mount(prefix, dir, file_count))
+    }
+
+    /// Mount files extracted from a PBO at the PBO's prefix
+    ///
+    /// `files` are relative to `base_dir` and laid out as they were inside the
+    /// PBO, which is how the extraction cache stores them. Returns the number
+    /// of files added.
+    pub fn mount_files(&mut self, prefix: &str, base_dir: &Path, files: &[PathBuf]) -> usize {
+        let names: Vec<(String, PathBuf)> = files.iter()
+            .map(|file| (file.to_string_lossy().into_owned(), base_dir.join(file)))
+            .collect();
+        let file_count = self.insert_all(
+            prefix,
+            names.iter().map(|(name, path)| (name.as_str(), VfsSource::File(path.clone()))),
+        );
+        trace!("Mounted {} files from {} at \\{}", file_count, base_dir.display(), prefix);
+        file_count
+    }
+
+    /// Look up the entry providing a game path
+    pub fn resolve(&self, game_path: &str) -> Option<&VfsEntry> {
+        self.entries.get(&normalize_game_path(game_path))
+    }
+
+    /// Whether a game path is provided by any mount
+    pub fn exists(&self, game_path: &str) -> bool {
+        self.resolve(game_path).is_some()
+    }
+
+    /// Read the contents of a game path
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path is not on the drive or its source cannot
+    /// be read.
+    pub fn read(&self, game_path: &str) -> PboOperationResult<Vec<u8>> {
+        let entry = self.resolve(game_path)
+            .ok_or_else(|| PboOperationError::file_not_found_in_pbo(game_path))?;
+        read_source(&entry.source)
+    }
+
+    /// Mounted PBOs and directories in mount order
+    pub fn mounts(&self) -> &[VfsMount] {
+        &self.mounts
+    }
+
+    /// All files on the drive, in no particular order
+    pub fn entries(&self) -> impl Iterator<Item = &VfsEntry> {
+        self.entries.values()
+    }
+
+    /// Number of files on the drive
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the drive has no files
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Write files with the given extensions to `output_dir`, laid out by game path
+    ///
+    /// The resulting directory behaves like a P drive and can be handed to the
+    /// HPP parser as an include root so `#include "\x\..."` lines resolve.
+    /// An empty extension list writes every file. Written files take the
+    /// modification time of their source, and files on disk whose source has
+    /// the same size and modification time are left alone, so materializing
+    /// into the same directory again only rewrites what changed. Entries read
+    /// from PBOs are always written. Returns the number of files written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be read or written.
+    pub fn materialize(&self, output_dir: &Path, extensions: &[&str]) -> PboOperationResult<usize> {
+        let mut written = 0;
+        let mut unchanged = 0;
+        for entry in self.entries.values() {
+            if !has_extension(&entry.game_path, extensions) {
+                continue;
+            }
+            let Some(target) = materialized_path(output_dir, &entry.game_path) else {
+                warn!("Skipping virtual file with unsafe path '{}'", entry.game_path);
+                continue;
+            };
+
+            let stamp = source_stamp(&entry.source);
+            if stamp.is_some() && stamp == file_stamp(&target) {
+                unchanged += 1;
+                continue;
+            }
+
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| PboOperationError::io_error("creating output directory", e))?;
+            }
+            let data = read_source(&entry.source)?;
+            std::fs::write(&target, data)
+                .map_err(|e| PboOperationError::io_error("writing virtual file", e))?;
+            if let Some((_, modified)) = stamp {
+                File::options().write(true).open(&target)
+                    .and_then(|file| file.set_modified(modified))
+                    .map_err(|e| PboOperationError::io_error("setting modification time", e))?;
+            }
+            written += 1;
+        }
+
+        debug!("Materialized {} files into {} ({} unchanged)", written, output_dir.display(), unchanged);
+        Ok(written)
+    }
+
+    /// Remove files below `output_dir` that [`materialize`](Self::materialize)
+    /// with the same extensions would not write
+    ///
+    /// Empty directories left behind are removed too. Returns the number of
+    /// files removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file or directory cannot be removed.
+    pub fn remove_stale(&self, output_dir: &Path, extensions: &[&str]) -> PboOperationResult<usize> {
+        let mut removed = 0;
+        let mut dirs = Vec::new();
+        for entry in WalkDir::new(output_dir).min_depth(1).into_iter().filter_map(|e| e.ok()) {
+            if entry.file_type().is_dir() {
+                dirs.push(entry.into_path());
+                continue;
+            }
+            let Ok(relative) = entry.path().strip_prefix(output_dir) else {
+                continue;
+            };
+            let game_path = relative.to_string_lossy().replace('/', "\\");
+            let keep = has_extension(&game_path, extensions)
+                && self.resolve(&game_path).is_some_and(|vfs_entry| {
+                    materialized_path(output_dir, &vfs_entry.game_path).as_deref() == Some(entry.path())
+                });
+            if !keep {
+                std::fs::remove_file(entry.path())
+                    .map_err(|e| PboOperationError::io_error("removing stale file", e))?;
+                removed += 1;
+            }
+        }
+
+        // Deepest first so parents are empty by the time they are checked
+        for dir in dirs.iter().rev() {
+            if std::fs::read_dir(dir).map(|mut d| d.next().is_none()).unwrap_or(false) {
+                std::fs::remove_dir(dir)
+                    .map_err(|e| PboOperationError::io_error("removing empty directory", e))?;
+            }
+        }
+
+        debug!("Removed {} stale files from {}", removed, output_dir.display());
+        Ok(removed)
+    }
+
+    fn insert_all<'a>(&mut self, prefix: &str, files: impl Iterator<Item = (&'a str, VfsSource)>) -> usize {
+        let mut added = 0;
+        for (name, source) in files {
+            let game_path = join_game_path(prefix, name);
+            let key = normalize_game_path(&game_path);
+            if let Some(existing) = self.entries.get(&key) {
+                trace!("{} is already provided by {:?}", game_path, existing.source);
+                continue;
+            }
+            self.entries.insert(key, VfsEntry { game_path, source });
+            added += 1;
+        }
+        added
+    }
+
+    fn push_mount(&mut self, prefix: String, source: &Path, file_count: usize) -> &VfsMount {
+        self.mounts.push(VfsMount {
+            prefix,
+            source: source.to_path_buf(),
+            file_count,
+        });
+        self.mounts.last().expect("mount was just pushed")
+    }
+}
+
+/// Normalize a game path for lookups
+///
+/// Separators become backslashes, leading separators are dropped and ASCII
+/// letters are lowercased, matching how the game compares paths.
+pub fn normalize_game_path(path: &str) -> String {
+    path.replace('/', "\\")
+        .trim_start_matches('\\')
+        .to_ascii_lowercase()
+}
+
+/// Read the `prefix` header extension of a PBO
+///
+/// # Errors
+///
+/// Returns an error if the PBO header cannot be read.
+pub fn read_prefix(pbo_path: &Path) -> PboOperationResult<Option<String>> {
+    let layout = integrity::read_layout(pbo_path)?;
+    Ok(layout.extension("prefix").map(|prefix| prefix.trim_matches('\\').to_string()))
+}
+
+fn join_game_path(prefix: &str, name: &str) -> String {
+    let prefix = prefix.replace('/', "\\");
+    let prefix = prefix.trim_matches('\\');
+    let name = name.replace('/', "\\");
+    let name = name.trim_start_matches('\\');
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}\\{}", prefix, name)
+    }
+}
+
+fn has_extension(game_path: &str, extensions: &[&str]) -> bool {
+    let extension = game_path.rsplit_once('.')
+        .map(|(_, ext)| ext.to_lowercase())
+        .unwrap_or_default();
+    extensions.is_empty() || extensions.contains(&extension.as_str())
+}
+
+/// Where a game path is written below `output_dir`, or `None` if it would escape it
+fn materialized_path(output_dir: &Path, game_path: &str) -> Option<PathBuf> {
+    // Prefixes and entry names come from untrusted PBO headers
+    let relative = game_path.trim_start_matches(['\\', '/']);
+    if !is_safe_entry_path(relative) {
+        return None;
+    }
+    Some(relative
+        .split('\\')
+        .filter(|part| !part.is_empty())
+        .fold(output_dir.to_path_buf(), |path, part| path.join(part)))
+}
+
+/// Size and modification time of a file on disk
+fn file_stamp(path: &Path) -> Option<(u64, SystemTime)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+/// Size and modification time of a source, if it can be had without reading it
+fn source_stamp(source: &VfsSource) -> Option<(u64, SystemTime)> {
+    match source {
+        VfsSource::File(path) => file_stamp(path),
+        VfsSource::Pbo { .. } => None,
+    }
+}
+
+fn read_prefix_file(dir: &Path) -> PboOperationResult<Option<String>> {
+    let path = dir.join(PBOPREFIX_FILE);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| PboOperationError::io_error("reading $PBOPREFIX$", e))?;
+    // Either a bare prefix or `prefix=...` lines as written by some tools
+    Ok(content.lines()
+        .map(str::trim)
+        .find_map(|line| match line.split_once('=') {
+            Some((key, value)) if key.trim().eq_ignore_ascii_case("prefix") => Some(value.trim()),
+            Some(_) => None,
+            None if !line.is_empty() => Some(line),
+            None => None,
+        })
+        .map(|prefix| prefix.trim_matches('\\').to_string()))
+}
+
+fn read_source(source: &VfsSource) -> PboOperationResult<Vec<u8>> {
+    match source {
+        VfsSource::File(path) => std::fs::read(path)
+            .map_err(|e| PboOperationError::io_error("reading virtual file", e)),
+        VfsSource::Pbo { pbo_path, entry } => {
+            let file = File::open(pbo_path)
+                .map_err(|e| PboOperationError::io_error("opening PBO file", e))?;
+            let mut pbo = ReadablePbo::from(file)
+                .map_err(PboOperationError::from)?;
+            let mut pbo_file = pbo.file(entry)
+                .map_err(PboOperationError::from)?
+                .ok_or_else(|| PboOperationError::file_not_found_in_pbo(entry.as_str()))?;
+
+            let mut buffer = Vec::new();
+            pbo_file.read_to_end(&mut buffer)
+                .map_err(|e| PboOperationError::io_error("reading file data", e))?;
+            Ok(buffer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_game_path() {
+        assert_eq!(normalize_game_path(r"\x\CBA\addons\main\script_macros.hpp"), r"x\cba\addons\main\script_macros.hpp");
+        assert_eq!(normalize_game_path("/a3/weapons_f/data/foo.paa"), r"a3\weapons_f\data\foo.paa");
+        assert_eq!(join_game_path(r"\x\cba\addons\main\", "script_macros.hpp"), r"x\cba\addons\main\script_macros.hpp");
+        assert_eq!(join_game_path("", "data/foo.paa"), r"data\foo.paa");
+    }
+
+    #[test]
+    fn test_mount_directory_uses_pboprefix() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(PBOPREFIX_FILE), "x\\cba\\addons\\main\n").unwrap();
+        std::fs::write(dir.path().join("script_macros.hpp"), "#define CBA 1").unwrap();
+
+        let mut drive = VirtualPDrive::new();
+        let mount = drive.mount_directory(dir.path(), None).unwrap();
+        assert_eq!(mount.prefix, r"x\cba\addons\main");
+        assert_eq!(mount.file_count, 1);
+
+        assert!(!drive.exists(r"\x\cba\addons\main\$PBOPREFIX$"));
+        assert_eq!(drive.read(r"\X\CBA\addons\main\script_macros.hpp").unwrap(), b"#define CBA 1");
+        assert!(drive.read(r"\x\cba\addons\main\missing.hpp").is_err());
+    }
+
+    #[test]
+    fn test_materialize_skips_unsafe_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("script_macros.hpp"), "#define CBA 1").unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let pdrive = output.path().join("pdrive");
+
+        let mut drive = VirtualPDrive::new();
+        drive.mount_directory(dir.path(), Some(r"..\..\escaped")).unwrap();
+        drive.mount_directory(dir.path(), Some(r"x\cba\addons\main")).unwrap();
+
+        assert_eq!(drive.materialize(&pdrive, &["hpp"]).unwrap(), 1);
+        assert!(pdrive.join("x/cba/addons/main/script_macros.hpp").is_file());
+        assert!(!output.path().join("escaped").exists());
+    }
+
+    #[test]
+    fn test_materialize_skips_unchanged_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("script_macros.hpp"), "#define CBA 1").unwrap();
+        std::fs::write(dir.path().join("script_component.hpp"), "#define COMPONENT main").unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let pdrive = output.path().join("pdrive");
+
+        let mut drive = VirtualPDrive::new();
+        drive.mount_directory(dir.path(), Some(r"x\cba\addons\main")).unwrap();
+        assert_eq!(drive.materialize(&pdrive, &["hpp"]).unwrap(), 2);
+        assert_eq!(drive.materialize(&pdrive, &["hpp"]).unwrap(), 0);
+
+        // A changed source is written again
+        std::fs::write(dir.path().join("script_macros.hpp"), "#define CBA 22").unwrap();
+        assert_eq!(drive.materialize(&pdrive, &["hpp"]).unwrap(), 1);
+        assert_eq!(
+            std::fs::read_to_string(pdrive.join("x/cba/addons/main/script_macros.hpp")).unwrap(),
+            "#define CBA 22"
+        );
+
+        // Files no longer on the drive are removed along with empty directories
+        std::fs::create_dir_all(pdrive.join("x/old")).unwrap();
+        std::fs::write(pdrive.join("x/old/gone.hpp"), "").unwrap();
+        std::fs::write(pdrive.join("x/cba/addons/main/notes.txt"), "").unwrap();
+        assert_eq!(drive.remove_stale(&pdrive, &["hpp"]).unwrap(), 2);
+        assert!(!pdrive.join("x/old").exists());
+        assert!(pdrive.join("x/cba/addons/main/script_component.hpp").is_file());
+    }
+}
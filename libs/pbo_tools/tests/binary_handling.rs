@@ -1,5 +1,7 @@
 use pbo_tools::core::PboConfig;
-use pbo_tools::fs::{convert_binary_file, process_binary_files};
+use pbo_tools::fs::{convert_binary_file, derapify_file, process_binary_files, write_rapified_file};
+use pbo_tools::rapify::{derapify, is_rapified};
+use pbo_tools::{HemttPboOperations, PboOperations};
 use std::path::Path;
 use tempfile::TempDir;
 use std::fs;
@@ -108,4 +110,53 @@ fn test_binary_conversion_with_custom_mappings() {
 
     assert!(!input_path.exists(), "Binary file should be removed: {:?}", input_path);
     assert!(output_path.exists(), "Target file should exist: {:?}", output_path);
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_rapified_config_conversion() {
+    init();
+    let temp_dir = TempDir::new().unwrap();
+    let input_path = temp_dir.path().join("config.bin");
+    let output_path = temp_dir.path().join("config.cpp");
+
+    let ops = HemttPboOperations::new();
+    let data = ops.read_file(Path::new("tests/data/headgear_pumpkin.pbo"), "config.bin").await.unwrap();
+    assert!(is_rapified(&data), "Fixture config.bin should be rapified");
+    fs::write(&input_path, &data).unwrap();
+
+    convert_binary_file(&input_path, &output_path).unwrap();
+
+    assert!(!input_path.exists(), "Binary file should be removed: {:?}", input_path);
+    let content = fs::read_to_string(&output_path).unwrap();
+    debug!("Derapified config:\n{}", content);
+    assert!(content.contains("class CfgPatches"));
+    assert!(content.contains("requiredAddons[] = {"));
+}
+
+#[test]
+fn test_derapify_file_skips_text() {
+    init();
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("mission.sqm");
+    fs::write(&path, "version = 53;").unwrap();
+
+    assert!(!derapify_file(&path).unwrap());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "version = 53;");
+}
+
+#[tokio::test]
+async fn test_rapified_config_round_trip() {
+    init();
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("config.bin");
+
+    let ops = HemttPboOperations::new();
+    let data = ops.read_file(Path::new("tests/data/mirrorform.pbo"), "config.bin").await.unwrap();
+    let config = derapify(&data).unwrap();
+
+    write_rapified_file(&config, &output_path).unwrap();
+
+    let written = fs::read(&output_path).unwrap();
+    assert_eq!(derapify(&written).unwrap(), config);
+    assert_eq!(written, data, "Re-encoding an unmodified config should be byte identical");
+}
@@ -1,6 +1,8 @@
 use pbo_tools::core::{PboApi, PboApiOps};
-use pbo_tools::ops::PboOperationError;
-use std::path::PathBuf;
+use pbo_tools::ops::{digest_pbo, PboOperationError};
+use pbo_tools::ops::types::ValidationIssueType;
+use sha1::{Digest, Sha1};
+use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
 #[tokio::test]
@@ -100,4 +102,54 @@ async fn test_timeout_error() {
         }
         Err(e) => panic!("Unexpected error: {:?}", e),
     }
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_checksum_mismatch_detected() {
+    let api = PboApi::builder()
+        .with_timeout(30)
+        .build();
+    let temp_dir = TempDir::new().unwrap();
+    let corrupted = temp_dir.path().join("mirrorform.pbo");
+
+    let valid = api.validate_pbo(&PathBuf::from("tests/data/mirrorform.pbo")).await.unwrap();
+    assert_eq!(valid.checksum_valid, Some(true));
+
+    // Flip a byte inside the data block, leaving the header intact
+    let mut data = std::fs::read("tests/data/mirrorform.pbo").unwrap();
+    let index = data.len() - 100;
+    data[index] ^= 0xff;
+    std::fs::write(&corrupted, &data).unwrap();
+
+    let validation = api.validate_pbo(&corrupted).await.unwrap();
+    assert_eq!(validation.checksum_valid, Some(false));
+    assert!(!validation.is_valid);
+    assert!(validation.errors.iter().any(|e| e.issue_type == ValidationIssueType::ChecksumMismatch));
+
+    // The single-pass digest agrees with the validation and hashes the whole file
+    let digest = digest_pbo(Path::new("tests/data/mirrorform.pbo")).unwrap();
+    assert_eq!(digest.checksum_valid, Some(true));
+    let digest = digest_pbo(&corrupted).unwrap();
+    assert_eq!(digest.checksum_valid, Some(false));
+    let expected: String = Sha1::digest(&data).iter().map(|b| format!("{:02x}", b)).collect();
+    assert_eq!(digest.content_hash, expected);
+}
+
+#[tokio::test]
+async fn test_deep_validation_detects_truncation() {
+    let api = PboApi::builder()
+        .with_timeout(30)
+        .build();
+    let temp_dir = TempDir::new().unwrap();
+    let truncated = temp_dir.path().join("mirrorform.pbo");
+
+    let valid = api.validate_pbo_deep(&PathBuf::from("tests/data/mirrorform.pbo")).await.unwrap();
+    assert!(valid.is_valid, "Fixture should pass deep validation: {:?}", valid.errors);
+
+    let data = std::fs::read("tests/data/mirrorform.pbo").unwrap();
+    std::fs::write(&truncated, &data[..data.len() / 2]).unwrap();
+
+    let validation = api.validate_pbo_deep(&truncated).await.unwrap();
+    assert!(!validation.is_valid);
+    assert!(validation.errors.iter().any(|e| e.issue_type == ValidationIssueType::FileSizeMismatch));
+}
@@ -1,5 +1,8 @@
 use pbo_tools::core::{PboApi, PboApiOps};
-use std::path::Path;
+use pbo_tools::ops::{ClassChangeKind, EntryChangeKind, PboGrepOptions, PboPackOptions};
+use pbo_tools::rapify::{self, RapClass, RapEntry};
+use pbo_tools::signatures::{self, SignatureStatus};
+use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 use std::fs;
 use log::debug;
@@ -238,4 +241,216 @@ async fn test_read_file_content() {
         assert!(!content.is_empty());
         debug!("Read {} bytes from file: {}", content.len(), first_file.file_path);
     }
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_pack_round_trip() {
+    let (api, temp_dir) = setup();
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+    let extract_dir = temp_dir.path().join("mirrorform");
+    let packed_pbo = temp_dir.path().join("mirrorform_repacked.pbo");
+
+    api.extract_all(test_pbo, &extract_dir).await.unwrap();
+    fs::write(extract_dir.join("notes.bak"), "scratch").unwrap();
+
+    let options = PboPackOptions::new()
+        .with_prefix("tc\\mirrorform")
+        .with_product("arma3")
+        .exclude("*.bak");
+    let summary = api.pack(&extract_dir, &packed_pbo, &options).await.unwrap();
+    assert_eq!(summary.excluded_count, 1);
+
+    // Same entries and contents as the original
+    let original: Vec<_> = api.list_contents(test_pbo).await.unwrap().into_iter().map(|f| (f.file_path, f.size)).collect();
+    let repacked: Vec<_> = api.list_contents(&packed_pbo).await.unwrap().into_iter().map(|f| (f.file_path, f.size)).collect();
+    assert_eq!(original, repacked);
+    for (file_path, _) in &original {
+        assert_eq!(
+            api.read_file(test_pbo, file_path).await.unwrap(),
+            api.read_file(&packed_pbo, file_path).await.unwrap(),
+            "Contents differ for {}", file_path
+        );
+    }
+
+    let properties = api.get_properties(&packed_pbo).await.unwrap();
+    assert_eq!(properties.prefix.as_deref(), Some("tc\\mirrorform"));
+    assert_eq!(properties.get_property("product").map(String::as_str), Some("arma3"));
+    assert_eq!(properties.checksum.as_deref(), Some(summary.checksum.as_str()));
+
+    let validation = api.validate_pbo(&packed_pbo).await.unwrap();
+    assert!(validation.is_valid, "Repacked PBO should be valid: {:?}", validation.errors);
+    assert!(validation.files_sorted);
+
+    // Packing again produces identical bytes
+    let second_pbo = temp_dir.path().join("mirrorform_second.pbo");
+    api.pack(&extract_dir, &second_pbo, &options).await.unwrap();
+    assert_eq!(fs::read(&packed_pbo).unwrap(), fs::read(&second_pbo).unwrap());
+
+    // Repacking over an existing PBO replaces it without leaving temporary files
+    api.pack(&extract_dir, &second_pbo, &options).await.unwrap();
+    assert_eq!(fs::read(&packed_pbo).unwrap(), fs::read(&second_pbo).unwrap());
+    let mut names: Vec<_> = fs::read_dir(temp_dir.path()).unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["mirrorform", "mirrorform_repacked.pbo", "mirrorform_second.pbo"]);
+}
+
+#[tokio::test]
+async fn test_verify_signature() {
+    let (api, temp_dir) = setup();
+    let test_pbo = Path::new("tests/data/headgear_pumpkin.pbo");
+    let signature = Path::new("tests/data/headgear_pumpkin.pbo.tc_arma.bisign");
+    let key = Path::new("tests/data/tc_arma.bikey");
+
+    assert_eq!(signatures::find_signatures(test_pbo), vec![signature.to_path_buf()]);
+    assert!(signatures::find_signatures(Path::new("tests/data/mirrorform.pbo")).is_empty());
+
+    let verification = api.verify_signature(test_pbo, signature, key).await.unwrap();
+    assert_eq!(verification.authority, "tc_arma");
+    assert_eq!(verification.status, SignatureStatus::Valid);
+
+    // Flipping a byte in the data block invalidates the signature
+    let tampered = temp_dir.path().join("headgear_pumpkin.pbo");
+    let mut bytes = fs::read(test_pbo).unwrap();
+    let index = bytes.len() - 100;
+    bytes[index] ^= 0xff;
+    fs::write(&tampered, bytes).unwrap();
+    let verification = api.verify_signature(&tampered, signature, key).await.unwrap();
+    assert!(matches!(verification.status, SignatureStatus::Invalid { .. }));
+
+    // A different key is reported as a mismatch
+    let other_key = temp_dir.path().join("other.bikey");
+    let mut bytes = fs::read(key).unwrap();
+    let index = bytes.len() - 1;
+    bytes[index] ^= 0xff;
+    fs::write(&other_key, bytes).unwrap();
+    let verification = api.verify_signature(test_pbo, signature, &other_key).await.unwrap();
+    assert!(matches!(verification.status, SignatureStatus::KeyMismatch { .. }));
+}
+
+#[tokio::test]
+async fn test_diff_pbo_versions() {
+    let (api, temp_dir) = setup();
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+    let old_dir = temp_dir.path().join("old");
+    let new_dir = temp_dir.path().join("new");
+    api.extract_all(test_pbo, &old_dir).await.unwrap();
+    api.extract_all(test_pbo, &new_dir).await.unwrap();
+
+    // Add a class to the rapified config, drop one file and add another
+    let mut config = rapify::derapify(&fs::read(new_dir.join("config.bin")).unwrap()).unwrap();
+    for entry in config.root.entries.iter_mut() {
+        if let RapEntry::Class { name, class } = entry {
+            if name == "CfgPatches" {
+                class.entries.push(RapEntry::Class {
+                    name: "TC_MIRROR_EXTRA".to_string(),
+                    class: RapClass { parent: None, entries: Vec::new() },
+                });
+            }
+        }
+    }
+    fs::write(new_dir.join("config.bin"), rapify::rapify(&config)).unwrap();
+    fs::remove_file(new_dir.join("logo_small.paa")).unwrap();
+    fs::write(new_dir.join("notes.txt"), "changelog").unwrap();
+
+    let options = PboPackOptions::new().with_prefix("tc\\mirrorform");
+    let old_pbo = temp_dir.path().join("old.pbo");
+    let new_pbo = temp_dir.path().join("new.pbo");
+    api.pack(&old_dir, &old_pbo, &options).await.unwrap();
+    api.pack(&new_dir, &new_pbo, &options).await.unwrap();
+
+    assert!(api.diff(&old_pbo, &old_pbo).await.unwrap().is_empty());
+
+    let diff = api.diff(&old_pbo, &new_pbo).await.unwrap();
+    let changes: Vec<_> = diff.entries.iter().map(|e| (e.path.as_str(), e.kind)).collect();
+    assert_eq!(changes, vec![
+        ("config.bin", EntryChangeKind::Modified),
+        ("logo_small.paa", EntryChangeKind::Removed),
+        ("notes.txt", EntryChangeKind::Added),
+    ]);
+
+    assert_eq!(diff.configs.len(), 1);
+    let config_diff = &diff.configs[0];
+    assert_eq!(config_diff.error, None);
+    assert_eq!(config_diff.changes.len(), 1);
+    assert_eq!(config_diff.changes[0].class_path, "CfgPatches/TC_MIRROR_EXTRA");
+    assert_eq!(config_diff.changes[0].kind, ClassChangeKind::Added { parent: None });
+}
+
+#[tokio::test]
+async fn test_grep_pbo_contents() {
+    let (api, _temp_dir) = setup();
+    let test_pbo = PathBuf::from("tests/data/mirrorform.pbo");
+    let grep = |paths: Vec<PathBuf>, options: PboGrepOptions| {
+        let api = &api;
+        async move {
+            let mut matches = Vec::new();
+            api.grep(&paths, &options, &mut |m| matches.push(m)).await.map(|_| matches)
+        }
+    };
+
+    // config.bin is rapified, so matches come from the decoded text
+    let matches = grep(vec![test_pbo.clone()], PboGrepOptions::new(r"class TC_MIRROR\b")).await.unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].pbo_path, test_pbo);
+    assert_eq!(matches[0].entry, "config.bin");
+    assert_eq!(matches[0].line_number, 3);
+    assert_eq!(matches[0].line.trim(), "class TC_MIRROR");
+
+    let options = PboGrepOptions::new("CLASS CFGPATCHES");
+    assert!(grep(vec![test_pbo.clone()], options.clone()).await.unwrap().is_empty());
+
+    // Directories are searched recursively for PBOs
+    let options = options.ignore_case(true);
+    let matches = grep(vec![PathBuf::from("tests/data")], options.clone()).await.unwrap();
+    let pbos: Vec<_> = matches.iter().map(|m| m.pbo_path.file_name().unwrap().to_str().unwrap()).collect();
+    assert_eq!(pbos, vec!["headgear_pumpkin.pbo", "mirrorform.pbo"]);
+
+    let options = options.with_path_glob("*.rvmat");
+    assert!(grep(vec![test_pbo.clone()], options).await.unwrap().is_empty());
+
+    assert!(grep(vec![test_pbo], PboGrepOptions::new("[unclosed")).await.is_err());
+}
+
+#[tokio::test]
+async fn test_salvage_damaged_pbo() {
+    let (api, temp_dir) = setup();
+    let original = fs::read("tests/data/mirrorform.pbo").unwrap();
+
+    // A broken checksum trailer does not cost any entries
+    let mut bad_checksum = original.clone();
+    *bad_checksum.last_mut().unwrap() ^= 0xff;
+    let bad_checksum_pbo = temp_dir.path().join("bad_checksum.pbo");
+    fs::write(&bad_checksum_pbo, &bad_checksum).unwrap();
+
+    let output_dir = temp_dir.path().join("checksum");
+    let report = api.salvage(&bad_checksum_pbo, &output_dir, "*").await.unwrap();
+    assert_eq!(report.checksum_valid, Some(false));
+    assert_eq!(report.recovered.len(), 7);
+    assert!(report.is_complete());
+    assert_eq!(
+        fs::read(output_dir.join("uniform/mirror.rvmat")).unwrap(),
+        api.read_file(Path::new("tests/data/mirrorform.pbo"), "uniform/mirror.rvmat").await.unwrap()
+    );
+
+    // Cutting the file inside mirror.p3d loses it and every entry stored after it
+    let truncated_pbo = temp_dir.path().join("truncated.pbo");
+    fs::write(&truncated_pbo, &original[..original.len() * 3 / 4]).unwrap();
+
+    let output_dir = temp_dir.path().join("truncated");
+    let report = api.salvage(&truncated_pbo, &output_dir, "*").await.unwrap();
+    assert!(report.header_error.is_none());
+    assert_eq!(report.recovered, vec![
+        "config.bin", "logo.paa", "logo_small.paa", "texHeaders.bin", "uniform/black.paa",
+    ]);
+    let lost: Vec<_> = report.lost.iter().map(|l| l.file_path.as_str()).collect();
+    assert_eq!(lost, vec!["uniform/mirror.p3d", "uniform/mirror.rvmat"]);
+    assert!(output_dir.join("config.bin").exists());
+    assert!(!output_dir.join("uniform/mirror.p3d").exists());
+
+    // Filters apply to salvage just like extraction
+    let report = api.salvage(&truncated_pbo, &temp_dir.path().join("filtered"), "uniform/*.rvmat").await.unwrap();
+    assert!(report.recovered.is_empty());
+    assert_eq!(report.lost.len(), 1);
+}
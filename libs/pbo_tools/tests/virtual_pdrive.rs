@@ -0,0 +1,63 @@
+use pbo_tools::core::{PboApi, PboApiOps};
+use pbo_tools::ops::PboPackOptions;
+use pbo_tools::vfs::{self, VfsSource, VirtualPDrive};
+use parser_hpp::{AdvancedProjectParser, PropertyValue};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+#[test]
+fn test_mount_pbos_by_prefix() {
+    let mut drive = VirtualPDrive::new();
+    assert_eq!(drive.mount_pbos_in(Path::new("tests/data")), 2);
+
+    let prefixes: Vec<_> = drive.mounts().iter().map(|m| m.prefix.as_str()).collect();
+    assert_eq!(prefixes, vec![r"tc\headgear_pumpkin", r"tc\mirrorform"]);
+
+    let entry = drive.resolve(r"\tc\mirrorform\uniform\mirror.p3d").unwrap();
+    assert_eq!(entry.game_path, r"tc\mirrorform\uniform\mirror.p3d");
+    assert!(matches!(&entry.source, VfsSource::Pbo { entry, .. } if entry == r"uniform\mirror.p3d"));
+
+    // Lookups ignore case and slash direction
+    assert!(drive.exists("tc/HEADGEAR_PUMPKIN/data/pumpkin_halloween_co.paa"));
+    assert!(!drive.exists(r"\tc\mirrorform\missing.paa"));
+
+    let config = drive.read(r"\tc\mirrorform\config.bin").unwrap();
+    assert!(pbo_tools::rapify::is_rapified(&config));
+
+    assert_eq!(vfs::read_prefix(Path::new("tests/data/mirrorform.pbo")).unwrap().as_deref(), Some(r"tc\mirrorform"));
+}
+
+#[tokio::test]
+async fn test_includes_resolve_through_virtual_pdrive() {
+    let temp_dir = TempDir::new().unwrap();
+    let api = PboApi::builder().with_timeout(30).build();
+
+    // Another mod shipping only its macros
+    let macros_dir = temp_dir.path().join("macros");
+    fs::create_dir_all(&macros_dir).unwrap();
+    fs::write(macros_dir.join("script_macros.hpp"), "#define OTHER_VALUE 789\n").unwrap();
+    let mods_dir = temp_dir.path().join("mods");
+    fs::create_dir_all(&mods_dir).unwrap();
+    let options = PboPackOptions::new().with_prefix(r"x\other\addons\main");
+    api.pack(&macros_dir, &mods_dir.join("other_main.pbo"), &options).await.unwrap();
+
+    let mut drive = VirtualPDrive::new();
+    assert_eq!(drive.mount_pbos_in(&mods_dir), 1);
+    let include_dir = temp_dir.path().join("include");
+    assert_eq!(drive.materialize(&include_dir, vfs::INCLUDE_EXTENSIONS).unwrap(), 1);
+
+    let project_dir = temp_dir.path().join("project");
+    fs::create_dir_all(project_dir.join("addons/main")).unwrap();
+    fs::write(
+        project_dir.join("addons/main/config.cpp"),
+        "#include \"\\x\\other\\addons\\main\\script_macros.hpp\"\nclass FromOtherMod { value = OTHER_VALUE; };",
+    ).unwrap();
+
+    let parser = AdvancedProjectParser::with_include_roots(&project_dir, None, &[include_dir]).unwrap();
+    let (classes, warnings) = parser.parse_file(Path::new("addons/main/config.cpp")).unwrap();
+    assert!(warnings.iter().all(|w| w.code != "PE12"));
+    assert_eq!(classes.len(), 1);
+    let value = classes[0].properties.iter().find(|p| p.name == "value").unwrap();
+    assert_eq!(value.value, PropertyValue::Number(789));
+}
@@ -17,6 +17,9 @@ use crate::{
     writers::ReportWriter,
 };
 
+/// Source file of a class and the line and column its definition starts at
+type ClassSource = (Option<String>, Option<(usize, usize)>);
+
 /// Coordinates the dependency analysis and reporting process
 pub struct ReportCoordinator<'a> {
     db: &'a DatabaseManager,
@@ -90,8 +93,8 @@ impl<'a> ReportCoordinator<'a> {
         let missions = self.mission_repo.get_all()?;
         info!("Processing {} missions...", missions.len());
 
-        let mut results: Vec<(String, String, Option<String>)> = Vec::new();
-        let mut class_source_cache: HashMap<String, Option<String>> = HashMap::new();
+        let mut results: Vec<(String, String, ClassSource)> = Vec::new();
+        let mut class_source_cache: HashMap<String, ClassSource> = HashMap::new();
         let mut processed_classes_for_mission: HashSet<(String, String)> = HashSet::new();
 
         for mission in missions {
@@ -103,25 +106,29 @@ impl<'a> ReportCoordinator<'a> {
                     continue; // Already processed this class for this mission
                 }
 
-                let source_path = match class_source_cache.get(&dep.class_name) {
-                    Some(cached_path) => cached_path.clone(),
+                let source = match class_source_cache.get(&dep.class_name) {
+                    Some(cached_source) => cached_source.clone(),
                     None => {
-                        let path = match self.class_repo.get(&dep.class_name)? {
+                        let source = match self.class_repo.get(&dep.class_name)? {
                             Some(class_model) => {
-                                if let Some(idx) = class_model.source_file_index {
+                                let path = if let Some(idx) = class_model.source_file_index {
                                     self.class_repo.get_source_path(idx)?
                                 } else {
                                     None
-                                }
+                                };
+                                let position = class_model
+                                    .location
+                                    .map(|location| (location.start_line, location.start_column));
+                                (path, position)
                             }
-                            None => None,
+                            None => (None, None),
                         };
-                        class_source_cache.insert(dep.class_name.clone(), path.clone());
-                        path
+                        class_source_cache.insert(dep.class_name.clone(), source.clone());
+                        source
                     }
                 };
 
-                results.push((mission.id.clone(), dep.class_name.clone(), source_path));
+                results.push((mission.id.clone(), dep.class_name.clone(), source));
                 processed_classes_for_mission.insert(mission_class_key);
             }
         }
@@ -132,16 +139,23 @@ impl<'a> ReportCoordinator<'a> {
             File::create(&report_path).map_err(|e| crate::error::ReporterError::Io(e))?;
         let mut writer = BufWriter::new(file);
 
-        writeln!(writer, "mission_id,class_name,source_path")
+        writeln!(writer, "mission_id,class_name,source_path,line,column")
             .map_err(|e| crate::error::ReporterError::Io(e))?;
 
-        for (mission_id, class_name, source_path) in results {
+        for (mission_id, class_name, (source_path, position)) in results {
             // Basic CSV escaping: wrap fields containing commas or quotes in double quotes, double internal quotes.
             // For simplicity, assuming mission_id and class_name won't have problematic characters.
             // Source path might, but often won't.
             let source_path_str = source_path.unwrap_or_else(|| "".to_string());
-            writeln!(writer, "{},{},{}", mission_id, class_name, source_path_str)
-                .map_err(|e| crate::error::ReporterError::Io(e))?;
+            let (line_str, column_str) = position
+                .map(|(line, column)| (line.to_string(), column.to_string()))
+                .unwrap_or_default();
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                mission_id, class_name, source_path_str, line_str, column_str
+            )
+            .map_err(|e| crate::error::ReporterError::Io(e))?;
         }
 
         writer
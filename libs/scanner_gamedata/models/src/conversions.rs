@@ -1,4 +1,8 @@
-use crate::PropertyValue;
+use std::collections::HashMap;
+
+use arma3_models::{GameDataClass, PropertyValue as ModelValue};
+
+use crate::{GameClass, PropertyValue};
 
 // Implement conversions from strings to PropertyValue
 impl From<String> for PropertyValue {
@@ -26,21 +30,87 @@ impl From<i32> for PropertyValue {
     }
 }
 
+impl From<f64> for PropertyValue {
+    fn from(value: f64) -> Self {
+        PropertyValue::Float(value)
+    }
+}
+
 // Implement conversions from arrays to PropertyValue
 impl From<Vec<String>> for PropertyValue {
     fn from(value: Vec<String>) -> Self {
-        PropertyValue::Array(value)
+        PropertyValue::Array(value.into_iter().map(PropertyValue::String).collect())
     }
 }
 
 impl<'a> From<&'a [String]> for PropertyValue {
     fn from(value: &'a [String]) -> Self {
-        PropertyValue::Array(value.to_vec())
+        PropertyValue::Array(value.iter().cloned().map(PropertyValue::String).collect())
     }
 }
 
 impl<'a> From<&'a [&str]> for PropertyValue {
     fn from(value: &'a [&str]) -> Self {
-        PropertyValue::Array(value.iter().map(|s| s.to_string()).collect())
+        PropertyValue::Array(value.iter().map(|s| PropertyValue::from(*s)).collect())
+    }
+}
+
+impl From<Vec<PropertyValue>> for PropertyValue {
+    fn from(value: Vec<PropertyValue>) -> Self {
+        PropertyValue::Array(value)
+    }
+}
+
+impl PropertyValue {
+    /// Convert the value to the model stored in the analysis database
+    ///
+    /// Nested classes are classes of their own there, so they have no value.
+    pub fn to_model_value(&self) -> Option<ModelValue> {
+        match self {
+            PropertyValue::String(value) => Some(ModelValue::String(value.clone())),
+            PropertyValue::Array(items) => Some(ModelValue::Array(
+                items.iter().filter_map(PropertyValue::to_model_value).collect(),
+            )),
+            PropertyValue::Number(value) => Some(ModelValue::Integer(*value)),
+            PropertyValue::Float(value) => Some(ModelValue::Number(*value)),
+            PropertyValue::Append(appended) => match appended.to_model_value()? {
+                ModelValue::Array(items) => Some(ModelValue::Append(items)),
+                item => Some(ModelValue::Append(vec![item])),
+            },
+            PropertyValue::Expression(expression) => Some(ModelValue::Expression(expression.clone())),
+            PropertyValue::Delete => Some(ModelValue::Delete),
+            PropertyValue::Class(_) => None,
+        }
+    }
+}
+
+impl GameClass {
+    /// Convert the class to the model stored in the analysis database
+    ///
+    /// Nested classes are left out of the properties, the scanner reports
+    /// them as classes of their own with this class as their container.
+    pub fn to_game_data_class(&self, source_file_index: Option<usize>) -> GameDataClass {
+        let mut properties = HashMap::new();
+        let mut property_locations = HashMap::new();
+        for property in &self.properties {
+            let Some(value) = property.value.to_model_value() else {
+                continue;
+            };
+            properties.insert(property.name.clone(), value);
+            if let Some(location) = property.location {
+                property_locations.insert(property.name.clone(), location);
+            }
+        }
+
+        GameDataClass {
+            name: self.name.clone(),
+            parent: self.parent.clone(),
+            container_class: self.container_class.clone(),
+            properties,
+            is_forward_declaration: self.is_forward_declaration,
+            source_file_index,
+            location: self.location,
+            property_locations,
+        }
     }
 }
@@ -1,9 +1,16 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 use std::path::{Path, PathBuf};
 
 // Include conversion modules
 pub mod conversions;
+pub mod patches;
+
+pub use patches::AddonPatch;
+
+/// Source locations are shared with the analysis models so they are stored as parsed
+pub use arma3_models::SourceLocation;
 
 /// Interface for file parsers
 pub trait FileParser: Send + Sync {
@@ -12,6 +19,52 @@ pub trait FileParser: Send + Sync {
 
     /// Get the name of the parser
     fn name(&self) -> &str;
+
+    /// Parse a single file, reporting why another parser had to be used
+    ///
+    /// Parsers without a fallback never report a reason.
+    fn parse_file_with_fallback(&self, file_path: &Path) -> ParsedFile {
+        ParsedFile {
+            classes: self.parse_file(file_path),
+            fallback_reason: None,
+        }
+    }
+
+    /// Parse a file another way after the caller gave up waiting for
+    /// [`FileParser::parse_file_with_fallback`], `reason` says why
+    ///
+    /// Parsers without a fallback return `None`.
+    fn parse_file_after_timeout(&self, _file_path: &Path, _reason: &str) -> Option<ParsedFile> {
+        None
+    }
+}
+
+/// Classes parsed from one file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedFile {
+    /// Classes found in the file
+    pub classes: Vec<GameClass>,
+
+    /// Why the file was parsed by a fallback parser, if it was
+    pub fallback_reason: Option<String>,
+}
+
+/// Parser that produced a class
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ParserKind {
+    /// Regex scanner, finds class declarations but no properties
+    Simple,
+    /// HEMTT-based parser with preprocessing and properties
+    Advanced,
+}
+
+impl fmt::Display for ParserKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParserKind::Simple => write!(f, "simple"),
+            ParserKind::Advanced => write!(f, "advanced"),
+        }
+    }
 }
 
 /// Represents a class in the game data
@@ -34,6 +87,14 @@ pub struct GameClass {
 
     /// Whether this is a forward declaration
     pub is_forward_declaration: bool,
+
+    /// Where the class is defined in `file_path`, if the parser knows
+    #[serde(default)]
+    pub location: Option<SourceLocation>,
+
+    /// Parser that produced the class, if recorded
+    #[serde(default)]
+    pub parsed_by: Option<ParserKind>,
 }
 
 impl GameClass {
@@ -46,6 +107,8 @@ impl GameClass {
             container_class: None,
             properties: Vec::new(),
             is_forward_declaration: false,
+            location: None,
+            parsed_by: None,
         }
     }
 
@@ -63,12 +126,14 @@ impl GameClass {
             container_class,
             properties: Vec::new(),
             is_forward_declaration: false,
+            location: None,
+            parsed_by: None,
         }
     }
 
     /// Add a property to the class
     pub fn add_property(&mut self, name: String, value: PropertyValue) {
-        self.properties.push(ClassProperty { name, value });
+        self.properties.push(ClassProperty { name, value, location: None });
     }
 
     /// Set the container class
@@ -85,6 +150,10 @@ pub struct ClassProperty {
 
     /// Property value
     pub value: PropertyValue,
+
+    /// Where the property is defined, if the parser knows
+    #[serde(default)]
+    pub location: Option<SourceLocation>,
 }
 
 /// Represents a property value
@@ -93,12 +162,25 @@ pub enum PropertyValue {
     /// String value
     String(String),
 
-    /// Array of strings
-    Array(Vec<String>),
+    /// Array whose items are `String`, `Number`, `Float` or `Array` values,
+    /// so arrays like `{{"a", 1}, {"b", 2.5}}` keep their structure and types
+    Array(Vec<PropertyValue>),
 
     /// Numeric value
     Number(i64),
 
+    /// Floating point value
+    Float(f64),
+
+    /// Array appended to the inherited value with `+=`
+    Append(Box<PropertyValue>),
+
+    /// Expression left unevaluated, as written in the config
+    Expression(String),
+
+    /// Nested class removed with `delete Name;`, the property name is the class
+    Delete,
+
     /// Nested class
     Class(Box<GameClass>),
 }
@@ -112,14 +194,23 @@ impl PropertyValue {
         }
     }
 
-    /// Get the value as an array, if it's an array
-    pub fn as_array(&self) -> Option<&[String]> {
+    /// Get the items of the value, if it's an array or an append of one
+    pub fn as_array(&self) -> Option<&[PropertyValue]> {
         match self {
             PropertyValue::Array(arr) => Some(arr),
+            PropertyValue::Append(appended) => appended.as_array(),
             _ => None,
         }
     }
 
+    /// Get the string items of the value, if it's an array or an append of one
+    ///
+    /// Items of other types, such as numbers or nested arrays, are skipped.
+    pub fn as_string_array(&self) -> Option<Vec<&str>> {
+        self.as_array()
+            .map(|items| items.iter().filter_map(PropertyValue::as_string).collect())
+    }
+
     /// Get the value as a number, if it's a number
     pub fn as_number(&self) -> Option<i64> {
         match self {
@@ -128,6 +219,20 @@ impl PropertyValue {
         }
     }
 
+    /// Get the value as a float, if it's a number of either kind
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            PropertyValue::Number(n) => Some(*n as f64),
+            PropertyValue::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    /// Whether the value is appended to the inherited one with `+=`
+    pub fn is_append(&self) -> bool {
+        matches!(self, PropertyValue::Append(_))
+    }
+
     /// Get the value as a class, if it's a class
     pub fn as_class(&self) -> Option<&GameClass> {
         match self {
@@ -242,6 +347,8 @@ pub mod convert {
             container_class,
             properties: Vec::new(),
             is_forward_declaration,
+            location: None,
+            parsed_by: None,
         }
     }
 
@@ -261,6 +368,7 @@ pub mod convert {
             .map(|prop| ClassProperty {
                 name: name_fn(prop),
                 value: value_fn(prop),
+                location: None,
             })
             .collect()
     }
@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::{GameClass, PropertyValue};
+
+/// An addon as declared by its entry in `CfgPatches`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AddonPatch {
+    /// Addon name, the class name inside `CfgPatches`
+    pub name: String,
+
+    /// Vehicle classes from `units[]`
+    pub units: Vec<String>,
+
+    /// Weapon classes from `weapons[]`
+    pub weapons: Vec<String>,
+
+    /// Addons from `requiredAddons[]`
+    pub required_addons: Vec<String>,
+
+    /// Game version from `requiredVersion`
+    pub required_version: Option<f64>,
+
+    /// Author from `author`, joined if given as an array
+    pub author: Option<String>,
+
+    /// Path to the config declaring the addon
+    pub file_path: PathBuf,
+}
+
+impl AddonPatch {
+    /// Read the addons declared in the top-level `CfgPatches` of a config
+    pub fn from_classes(classes: &[GameClass]) -> Vec<AddonPatch> {
+        classes
+            .iter()
+            .filter(|class| class.container_class.is_none() && class.name.eq_ignore_ascii_case("CfgPatches"))
+            .flat_map(|cfg_patches| &cfg_patches.properties)
+            .filter_map(|property| match &property.value {
+                PropertyValue::Class(patch) if !patch.is_forward_declaration => Some(Self::from_class(patch)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Read an addon from its class inside `CfgPatches`
+    pub fn from_class(class: &GameClass) -> AddonPatch {
+        let property = |name: &str| {
+            class.properties
+                .iter()
+                .find(|property| property.name.eq_ignore_ascii_case(name))
+                .map(|property| &property.value)
+        };
+        let names = |name: &str| -> Vec<String> {
+            property(name)
+                .and_then(PropertyValue::as_string_array)
+                .map(|items| {
+                    items.iter()
+                        .map(|item| item.trim_matches('"').to_string())
+                        .filter(|item| !item.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        let author = match property("author") {
+            Some(PropertyValue::String(author)) => Some(author.clone()),
+            Some(value) => value.as_string_array().map(|authors| authors.join(", ")),
+            None => None,
+        };
+
+        AddonPatch {
+            name: class.name.clone(),
+            units: names("units"),
+            weapons: names("weapons"),
+            required_addons: names("requiredAddons"),
+            required_version: property("requiredVersion").and_then(PropertyValue::as_float),
+            author: author.filter(|author| !author.is_empty()),
+            file_path: class.file_path.clone(),
+        }
+    }
+
+    /// Whether this addon lists `addon` in `requiredAddons[]`, ignoring case
+    pub fn requires(&self, addon: &str) -> bool {
+        self.required_addons.iter().any(|required| required.eq_ignore_ascii_case(addon))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_classes() {
+        let file = PathBuf::from("x/mod/addons/main/config.cpp");
+        let mut patch = GameClass::with_container("mod_main".to_string(), None, file.clone(), Some("CfgPatches".to_string()));
+        patch.add_property("units".to_string(), PropertyValue::Array(vec![]));
+        patch.add_property("weapons".to_string(), PropertyValue::Array(vec!["mod_rifle".into()]));
+        patch.add_property("requiredAddons".to_string(), PropertyValue::Array(vec!["A3_Weapons_F".into(), "cba_main".into()]));
+        patch.add_property("requiredVersion".to_string(), PropertyValue::Float(2.1));
+        patch.add_property("author".to_string(), PropertyValue::Array(vec!["Alpha".into(), "Bravo".into()]));
+
+        let mut cfg_patches = GameClass::new("CfgPatches".to_string(), None, file.clone());
+        cfg_patches.add_property("mod_main".to_string(), PropertyValue::Class(Box::new(patch.clone())));
+        let other = GameClass::new("CfgWeapons".to_string(), None, file.clone());
+
+        let patches = AddonPatch::from_classes(&[cfg_patches, patch, other]);
+
+        assert_eq!(patches.len(), 1);
+        let addon = &patches[0];
+        assert_eq!(addon.name, "mod_main");
+        assert!(addon.units.is_empty());
+        assert_eq!(addon.weapons, vec!["mod_rifle"]);
+        assert!(addon.requires("a3_weapons_f"));
+        assert_eq!(addon.required_version, Some(2.1));
+        assert_eq!(addon.author.as_deref(), Some("Alpha, Bravo"));
+        assert_eq!(addon.file_path, file);
+    }
+}
@@ -0,0 +1,334 @@
+//! Addon dependency graph
+//!
+//! Addons declare what they need in `CfgPatches` `requiredAddons[]`. The
+//! graph finds requirements no scanned addon provides, groups of addons that
+//! require each other, and the order the game loads addons in.
+
+use gamedata_scanner_models::AddonPatch;
+use log::warn;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap};
+
+/// A requirement no addon in the graph provides
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingRequirement {
+    /// Addon listing the requirement
+    pub addon: String,
+    /// Name from its `requiredAddons[]`
+    pub required: String,
+}
+
+/// Dependency graph of addons through `requiredAddons[]`
+#[derive(Debug, Clone, Default)]
+pub struct AddonGraph {
+    addons: Vec<AddonPatch>,
+    /// Index into `addons` by lowercase name
+    by_name: HashMap<String, usize>,
+    /// Indices of the addons each addon requires
+    requirements: Vec<Vec<usize>>,
+    missing: Vec<MissingRequirement>,
+}
+
+impl AddonGraph {
+    /// Build the graph of the given addons
+    ///
+    /// Addon names are matched ignoring case. An addon declared more than
+    /// once is kept at its first declaration.
+    pub fn new(addons: Vec<AddonPatch>) -> Self {
+        let mut unique = Vec::with_capacity(addons.len());
+        let mut by_name = HashMap::new();
+        for addon in addons {
+            match by_name.entry(addon.name.to_lowercase()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(unique.len());
+                    unique.push(addon);
+                }
+                Entry::Occupied(_) => {
+                    warn!("Addon {} is declared again in {}, ignoring it", addon.name, addon.file_path.display());
+                }
+            }
+        }
+
+        let mut requirements = Vec::with_capacity(unique.len());
+        let mut missing = Vec::new();
+        for addon in &unique {
+            let mut required_indices = Vec::new();
+            for required in &addon.required_addons {
+                match by_name.get(&required.to_lowercase()) {
+                    Some(&index) => required_indices.push(index),
+                    None => missing.push(MissingRequirement {
+                        addon: addon.name.clone(),
+                        required: required.clone(),
+                    }),
+                }
+            }
+            requirements.push(required_indices);
+        }
+
+        Self {
+            addons: unique,
+            by_name,
+            requirements,
+            missing,
+        }
+    }
+
+    /// Addons in the graph, in the order they were given
+    pub fn addons(&self) -> &[AddonPatch] {
+        &self.addons
+    }
+
+    /// Get an addon by name, ignoring case
+    pub fn get(&self, name: &str) -> Option<&AddonPatch> {
+        self.by_name.get(&name.to_lowercase()).map(|&index| &self.addons[index])
+    }
+
+    /// Addons the named addon requires, directly or through others
+    pub fn requirements_of(&self, name: &str) -> Vec<&AddonPatch> {
+        let Some(&start) = self.by_name.get(&name.to_lowercase()) else {
+            return Vec::new();
+        };
+        let mut seen = vec![false; self.addons.len()];
+        let mut pending = self.requirements[start].clone();
+        let mut found = Vec::new();
+        while let Some(index) = pending.pop() {
+            if index == start || std::mem::replace(&mut seen[index], true) {
+                continue;
+            }
+            found.push(index);
+            pending.extend(&self.requirements[index]);
+        }
+        found.sort_unstable();
+        found.into_iter().map(|index| &self.addons[index]).collect()
+    }
+
+    /// Requirements that no addon in the graph provides
+    pub fn missing_requirements(&self) -> &[MissingRequirement] {
+        &self.missing
+    }
+
+    /// Groups of addons that require each other, directly or through others
+    ///
+    /// An addon requiring itself is a group of one.
+    pub fn cycles(&self) -> Vec<Vec<String>> {
+        strongly_connected(&self.requirements)
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1 || self.requirements[component[0]].contains(&component[0])
+            })
+            .map(|component| component.into_iter().map(|index| self.addons[index].name.clone()).collect())
+            .collect()
+    }
+
+    /// Addon names in the order the game loads them
+    ///
+    /// Every addon comes after the addons it requires. Otherwise addons keep
+    /// the order they were given in, and a cycle is broken at its member
+    /// given first.
+    pub fn load_order(&self) -> Vec<String> {
+        let names: Vec<&str> = self.addons.iter().map(|addon| addon.name.as_str()).collect();
+        order_by_requirements(&self.requirements, &names)
+            .into_iter()
+            .map(|index| self.addons[index].name.clone())
+            .collect()
+    }
+}
+
+/// Order nodes so that each comes after the nodes it requires
+///
+/// Returns node indices. Nodes keep their given order where requirements
+/// allow. When only nodes in cycles are left, the first of them in a cycle
+/// that waits on no other unplaced node is placed to break it.
+pub(crate) fn order_by_requirements(requirements: &[Vec<usize>], names: &[&str]) -> Vec<usize> {
+    let count = requirements.len();
+    let mut waiting_on = vec![0; count];
+    let mut dependents = vec![Vec::new(); count];
+    for (index, required) in requirements.iter().enumerate() {
+        let mut required: Vec<usize> = required.iter().copied().filter(|&required| required != index).collect();
+        required.sort_unstable();
+        required.dedup();
+        waiting_on[index] = required.len();
+        for required in required {
+            dependents[required].push(index);
+        }
+    }
+
+    let mut component_of = vec![0; count];
+    let components = strongly_connected(requirements);
+    for (component, members) in components.iter().enumerate() {
+        for &member in members {
+            component_of[member] = component;
+        }
+    }
+
+    let mut ready: BTreeSet<usize> = (0..count).filter(|&index| waiting_on[index] == 0).collect();
+    let mut placed = vec![false; count];
+    let mut order = Vec::with_capacity(count);
+    while order.len() < count {
+        let next = match ready.pop_first() {
+            Some(index) => index,
+            None => {
+                let waits_only_on_itself = |component: usize| {
+                    components[component].iter().all(|&member| {
+                        requirements[member]
+                            .iter()
+                            .all(|&required| placed[required] || component_of[required] == component)
+                    })
+                };
+                let index = (0..count)
+                    .find(|&index| !placed[index] && waits_only_on_itself(component_of[index]))
+                    .expect("unplaced nodes always include a cycle waiting only on itself");
+                warn!("Breaking a requiredAddons cycle at addon {}", names[index]);
+                index
+            }
+        };
+
+        placed[next] = true;
+        order.push(next);
+        for &dependent in &dependents[next] {
+            waiting_on[dependent] -= 1;
+            if waiting_on[dependent] == 0 && !placed[dependent] {
+                ready.insert(dependent);
+            }
+        }
+    }
+    order
+}
+
+/// Strongly connected components of the requirement graph, each sorted and
+/// ordered by their first node
+fn strongly_connected(requirements: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct Tarjan<'a> {
+        requirements: &'a [Vec<usize>],
+        index: Vec<Option<usize>>,
+        low_link: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        next_index: usize,
+        components: Vec<Vec<usize>>,
+    }
+
+    impl Tarjan<'_> {
+        fn visit(&mut self, node: usize) {
+            self.index[node] = Some(self.next_index);
+            self.low_link[node] = self.next_index;
+            self.next_index += 1;
+            self.stack.push(node);
+            self.on_stack[node] = true;
+
+            let requirements = self.requirements;
+            for &required in &requirements[node] {
+                match self.index[required] {
+                    None => {
+                        self.visit(required);
+                        self.low_link[node] = self.low_link[node].min(self.low_link[required]);
+                    }
+                    Some(index) if self.on_stack[required] => {
+                        self.low_link[node] = self.low_link[node].min(index);
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            if Some(self.low_link[node]) == self.index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                component.sort_unstable();
+                self.components.push(component);
+            }
+        }
+    }
+
+    let count = requirements.len();
+    let mut tarjan = Tarjan {
+        requirements,
+        index: vec![None; count],
+        low_link: vec![0; count],
+        on_stack: vec![false; count],
+        stack: Vec::new(),
+        next_index: 0,
+        components: Vec::new(),
+    };
+    for node in 0..count {
+        if tarjan.index[node].is_none() {
+            tarjan.visit(node);
+        }
+    }
+
+    let mut components = tarjan.components;
+    components.sort_unstable_by_key(|component| component[0]);
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn addon(name: &str, required: &[&str]) -> AddonPatch {
+        AddonPatch {
+            name: name.to_string(),
+            units: Vec::new(),
+            weapons: Vec::new(),
+            required_addons: required.iter().map(|r| r.to_string()).collect(),
+            required_version: None,
+            author: None,
+            file_path: PathBuf::from(format!("{}/config.cpp", name)),
+        }
+    }
+
+    #[test]
+    fn test_load_order_follows_requirements() {
+        let graph = AddonGraph::new(vec![
+            addon("ace_main", &["cba_main"]),
+            addon("cba_main", &["A3_Data_F"]),
+            addon("A3_Data_F", &[]),
+            addon("ace_common", &["ACE_Main"]),
+        ]);
+
+        assert_eq!(graph.load_order(), vec!["A3_Data_F", "cba_main", "ace_main", "ace_common"]);
+        assert!(graph.missing_requirements().is_empty());
+        assert!(graph.cycles().is_empty());
+        let requirements: Vec<&str> = graph.requirements_of("ace_common").iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(requirements, vec!["ace_main", "cba_main", "A3_Data_F"]);
+    }
+
+    #[test]
+    fn test_missing_requirements_and_cycles() {
+        let graph = AddonGraph::new(vec![
+            addon("mod_a", &["mod_b", "mod_missing"]),
+            addon("mod_b", &["mod_c"]),
+            addon("mod_c", &["mod_a"]),
+            addon("mod_self", &["mod_self"]),
+            addon("mod_free", &[]),
+            addon("MOD_A", &[]),
+        ]);
+
+        assert_eq!(graph.addons().len(), 5, "Duplicate declarations are ignored");
+        assert_eq!(graph.missing_requirements(), &[MissingRequirement {
+            addon: "mod_a".to_string(),
+            required: "mod_missing".to_string(),
+        }]);
+        assert_eq!(graph.cycles(), vec![vec!["mod_a", "mod_b", "mod_c"], vec!["mod_self"]]);
+        assert_eq!(graph.load_order(), vec!["mod_self", "mod_free", "mod_a", "mod_c", "mod_b"]);
+    }
+
+    #[test]
+    fn test_load_order_breaks_required_cycles_first() {
+        let graph = AddonGraph::new(vec![
+            addon("late_a", &["late_b", "early_a"]),
+            addon("late_b", &["late_a"]),
+            addon("early_a", &["early_b"]),
+            addon("early_b", &["early_a"]),
+        ]);
+
+        // The late cycle requires the early one, so it is broken second
+        assert_eq!(graph.load_order(), vec!["early_a", "early_b", "late_a", "late_b"]);
+    }
+}
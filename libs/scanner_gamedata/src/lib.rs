@@ -35,12 +35,17 @@ use std::sync::Arc;
 
 // Re-export models from models
 pub use gamedata_scanner_models::{
-    ClassProperty, FileParser, GameClass, PropertyValue, ScanResult, Scanner as ClassScanner,
+    AddonPatch, ClassProperty, FileParser, GameClass, PropertyValue, ScanResult, Scanner as ClassScanner,
 };
 // Do not re-export get_parser directly as its signature changed and is internal to Scanner::new
+pub use parser_hpp::ParserMode;
 pub use scanner::{FileScanResult, ScannerConfig, ScannerResult}; // Added FileScanResult
+pub use addons::{AddonGraph, MissingRequirement};
+pub use resolver::{AddonConfig, ResolvedClass, ResolvedConfig, ResolvedProperty};
 
+pub mod addons;
 pub mod parser_factory;
+pub mod resolver;
 pub mod scanner;
 
 /// Main scanner interface for processing game data files
@@ -68,8 +73,7 @@ impl Scanner {
                 root_path.display()
             ));
         }
-        let parser =
-            parser_factory::get_parser(&root_path, config.use_advanced_parser)?;
+        let parser = parser_factory::get_parser(&root_path, &config.include_roots, config.effective_parser_mode())?;
         Ok(Self {
             project_root_dir: root_path,
             parser,
@@ -1,25 +1,35 @@
 use gamedata_scanner_models::FileParser;
+use parser_hpp::{AdvancedFileParserWrapper, AdvancedProjectParser, HybridFileParser, ParserMode, SimpleClassScanner};
 use std::sync::Arc;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-/// Returns a file parser implementation based on the parser type
-/// Note: use_advanced parameter is kept for API compatibility but ignored since the unified parser supports both modes
-pub fn get_parser(project_root_dir: &Path, _use_advanced: bool) -> Result<Arc<dyn FileParser>, String> {
-    match ::parser_hpp::AdvancedProjectParser::new(
+/// Returns a file parser implementation for the parsing mode
+///
+/// The advanced parser resolves absolute includes from `include_roots`.
+pub fn get_parser(
+    project_root_dir: &Path,
+    include_roots: &[PathBuf],
+    mode: ParserMode,
+) -> Result<Arc<dyn FileParser>, String> {
+    if mode == ParserMode::Simple {
+        return Ok(Arc::new(SimpleClassScanner::new()));
+    }
+
+    let project_parser = match AdvancedProjectParser::with_include_roots(
         project_root_dir,
         None, // Auto-discover hemtt.toml in project_root_dir
+        include_roots,
     ) {
-        Ok(project_parser) => {
-            let wrapper = ::parser_hpp::AdvancedFileParserWrapper::new(
-                Arc::new(project_parser),
-                project_root_dir,
-            );
-            Ok(Arc::new(wrapper))
-        }
+        Ok(project_parser) => Arc::new(project_parser),
         Err(e) => {
             let err_msg = format!("Failed to create AdvancedProjectParser for root '{}': {:?}", project_root_dir.display(), e);
             log::error!("{}", err_msg);
-            Err(err_msg)
+            return Err(err_msg);
         }
+    };
+
+    match mode {
+        ParserMode::Hybrid => Ok(Arc::new(HybridFileParser::new(project_parser, project_root_dir))),
+        _ => Ok(Arc::new(AdvancedFileParserWrapper::new(project_parser, project_root_dir))),
     }
 }
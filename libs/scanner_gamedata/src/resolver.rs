@@ -0,0 +1,515 @@
+//! Effective config resolution across addons
+//!
+//! Addons patch each other's classes, so the class the game sees is the
+//! result of applying every addon's config in load order. The resolver
+//! merges all definitions of each class path, applies `delete` statements
+//! and `+=` appends, follows inheritance and records which addon set each
+//! property last.
+
+use gamedata_scanner_models::{AddonPatch, GameClass, PropertyValue, SourceLocation};
+use log::warn;
+
+use crate::addons::order_by_requirements;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+
+/// Separator between the class names of a path, e.g. `CfgWeapons/arifle_MX_F`
+pub const PATH_SEPARATOR: char = '/';
+
+/// Classes of one addon's config
+#[derive(Debug, Clone)]
+pub struct AddonConfig {
+    /// Name the addon is known by, its first `CfgPatches` entry
+    pub name: String,
+    /// Every addon the config declares in `CfgPatches`
+    pub patches: Vec<AddonPatch>,
+    /// Classes found in the config
+    pub classes: Vec<GameClass>,
+}
+
+impl AddonConfig {
+    /// Describe an addon from the classes of its config
+    ///
+    /// The name and requirements are read from `CfgPatches`. A config
+    /// without one is named `fallback_name` and requires nothing.
+    pub fn from_classes(fallback_name: impl Into<String>, classes: Vec<GameClass>) -> Self {
+        let patches = AddonPatch::from_classes(&classes);
+        Self {
+            name: patches.first().map(|patch| patch.name.clone()).unwrap_or_else(|| fallback_name.into()),
+            patches,
+            classes,
+        }
+    }
+
+    /// Addons listed in `requiredAddons[]` of its `CfgPatches` entries
+    pub fn required_addons(&self) -> impl Iterator<Item = &str> {
+        self.patches.iter().flat_map(|patch| patch.required_addons.iter().map(String::as_str))
+    }
+}
+
+/// A property as the game sees it, with where its value came from
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedProperty {
+    /// Property name as written in the definition that set it
+    pub name: String,
+    /// Effective value, with appends applied
+    pub value: PropertyValue,
+    /// Addon whose definition set the value last
+    pub addon: String,
+    /// Path of the class that set the value; another class's path if inherited
+    pub class_path: String,
+    /// File of the definition that set the value
+    pub file_path: PathBuf,
+    /// Where in `file_path` the value was set
+    pub location: Option<SourceLocation>,
+}
+
+/// A class after all addons have been applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedClass {
+    /// Full path of the class, e.g. `CfgWeapons/arifle_MX_F`
+    pub path: String,
+    /// Class name
+    pub name: String,
+    /// Parent class name of the last definition
+    pub parent: Option<String>,
+    /// Path of the parent class, if it was found
+    pub parent_path: Option<String>,
+    /// Addons that defined or patched the class, in load order
+    pub defined_by: Vec<String>,
+    /// Effective properties by lowercase name, inherited ones included
+    pub properties: BTreeMap<String, ResolvedProperty>,
+}
+
+impl ResolvedClass {
+    /// Get an effective property by name, ignoring case
+    pub fn property(&self, name: &str) -> Option<&ResolvedProperty> {
+        self.properties.get(&name.to_lowercase())
+    }
+
+    /// Whether the property's value comes from a parent class
+    pub fn is_inherited(&self, property: &ResolvedProperty) -> bool {
+        !property.class_path.eq_ignore_ascii_case(&self.path)
+    }
+}
+
+/// Classes the game sees once every addon is loaded
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedConfig {
+    /// Addon names in the order they were applied
+    pub load_order: Vec<String>,
+    /// Classes by lowercase path
+    pub classes: BTreeMap<String, ResolvedClass>,
+}
+
+impl ResolvedConfig {
+    /// Get a class by its path, ignoring case
+    pub fn class(&self, path: &str) -> Option<&ResolvedClass> {
+        self.classes.get(&path.to_lowercase())
+    }
+}
+
+/// Merge the configs of `addons` into the classes the game would see
+///
+/// Addons are applied in [`load_order`]. A later definition of a class
+/// overrides earlier property values and, as in the game, its parent.
+/// Classes inherited only through a parent's nested classes are not
+/// listed on their own.
+pub fn resolve(addons: &[AddonConfig]) -> ResolvedConfig {
+    let order = load_order(addons);
+
+    let mut defined = BTreeMap::new();
+    for &index in &order {
+        let addon = &addons[index];
+        for class in addon.classes.iter().filter(|class| class.container_class.is_none()) {
+            apply_class(&mut defined, &addon.name, class, None);
+        }
+    }
+
+    let mut classes = BTreeMap::new();
+    for key in defined.keys() {
+        resolve_class(key, &defined, &mut classes, &mut HashSet::new());
+    }
+
+    ResolvedConfig {
+        load_order: order.iter().map(|&index| addons[index].name.clone()).collect(),
+        classes,
+    }
+}
+
+/// Order addons so that each comes after the addons it requires
+///
+/// Returns indices into `addons`. Addons keep their given order where their
+/// requirements allow. Requirements no given addon provides are ignored, and
+/// a cycle is broken at its member given first.
+pub fn load_order(addons: &[AddonConfig]) -> Vec<usize> {
+    let mut providers = HashMap::new();
+    for (index, addon) in addons.iter().enumerate() {
+        for patch in &addon.patches {
+            providers.entry(patch.name.to_lowercase()).or_insert(index);
+        }
+    }
+
+    let requirements: Vec<Vec<usize>> = addons
+        .iter()
+        .enumerate()
+        .map(|(index, addon)| {
+            addon.required_addons()
+                .filter_map(|required| providers.get(&required.to_lowercase()).copied())
+                .filter(|&provider| provider != index)
+                .collect()
+        })
+        .collect();
+
+    let names: Vec<&str> = addons.iter().map(|addon| addon.name.as_str()).collect();
+    order_by_requirements(&requirements, &names)
+}
+
+fn join_path(container: Option<&str>, name: &str) -> String {
+    match container {
+        Some(container) => format!("{}{}{}", container, PATH_SEPARATOR, name),
+        None => name.to_string(),
+    }
+}
+
+/// Apply one definition of a class and its nested classes
+fn apply_class(
+    defined: &mut BTreeMap<String, ResolvedClass>,
+    addon: &str,
+    class: &GameClass,
+    container: Option<&str>,
+) {
+    // `class Name;` only refers to a class defined elsewhere
+    if class.is_forward_declaration {
+        return;
+    }
+
+    let key = join_path(container, &class.name).to_lowercase();
+    let entry = defined.entry(key.clone()).or_insert_with(|| ResolvedClass {
+        path: join_path(container, &class.name),
+        name: class.name.clone(),
+        parent: None,
+        parent_path: None,
+        defined_by: Vec::new(),
+        properties: BTreeMap::new(),
+    });
+    entry.parent = class.parent.clone();
+    if entry.defined_by.last().map(String::as_str) != Some(addon) {
+        entry.defined_by.push(addon.to_string());
+    }
+    let path = entry.path.clone();
+
+    for property in &class.properties {
+        match &property.value {
+            PropertyValue::Class(nested) => apply_class(defined, addon, nested, Some(&path)),
+            PropertyValue::Delete => remove_class(defined, &join_path(Some(&path), &property.name)),
+            value => {
+                let Some(entry) = defined.get_mut(&key) else {
+                    // The class deleted itself through a nested class of the same name
+                    return;
+                };
+                let name = property.name.to_lowercase();
+                let value = match (value, entry.properties.get(&name)) {
+                    (PropertyValue::Append(_), Some(existing)) => append_values(&existing.value, value),
+                    _ => value.clone(),
+                };
+                entry.properties.insert(name, ResolvedProperty {
+                    name: property.name.clone(),
+                    value,
+                    addon: addon.to_string(),
+                    class_path: path.clone(),
+                    file_path: class.file_path.clone(),
+                    location: property.location,
+                });
+            }
+        }
+    }
+}
+
+/// Remove a class and every class nested in it
+fn remove_class(defined: &mut BTreeMap<String, ResolvedClass>, path: &str) {
+    let key = path.to_lowercase();
+    let prefix = format!("{}{}", key, PATH_SEPARATOR);
+    defined.retain(|existing, _| *existing != key && !existing.starts_with(&prefix));
+}
+
+/// Find the class a parent name refers to
+///
+/// The name is looked up beside the class first and then in each enclosing
+/// class, as the game does. A nested class named like its parent, as in
+/// `class Turrets: Turrets`, extends the class of that name in the parents
+/// of its container, e.g. `Tank/Turrets` for `Tank_F/Turrets`.
+fn find_parent(
+    defined: &BTreeMap<String, ResolvedClass>,
+    key: &str,
+    parent: &str,
+    visited: &mut HashSet<String>,
+) -> Option<String> {
+    let parent = parent.to_lowercase();
+    let mut scope = key.rsplit_once(PATH_SEPARATOR).map(|(container, _)| container);
+    if let Some(container) = scope {
+        if key.ends_with(&format!("{}{}", PATH_SEPARATOR, parent)) {
+            if let Some(inherited) = find_in_parents(defined, container, &parent, visited) {
+                return Some(inherited);
+            }
+        }
+    }
+
+    loop {
+        let candidate = join_path(scope, &parent);
+        if candidate != key && defined.contains_key(&candidate) {
+            return Some(candidate);
+        }
+        scope = match scope {
+            Some(scope) => scope.rsplit_once(PATH_SEPARATOR).map(|(container, _)| container),
+            None => return None,
+        };
+    }
+}
+
+/// Find the nested class `name` in the nearest parent of `container` defining it
+fn find_in_parents(
+    defined: &BTreeMap<String, ResolvedClass>,
+    container: &str,
+    name: &str,
+    visited: &mut HashSet<String>,
+) -> Option<String> {
+    let mut current = container.to_string();
+    while visited.insert(current.clone()) {
+        let parent = defined.get(&current)?.parent.as_deref()?;
+        let parent_key = find_parent(defined, &current, parent, visited)?;
+        let candidate = join_path(Some(&parent_key), name);
+        if defined.contains_key(&candidate) {
+            return Some(candidate);
+        }
+        current = parent_key;
+    }
+    None
+}
+
+/// Resolve the effective properties of a class, resolving its parents first
+fn resolve_class(
+    key: &str,
+    defined: &BTreeMap<String, ResolvedClass>,
+    resolved: &mut BTreeMap<String, ResolvedClass>,
+    visiting: &mut HashSet<String>,
+) {
+    if resolved.contains_key(key) || !visiting.insert(key.to_string()) {
+        return;
+    }
+    let class = &defined[key];
+
+    let parent_key = class.parent.as_deref().and_then(|parent| find_parent(defined, key, parent, &mut HashSet::new()));
+    let mut properties = BTreeMap::new();
+    if let Some(parent_key) = &parent_key {
+        resolve_class(parent_key, defined, resolved, visiting);
+        match resolved.get(parent_key) {
+            Some(parent) => properties = parent.properties.clone(),
+            None => warn!("Class {} inherits from itself through {}", class.path, defined[parent_key].path),
+        }
+    }
+
+    for (name, property) in &class.properties {
+        let value = match (&property.value, properties.get(name)) {
+            (PropertyValue::Append(_), Some(inherited)) => append_values(&inherited.value, &property.value),
+            (PropertyValue::Append(appended), None) => (**appended).clone(),
+            (value, _) => value.clone(),
+        };
+        properties.insert(name.clone(), ResolvedProperty { value, ..property.clone() });
+    }
+
+    visiting.remove(key);
+    resolved.insert(key.to_string(), ResolvedClass {
+        parent_path: parent_key.map(|parent_key| defined[&parent_key].path.clone()),
+        properties,
+        ..class.clone()
+    });
+}
+
+/// Append the items of `appended` to `base`
+///
+/// A base that is itself still an append stays one. Values that are not
+/// arrays cannot be appended to, so `appended` replaces them.
+fn append_values(base: &PropertyValue, appended: &PropertyValue) -> PropertyValue {
+    match (base, appended) {
+        (_, PropertyValue::Append(appended)) => append_values(base, appended),
+        (PropertyValue::Append(base), _) => PropertyValue::Append(Box::new(append_values(base, appended))),
+        (PropertyValue::Array(base), PropertyValue::Array(appended)) => {
+            PropertyValue::Array(base.iter().chain(appended).cloned().collect())
+        }
+        _ => appended.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gamedata_scanner_models::ClassProperty;
+
+    fn class(name: &str, parent: Option<&str>, file: &str, properties: Vec<(&str, PropertyValue)>) -> GameClass {
+        let mut class = GameClass::new(name.to_string(), parent.map(String::from), PathBuf::from(file));
+        for (name, value) in properties {
+            class.add_property(name.to_string(), value);
+        }
+        class
+    }
+
+    fn strings(items: &[&str]) -> PropertyValue {
+        PropertyValue::from(items)
+    }
+
+    fn with_nested(mut container: GameClass, classes: Vec<GameClass>) -> GameClass {
+        for nested_class in classes {
+            container.properties.push(ClassProperty {
+                name: nested_class.name.clone(),
+                value: PropertyValue::Class(Box::new(nested_class)),
+                location: None,
+            });
+        }
+        container
+    }
+
+    fn patches(file: &str, name: &str, required: &[&str]) -> GameClass {
+        with_nested(
+            class("CfgPatches", None, file, vec![]),
+            vec![class(name, None, file, vec![("requiredAddons", strings(required))])],
+        )
+    }
+
+    fn vanilla() -> AddonConfig {
+        let file = "a3/weapons_f/config.cpp";
+        AddonConfig::from_classes("weapons_f", vec![
+            patches(file, "A3_Weapons_F", &[]),
+            with_nested(class("CfgWeapons", None, file, vec![]), vec![
+                class("Rifle_Base_F", None, file, vec![
+                    ("scope", PropertyValue::Number(1)),
+                    ("magazineWell", strings(&["STANAG_556x45"])),
+                ]),
+                class("arifle_MX_F", Some("Rifle_Base_F"), file, vec![
+                    ("scope", PropertyValue::Number(2)),
+                    ("displayName", PropertyValue::String("MX 6.5 mm".to_string())),
+                ]),
+                class("arifle_Old_F", Some("Rifle_Base_F"), file, vec![]),
+            ]),
+        ])
+    }
+
+    fn patch() -> AddonConfig {
+        let file = "x/mod/addons/weapons/config.cpp";
+        AddonConfig::from_classes("mod_weapons", vec![
+            patches(file, "mod_weapons", &["A3_Weapons_F"]),
+            with_nested(class("CfgWeapons", None, file, vec![("arifle_Old_F", PropertyValue::Delete)]), vec![
+                class("Rifle_Base_F", None, file, vec![]),
+                class("arifle_MX_F", Some("Rifle_Base_F"), file, vec![
+                    ("displayName", PropertyValue::String("MX (patched)".to_string())),
+                    ("magazineWell", PropertyValue::Append(Box::new(strings(&["CBA_556x45"])))),
+                ]),
+            ]),
+        ])
+    }
+
+    #[test]
+    fn test_addon_from_cfg_patches() {
+        let addon = patch();
+        assert_eq!(addon.name, "mod_weapons");
+        assert_eq!(addon.required_addons().collect::<Vec<_>>(), vec!["A3_Weapons_F"]);
+
+        let unnamed = AddonConfig::from_classes("missionconfig", vec![class("CfgSounds", None, "description.ext", vec![])]);
+        assert_eq!(unnamed.name, "missionconfig");
+        assert!(unnamed.patches.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_in_load_order() {
+        // The patch is given first but requires the vanilla addon
+        let config = resolve(&[patch(), vanilla()]);
+        assert_eq!(config.load_order, vec!["A3_Weapons_F", "mod_weapons"]);
+
+        let mx = config.class("cfgweapons/ARIFLE_MX_F").unwrap();
+        assert_eq!(mx.path, "CfgWeapons/arifle_MX_F");
+        assert_eq!(mx.parent_path.as_deref(), Some("CfgWeapons/Rifle_Base_F"));
+        assert_eq!(mx.defined_by, vec!["A3_Weapons_F", "mod_weapons"]);
+
+        let display_name = mx.property("displayName").unwrap();
+        assert_eq!(display_name.value, PropertyValue::String("MX (patched)".to_string()));
+        assert_eq!(display_name.addon, "mod_weapons");
+        assert!(!mx.is_inherited(display_name));
+
+        let scope = mx.property("scope").unwrap();
+        assert_eq!((scope.value.clone(), scope.addon.as_str()), (PropertyValue::Number(2), "A3_Weapons_F"));
+
+        // The append is applied to the value inherited from the base class
+        let magazine_well = mx.property("magazineWell").unwrap();
+        assert_eq!(magazine_well.value, strings(&["STANAG_556x45", "CBA_556x45"]));
+        assert_eq!(magazine_well.addon, "mod_weapons");
+
+        assert!(config.class("CfgWeapons/arifle_Old_F").is_none());
+    }
+
+    #[test]
+    fn test_inherited_properties_keep_their_origin() {
+        let file = "config.cpp";
+        let wheels = with_nested(class("Wheels", None, file, vec![]), vec![
+            class("Base", None, file, vec![("width", PropertyValue::Float(0.3))]),
+            class("LF", Some("Base"), file, vec![]),
+        ]);
+        let addon = AddonConfig::from_classes("test", vec![with_nested(class("CfgVehicles", None, file, vec![]), vec![
+            class("Car", None, file, vec![("maxSpeed", PropertyValue::Number(120))]),
+            with_nested(class("Car_F", Some("Car"), file, vec![("mass", PropertyValue::Float(1200.5))]), vec![wheels]),
+        ])]);
+
+        let config = resolve(&[addon]);
+        let car = config.class("CfgVehicles/Car_F").unwrap();
+        let max_speed = car.property("maxSpeed").unwrap();
+        assert!(car.is_inherited(max_speed));
+        assert_eq!(max_speed.class_path, "CfgVehicles/Car");
+        assert_eq!(car.property("mass").unwrap().value, PropertyValue::Float(1200.5));
+
+        // Nested classes look their parent up in the enclosing classes
+        let wheel = config.class("CfgVehicles/Car_F/Wheels/LF").unwrap();
+        assert_eq!(wheel.parent_path.as_deref(), Some("CfgVehicles/Car_F/Wheels/Base"));
+        assert_eq!(wheel.property("width").unwrap().value, PropertyValue::Float(0.3));
+    }
+
+    #[test]
+    fn test_load_order_breaks_cycles() {
+        let first = AddonConfig::from_classes("first", vec![patches("a.cpp", "first", &["second"])]);
+        let second = AddonConfig::from_classes("second", vec![patches("b.cpp", "second", &["first", "missing"])]);
+        assert_eq!(load_order(&[first, second]), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_nested_class_extends_same_name_in_container_parent() {
+        let file = "config.cpp";
+        let tank = with_nested(class("Tank", None, file, vec![]), vec![
+            with_nested(class("Turrets", None, file, vec![]), vec![
+                class("MainTurret", None, file, vec![("gunnerName", PropertyValue::String("Gunner".to_string()))]),
+            ]),
+            class("HitPoints", None, file, vec![("armor", PropertyValue::Number(10))]),
+        ]);
+        let tank_f = with_nested(class("Tank_F", Some("Tank"), file, vec![]), vec![
+            with_nested(class("Turrets", Some("Turrets"), file, vec![]), vec![
+                class("MainTurret", Some("MainTurret"), file, vec![("weapons", strings(&["cannon_120mm"]))]),
+            ]),
+        ]);
+        let mbt = with_nested(class("MBT_01_base_F", Some("Tank_F"), file, vec![]), vec![
+            class("HitPoints", Some("HitPoints"), file, vec![]),
+        ]);
+        let addon = AddonConfig::from_classes("test", vec![
+            with_nested(class("CfgVehicles", None, file, vec![]), vec![tank, tank_f, mbt]),
+        ]);
+
+        let config = resolve(&[addon]);
+        let turrets = config.class("CfgVehicles/Tank_F/Turrets").unwrap();
+        assert_eq!(turrets.parent_path.as_deref(), Some("CfgVehicles/Tank/Turrets"));
+
+        let main_turret = config.class("CfgVehicles/Tank_F/Turrets/MainTurret").unwrap();
+        assert_eq!(main_turret.parent_path.as_deref(), Some("CfgVehicles/Tank/Turrets/MainTurret"));
+        assert_eq!(main_turret.property("gunnerName").unwrap().value, PropertyValue::String("Gunner".to_string()));
+
+        // Tank_F does not declare HitPoints, so the one of Tank is extended
+        let hit_points = config.class("CfgVehicles/MBT_01_base_F/HitPoints").unwrap();
+        assert_eq!(hit_points.parent_path.as_deref(), Some("CfgVehicles/Tank/HitPoints"));
+        assert_eq!(hit_points.property("armor").unwrap().value, PropertyValue::Number(10));
+    }
+}
@@ -1,4 +1,7 @@
+use crate::resolver::AddonConfig;
+use arma3_models::GameDataClasses;
 use gamedata_scanner_models::GameClass;
+use parser_hpp::ParserMode;
 use indicatif::{ProgressBar, ProgressStyle};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -20,6 +23,9 @@ pub struct FileScanResult {
     pub classes: Vec<GameClass>,
     /// Time taken to parse the file in milliseconds (only set if diagnostic mode is enabled)
     pub parse_time_ms: Option<u64>,
+    /// Why the file was parsed by the simple parser in hybrid mode
+    #[serde(default)]
+    pub fallback_reason: Option<String>,
 }
 
 /// Configuration for the scanner
@@ -35,10 +41,32 @@ pub struct ScannerConfig {
     pub timeout: u64,
     /// Enable diagnostic mode to track parsing time for each file
     pub diagnostic_mode: bool,
-    /// Whether to use the advanced parser (true) or simple parser (false)
+    /// Parser used for each file (default: advanced)
+    ///
+    /// In hybrid mode files that are not parsed within `timeout` are
+    /// scanned with the simple parser instead.
+    pub parser_mode: ParserMode,
+    /// Directories laid out like a P drive that the advanced parser searches
+    /// for absolute includes such as `\x\cba\addons\main\script_macros.hpp`
+    pub include_roots: Vec<PathBuf>,
+    /// Use the advanced parser whatever `parser_mode` says
+    #[deprecated(note = "set `parser_mode` to `ParserMode::Advanced` instead")]
     pub use_advanced_parser: bool,
 }
 
+impl ScannerConfig {
+    /// Parser mode the scanner uses, taking the deprecated
+    /// `use_advanced_parser` flag into account
+    #[allow(deprecated)]
+    pub fn effective_parser_mode(&self) -> ParserMode {
+        if self.use_advanced_parser {
+            ParserMode::Advanced
+        } else {
+            self.parser_mode
+        }
+    }
+}
+
 impl Default for ScannerConfig {
     fn default() -> Self {
         Self {
@@ -47,6 +75,9 @@ impl Default for ScannerConfig {
             extensions: vec!["hpp".to_string(), "cpp".to_string(), "ext".to_string()],
             timeout: 120,
             diagnostic_mode: false,
+            parser_mode: ParserMode::Advanced,
+            include_roots: Vec::new(),
+            #[allow(deprecated)]
             use_advanced_parser: false,
         }
     }
@@ -78,8 +109,10 @@ pub struct DiagnosticInfo {
     pub file_parse_times: HashMap<PathBuf, u64>,
     /// List of slowest files (sorted by parse time, descending)
     pub slowest_files: Vec<(PathBuf, u64)>,
-    /// Number of files that timed out
+    /// Number of files that timed out, including those parsed by the fallback
     pub timeout_count: usize,
+    /// Number of files parsed by the simple parser after the advanced parser failed
+    pub fallback_count: usize,
 }
 
 impl DiagnosticInfo {
@@ -98,7 +131,11 @@ impl DiagnosticInfo {
         }
 
         // Count timeout errors
-        let timeout_count = errors.values().filter(|e| e.contains("timed out")).count();
+        let timeout_count = errors.values().filter(|e| e.contains("timed out")).count()
+            + results.values()
+                .filter(|r| r.fallback_reason.as_deref().is_some_and(|reason| reason.contains("timed out")))
+                .count();
+        let fallback_count = results.values().filter(|r| r.fallback_reason.is_some()).count();
 
         // Create sorted list of slowest files
         let mut slowest_files: Vec<(PathBuf, u64)> = file_parse_times
@@ -119,6 +156,7 @@ impl DiagnosticInfo {
             file_parse_times,
             slowest_files,
             timeout_count,
+            fallback_count,
         }
     }
 
@@ -136,6 +174,7 @@ impl DiagnosticInfo {
             self.file_parse_times.len()
         ));
         report.push_str(&format!("Files timed out: {}\n", self.timeout_count));
+        report.push_str(&format!("Files parsed by fallback: {}\n", self.fallback_count));
 
         if !self.file_parse_times.is_empty() {
             let total_parse_time: u64 = self.file_parse_times.values().sum();
@@ -159,6 +198,39 @@ impl DiagnosticInfo {
     }
 }
 
+impl ScannerResult {
+    /// Collect the scanned classes into the models stored in the analysis database
+    ///
+    /// Files are indexed in path order so repeated scans give the same indices.
+    pub fn to_game_data(&self) -> GameDataClasses {
+        let mut game_data = GameDataClasses::new();
+        let mut paths: Vec<&PathBuf> = self.results.keys().collect();
+        paths.sort();
+
+        for path in paths {
+            let index = game_data.add_file_source(path.clone());
+            for class in &self.results[path].classes {
+                game_data.add_class(class.to_game_data_class(Some(index)));
+            }
+        }
+
+        game_data
+    }
+
+    /// Describe each scanned config as an addon for the resolver
+    ///
+    /// Configs are listed in the same path order as [`Self::to_game_data`].
+    /// A config without `CfgPatches` is named by its path.
+    pub fn to_addon_configs(&self) -> Vec<AddonConfig> {
+        let mut paths: Vec<&PathBuf> = self.results.keys().collect();
+        paths.sort();
+
+        paths.into_iter()
+            .map(|path| AddonConfig::from_classes(path.display().to_string(), self.results[path].classes.clone()))
+            .collect()
+    }
+}
+
 /// Scans a directory recursively for game data files and processes them in parallel.
 /// The `scan_dir` is the specific directory to start scanning from (can be project_root or a sub-directory).
 /// The `project_root_dir` is used by the parser for context if needed (e.g. for advanced parsing).
@@ -354,14 +426,15 @@ pub(crate) fn process_single_file(
 
         let result = {
             // Use the passed-in parser
-            let classes = thread_parser.parse_file(&file_path_clone);
+            let parsed = thread_parser.parse_file_with_fallback(&file_path_clone);
             // Calculate parsing time if diagnostic mode is enabled
             let parse_time_ms = start_time.map(|start| start.elapsed().as_millis() as u64);
 
             Ok(FileScanResult {
                 file_path: file_path_clone.clone(),
-                classes,
+                classes: parsed.classes,
                 parse_time_ms,
+                fallback_reason: parsed.fallback_reason,
             })
         };
 
@@ -372,14 +445,25 @@ pub(crate) fn process_single_file(
     // Wait for the result with a timeout
     match rx.recv_timeout(Duration::from_secs(timeout_secs)) {
         Ok(result) => result,
-        Err(RecvTimeoutError::Timeout) => Err(io::Error::new(
-            io::ErrorKind::TimedOut,
-            format!(
-                "Processing timed out after {} seconds for file: {}",
-                timeout_secs,
-                file_path.display()
-            ),
-        )),
+        Err(RecvTimeoutError::Timeout) => {
+            let reason = format!("parsing timed out after {} seconds", timeout_secs);
+            match parser.parse_file_after_timeout(file_path, &reason) {
+                Some(parsed) => Ok(FileScanResult {
+                    file_path: file_path.to_path_buf(),
+                    classes: parsed.classes,
+                    parse_time_ms: None,
+                    fallback_reason: parsed.fallback_reason,
+                }),
+                None => Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!(
+                        "Processing timed out after {} seconds for file: {}",
+                        timeout_secs,
+                        file_path.display()
+                    ),
+                )),
+            }
+        }
         Err(RecvTimeoutError::Disconnected) => Err(io::Error::new(
             io::ErrorKind::Other,
             format!(
@@ -393,6 +477,7 @@ pub(crate) fn process_single_file(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use gamedata_scanner_models::ParserKind;
     use std::fs::File;
     use std::io::Write;
     use tempfile::TempDir;
@@ -483,4 +568,174 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_scanner_hybrid_fallback() -> io::Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        create_test_file(
+            temp_dir.path(),
+            "good.hpp",
+            r#"
+            class GoodClass {
+                displayName = "Good";
+            };
+        "#,
+        )?;
+        let broken = create_test_file(
+            temp_dir.path(),
+            "broken.hpp",
+            r#"
+            #include "does_not_exist.hpp"
+            class BrokenClass {
+                displayName = "Broken";
+            };
+        "#,
+        )?;
+
+        let config = ScannerConfig {
+            show_progress: false,
+            timeout: 30,
+            parser_mode: ParserMode::Hybrid,
+            ..Default::default()
+        };
+
+        let scanner = crate::Scanner::new(temp_dir.path(), config)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        let result = scanner.scan_directory(".")?;
+
+        assert_eq!(result.successful_files, 2);
+        let broken_result = &result.results[&broken];
+        assert!(broken_result.fallback_reason.is_some());
+        assert_eq!(broken_result.classes.len(), 1);
+        assert_eq!(broken_result.classes[0].parsed_by, Some(ParserKind::Simple));
+
+        let good_result = result.results.values().find(|r| r.fallback_reason.is_none()).unwrap();
+        assert_eq!(good_result.classes[0].name, "GoodClass");
+        assert_eq!(good_result.classes[0].parsed_by, Some(ParserKind::Advanced));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scanner_resolves_includes_from_include_roots() -> io::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let include_dir = TempDir::new()?;
+        let other_dir = include_dir.path().join("x/other/addons/main");
+        fs::create_dir_all(&other_dir)?;
+        create_test_file(&other_dir, "script_macros.hpp", "#define OTHER_VALUE 789\n")?;
+        let config_path = create_test_file(
+            temp_dir.path(),
+            "config.cpp",
+            "#include \"\\x\\other\\addons\\main\\script_macros.hpp\"\nclass FromOtherMod { value = OTHER_VALUE; };",
+        )?;
+
+        let config = ScannerConfig {
+            show_progress: false,
+            timeout: 30,
+            parser_mode: ParserMode::Advanced,
+            include_roots: vec![include_dir.path().to_path_buf()],
+            ..Default::default()
+        };
+
+        let scanner = crate::Scanner::new(temp_dir.path(), config)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        let result = scanner.scan_directory(".")?;
+
+        let classes = &result.results[&config_path].classes;
+        assert_eq!(classes.len(), 1);
+        let value = classes[0].properties.iter().find(|p| p.name == "value").unwrap();
+        assert_eq!(value.value, gamedata_scanner_models::PropertyValue::Number(789));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_game_data_keeps_locations() -> io::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_path = create_test_file(
+            temp_dir.path(),
+            "config.cpp",
+            "class CfgVehicles {\n    class Car;\n    class Offroad: Car {\n        scope = 2;\n    };\n};\n",
+        )?;
+
+        let config = ScannerConfig {
+            show_progress: false,
+            timeout: 30,
+            parser_mode: ParserMode::Advanced,
+            ..Default::default()
+        };
+
+        let scanner = crate::Scanner::new(temp_dir.path(), config)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        let result = scanner.scan_directory(".")?;
+        let scanned = result.results[&config_path].classes.iter().find(|c| c.name == "Offroad").unwrap();
+
+        let game_data = result.to_game_data();
+        let offroad = game_data.classes.iter().find(|c| c.name == "Offroad").unwrap();
+        assert_eq!(game_data.get_file_source(offroad.source_file_index.unwrap()), Some(&config_path));
+        assert_eq!(offroad.location, scanned.location);
+        assert_eq!(offroad.location.map(|l| l.start_line), Some(3));
+        assert_eq!(offroad.property_locations.get("scope").map(|l| l.start_line), Some(4));
+
+        // Nested classes are classes of their own, not properties of their container
+        let vehicles = game_data.classes.iter().find(|c| c.name == "CfgVehicles").unwrap();
+        assert!(vehicles.properties.is_empty());
+        assert_eq!(offroad.container_class.as_deref(), Some("CfgVehicles"));
+
+        Ok(())
+    }
+
+    /// Parser that never finishes in time but has a fallback
+    struct SlowParser;
+
+    impl gamedata_scanner_models::FileParser for SlowParser {
+        fn parse_file(&self, _file_path: &Path) -> Vec<GameClass> {
+            thread::sleep(Duration::from_secs(3));
+            Vec::new()
+        }
+
+        fn name(&self) -> &str {
+            "SlowParser"
+        }
+
+        fn parse_file_after_timeout(&self, file_path: &Path, reason: &str) -> Option<gamedata_scanner_models::ParsedFile> {
+            Some(gamedata_scanner_models::ParsedFile {
+                classes: vec![GameClass::new("Fallback".to_string(), None, file_path.to_path_buf())],
+                fallback_reason: Some(reason.to_string()),
+            })
+        }
+    }
+
+    #[test]
+    fn test_timed_out_file_uses_parser_fallback() -> io::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = create_test_file(temp_dir.path(), "slow.hpp", "class Slow {};")?;
+
+        let result = process_single_file(Arc::new(SlowParser), &path, 1, false)?;
+        assert_eq!(result.classes[0].name, "Fallback");
+        assert!(result.fallback_reason.unwrap().contains("timed out"));
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_advanced_flag_forces_advanced_parser() {
+        let config = ScannerConfig::default();
+        assert_eq!(config.effective_parser_mode(), ParserMode::Advanced);
+
+        let config = ScannerConfig {
+            parser_mode: ParserMode::Hybrid,
+            ..Default::default()
+        };
+        assert_eq!(config.effective_parser_mode(), ParserMode::Hybrid);
+
+        let config = ScannerConfig {
+            parser_mode: ParserMode::Simple,
+            use_advanced_parser: true,
+            ..Default::default()
+        };
+        assert_eq!(config.effective_parser_mode(), ParserMode::Advanced);
+    }
 }
@@ -12,6 +12,8 @@ pub enum ParserType {
     Simple,
     /// The advanced parser with full property parsing
     Advanced,
+    /// The advanced parser, with the simple scanner for files it fails on or times out on
+    Hybrid,
 }
 
 impl fmt::Display for ParserType {
@@ -19,6 +21,7 @@ impl fmt::Display for ParserType {
         match self {
             ParserType::Simple => write!(f, "simple"),
             ParserType::Advanced => write!(f, "advanced"),
+            ParserType::Hybrid => write!(f, "hybrid"),
         }
     }
 }
@@ -30,6 +33,7 @@ impl FromStr for ParserType {
         match s.to_lowercase().as_str() {
             "simple" => Ok(ParserType::Simple),
             "advanced" => Ok(ParserType::Advanced),
+            "hybrid" => Ok(ParserType::Hybrid),
             _ => Err(format!("Unknown parser type: {}", s)),
         }
     }
@@ -79,10 +83,14 @@ pub struct Args {
     #[clap(long, default_value = "60")]
     pub timeout_secs: u64,
     
-    /// Which parser to use (simple or advanced)
+    /// Which parser to use (simple, advanced or hybrid)
     #[clap(long, default_value = "advanced")]
     pub parser_type: ParserType,
     
+    /// P-drive style directories the advanced parser searches for absolute includes
+    #[clap(long = "include-root", value_delimiter = ',')]
+    pub include_roots: Vec<PathBuf>,
+    
     /// Verbosity level
     /// (0=error, 1=warn, 2=info, 3=debug, 4=trace)
     #[clap(short, long, default_value = "2")]
@@ -148,11 +148,12 @@ pub fn process_file(file_path: &Path, output_dir: Option<&Path>, args: &Args) ->
     // Create channel for result communication
     let (tx, rx) = mpsc::channel();
     let file_path_thread = file_path.to_path_buf();
-    let use_advanced = matches!(args.parser_type, ParserType::Advanced);
+    let parser_type = args.parser_type;
+    let include_roots = args.include_roots.clone();
     
     // Spawn processing in a separate thread
     thread::spawn(move || {
-        let result = process_with_scanner(&file_path_thread, use_advanced);
+        let result = process_with_scanner(&file_path_thread, parser_type, &include_roots);
         let _ = tx.send(result); // Ignore send error as receiver might be dropped on timeout
     });
     
@@ -165,6 +166,11 @@ pub fn process_file(file_path: &Path, output_dir: Option<&Path>, args: &Args) ->
     
     let parse_result = match rx.recv_timeout(timeout) {
         Ok(result) => result,
+        Err(_) if args.parser_type == ParserType::Hybrid => {
+            let reason = format!("processing timeout after {}ms (limit: {}s)",
+                start_time.elapsed().as_millis(), args.timeout_secs);
+            Ok(parse_with_fallback(file_path, &reason))
+        }
         Err(_) => {
             // Handle timeout
             let duration = start_time.elapsed().as_millis() as u64;
@@ -252,57 +258,80 @@ pub fn process_file(file_path: &Path, output_dir: Option<&Path>, args: &Args) ->
 /// Process a file using the gamedata scanner
 /// Returns either (classes, warnings) on success or error string on hard failure
 /// Phase 6 cleanup: PE12 warnings are included in success case, not error case
-fn process_with_scanner(file_path: &Path, use_advanced: bool) -> Result<(Vec<gamedata_scanner_models::GameClass>, Vec<::parser_hpp::ParseWarning>), String> {
-    if use_advanced {
-        // Find the project root by looking for hemtt.toml, starting from the file's directory
-        let project_root = find_project_root(file_path)
-            .unwrap_or_else(|| {
-                // Fallback to workspace root if no hemtt.toml found in file's hierarchy
-                std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
-            });
-        
-        // Check if hemtt.toml exists in the determined project root
-        let hemtt_toml_path = project_root.join("hemtt.toml");
-        let config_path = if hemtt_toml_path.exists() {
-            debug!("Found hemtt.toml at: {}", hemtt_toml_path.display());
-            Some(hemtt_toml_path)
-        } else {
-            debug!("No hemtt.toml found at: {} - linting will be disabled", hemtt_toml_path.display());
-            None
-        };
-        
-        // Create the AdvancedProjectParser with consistent project root
-        let project_parser = ::parser_hpp::AdvancedProjectParser::new(
-            &project_root,
-            config_path.as_deref()
-        ).map_err(|e| format!("Failed to create AdvancedProjectParser: {:?}", e))?;
-        
-        // Validate that config was loaded successfully
-        if project_parser.has_project_config() {
-            debug!("hemtt.toml config loaded successfully - linting rules are active");
-        } else {
-            debug!("No hemtt.toml config loaded - linting rules are disabled");
-        }
-        
-        // Calculate the relative path from project root to the file
-        let relative_path = file_path.strip_prefix(&project_root)
-            .map_err(|_| format!("File {} is not within project root {}",
-                                file_path.display(), project_root.display()))?;
-        
-        // Call project_parser.parse_file(relative_path)
-        match project_parser.parse_file(relative_path) {
-            Ok((classes, parse_warnings_from_advanced_parser)) => {
-                Ok((classes, parse_warnings_from_advanced_parser))
-            },
-            Err(parse_error) => {
-                Err(format!("{:?}", parse_error))
-            }
+fn process_with_scanner(file_path: &Path, parser_type: ParserType, include_roots: &[PathBuf]) -> Result<(Vec<gamedata_scanner_models::GameClass>, Vec<::parser_hpp::ParseWarning>), String> {
+    match parser_type {
+        ParserType::Simple => {
+            // Call ::parser_hpp::parse_file(file_path) and return empty warnings
+            let simple_classes = ::parser_hpp::parse_file(file_path)
+                .map_err(|e| format!("{:?}", e))?;
+            Ok((simple_classes, Vec::new()))
         }
+        ParserType::Advanced => process_with_advanced(file_path, include_roots),
+        ParserType::Hybrid => Ok(process_with_advanced(file_path, include_roots)
+            .unwrap_or_else(|reason| parse_with_fallback(file_path, &reason))),
+    }
+}
+
+/// Scan a file with the simple scanner after the advanced parser failed,
+/// reporting why as a warning
+fn parse_with_fallback(file_path: &Path, reason: &str) -> (Vec<gamedata_scanner_models::GameClass>, Vec<::parser_hpp::ParseWarning>) {
+    warn!("Falling back to the simple parser for {}: {}", file_path.display(), reason);
+    let classes = ::parser_hpp::SimpleClassScanner::new().scan_file(file_path);
+    let warning = ::parser_hpp::ParseWarning {
+        code: "FALLBACK".to_string(),
+        message: format!("Parsed by the simple parser: {}", reason),
+        severity: "Warning".to_string(),
+        file_path: file_path.display().to_string(),
+    };
+    (classes, vec![warning])
+}
+
+/// Parse a file with the advanced parser in the project the file belongs to
+fn process_with_advanced(file_path: &Path, include_roots: &[PathBuf]) -> Result<(Vec<gamedata_scanner_models::GameClass>, Vec<::parser_hpp::ParseWarning>), String> {
+    // Find the project root by looking for hemtt.toml, starting from the file's directory
+    let project_root = find_project_root(file_path)
+        .unwrap_or_else(|| {
+            // Fallback to workspace root if no hemtt.toml found in file's hierarchy
+            std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
+        });
+    
+    // Check if hemtt.toml exists in the determined project root
+    let hemtt_toml_path = project_root.join("hemtt.toml");
+    let config_path = if hemtt_toml_path.exists() {
+        debug!("Found hemtt.toml at: {}", hemtt_toml_path.display());
+        Some(hemtt_toml_path)
     } else {
-        // Call ::parser_hpp::parse_file(file_path) and return empty warnings
-        let simple_classes = ::parser_hpp::parse_file(file_path)
-            .map_err(|e| format!("{:?}", e))?;
-        Ok((simple_classes, Vec::new()))
+        debug!("No hemtt.toml found at: {} - linting will be disabled", hemtt_toml_path.display());
+        None
+    };
+    
+    // Create the AdvancedProjectParser with consistent project root
+    let project_parser = ::parser_hpp::AdvancedProjectParser::with_include_roots(
+        &project_root,
+        config_path.as_deref(),
+        include_roots,
+    ).map_err(|e| format!("Failed to create AdvancedProjectParser: {:?}", e))?;
+    
+    // Validate that config was loaded successfully
+    if project_parser.has_project_config() {
+        debug!("hemtt.toml config loaded successfully - linting rules are active");
+    } else {
+        debug!("No hemtt.toml config loaded - linting rules are disabled");
+    }
+    
+    // Calculate the relative path from project root to the file
+    let relative_path = file_path.strip_prefix(&project_root)
+        .map_err(|_| format!("File {} is not within project root {}",
+                            file_path.display(), project_root.display()))?;
+    
+    // Call project_parser.parse_file(relative_path)
+    match project_parser.parse_file(relative_path) {
+        Ok((classes, parse_warnings_from_advanced_parser)) => {
+            Ok((classes, parse_warnings_from_advanced_parser))
+        },
+        Err(parse_error) => {
+            Err(format!("{:?}", parse_error))
+        }
     }
 }
 
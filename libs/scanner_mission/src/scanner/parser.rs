@@ -97,8 +97,8 @@ pub fn parse_hpp(file_path: &Path) -> Result<Vec<ClassReference>> {
                     if is_equipment_array(&property_name) {
                         debug!("Processing equipment array: {}", property_name);
                         
-                        // Process each array item, stripping any extra quotes
-                        for item in items {
+                        // Process each string item, stripping any extra quotes
+                        for item in items.iter().filter_map(PropertyValue::as_string) {
                             // Skip empty items and preprocessor macros
                             let clean_item = item.trim().trim_matches('"');
                             if !clean_item.is_empty() && 
@@ -134,6 +134,7 @@ impl ConfigManager {
             timeout: 30,
             project_root: Some(PathBuf::from("./project_root")),
             weapons_by_mod: Some(PathBuf::from("./output/weapons_by_mod.txt")),
+            include_roots: vec![PathBuf::from("./pdrive")],
         };
 
         self.save_config(&sample_config, path)?;
@@ -218,6 +219,10 @@ r#"# Weapon Magazine Scanner Configuration
             config.weapons_by_mod = args.weapons_by_mod().clone();
         }
 
+        if !args.include_roots().is_empty() {
+            config.include_roots = args.include_roots().to_vec();
+        }
+
         config
     }
 }
@@ -234,6 +239,7 @@ pub trait CliArgsProvider {
     fn timeout(&self) -> u64;
     fn project_root(&self) -> &Option<PathBuf>;
     fn weapons_by_mod(&self) -> &Option<PathBuf>;
+    fn include_roots(&self) -> &[PathBuf];
 }
 
 impl Default for ConfigManager {
@@ -64,6 +64,10 @@ pub struct Args {
     /// Output file for weapons grouped by mod (overrides config file)
     #[arg(long)]
     pub weapons_by_mod: Option<PathBuf>,
+
+    /// P-drive style directory used to resolve absolute includes (repeatable, overrides config file)
+    #[arg(long = "include-root")]
+    pub include_roots: Vec<PathBuf>,
 }
 
 impl CliArgsProvider for Args {
@@ -106,6 +110,10 @@ impl CliArgsProvider for Args {
     fn weapons_by_mod(&self) -> &Option<PathBuf> {
         &self.weapons_by_mod
     }
+
+    fn include_roots(&self) -> &[PathBuf] {
+        &self.include_roots
+    }
 }
 
 /// Convert Windows UNC path to clean display format
@@ -213,7 +221,12 @@ fn main() -> Result<()> {
     let mut db = Database::new(&config.database)?;
     
     // Create scanner with proper project root
-    let scanner = WeaponMagazineScanner::new(&canonical_project_root, config.threads, config.timeout)?;
+    let scanner = WeaponMagazineScanner::with_include_roots(
+        &canonical_project_root,
+        config.threads,
+        config.timeout,
+        &config.include_roots,
+    )?;
 
     log::info!("Scanner initialized:");
     log::info!("  Project root: {}", clean_path_display(&canonical_project_root));
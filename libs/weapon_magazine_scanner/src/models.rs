@@ -41,6 +41,10 @@ pub struct AppConfig {
 
     /// Output file for weapons grouped by mod (optional)
     pub weapons_by_mod: Option<PathBuf>,
+
+    /// Directories laid out like a P drive used to resolve absolute includes
+    #[serde(default)]
+    pub include_roots: Vec<PathBuf>,
 }
 
 fn default_output() -> PathBuf {
@@ -76,6 +80,7 @@ impl Default for AppConfig {
             timeout: default_timeout(),
             project_root: None,
             weapons_by_mod: None,
+            include_roots: Vec::new(),
         }
     }
 }
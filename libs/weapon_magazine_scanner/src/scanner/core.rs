@@ -1,5 +1,5 @@
 use anyhow::Result;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 
 use crate::models::{WeaponInfo, MagazineWellInfo, ScanResult}; // Removed FolderHash
@@ -25,12 +25,22 @@ pub struct WeaponMagazineScanner {
 }
 
 impl WeaponMagazineScanner {
-    pub fn new(project_root: &Path, thread_count: usize, _timeout_seconds: u64) -> Result<Self> {
+    pub fn new(project_root: &Path, thread_count: usize, timeout_seconds: u64) -> Result<Self> {
+        Self::with_include_roots(project_root, thread_count, timeout_seconds, &[])
+    }
+
+    /// Create a scanner that also resolves includes from P-drive style directories
+    pub fn with_include_roots(
+        project_root: &Path,
+        thread_count: usize,
+        _timeout_seconds: u64,
+        include_roots: &[PathBuf],
+    ) -> Result<Self> {
         const DEFAULT_TIMEOUT: u64 = 30; // Enforce consistent 30 second timeout
         Ok(Self {
             thread_count,
             timeout_seconds: DEFAULT_TIMEOUT,
-            file_processor: FileProcessor::new(project_root, DEFAULT_TIMEOUT)?,
+            file_processor: FileProcessor::with_include_roots(project_root, DEFAULT_TIMEOUT, include_roots)?,
             hash_calculator: HashCalculator::new(),
         })
     }
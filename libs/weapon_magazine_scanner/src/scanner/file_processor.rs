@@ -55,10 +55,15 @@ pub struct FileProcessor {
 }
 
 impl FileProcessor {
-    pub fn new(project_root: &Path, _timeout_seconds: u64) -> Result<Self> {
+    pub fn new(project_root: &Path, timeout_seconds: u64) -> Result<Self> {
+        Self::with_include_roots(project_root, timeout_seconds, &[])
+    }
+
+    /// Create a processor whose parser also resolves includes from P-drive style directories
+    pub fn with_include_roots(project_root: &Path, _timeout_seconds: u64, include_roots: &[PathBuf]) -> Result<Self> {
         const DEFAULT_TIMEOUT: u64 = 10; // Enforce consistent 10 second timeout
         log::info!("Initializing FileProcessor for project root: {}", clean_path_display(project_root));
-        let parser_instance = AdvancedProjectParser::new(project_root, None)
+        let parser_instance = AdvancedProjectParser::with_include_roots(project_root, None, include_roots)
             .map_err(|e| anyhow::anyhow!("Failed to create AdvancedProjectParser for root {}: {}", clean_path_display(project_root), e))?;
         Ok(Self {
             timeout_seconds: DEFAULT_TIMEOUT,
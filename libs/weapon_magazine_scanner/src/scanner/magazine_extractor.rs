@@ -54,7 +54,7 @@ impl MagazineExtractor {
         // Each property here (e.g., "CBA_30Rnd_556x45_Stanag") is an array of magazine class names
         for prop in &well_class_definition.properties {
             let magazine_group_name = &prop.name; // e.g. "ACE_Magazines", "BI_Magazines"
-            if let PropertyValue::Array(magazine_list_pv) = &prop.value {
+            if let Some(magazine_list_pv) = prop.value.as_string_array() {
                 let magazine_names: Vec<String> = magazine_list_pv.iter()
                     .map(|name_str| name_str.trim_matches('"').to_string())
                     .collect();
@@ -63,7 +63,7 @@ impl WeaponExtractor {
         // Iterate through properties to find "magazineWell"
         for prop in &class.properties {
             if prop.name == "magazineWell" {
-                if let PropertyValue::Array(wells_pv) = &prop.value {
+                if let Some(wells_pv) = prop.value.as_string_array() {
                     for well_name_str in wells_pv {
                         magazine_wells.push(well_name_str.trim_matches('"').to_string());
                     }
@@ -73,7 +73,7 @@ impl WeaponExtractor {
             else if let PropertyValue::Class(ref nested_class) = prop.value {
                 for nested_prop in &nested_class.properties {
                     if nested_prop.name == "magazineWell" {
-                        if let PropertyValue::Array(wells_pv) = &nested_prop.value {
+                        if let Some(wells_pv) = nested_prop.value.as_string_array() {
                             for well_name_str in wells_pv {
                                 magazine_wells.push(well_name_str.trim_matches('"').to_string());
                             }
@@ -98,7 +98,7 @@ impl WeaponExtractor {
         // Iterate through properties to find "weapons"
         for prop in &class.properties {
             if prop.name == "weapons" {
-                if let PropertyValue::Array(weapons_pv) = &prop.value {
+                if let Some(weapons_pv) = prop.value.as_string_array() {
                     let weapon_names: Vec<String> = weapons_pv.iter()
                         .map(|name_str| name_str.trim_matches('"').to_string())
                         .collect();
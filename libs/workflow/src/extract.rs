@@ -162,6 +162,7 @@ impl ExtractorInterface for MockExtractor {
             ],
             elapsed_time: std::time::Duration::from_secs(10),
             errors: Vec::new(),
+            ..ExtractionSummary::default()
         })
     }
     
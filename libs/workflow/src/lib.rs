@@ -9,7 +9,7 @@ pub mod export;
 // Re-export key types for convenience
 pub use types::workflow::{WorkflowType, ContentType, Workflow};
 pub use types::options::{WorkflowOptions, ExtractionOptions, ProcessingOptions, ReportingOptions, ReportFormat, ExportOptions};
-pub use types::summary::{ExtractionSummary, ProcessingSummary, ReportingSummary, ExportSummary};
+pub use types::summary::{ExtractionSummary, PboTiming, ProcessingSummary, ReportingSummary, ExportSummary};
 
 // Re-export orchestrator types
 pub use orchestrator::{
@@ -5,4 +5,4 @@ pub mod summary;
 // Re-export all types for convenience
 pub use workflow::{WorkflowType, ContentType, Workflow};
 pub use options::{WorkflowOptions, ExtractionOptions, ProcessingOptions, ReportingOptions, ReportFormat, ExportOptions};
-pub use summary::{ExtractionSummary, ProcessingSummary, ReportingSummary, ExportSummary};
\ No newline at end of file
+pub use summary::{ExtractionSummary, PboTiming, ProcessingSummary, ReportingSummary, ExportSummary};
\ No newline at end of file
@@ -2,6 +2,35 @@ use std::path::PathBuf;
 use std::time::Duration;
 use serde::{Serialize, Deserialize};
 
+/// Number of PBOs kept in [`ExtractionSummary::slowest_pbos`]
+pub const SLOWEST_PBO_COUNT: usize = 10;
+
+/// Time and data spent extracting a single PBO
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PboTiming {
+    /// Path of the PBO
+    pub path: PathBuf,
+    
+    /// Wall time of the extraction
+    pub elapsed_time: Duration,
+    
+    /// Size of the PBO that was read
+    pub bytes_read: u64,
+    
+    /// Number of files extracted
+    pub entries_extracted: usize,
+}
+
+impl std::fmt::Display for PboTiming {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({:.2}s, {:.1} MiB, {} files)",
+               self.path.display(),
+               self.elapsed_time.as_secs_f64(),
+               self.bytes_read as f64 / (1024.0 * 1024.0),
+               self.entries_extracted)
+    }
+}
+
 /// Summary of extraction
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtractionSummary {
@@ -16,6 +45,18 @@ pub struct ExtractionSummary {
     
     /// Errors encountered during extraction
     pub errors: Vec<String>,
+    
+    /// Total size of the PBOs read
+    #[serde(default)]
+    pub bytes_read: u64,
+    
+    /// The slowest PBOs that finished, slowest first
+    #[serde(default)]
+    pub slowest_pbos: Vec<PboTiming>,
+    
+    /// PBOs whose extraction timed out
+    #[serde(default)]
+    pub timeouts: Vec<PboTiming>,
 }
 
 impl ExtractionSummary {
@@ -40,6 +81,34 @@ impl ExtractionSummary {
         self.elapsed_time = elapsed;
     }
     
+    /// Add the time spent on a PBO
+    ///
+    /// Only the [`SLOWEST_PBO_COUNT`] slowest PBOs that did not time out are kept.
+    pub fn add_pbo_timing(&mut self, timing: PboTiming, timed_out: bool) {
+        self.bytes_read += timing.bytes_read;
+        
+        if timed_out {
+            self.timeouts.push(timing);
+            return;
+        }
+        
+        let index = self.slowest_pbos.partition_point(|slower| slower.elapsed_time >= timing.elapsed_time);
+        if index < SLOWEST_PBO_COUNT {
+            self.slowest_pbos.insert(index, timing);
+            self.slowest_pbos.truncate(SLOWEST_PBO_COUNT);
+        }
+    }
+    
+    /// Get the bytes of PBOs read per second over the whole extraction
+    pub fn throughput(&self) -> f64 {
+        let seconds = self.elapsed_time.as_secs_f64();
+        if seconds == 0.0 {
+            return 0.0;
+        }
+        
+        self.bytes_read as f64 / seconds
+    }
+    
     /// Check if extraction was successful (no errors)
     pub fn is_successful(&self) -> bool {
         self.errors.is_empty()
@@ -67,6 +136,9 @@ impl Default for ExtractionSummary {
             extraction_paths: Vec::new(),
             elapsed_time: Duration::from_secs(0),
             errors: Vec::new(),
+            bytes_read: 0,
+            slowest_pbos: Vec::new(),
+            timeouts: Vec::new(),
         }
     }
 }
@@ -81,6 +153,24 @@ impl std::fmt::Display for ExtractionSummary {
             write!(f, " with {} errors", self.errors.len())?;
         }
         
+        if self.bytes_read > 0 {
+            write!(f, " ({:.1} MiB/s)", self.throughput() / (1024.0 * 1024.0))?;
+        }
+        
+        if !self.slowest_pbos.is_empty() {
+            write!(f, "\n    Slowest PBOs:")?;
+            for timing in &self.slowest_pbos {
+                write!(f, "\n      {}", timing)?;
+            }
+        }
+        
+        if !self.timeouts.is_empty() {
+            write!(f, "\n    Timeouts:")?;
+            for timing in &self.timeouts {
+                write!(f, "\n      {}", timing)?;
+            }
+        }
+        
         Ok(())
     }
 }
@@ -475,6 +565,33 @@ mod tests {
         assert_eq!(summary.success_rate(), 2.0 / 3.0);
     }
     
+    #[test]
+    fn test_extraction_timings() {
+        let mut summary = ExtractionSummary::new();
+        let timing = |name: &str, secs: u64| PboTiming {
+            path: PathBuf::from(name),
+            elapsed_time: Duration::from_secs(secs),
+            bytes_read: 1024 * 1024,
+            entries_extracted: 3,
+        };
+        
+        for secs in 0..15 {
+            summary.add_pbo_timing(timing(&format!("addon{}.pbo", secs), secs), false);
+        }
+        summary.add_pbo_timing(timing("huge.pbo", 60), true);
+        summary.set_elapsed_time(Duration::from_secs(16));
+        
+        assert_eq!(summary.slowest_pbos.len(), SLOWEST_PBO_COUNT);
+        assert_eq!(summary.slowest_pbos[0].path, PathBuf::from("addon14.pbo"));
+        assert_eq!(summary.slowest_pbos[SLOWEST_PBO_COUNT - 1].path, PathBuf::from("addon5.pbo"));
+        assert_eq!(summary.timeouts, vec![timing("huge.pbo", 60)]);
+        assert_eq!(summary.throughput(), 1024.0 * 1024.0);
+        
+        let display = format!("{}", summary);
+        assert!(display.contains("Slowest PBOs:"));
+        assert!(display.contains("Timeouts:\n      huge.pbo (60.00s, 1.0 MiB, 3 files)"));
+    }
+    
     #[test]
     fn test_processing_summary() {
         let mut summary = ProcessingSummary::new();
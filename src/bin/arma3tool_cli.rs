@@ -1,5 +1,5 @@
 use anyhow::{anyhow, Context, Result};
-use arma3_tool::cli::{Cli, Commands};
+use arma3_tool::cli::{CacheCommands, Cli, Commands};
 use arma3_config::ScanConfig;
 use clap::Parser;
 use std::path::PathBuf;
@@ -150,6 +150,41 @@ async fn main() -> Result<()> {
             arma3_tool::cli::run_report(final_analysis_db_path, output_dir, &config).await?;
             // Pass analysis DB for report
         }
+        Commands::Cache { command } => match command {
+            CacheCommands::Prune {
+                cache_dir,
+                extractor_db_path,
+                dry_run,
+            } => {
+                // Apply CLI overrides to config before creating ExtractionConfig
+                if let Some(cd) = cache_dir {
+                    config.cache_dir = cd;
+                }
+                if let Some(edb) = extractor_db_path {
+                    config.extractor_database_path = Some(edb);
+                }
+
+                let extraction_config = config.to_extractor_config();
+                arma3_tool::cli::run_cache_prune(extraction_config, dry_run).await?;
+            }
+        },
+        Commands::Failures {
+            cache_dir,
+            extractor_db_path,
+            category,
+            retry,
+        } => {
+            // Apply CLI overrides to config before creating ExtractionConfig
+            if let Some(cd) = cache_dir {
+                config.cache_dir = cd;
+            }
+            if let Some(edb) = extractor_db_path {
+                config.extractor_database_path = Some(edb);
+            }
+
+            let extraction_config = config.to_extractor_config();
+            arma3_tool::cli::run_failures(extraction_config, category.as_deref(), retry).await?;
+        }
         Commands::FuzzyReport {
             cache_dir,
             analysis_db_path,
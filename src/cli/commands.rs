@@ -87,6 +87,31 @@ pub enum Commands {
         output_dir: Option<PathBuf>,
     },
 
+    /// Manage the extraction cache
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommands,
+    },
+
+    /// List PBOs that failed to extract, grouped by error category
+    Failures {
+        /// Override cache directory from config
+        #[arg(long)]
+        cache_dir: Option<PathBuf>,
+        
+        /// Override extractor database file path (stores PBO extraction state)
+        #[arg(long)]
+        extractor_db_path: Option<PathBuf>,
+        
+        /// Only include failures in this category, e.g. `corrupt` or `timeout`
+        #[arg(long)]
+        category: Option<String>,
+        
+        /// Extract the listed PBOs again
+        #[arg(long)]
+        retry: bool,
+    },
+
     /// Generate a report of missing classes with fuzzy match suggestions
     FuzzyReport {
         /// Override cache directory from config (used for default DB path if needed)
@@ -102,3 +127,21 @@ pub enum Commands {
         output_dir: Option<PathBuf>,
     },
 }
+
+#[derive(Subcommand)]
+pub enum CacheCommands {
+    /// Remove cached files and records of PBOs no longer found in the source directories
+    Prune {
+        /// Override cache directory from config
+        #[arg(long)]
+        cache_dir: Option<PathBuf>,
+        
+        /// Override extractor database file path (stores PBO extraction state)
+        #[arg(long)]
+        extractor_db_path: Option<PathBuf>,
+        
+        /// Only report what would be removed
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
@@ -245,8 +245,8 @@ fn load_and_filter_classes(
 
             // Apply exclusion check: skip if scope <= max_scope (typically 1)
             if let Some(scope_prop) = class.properties.get("scope") {
-                if let arma3_models::types::PropertyValue::Number(scope) = scope_prop {
-                    if *scope as i32 <= config.get_max_scope() {
+                if let Some(scope) = scope_prop.as_number() {
+                    if scope as i32 <= config.get_max_scope() {
                         return false;
                     }
                 }
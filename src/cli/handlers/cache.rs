@@ -0,0 +1,26 @@
+use anyhow::Result;
+use arma3_extractor::{ExtractionConfig, ExtractionManager};
+use log::info;
+
+/// Remove cache entries of PBOs that disappeared from the source directories
+pub async fn run_cache_prune(config: ExtractionConfig, dry_run: bool) -> Result<()> {
+    info!("Pruning extraction cache in {}", config.cache_dir.display());
+
+    let mut manager = ExtractionManager::new(config)?;
+    let report = manager.prune(dry_run)?;
+
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    for pbo_path in &report.removed_pbos {
+        println!("{} {}", verb, pbo_path.display());
+    }
+    println!(
+        "{} {} PBOs, {} cached files and {} blobs, reclaiming {:.1} MiB",
+        verb,
+        report.removed_pbos.len(),
+        report.removed_files.len(),
+        report.removed_blobs,
+        report.reclaimed_bytes as f64 / (1024.0 * 1024.0)
+    );
+
+    Ok(())
+}
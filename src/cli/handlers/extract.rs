@@ -3,9 +3,9 @@ use arma3_extractor::ExtractionConfig;
 use arma3_workflow::{
     WorkflowOrchestrator, ExtractWorkflowHandler, ExtractorInterface,
     Workflow, WorkflowType, ContentType, WorkflowOptions, ExtractionOptions,
-    ExtractionSummary, WorkflowError
+    ExtractionSummary, PboTiming, WorkflowError
 };
-use arma3_extractor::{extract_game_data, ExtractionConfig as ExtractorExtractionConfig};
+use arma3_extractor::{ExtractionConfig as ExtractorExtractionConfig, ExtractionManager, ExtractionOutcome};
 use async_trait::async_trait;
 use log::info;
 use std::path::PathBuf;
@@ -37,20 +37,42 @@ impl ExtractorInterface for DirectExtractorImpl {
         extractor_config.game_data_dirs = vec![source_dir.clone()];
         extractor_config.game_data_extensions = self.config.game_data_extensions.clone();
         extractor_config.game_data_cache_dir = output_dir.clone();
+        extractor_config.threads = self.config.threads;
+        extractor_config.timeout = self.config.timeout;
         
         // Use arma3_extractor directly
-        let result = extract_game_data(extractor_config)
+        let mut manager = ExtractionManager::new(extractor_config)
+            .map_err(|e| WorkflowError::extraction_error(e.to_string()))?;
+        let result = manager.process_game_data(false)
             .await
             .map_err(|e| WorkflowError::extraction_error(e.to_string()))?;
         
         let elapsed_time = start_time.elapsed();
         
-        Ok(ExtractionSummary {
+        let mut summary = ExtractionSummary {
             extracted_pbos: result.len(),
             extraction_paths: result,
             elapsed_time,
-            errors: Vec::new(), // TODO: Include any errors from extraction result
-        })
+            ..ExtractionSummary::default()
+        };
+        
+        // Record how long each PBO took, so thread counts and timeouts can be tuned
+        for (path, stats) in manager.extraction_stats() {
+            let timing = PboTiming {
+                path: path.clone(),
+                elapsed_time: stats.duration(),
+                bytes_read: stats.bytes_read,
+                entries_extracted: stats.entries_extracted,
+            };
+            match stats.outcome {
+                ExtractionOutcome::Extracted => summary.add_pbo_timing(timing, false),
+                ExtractionOutcome::TimedOut => summary.add_pbo_timing(timing, true),
+                ExtractionOutcome::Failed => summary.add_error(format!("Failed to extract {}", path.display())),
+                ExtractionOutcome::Cancelled => summary.add_error(format!("Extraction of {} was cancelled", path.display())),
+            }
+        }
+        
+        Ok(summary)
     }
     
     async fn validate_extraction_config(&self, options: &ExtractionOptions) -> arma3_workflow::Result<()> {
@@ -118,6 +140,13 @@ pub async fn run_extract(config: ExtractionConfig) -> Result<()> {
             result.duration
         );
         
+        for timing in &extraction_summary.slowest_pbos {
+            info!("  Slow: {}", timing);
+        }
+        for timing in &extraction_summary.timeouts {
+            log::warn!("  Timed out: {}", timing);
+        }
+        
         if !extraction_summary.errors.is_empty() {
             info!("Extraction completed with {} warnings/errors:", extraction_summary.errors.len());
             for error in &extraction_summary.errors {
@@ -0,0 +1,57 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use arma3_extractor::{ExtractionConfig, ExtractionManager, FailedExtraction};
+use log::info;
+
+/// List PBOs that failed to extract grouped by error category, optionally extracting them again
+pub async fn run_failures(config: ExtractionConfig, category: Option<&str>, retry: bool) -> Result<()> {
+    info!("Reviewing failed extractions in {}", config.db_path.display());
+
+    let mut manager = ExtractionManager::new(config)?;
+    let failures: Vec<FailedExtraction> = manager.failures()?
+        .into_iter()
+        .filter(|failure| category.is_none_or(|category| failure.category == category))
+        .collect();
+
+    if failures.is_empty() {
+        println!("No failed extractions");
+        return Ok(());
+    }
+
+    let mut by_category: BTreeMap<&str, Vec<&FailedExtraction>> = BTreeMap::new();
+    for failure in &failures {
+        by_category.entry(failure.category.as_str()).or_default().push(failure);
+    }
+
+    for (category, failures) in &by_category {
+        println!("{} ({})", category, failures.len());
+        for failure in failures {
+            // Failures recorded by older versions only have the normalized ID
+            let pbo_path = if failure.pbo_path.as_os_str().is_empty() {
+                failure.pbo_id.clone()
+            } else {
+                failure.pbo_path.display().to_string()
+            };
+            println!(
+                "  {} [{} attempt{}{}]: {}",
+                pbo_path,
+                failure.attempts,
+                if failure.attempts == 1 { "" } else { "s" },
+                if failure.retryable { ", retryable" } else { "" },
+                failure.error_message
+            );
+        }
+    }
+    println!("{} failed PBOs in {} categories", failures.len(), by_category.len());
+
+    if retry {
+        let extracted = manager.retry_failures(&failures).await?;
+        for pbo_path in &extracted {
+            println!("Extracted {}", pbo_path.display());
+        }
+        println!("{} of {} PBOs extracted on retry", extracted.len(), failures.len());
+    }
+
+    Ok(())
+}
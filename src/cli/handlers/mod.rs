@@ -3,9 +3,13 @@ pub mod process;
 pub mod report;
 pub mod export;
 pub mod fuzzy_report;
+pub mod cache;
+pub mod failures;
 
 pub use extract::run_extract;
 pub use process::run_process;
 pub use report::run_report;
 pub use export::run_export;
-pub use fuzzy_report::run_fuzzy_report;
\ No newline at end of file
+pub use fuzzy_report::run_fuzzy_report;
+pub use cache::run_cache_prune;
+pub use failures::run_failures;
\ No newline at end of file
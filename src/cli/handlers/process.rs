@@ -1,25 +1,29 @@
 use anyhow::Result;
-use arma3_extractor::ExtractionConfig;
-use gamedata_scanner::{Scanner, ScannerConfig};
+use arma3_database::{AddonModel, AddonRepository, Cache, ClassRepository, DatabaseManager, MissionModel, MissionRepository, ModInfo, PboModel, PboType, ResolvedPropertyModel, ResolvedPropertyRepository};
+use arma3_extractor::{ExtractionConfig, ExtractionManager, MissionSource, StateBackend};
+use gamedata_scanner::{resolver, AddonPatch, ParserMode, ResolvedProperty, Scanner, ScannerConfig, ScannerResult};
 use arma3_workflow::{
     WorkflowOrchestrator, ProcessWorkflowHandler, ScannerInterface,
     Workflow, WorkflowType, ContentType, WorkflowOptions, ProcessingOptions,
     ProcessingSummary, WorkflowError
 };
 use async_trait::async_trait;
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
-use log::info;
+use log::{info, warn};
 
 /// Direct implementation of ScannerInterface using gamedata_scanner library
 pub struct DirectScannerImpl {
     _config: ExtractionConfig,
-    _db_path: PathBuf,
+    db_path: PathBuf,
+    include_roots: Vec<PathBuf>,
 }
 
 impl DirectScannerImpl {
-    pub fn new(config: ExtractionConfig, db_path: PathBuf) -> Self {
-        Self { _config: config, _db_path: db_path }
+    /// Create a scanner whose parser resolves absolute includes from `include_roots`
+    pub fn new(config: ExtractionConfig, db_path: PathBuf, include_roots: Vec<PathBuf>) -> Self {
+        Self { _config: config, db_path, include_roots }
     }
 }
 
@@ -37,6 +41,8 @@ impl ScannerInterface for DirectScannerImpl {
         let mut scanner_config = ScannerConfig::default();
         scanner_config.show_progress = options.verbose;
         scanner_config.timeout = 60; // seconds
+        scanner_config.include_roots = self.include_roots.clone();
+        scanner_config.parser_mode = ParserMode::Hybrid;
         
         // Create scanner for the source directory
         let scanner = Scanner::new(source_dir, scanner_config)
@@ -46,6 +52,9 @@ impl ScannerInterface for DirectScannerImpl {
         let scan_result = scanner.scan_directory(".")
             .map_err(|e| WorkflowError::processing_error(format!("Failed to scan directory: {}", e)))?;
         
+        import_game_data(&scan_result, &self.db_path)
+            .map_err(|e| WorkflowError::processing_error(format!("Failed to store scanned classes: {}", e)))?;
+        
         let elapsed_time = start_time.elapsed();
         
         // Calculate totals from scan result
@@ -90,8 +99,11 @@ impl ScannerInterface for DirectScannerImpl {
 pub async fn run_process(config: ExtractionConfig, db_path: PathBuf) -> Result<()> {
     info!("Starting processing using workflow orchestration");
     
+    // Headers of other addons are included through the extracted game data
+    let include_root = ExtractionManager::new(config.clone())?.materialize_include_root()?;
+    
     // Create the direct scanner implementation
-    let scanner = Box::new(DirectScannerImpl::new(config.clone(), db_path.clone()));
+    let scanner = Box::new(DirectScannerImpl::new(config.clone(), db_path.clone(), vec![include_root]));
     
     // Create the workflow handler
     let process_handler = ProcessWorkflowHandler::new(scanner);
@@ -152,6 +164,173 @@ pub async fn run_process(config: ExtractionConfig, db_path: PathBuf) -> Result<(
         return Err(anyhow::anyhow!("Workflow completed with {} errors", result.errors.len()));
     }
     
+    let synced = sync_pbo_records(&config, &db_path)?;
+    info!("Recorded {} PBOs in the analysis database", synced);
+    
+    // Missions are found without extracting anything; their PBOs were recorded above
+    let mut manager = ExtractionManager::new(config.clone())?;
+    manager.process_all_missions(true).await?;
+    let missions = sync_missions(manager.mission_sources(), &db_path)?;
+    info!("Recorded {} missions in the analysis database", missions);
+    
     info!("Processing workflow completed successfully");
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Store the scanned classes, with where each one is defined, and the
+/// addons declared in `CfgPatches` in the analysis database in place of
+/// those of an earlier scan
+fn import_game_data(scan_result: &ScannerResult, db_path: &Path) -> Result<()> {
+    let db = DatabaseManager::new(db_path)
+        .map_err(|e| anyhow::anyhow!("Failed to open database: {}", e))?;
+    
+    let game_data = scan_result.to_game_data();
+    ClassRepository::new(&db).import_from_game_data(&game_data)?;
+    
+    // Keep the first declaration of an addon, as the addon graph does
+    let mut declared = HashSet::new();
+    let addons: Vec<AddonModel> = game_data.file_sources.iter()
+        .enumerate()
+        .flat_map(|(index, path)| {
+            AddonPatch::from_classes(&scan_result.results[path].classes)
+                .into_iter()
+                .map(move |patch| (patch, index))
+        })
+        .filter(|(patch, _)| {
+            let first = declared.insert(patch.name.to_lowercase());
+            if !first {
+                warn!("Addon {} is declared again in {}, ignoring it", patch.name, patch.file_path.display());
+            }
+            first
+        })
+        .map(|(patch, index)| addon_model(patch, index))
+        .collect();
+    let addon_repo = AddonRepository::new(&db);
+    addon_repo.clear_all()?;
+    addon_repo.bulk_import(&addons)?;
+    info!("Recorded {} addons from CfgPatches", addons.len());
+    
+    let resolved = resolver::resolve(&scan_result.to_addon_configs());
+    let properties: Vec<ResolvedPropertyModel> = resolved.classes.values()
+        .flat_map(|class| {
+            class.properties.values()
+                .filter(|property| !class.is_inherited(property))
+                .filter_map(|property| resolved_property_model(&class.path, property, &game_data.file_sources))
+        })
+        .collect();
+    ResolvedPropertyRepository::new(&db).replace_all(&properties)?;
+    info!("Resolved {} classes in load order, storing {} effective properties", resolved.classes.len(), properties.len());
+    
+    Ok(())
+}
+
+/// Resolved property model of a value set on the class at `class_path`
+///
+/// Nested classes have no value of their own and give `None`.
+fn resolved_property_model(class_path: &str, property: &ResolvedProperty, file_sources: &[PathBuf]) -> Option<ResolvedPropertyModel> {
+    Some(ResolvedPropertyModel {
+        class_path: class_path.to_string(),
+        name: property.name.clone(),
+        value: property.value.to_model_value()?,
+        addon: property.addon.clone(),
+        source_file_index: file_sources.iter().position(|path| *path == property.file_path),
+        location: property.location,
+    })
+}
+
+/// Addon model of a `CfgPatches` entry declared in the file at `source_file_index`
+fn addon_model(patch: AddonPatch, source_file_index: usize) -> AddonModel {
+    let mut addon = AddonModel::new(patch.name, Some(source_file_index));
+    addon.required_version = patch.required_version;
+    addon.author = patch.author;
+    addon.units = patch.units;
+    addon.weapons = patch.weapons;
+    addon.required_addons = patch.required_addons;
+    addon
+}
+
+/// Copy the extractor's PBO records, with the mod each PBO belongs to, into
+/// the analysis database so reports can name mods instead of PBO paths
+///
+/// Nothing is copied when the extractor keeps its state in the analysis
+/// database itself.
+fn sync_pbo_records(config: &ExtractionConfig, db_path: &Path) -> Result<usize> {
+    let db = DatabaseManager::new(db_path)
+        .map_err(|e| anyhow::anyhow!("Failed to open database: {}", e))?;
+    let cache = Cache::new(&db);
+    
+    if config.state_backend == StateBackend::Sqlite && config.db_path == db_path {
+        let records = cache.get_by_type(PboType::GameData)?.len() + cache.get_by_type(PboType::Mission)?.len();
+        return Ok(records);
+    }
+    
+    let extractor_db = arma3_extractor::open_store(config)?;
+    
+    let game_data = extractor_db.get_game_data_metadata()?;
+    let missions = extractor_db.get_mission_metadata()?;
+    let records = game_data.iter().map(|record| (record, PboType::GameData))
+        .chain(missions.iter().map(|record| (record, PboType::Mission)));
+    
+    let mut synced = 0;
+    for (record, pbo_type) in records {
+        let mut pbo = PboModel::new(
+            &record.full_path,
+            pbo_type,
+            record.file_size,
+            record.last_modified,
+            record.extraction_time,
+        );
+        pbo.base_dir = record.base_dir.clone();
+        if let Some(mod_identity) = &record.mod_identity {
+            pbo = pbo.with_mod_info(ModInfo {
+                folder: mod_identity.folder.clone(),
+                name: mod_identity.name.clone(),
+                published_id: mod_identity.published_id,
+                timestamp: mod_identity.timestamp,
+            });
+        }
+        
+        cache.create(&pbo)?;
+        cache.update_extraction_info(&pbo.id, record.extraction_time, &record.extracted_files, &record.used_extensions)?;
+        synced += 1;
+    }
+    
+    Ok(synced)
+}
+
+/// Record the missions found in the mission directories in the analysis
+/// database, with the PBO and archive each one came from
+///
+/// A mission is only linked to a PBO the database has a record of. Missions
+/// that were recorded before keep their components and dependencies.
+fn sync_missions(sources: &[MissionSource], db_path: &Path) -> Result<usize> {
+    let db = DatabaseManager::new(db_path)
+        .map_err(|e| anyhow::anyhow!("Failed to open database: {}", e))?;
+    let cache = Cache::new(&db);
+    let repository = MissionRepository::new(&db);
+    
+    for source in sources {
+        let mut mission = source.to_mission();
+        
+        // Records are copied under the path as it is, or kept under the extractor's lowercased ID
+        let mut source_pbo = None;
+        if let Some(pbo_id) = mission.source_pbo.take() {
+            for id in [pbo_id, arma3_database::normalize_path(&source.path)] {
+                if cache.get(&id)?.is_some() {
+                    source_pbo = Some(id);
+                    break;
+                }
+            }
+        }
+        mission.source_pbo = source_pbo;
+        
+        let model = MissionModel::from_mission(&mission);
+        if repository.get(&model.id)?.is_some() {
+            repository.update(&model)?;
+        } else {
+            repository.create(&model)?;
+        }
+    }
+    
+    Ok(sources.len())
+}
@@ -2,8 +2,8 @@ mod commands;
 mod handlers;
 mod export_optimized;
 
-pub use commands::Commands;
-pub use handlers::{run_extract, run_process, run_report, run_fuzzy_report, run_export};
+pub use commands::{CacheCommands, Commands};
+pub use handlers::{run_extract, run_process, run_report, run_fuzzy_report, run_export, run_cache_prune, run_failures};
 pub use export_optimized::run_export_optimized;
 
 use clap::Parser;
@@ -363,4 +363,53 @@ async fn test_database_workflow_isolation() {
     
     assert!(db1.is_ok());
     assert!(db2.is_ok());
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_scanned_property_values_round_trip_through_class_properties() {
+    use arma3_database::ClassRepository;
+    use arma3_models::{GameDataClasses, PropertyValue as ModelValue};
+    use gamedata_scanner::{GameClass, PropertyValue};
+
+    let temp_dir = TempDir::new().unwrap();
+    let db = DatabaseManager::new(&temp_dir.path().join("test.db")).unwrap();
+
+    let mut class = GameClass::new("Rifle".to_string(), None, PathBuf::from("config.cpp"));
+    class.add_property("displayName".to_string(), PropertyValue::String("Rifle".to_string()));
+    class.add_property("scope".to_string(), PropertyValue::Number(2));
+    class.add_property("uniqueId".to_string(), PropertyValue::Number(9_007_199_254_740_993));
+    class.add_property("mass".to_string(), PropertyValue::Float(12.5));
+    class.add_property("magazines".to_string(), PropertyValue::Array(vec!["30Rnd_mag".into(), PropertyValue::Number(30)]));
+    class.add_property("weaponPoolAvailable".to_string(), PropertyValue::Array(vec![
+        PropertyValue::Array(vec!["a".into(), PropertyValue::Number(1)]),
+        PropertyValue::Array(vec!["b".into(), PropertyValue::Float(0.5)]),
+    ]));
+    class.add_property("muzzles".to_string(), PropertyValue::Append(Box::new(PropertyValue::Array(vec!["this".into()]))));
+    class.add_property("reloadTime".to_string(), PropertyValue::Expression("60 / 700".to_string()));
+    class.add_property("OldMode".to_string(), PropertyValue::Delete);
+
+    let mut game_data = GameDataClasses::new();
+    let index = game_data.add_file_source(class.file_path.clone());
+    let converted = class.to_game_data_class(Some(index));
+    game_data.add_class(converted.clone());
+
+    let repo = ClassRepository::new(&db);
+    repo.import_from_game_data(&game_data).unwrap();
+    let stored = repo.get_all().unwrap().into_iter().find(|c| c.id == "Rifle").unwrap();
+
+    assert_eq!(stored.properties, converted.properties);
+    assert_eq!(stored.properties["scope"], ModelValue::Integer(2));
+    assert_eq!(stored.properties["uniqueId"], ModelValue::Integer(9_007_199_254_740_993));
+    assert_eq!(stored.properties["mass"], ModelValue::Number(12.5));
+    assert_eq!(stored.properties["magazines"], ModelValue::Array(vec![
+        ModelValue::String("30Rnd_mag".to_string()),
+        ModelValue::Integer(30),
+    ]));
+    assert_eq!(stored.properties["weaponPoolAvailable"], ModelValue::Array(vec![
+        ModelValue::Array(vec![ModelValue::String("a".to_string()), ModelValue::Integer(1)]),
+        ModelValue::Array(vec![ModelValue::String("b".to_string()), ModelValue::Number(0.5)]),
+    ]));
+    assert_eq!(stored.properties["muzzles"], ModelValue::Append(vec![ModelValue::String("this".to_string())]));
+    assert_eq!(stored.properties["reloadTime"], ModelValue::Expression("60 / 700".to_string()));
+    assert_eq!(stored.properties["OldMode"], ModelValue::Delete);
+}
@@ -20,6 +20,8 @@ fn create_inheritance_test_data() -> Vec<ClassModel> {
             container_class: None,
             source_file_index: None,
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: HashMap::new(),
         },
         
@@ -29,6 +31,8 @@ fn create_inheritance_test_data() -> Vec<ClassModel> {
             container_class: Some("CfgWeapons".to_string()),
             source_file_index: Some(1),
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: {
                 let mut props = HashMap::new();
                 props.insert("displayName".to_string(), PropertyValue::String("Rifle Base".to_string()));
@@ -43,6 +47,8 @@ fn create_inheritance_test_data() -> Vec<ClassModel> {
             container_class: Some("CfgWeapons".to_string()),
             source_file_index: Some(1),
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: {
                 let mut props = HashMap::new();
                 props.insert("displayName".to_string(), PropertyValue::String("Pistol Base".to_string()));
@@ -57,6 +63,8 @@ fn create_inheritance_test_data() -> Vec<ClassModel> {
             container_class: Some("CfgWeapons".to_string()),
             source_file_index: Some(1),
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: {
                 let mut props = HashMap::new();
                 props.insert("displayName".to_string(), PropertyValue::String("Launcher Base".to_string()));
@@ -72,6 +80,8 @@ fn create_inheritance_test_data() -> Vec<ClassModel> {
             container_class: Some("CfgWeapons".to_string()),
             source_file_index: Some(1),
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: {
                 let mut props = HashMap::new();
                 props.insert("displayName".to_string(), PropertyValue::String("MX 6.5 mm".to_string()));
@@ -86,6 +96,8 @@ fn create_inheritance_test_data() -> Vec<ClassModel> {
             container_class: Some("CfgWeapons".to_string()),
             source_file_index: Some(1),
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: {
                 let mut props = HashMap::new();
                 props.insert("displayName".to_string(), PropertyValue::String("P07 9 mm".to_string()));
@@ -100,6 +112,8 @@ fn create_inheritance_test_data() -> Vec<ClassModel> {
             container_class: Some("CfgWeapons".to_string()),
             source_file_index: Some(1),
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: {
                 let mut props = HashMap::new();
                 props.insert("displayName".to_string(), PropertyValue::String("RPG-32".to_string()));
@@ -115,6 +129,8 @@ fn create_inheritance_test_data() -> Vec<ClassModel> {
             container_class: None,
             source_file_index: None,
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: HashMap::new(),
         },
         
@@ -124,6 +140,8 @@ fn create_inheritance_test_data() -> Vec<ClassModel> {
             container_class: Some("CfgVehicles".to_string()),
             source_file_index: Some(2),
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: {
                 let mut props = HashMap::new();
                 props.insert("displayName".to_string(), PropertyValue::String("Uniform Base".to_string()));
@@ -138,6 +156,8 @@ fn create_inheritance_test_data() -> Vec<ClassModel> {
             container_class: Some("CfgVehicles".to_string()),
             source_file_index: Some(2),
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: {
                 let mut props = HashMap::new();
                 props.insert("displayName".to_string(), PropertyValue::String("Basic Body".to_string()));
@@ -153,6 +173,8 @@ fn create_inheritance_test_data() -> Vec<ClassModel> {
             container_class: Some("CfgVehicles".to_string()),
             source_file_index: Some(3),
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: {
                 let mut props = HashMap::new();
                 props.insert("displayName".to_string(), PropertyValue::String("Vest Base".to_string()));
@@ -167,6 +189,8 @@ fn create_inheritance_test_data() -> Vec<ClassModel> {
             container_class: Some("CfgVehicles".to_string()),
             source_file_index: Some(3),
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: {
                 let mut props = HashMap::new();
                 props.insert("displayName".to_string(), PropertyValue::String("Carrier Rig (Green)".to_string()));
@@ -182,6 +206,8 @@ fn create_inheritance_test_data() -> Vec<ClassModel> {
             container_class: Some("CfgVehicles".to_string()),
             source_file_index: Some(4),
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: {
                 let mut props = HashMap::new();
                 props.insert("displayName".to_string(), PropertyValue::String("Backpack Base".to_string()));
@@ -196,6 +222,8 @@ fn create_inheritance_test_data() -> Vec<ClassModel> {
             container_class: Some("CfgVehicles".to_string()),
             source_file_index: Some(4),
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: {
                 let mut props = HashMap::new();
                 props.insert("displayName".to_string(), PropertyValue::String("Assault Pack (Green)".to_string()));
@@ -211,6 +239,8 @@ fn create_inheritance_test_data() -> Vec<ClassModel> {
             container_class: Some("CfgWeapons".to_string()),
             source_file_index: Some(1),
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: {
                 let mut props = HashMap::new();
                 props.insert("displayName".to_string(), PropertyValue::String("MX Base".to_string()));
@@ -226,6 +256,8 @@ fn create_inheritance_test_data() -> Vec<ClassModel> {
             container_class: Some("CfgVehicles".to_string()),
             source_file_index: Some(2),
             is_forward_declaration: false,
+            location: None,
+            property_locations: HashMap::new(),
             properties: {
                 let mut props = HashMap::new();
                 props.insert("displayName".to_string(), PropertyValue::String("Soldier".to_string()));
@@ -10,8 +10,11 @@ use arma3_tool::cli::{run_extract, run_process, run_report, run_export};
 use arma3_workflow::orchestrator::WorkflowOrchestrator;
 use arma3_workflow::types::{Workflow, WorkflowType, ContentType};
 use arma3_workflow::types::options::{WorkflowOptions, ExtractionOptions, ProcessingOptions, ReportingOptions, ExportOptions, ReportFormat};
-use arma3_database::DatabaseManager;
-use arma3_extractor::ExtractionConfig;
+use arma3_database::{AddonRepository, DatabaseManager, MissionDependencyModel, MissionModel, MissionRepository, ResolvedPropertyRepository};
+use arma3_models::types::PropertyValue;
+use arma3_database::queries::class_repository::ClassRepository;
+use arma3_reporter::ReportCoordinator;
+use arma3_extractor::{ExtractionConfig, ExtractionManager, RetryPolicy, StateBackend};
 
 /// Helper function to create test PBO files
 fn create_test_pbo_files(dir: &PathBuf) -> std::io::Result<()> {
@@ -39,6 +42,9 @@ fn create_test_extraction_config(work_dir: &PathBuf, db_path: &PathBuf) -> Extra
         timeout: 10,
         verbose: true,
         db_path: db_path.clone(),
+        state_backend: StateBackend::File,
+        retry_policy: RetryPolicy::default(),
+        salvage_failed: false,
     }
 }
 
@@ -443,4 +449,221 @@ async fn test_performance_characteristics() {
     assert!(creation_time.as_secs() < 1);
     
     println!("Created 100 workflows in: {:?}", creation_time);
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_process_records_missions_with_their_sources() {
+    use std::io::Write;
+
+    let temp_dir = TempDir::new().unwrap();
+    let work_dir = temp_dir.path().join("work");
+    let db_path = temp_dir.path().join("analysis.db");
+    let mut config = create_test_extraction_config(&work_dir, &db_path);
+    config.game_data_dirs = Vec::new();
+
+    // A bundle with a packed mission next to an unpacked one
+    let mission_dir = work_dir.join("mission_pbos");
+    fs::create_dir_all(&mission_dir).unwrap();
+    let pbo_bytes = fs::read(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("libs/pbo_extractor/tests/fixtures/missions/adv48_Joust.VR.pbo")
+    ).unwrap();
+    let archive_path = mission_dir.join("bundle.zip");
+    let mut zip = zip::ZipWriter::new(fs::File::create(&archive_path).unwrap());
+    let options = zip::write::SimpleFileOptions::default();
+    zip.start_file("packed/adv48_Joust.VR.pbo", options).unwrap();
+    zip.write_all(&pbo_bytes).unwrap();
+    zip.start_file("tvt_raid.Stratis/mission.sqm", options).unwrap();
+    zip.write_all(b"version = 54;").unwrap();
+    zip.finish().unwrap();
+
+    ExtractionManager::new(config.clone()).unwrap().process_all_missions(false).await.unwrap();
+    run_process(config, db_path.clone()).await.unwrap();
+
+    let db = DatabaseManager::new(&db_path).unwrap();
+    let missions = MissionRepository::new(&db).get_all().unwrap();
+    assert_eq!(missions.len(), 2);
+
+    // Both name the archive; only the packed one links to a PBO record
+    let archive_id = archive_path.to_string_lossy().to_lowercase().replace('\\', "/");
+    for mission in &missions {
+        assert_eq!(mission.source_archive.as_deref(), Some(archive_id.as_str()));
+    }
+    let joust = missions.iter().find(|mission| mission.name == "adv48_Joust.VR").unwrap();
+    let raid = missions.iter().find(|mission| mission.name == "tvt_raid.Stratis").unwrap();
+    assert!(joust.source_pbo_id.is_some());
+    assert_eq!(raid.source_pbo_id, None);
+
+    let foreign_key_violations: i64 = db.with_connection(|conn| {
+        Ok(conn.query_row("SELECT COUNT(*) FROM pragma_foreign_key_check", [], |row| row.get(0))?)
+    }).unwrap();
+    assert_eq!(foreign_key_violations, 0);
+}
+
+#[tokio::test]
+async fn test_process_records_class_locations_for_reports() {
+    let temp_dir = TempDir::new().unwrap();
+    let work_dir = temp_dir.path().join("work");
+    let db_path = temp_dir.path().join("analysis.db");
+    let mut config = create_test_extraction_config(&work_dir, &db_path);
+    config.game_data_dirs = Vec::new();
+    config.mission_dirs = Vec::new();
+
+    let addon_dir = config.game_data_cache_dir.join("my_addon");
+    fs::create_dir_all(&addon_dir).unwrap();
+    fs::create_dir_all(&config.mission_cache_dir).unwrap();
+    fs::write(
+        addon_dir.join("config.cpp"),
+        "class CfgVehicles {\n    class Car;\n    class Offroad: Car {\n        scope = 2;\n    };\n};\n",
+    ).unwrap();
+
+    run_process(config, db_path.clone()).await.unwrap();
+
+    let db = DatabaseManager::new(&db_path).unwrap();
+    let mission_repo = MissionRepository::new(&db);
+    mission_repo.create(&MissionModel::new(
+        "test_mission".to_string(),
+        "Test Mission".to_string(),
+        PathBuf::from("missions/test.pbo"),
+        chrono::Utc::now(),
+    )).unwrap();
+    mission_repo.add_dependency(&MissionDependencyModel::new(
+        "test_mission".to_string(),
+        "Offroad".to_string(),
+        "Direct".to_string(),
+        PathBuf::from("init.sqf"),
+    )).unwrap();
+
+    let output_dir = temp_dir.path().join("reports");
+    fs::create_dir_all(&output_dir).unwrap();
+    ReportCoordinator::new(&db, None).generate_mission_class_source_report(&output_dir).unwrap();
+
+    let csv = fs::read_to_string(output_dir.join("mission_class_sources.csv")).unwrap();
+    let row = csv.lines().find(|line| line.starts_with("test_mission,Offroad,")).unwrap();
+    let fields: Vec<&str> = row.split(',').collect();
+    assert!(fields[2].ends_with("my_addon/config.cpp"));
+    assert_eq!((fields[3], fields[4]), ("3", "11"));
+}
+
+#[tokio::test]
+async fn test_process_records_addons_from_cfg_patches() {
+    let temp_dir = TempDir::new().unwrap();
+    let work_dir = temp_dir.path().join("work");
+    let db_path = temp_dir.path().join("analysis.db");
+    let mut config = create_test_extraction_config(&work_dir, &db_path);
+    config.game_data_dirs = Vec::new();
+    config.mission_dirs = Vec::new();
+
+    let addon_dir = config.game_data_cache_dir.join("my_addon");
+    fs::create_dir_all(&addon_dir).unwrap();
+    fs::create_dir_all(&config.mission_cache_dir).unwrap();
+    fs::write(
+        addon_dir.join("config.cpp"),
+        r#"class CfgPatches {
+    class my_addon {
+        units[] = {"my_car"};
+        weapons[] = {};
+        requiredVersion = 2.1;
+        requiredAddons[] = {"A3_Data_F", "cba_main"};
+        author = "Alpha";
+    };
+};
+class CfgVehicles {
+    class my_car {};
+};
+"#,
+    ).unwrap();
+
+    // A later declaration of the same addon is ignored, the first one wins
+    let copy_dir = config.game_data_cache_dir.join("z_copy");
+    fs::create_dir_all(&copy_dir).unwrap();
+    fs::write(
+        copy_dir.join("config.cpp"),
+        r#"class CfgPatches {
+    class MY_ADDON {
+        requiredAddons[] = {};
+        author = "Beta";
+    };
+};
+"#,
+    ).unwrap();
+
+    run_process(config, db_path.clone()).await.unwrap();
+
+    let db = DatabaseManager::new(&db_path).unwrap();
+    let addons = AddonRepository::new(&db).get_all().unwrap();
+    assert_eq!(addons.len(), 1);
+    let addon = &addons[0];
+    assert_eq!(addon.name, "my_addon");
+    assert_eq!(addon.units, vec!["my_car"]);
+    assert!(addon.weapons.is_empty());
+    assert_eq!(addon.required_addons, vec!["A3_Data_F", "cba_main"]);
+    assert_eq!(addon.required_version, Some(2.1));
+    assert_eq!(addon.author.as_deref(), Some("Alpha"));
+
+    let source_path = ClassRepository::new(&db).get_source_path(addon.source_file_index.unwrap()).unwrap().unwrap();
+    assert!(source_path.ends_with("my_addon/config.cpp"));
+}
+
+#[tokio::test]
+async fn test_process_stores_resolved_properties() {
+    let temp_dir = TempDir::new().unwrap();
+    let work_dir = temp_dir.path().join("work");
+    let db_path = temp_dir.path().join("analysis.db");
+    let mut config = create_test_extraction_config(&work_dir, &db_path);
+    config.game_data_dirs = Vec::new();
+    config.mission_dirs = Vec::new();
+
+    // The patch sorts before its base by path but loads after it
+    let base_dir = config.game_data_cache_dir.join("z_base");
+    let patch_dir = config.game_data_cache_dir.join("a_patch");
+    fs::create_dir_all(&base_dir).unwrap();
+    fs::create_dir_all(&patch_dir).unwrap();
+    fs::create_dir_all(&config.mission_cache_dir).unwrap();
+    fs::write(
+        base_dir.join("config.cpp"),
+        r#"class CfgPatches {
+    class base_addon {
+        requiredAddons[] = {};
+    };
+};
+class CfgVehicles {
+    class my_car {
+        scope = 1;
+        displayName = "Car";
+    };
+};
+"#,
+    ).unwrap();
+    fs::write(
+        patch_dir.join("config.cpp"),
+        r#"class CfgPatches {
+    class patch_addon {
+        requiredAddons[] = {"base_addon"};
+    };
+};
+class CfgVehicles {
+    class my_car {
+        scope = 2;
+    };
+};
+"#,
+    ).unwrap();
+
+    run_process(config, db_path.clone()).await.unwrap();
+
+    let db = DatabaseManager::new(&db_path).unwrap();
+    let properties = ResolvedPropertyRepository::new(&db).get_for_class("CfgVehicles/my_car").unwrap();
+    assert_eq!(properties.len(), 2);
+
+    let display_name = properties.iter().find(|p| p.name == "displayName").unwrap();
+    assert_eq!(display_name.value, PropertyValue::String("Car".to_string()));
+    assert_eq!(display_name.addon, "base_addon");
+
+    let scope = properties.iter().find(|p| p.name == "scope").unwrap();
+    assert_eq!(scope.value, PropertyValue::Integer(2));
+    assert_eq!(scope.addon, "patch_addon");
+    assert_eq!(scope.location.map(|l| l.start_line), Some(8));
+
+    let source_path = ClassRepository::new(&db).get_source_path(scope.source_file_index.unwrap()).unwrap().unwrap();
+    assert!(source_path.ends_with("a_patch/config.cpp"));
+}